@@ -0,0 +1,143 @@
+use std::{fmt, io};
+
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::{
+        error::AuthError,
+        password::{hash_password, validate_password},
+    },
+    query::{ListParams, Page, MAX_PAGE_LIMIT},
+    repository::{RepositoryError, Result},
+    users::model::{Membership, Permission, User, UserRole},
+};
+
+pub mod seed;
+pub mod transfer;
+
+// Name of the role given to the administrators of the library
+pub const ADMIN_ROLE_NAME: &str = "Administrator";
+
+// Id of the role given to the administrators of the library, which is always the same so that the
+// role cannot be mistaken for another one that was given the same name
+pub const ADMIN_ROLE_ID: Uuid = Uuid::from_u128(1);
+
+#[derive(thiserror::Error, Debug)]
+pub enum AdminError {
+    RepositoryError(#[from] RepositoryError),
+    AuthError(#[from] AuthError),
+    FileError(#[from] io::Error),
+    FormatError(#[from] serde_json::Error),
+    NotEmpty,
+    Conflict(String),
+}
+
+impl fmt::Display for AdminError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AdminError::RepositoryError(err) => write!(f, "{}", err),
+            AdminError::AuthError(err) => write!(f, "{}", err),
+            AdminError::FileError(err) => write!(f, "could not access the file: {}", err),
+            AdminError::FormatError(err) => write!(f, "file is not a valid export: {}", err),
+            AdminError::NotEmpty => write!(f, "database already has books or users in it"),
+            AdminError::Conflict(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+// Creates a user with the administrator role, creating the role as well if there is none yet
+pub fn create_admin(state: &AppState, username: &str, password: &str) -> Result<User, AdminError> {
+    validate_password(password)?;
+
+    if !state.users.is_username_valid(username, None)? {
+        return Err(AdminError::Conflict(format!(
+            "user with username {} already exists",
+            username
+        )));
+    }
+
+    let admin_role = match state.users.get_user_role(ADMIN_ROLE_ID) {
+        Ok(admin_role) => admin_role,
+        Err(RepositoryError::NotFound) => {
+            let settings = &state.library_settings;
+
+            // Administrators run the library rather than borrow from it
+            state.users.add_user_role(UserRole {
+                id: ADMIN_ROLE_ID,
+                name: ADMIN_ROLE_NAME.to_string(),
+                num_borrowable_books: 0,
+                loan_period_days: settings.default_loan_period_days,
+                max_renewals: settings.default_max_renewals,
+                fine_per_day_cents: 0,
+                fine_cap_cents: 0,
+                permissions: Permission::ALL.to_vec(),
+            })?
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let user = User {
+        id: Uuid::new_v4(),
+        username: username.to_string(),
+        display_name: None,
+        email: None,
+        membership: Membership::default(),
+        deleted_at: None,
+    };
+
+    let user = state
+        .users
+        .add_user(user, admin_role.id, Some(&hash_password(password)))?;
+
+    Ok(user)
+}
+
+// Replaces the password of the user with the username, such as for a user who forgot theirs
+pub fn set_password(state: &AppState, username: &str, password: &str) -> Result<(), AdminError> {
+    validate_password(password)?;
+
+    let credentials = state.auth.get_credentials(username)?;
+    state
+        .auth
+        .set_password_hash(credentials.user_id, &hash_password(password))?;
+
+    Ok(())
+}
+
+// Number of deleted records removed by a purge
+#[derive(Debug, Default, PartialEq)]
+pub struct PurgeSummary {
+    pub books: u32,
+    pub authors: u32,
+    pub users: u32,
+}
+
+// Removes for good the books, authors and users deleted longer ago than the retention period
+pub fn purge_deleted(state: &AppState) -> Result<PurgeSummary, AdminError> {
+    let retention_days = state.library_settings.deleted_retention_days;
+    let deleted_before = Utc::now() - Duration::days(retention_days as i64);
+
+    Ok(PurgeSummary {
+        books: state.catalog.purge_deleted_books(deleted_before)?,
+        authors: state.catalog.purge_deleted_authors(deleted_before)?,
+        users: state.users.purge_deleted_users(deleted_before)?,
+    })
+}
+
+// Collects every page of a list, in the order it is listed in without sorting
+pub fn list_all<T>(list: impl Fn(&ListParams) -> Result<Page<T>>) -> Result<Vec<T>> {
+    let mut all = Vec::new();
+
+    loop {
+        let page = list(&ListParams::page(MAX_PAGE_LIMIT, all.len() as u32))?;
+        let is_last = page.next.is_none();
+
+        all.extend(page.items);
+
+        if is_last {
+            return Ok(all);
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    catalog::model::{
+        Author, Book, BookContributorRequest, Category, ContributorRole, Item, ItemCondition,
+        ItemStatus,
+    },
+    query::ListParams,
+    users::model::{Membership, Permission, User, UserRole},
+};
+
+use super::{transfer::ImportSummary, AdminError};
+
+// Name, country and description of each demo author
+const AUTHORS: &[(&str, &str, &str)] = &[
+    (
+        "Isaac Asimov",
+        "USA",
+        "Writer of science fiction and popular science",
+    ),
+    ("Ursula K. Le Guin", "USA", "Writer of speculative fiction"),
+    (
+        "Jane Austen",
+        "United Kingdom",
+        "Novelist of the English landed gentry",
+    ),
+];
+
+const CATEGORIES: &[&str] = &["Science Fiction", "Fantasy", "Classics"];
+
+// Name, description, index of the author and indices of the categories of each demo book
+const BOOKS: &[(&str, &str, usize, &[usize])] = &[
+    (
+        "Foundation",
+        "A mathematician foresees the fall of the Galactic Empire",
+        0,
+        &[0],
+    ),
+    (
+        "I, Robot",
+        "Stories of robots bound by the Three Laws of Robotics",
+        0,
+        &[0],
+    ),
+    (
+        "A Wizard of Earthsea",
+        "A young wizard confronts the shadow he set loose",
+        1,
+        &[1],
+    ),
+    (
+        "The Left Hand of Darkness",
+        "An envoy visits a world whose people have no fixed sex",
+        1,
+        &[0],
+    ),
+    (
+        "Pride and Prejudice",
+        "Elizabeth Bennet and Mr. Darcy overcome their first impressions",
+        2,
+        &[2],
+    ),
+];
+
+// Number of copies added of each demo book
+const COPIES_PER_BOOK: usize = 2;
+
+// Name, number of borrowable books, loan period and permissions of each demo role
+const USER_ROLES: &[(&str, i32, i32, &[Permission])] = &[
+    ("Student", 3, 14, &[]),
+    (
+        "Staff",
+        10,
+        28,
+        &[Permission::CatalogWrite, Permission::CirculationStaff],
+    ),
+];
+
+// Username and index of the role of each demo user
+const USERS: &[(&str, usize)] = &[("alice", 0), ("bob", 0), ("carol", 1)];
+
+// Fills an empty library with a small catalog and a few users to try the application out with
+pub fn seed_library(state: &AppState) -> Result<ImportSummary, AdminError> {
+    let params = ListParams::page(1, 0);
+
+    if state.catalog.list_books(&params)?.total > 0 || state.users.list_users(&params)?.total > 0 {
+        return Err(AdminError::NotEmpty);
+    }
+
+    let settings = &state.library_settings;
+    let mut summary = ImportSummary::default();
+
+    let mut category_ids = Vec::new();
+    for name in CATEGORIES {
+        let category = state.catalog.add_category(Category {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+        })?;
+
+        category_ids.push(category.id);
+        summary.categories += 1;
+    }
+
+    let mut author_ids = Vec::new();
+    for (name, country, description) in AUTHORS {
+        let author = state.catalog.add_author(Author {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: description.to_string(),
+            country: country.to_string(),
+            deleted_at: None,
+        })?;
+
+        author_ids.push(author.id);
+        summary.authors += 1;
+    }
+
+    for (book_index, (name, description, author_index, category_indices)) in
+        BOOKS.iter().enumerate()
+    {
+        let book = Book {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            description: description.to_string(),
+            language: "English".to_string(),
+            contributors: Vec::new(),
+            categories: Vec::new(),
+            total_copies: 0,
+            available_copies: 0,
+            deleted_at: None,
+        };
+        let contributors = [BookContributorRequest {
+            author_id: author_ids[*author_index],
+            role: ContributorRole::Author,
+        }];
+        let book_category_ids: Vec<Uuid> = category_indices
+            .iter()
+            .map(|index| category_ids[*index])
+            .collect();
+
+        let book = state
+            .catalog
+            .add_book(book, &contributors, &book_category_ids)?;
+        summary.books += 1;
+
+        for copy in 0..COPIES_PER_BOOK {
+            state.catalog.add_item(Item {
+                id: Uuid::new_v4(),
+                book_id: book.id,
+                barcode: format!("DEMO-{:03}-{}", book_index + 1, copy + 1),
+                shelf_location: format!("A{}", book_index + 1),
+                condition: ItemCondition::Good,
+                status: ItemStatus::Available,
+            })?;
+            summary.items += 1;
+        }
+    }
+
+    let mut user_role_ids = Vec::new();
+    for (name, num_borrowable_books, loan_period_days, permissions) in USER_ROLES {
+        let user_role = state.users.add_user_role(UserRole {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            num_borrowable_books: *num_borrowable_books,
+            loan_period_days: *loan_period_days,
+            max_renewals: settings.default_max_renewals,
+            fine_per_day_cents: settings.default_fine_per_day_cents,
+            fine_cap_cents: settings.default_fine_cap_cents,
+            permissions: permissions.to_vec(),
+        })?;
+
+        user_role_ids.push(user_role.id);
+        summary.user_roles += 1;
+    }
+
+    for (username, user_role_index) in USERS {
+        let user = User {
+            id: Uuid::new_v4(),
+            username: username.to_string(),
+            display_name: None,
+            email: None,
+            membership: Membership::default(),
+            deleted_at: None,
+        };
+
+        state
+            .users
+            .add_user(user, user_role_ids[*user_role_index], None)?;
+        summary.users += 1;
+    }
+
+    Ok(summary)
+}
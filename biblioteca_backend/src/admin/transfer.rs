@@ -0,0 +1,200 @@
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::AppState,
+    catalog::model::{Author, Book, BookContributorRequest, Category, Item, ItemStatus},
+    repository::RepositoryError,
+    users::model::{FullUser, User, UserRole},
+};
+
+use super::{list_all, AdminError};
+
+// Catalog and users of the library, as written by an export and read by an import
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LibraryExport {
+    pub categories: Vec<Category>,
+    pub authors: Vec<Author>,
+    pub books: Vec<Book>,
+    pub items: Vec<Item>,
+    pub user_roles: Vec<UserRole>,
+    pub users: Vec<FullUser>,
+}
+
+// Number of records of each kind added by an import, leaving out those that already existed
+#[derive(Debug, Default, PartialEq)]
+pub struct ImportSummary {
+    pub categories: usize,
+    pub authors: usize,
+    pub books: usize,
+    pub items: usize,
+    pub user_roles: usize,
+    pub users: usize,
+}
+
+pub fn export_library(state: &AppState) -> Result<LibraryExport, AdminError> {
+    let books = list_all(|params| state.catalog.list_books(params))?;
+
+    let mut items = Vec::new();
+    for book in books.iter() {
+        items.extend(state.catalog.list_items_of_book(book.id)?);
+    }
+
+    Ok(LibraryExport {
+        categories: list_all(|params| state.catalog.list_categories(params))?,
+        authors: list_all(|params| state.catalog.list_authors(params))?,
+        books,
+        items,
+        user_roles: list_all(|params| state.users.list_user_roles(params))?,
+        users: list_all(|params| state.users.list_users(params))?,
+    })
+}
+
+pub fn write_export(state: &AppState, writer: impl Write) -> Result<(), AdminError> {
+    let export = export_library(state)?;
+
+    serde_json::to_writer_pretty(writer, &export)?;
+
+    Ok(())
+}
+
+// Adds every record of the export that is not in the library yet, matching them by their ids.
+// Records are added one at a time, so an import that fails partway can be run again once the
+// export has been fixed.
+pub fn import_library(
+    state: &AppState,
+    export: LibraryExport,
+) -> Result<ImportSummary, AdminError> {
+    let mut summary = ImportSummary::default();
+
+    for category in export.categories {
+        if state.catalog.is_category_exists(category.id)? {
+            continue;
+        }
+
+        if !state.catalog.is_category_name_valid(&category.name, None)? {
+            return Err(AdminError::Conflict(format!(
+                "category with name {} already exists",
+                category.name
+            )));
+        }
+
+        state.catalog.add_category(category)?;
+        summary.categories += 1;
+    }
+
+    for author in export.authors {
+        if state.catalog.is_author_exists(author.id)? {
+            continue;
+        }
+
+        state.catalog.add_author(author)?;
+        summary.authors += 1;
+    }
+
+    for book in export.books {
+        if state.catalog.is_book_exists(book.id)? {
+            continue;
+        }
+
+        let contributors: Vec<BookContributorRequest> = book
+            .contributors
+            .iter()
+            .map(|contributor| BookContributorRequest {
+                author_id: contributor.author_id,
+                role: contributor.role,
+            })
+            .collect();
+        let category_ids: Vec<_> = book.categories.iter().map(|category| category.id).collect();
+
+        state.catalog.add_book(book, &contributors, &category_ids)?;
+        summary.books += 1;
+    }
+
+    for mut item in export.items {
+        match state.catalog.get_item(item.id) {
+            Ok(_) => continue,
+            Err(RepositoryError::NotFound) => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        if !state.catalog.is_barcode_valid(&item.barcode, None)? {
+            return Err(AdminError::Conflict(format!(
+                "copy with barcode {} already exists",
+                item.barcode
+            )));
+        }
+
+        // Loans and holds are not part of an export, so copies set aside for them are freed
+        if matches!(item.status, ItemStatus::Borrowed | ItemStatus::OnHold) {
+            item.status = ItemStatus::Available;
+        }
+
+        state.catalog.add_item(item)?;
+        summary.items += 1;
+    }
+
+    for user_role in export.user_roles {
+        match state.users.get_user_role(user_role.id) {
+            Ok(_) => continue,
+            Err(RepositoryError::NotFound) => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        state.users.add_user_role(user_role)?;
+        summary.user_roles += 1;
+    }
+
+    for user in export.users {
+        if state.users.is_user_exists(user.id)? {
+            continue;
+        }
+
+        if !state.users.is_username_valid(&user.username, None)? {
+            return Err(AdminError::Conflict(format!(
+                "user with username {} already exists",
+                user.username
+            )));
+        }
+
+        let Some(first_user_role) = user.user_roles.first() else {
+            return Err(AdminError::Conflict(format!(
+                "user with username {} has no role",
+                user.username
+            )));
+        };
+        let first_user_role_id = first_user_role.user_role.id;
+
+        state.users.add_user(
+            User {
+                id: user.id,
+                username: user.username,
+                display_name: user.display_name,
+                email: user.email,
+                membership: user.membership,
+                deleted_at: None,
+            },
+            first_user_role_id,
+            None,
+        )?;
+
+        // The first role is granted again to carry over its expiry
+        for assigned in user.user_roles {
+            state
+                .users
+                .grant_user_role(user.id, assigned.user_role.id, assigned.expires_at)?;
+        }
+
+        summary.users += 1;
+    }
+
+    Ok(summary)
+}
+
+pub fn read_import(state: &AppState, reader: impl Read) -> Result<ImportSummary, AdminError> {
+    let export: LibraryExport = serde_json::from_reader(reader)?;
+
+    import_library(state, export)
+}
@@ -1,28 +1,135 @@
-use axum::{extract::State, Router};
+use std::sync::Arc;
+
+use axum::{extract::State, middleware::from_fn_with_state, Router};
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
 
 use crate::{
-    catalog::{authors::authors_router, books::books_router},
-    library::controller::library_router,
-    users::controller::users_router,
+    audit::{controller::audit_router, repository::AuditRepository},
+    auth::{
+        api_keys::api_keys_router,
+        controller::auth_router,
+        middleware::{authorize, Access},
+        model::AuthSettings,
+        repository::AuthRepository,
+        token::TokenSigner,
+    },
+    catalog::{
+        authors::authors_router, books::books_router, categories::categories_router,
+        items::items_router, repository::CatalogRepository, search::search_router,
+    },
+    library::{
+        controller::library_router, fines::fines_router, holds::holds_router,
+        model::LibrarySettings, repository::LibraryRepository,
+    },
+    repository::{MemoryRepository, SqliteRepository},
+    users::{
+        controller::users_router, membership::membership_router, model::Permission,
+        repository::UserRepository,
+    },
 };
 
-pub fn create_new_state(db_pool: Pool<SqliteConnectionManager>) -> AppState {
-    AppState { db_pool }
+pub fn create_new_state(
+    db_pool: Pool<SqliteConnectionManager>,
+    library_settings: LibrarySettings,
+    auth_settings: AuthSettings,
+) -> AppState {
+    create_state_with_repository(
+        Arc::new(SqliteRepository::new(db_pool)),
+        library_settings,
+        auth_settings,
+    )
+}
+
+// Creates a state backed by a PostgreSQL database, which replicas of the app can share
+#[cfg(feature = "postgres")]
+pub fn create_postgres_state(
+    db_pool: Pool<r2d2_postgres::PostgresConnectionManager<postgres::NoTls>>,
+    library_settings: LibrarySettings,
+    auth_settings: AuthSettings,
+) -> AppState {
+    create_state_with_repository(
+        Arc::new(crate::repository::PostgresRepository::new(db_pool)),
+        library_settings,
+        auth_settings,
+    )
+}
+
+// Creates a state that keeps everything in memory, which is lost once the app stops
+pub fn create_in_memory_state(
+    library_settings: LibrarySettings,
+    auth_settings: AuthSettings,
+) -> AppState {
+    create_state_with_repository(
+        Arc::new(MemoryRepository::new()),
+        library_settings,
+        auth_settings,
+    )
+}
+
+// Creates a state where every domain is stored by the same repository
+pub fn create_state_with_repository<R>(
+    repository: Arc<R>,
+    library_settings: LibrarySettings,
+    auth_settings: AuthSettings,
+) -> AppState
+where
+    R: CatalogRepository
+        + UserRepository
+        + LibraryRepository
+        + AuthRepository
+        + AuditRepository
+        + 'static,
+{
+    AppState {
+        catalog: repository.clone(),
+        users: repository.clone(),
+        library: repository.clone(),
+        auth: repository.clone(),
+        audit: repository,
+        library_settings,
+        tokens: TokenSigner::new(auth_settings.token_secret.as_deref()),
+        auth_settings,
+    }
 }
 
 pub fn create_app(State(state): State<AppState>) -> Router {
+    let catalog_access = Access::PublicRead(Permission::CatalogWrite);
+    let users_access = Access::Restricted(Permission::UsersAdmin);
+    let membership_access = Access::Restricted(Permission::CirculationStaff);
+    let audit_access = Access::Restricted(Permission::AuditRead);
+
     // Create router
     Router::new()
-        .merge(books_router())
-        .merge(authors_router())
-        .merge(users_router())
-        .merge(library_router())
+        .merge(guard(books_router(), &state, catalog_access))
+        .merge(guard(authors_router(), &state, catalog_access))
+        .merge(guard(categories_router(), &state, catalog_access))
+        .merge(guard(items_router(), &state, catalog_access))
+        .merge(search_router())
+        .merge(guard(users_router(), &state, users_access))
+        .merge(guard(membership_router(), &state, membership_access))
+        .merge(guard(library_router(), &state, Access::LoggedIn))
+        .merge(guard(holds_router(), &state, Access::LoggedIn))
+        .merge(guard(fines_router(), &state, Access::LoggedIn))
+        .merge(auth_router())
+        .merge(guard(api_keys_router(), &state, users_access))
+        .merge(guard(audit_router(), &state, audit_access))
         .with_state(state)
 }
 
+// Checks the requests of every route of the router against the access before handling them
+fn guard(router: Router<AppState>, state: &AppState, access: Access) -> Router<AppState> {
+    router.route_layer(from_fn_with_state((state.clone(), access), authorize))
+}
+
 #[derive(Clone)]
 pub struct AppState {
-    pub db_pool: Pool<SqliteConnectionManager>,
+    pub catalog: Arc<dyn CatalogRepository>,
+    pub users: Arc<dyn UserRepository>,
+    pub library: Arc<dyn LibraryRepository>,
+    pub auth: Arc<dyn AuthRepository>,
+    pub audit: Arc<dyn AuditRepository>,
+    pub library_settings: LibrarySettings,
+    pub auth_settings: AuthSettings,
+    pub tokens: TokenSigner,
 }
@@ -0,0 +1,56 @@
+use std::future::Future;
+
+use uuid::Uuid;
+
+use crate::auth::extractor::Principal;
+
+use super::model::ActorKind;
+
+tokio::task_local! {
+    static AUDIT_CONTEXT: AuditContext;
+}
+
+// Who the changes made while handling a request are made by, and the id of the request, which
+// the repositories record with each change
+#[derive(Debug, Clone)]
+pub struct AuditContext {
+    pub actor_kind: ActorKind,
+    pub actor_id: Option<Uuid>,
+    pub request_id: Option<String>,
+}
+
+impl AuditContext {
+    pub fn new(principal: &Principal, request_id: String) -> Self {
+        let (actor_kind, actor_id) = match principal {
+            Principal::User { user_id, .. } => (ActorKind::User, *user_id),
+            Principal::ApiKey { key_id, .. } => (ActorKind::ApiKey, *key_id),
+        };
+
+        AuditContext {
+            actor_kind,
+            actor_id: Some(actor_id),
+            request_id: Some(request_id),
+        }
+    }
+
+    // Changes made outside of a request, such as from the command line, are made by the system
+    pub fn system() -> Self {
+        AuditContext {
+            actor_kind: ActorKind::System,
+            actor_id: None,
+            request_id: None,
+        }
+    }
+
+    // Gets the context of the request being handled, if any
+    pub fn current() -> Self {
+        AUDIT_CONTEXT
+            .try_with(|context| context.clone())
+            .unwrap_or_else(|_| AuditContext::system())
+    }
+
+    // Runs the handling of a request with this as its context
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        AUDIT_CONTEXT.scope(self, future).await
+    }
+}
@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Query, State},
+    http::Uri,
+    routing::get,
+    Json, Router,
+};
+
+use crate::{
+    app::AppState,
+    error::Error,
+    query::{ListParams, Page, QueryError},
+};
+
+use super::{
+    model::{AuditEntry, AuditFilters},
+    repository::AUDIT_LIST,
+};
+
+pub fn audit_router() -> Router<AppState> {
+    Router::new().route("/audit", get(list_audit_entries))
+}
+
+// Lists the changes made to the library, narrowed down by who made them, to what and when
+async fn list_audit_entries(
+    state: State<AppState>,
+    uri: Uri,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Page<AuditEntry>>, Error> {
+    tracing::debug!("GET /audit with query params: {:?}", params);
+
+    let params = ListParams::parse(&uri, params, &AUDIT_LIST)
+        .map_err(|err| Error::bad_request(err.to_string()))?;
+
+    let filters =
+        parse_audit_filters(&params).map_err(|err| Error::bad_request(err.to_string()))?;
+
+    match state.audit.list_audit_entries(&filters, &params) {
+        Ok(entries) => Ok(Json(entries)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Reads the filters of the audit log, which match exactly
+fn parse_audit_filters(params: &ListParams) -> Result<AuditFilters, QueryError> {
+    Ok(AuditFilters {
+        actor_id: params.filters.get_parsed("actor_id")?,
+        action: params.filters.get_parsed("action")?,
+        entity_type: params.filters.get_parsed("entity_type")?,
+        entity_id: params.filters.get_parsed("entity_id")?,
+        request_id: params.filters.get("request_id").cloned(),
+        from: params.filters.get_parsed("from")?,
+        to: params.filters.get_parsed("to")?,
+    })
+}
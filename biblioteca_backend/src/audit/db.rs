@@ -0,0 +1,100 @@
+use rusqlite::{Connection, Row};
+use serde_json::Value;
+
+use crate::{
+    query::{ListParams, Page},
+    repository::{sqlite::QueryBuilder, Result, SqliteRepository},
+};
+
+use super::{
+    model::{AuditEntry, AuditFilters},
+    repository::AuditRepository,
+};
+
+const AUDIT_SORT_COLUMNS: &[(&str, &str)] = &[("occurred_at", "occurred_at")];
+
+impl AuditRepository for SqliteRepository {
+    fn list_audit_entries(
+        &self,
+        filters: &AuditFilters,
+        params: &ListParams,
+    ) -> Result<Page<AuditEntry>> {
+        let conn = self.pool.get().unwrap();
+
+        let mut query = QueryBuilder::new(
+            "SELECT id, occurred_at, actor_kind, actor_id, action, entity_type, entity_id, before, after, request_id FROM audit_log",
+        );
+        if let Some(actor_id) = filters.actor_id {
+            query.condition("actor_id = ?", actor_id);
+        }
+        if let Some(action) = filters.action {
+            query.condition("action = ?", action);
+        }
+        if let Some(entity_type) = filters.entity_type {
+            query.condition("entity_type = ?", entity_type);
+        }
+        if let Some(entity_id) = filters.entity_id {
+            query.condition("entity_id = ?", entity_id);
+        }
+        if let Some(request_id) = &filters.request_id {
+            query.condition("request_id = ?", request_id.clone());
+        }
+        if let Some(from) = filters.from {
+            query.condition("occurred_at >= ?", from);
+        }
+        if let Some(to) = filters.to {
+            query.condition("occurred_at < ?", to);
+        }
+        query.paginate(params, AUDIT_SORT_COLUMNS, "rowid");
+
+        let mut stmt = conn.prepare(&query.sql())?;
+
+        let entries = stmt
+            .query_map(query.params(), audit_entry_from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(Page::new(entries, query.count(&conn)?, params))
+    }
+}
+
+// Records the entry as part of the transaction of the change it describes
+pub(crate) fn add_audit_entry(conn: &Connection, entry: &AuditEntry) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO audit_log (id, occurred_at, actor_kind, actor_id, action, entity_type, entity_id, before, after, request_id)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        (
+            &entry.id,
+            &entry.occurred_at,
+            &entry.actor_kind,
+            &entry.actor_id,
+            &entry.action,
+            &entry.entity_type,
+            &entry.entity_id,
+            entry.before.as_ref().map(|before| before.to_string()),
+            entry.after.as_ref().map(|after| after.to_string()),
+            &entry.request_id,
+        ),
+    )?;
+
+    Ok(())
+}
+
+fn audit_entry_from_row(row: &Row) -> rusqlite::Result<AuditEntry> {
+    Ok(AuditEntry {
+        id: row.get(0)?,
+        occurred_at: row.get(1)?,
+        actor_kind: row.get(2)?,
+        actor_id: row.get(3)?,
+        action: row.get(4)?,
+        entity_type: row.get(5)?,
+        entity_id: row.get(6)?,
+        before: parse_json(row.get(7)?),
+        after: parse_json(row.get(8)?),
+        request_id: row.get(9)?,
+    })
+}
+
+// Reads the JSON the fields of an entity are stored as
+fn parse_json(value: Option<String>) -> Option<Value> {
+    value.and_then(|value| serde_json::from_str(&value).ok())
+}
@@ -0,0 +1,48 @@
+use crate::{
+    query::{ListParams, Page},
+    repository::{memory::paginate, MemoryRepository, Result},
+};
+
+use super::{
+    model::{AuditEntry, AuditFilters},
+    repository::AuditRepository,
+};
+
+impl AuditRepository for MemoryRepository {
+    fn list_audit_entries(
+        &self,
+        filters: &AuditFilters,
+        params: &ListParams,
+    ) -> Result<Page<AuditEntry>> {
+        let entries = self
+            .data()
+            .audit_entries
+            .iter()
+            .filter(|entry| is_entry_matching(entry, filters))
+            .cloned()
+            .collect();
+
+        Ok(paginate(entries, params, |a, b, _| {
+            a.occurred_at.cmp(&b.occurred_at)
+        }))
+    }
+}
+
+fn is_entry_matching(entry: &AuditEntry, filters: &AuditFilters) -> bool {
+    filters
+        .actor_id
+        .is_none_or(|actor_id| entry.actor_id == Some(actor_id))
+        && filters.action.is_none_or(|action| entry.action == action)
+        && filters
+            .entity_type
+            .is_none_or(|entity_type| entry.entity_type == entity_type)
+        && filters
+            .entity_id
+            .is_none_or(|entity_id| entry.entity_id == entity_id)
+        && filters
+            .request_id
+            .as_ref()
+            .is_none_or(|request_id| entry.request_id.as_ref() == Some(request_id))
+        && filters.from.is_none_or(|from| entry.occurred_at >= from)
+        && filters.to.is_none_or(|to| entry.occurred_at < to)
+}
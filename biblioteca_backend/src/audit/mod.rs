@@ -0,0 +1,9 @@
+pub mod context;
+pub mod controller;
+pub mod model;
+pub mod repository;
+
+pub(crate) mod db;
+mod memory;
+#[cfg(feature = "postgres")]
+pub(crate) mod postgres;
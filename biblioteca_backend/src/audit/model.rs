@@ -0,0 +1,272 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use super::context::AuditContext;
+
+// A change made to an entity, recorded along with the change itself and never altered after
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    pub actor_kind: ActorKind,
+    // User or API key that made the change, which changes made by the system have none of
+    pub actor_id: Option<Uuid>,
+    pub action: AuditAction,
+    pub entity_type: EntityType,
+    pub entity_id: Uuid,
+    // Fields of the entity before and after the change, only those that changed if it has both
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub request_id: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new<T: Serialize>(
+        context: &AuditContext,
+        action: AuditAction,
+        entity_type: EntityType,
+        entity_id: Uuid,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> Self {
+        let before = before.map(|before| serde_json::to_value(before).unwrap());
+        let after = after.map(|after| serde_json::to_value(after).unwrap());
+
+        let (before, after) = match (before, after) {
+            (Some(Value::Object(before)), Some(Value::Object(after))) => {
+                let (before, after) = diff_fields(before, after);
+                (Some(Value::Object(before)), Some(Value::Object(after)))
+            }
+            values => values,
+        };
+
+        AuditEntry {
+            id: Uuid::new_v4(),
+            occurred_at: Utc::now(),
+            actor_kind: context.actor_kind,
+            actor_id: context.actor_id,
+            action,
+            entity_type,
+            entity_id,
+            before,
+            after,
+            request_id: context.request_id.clone(),
+        }
+    }
+}
+
+// Keeps only the fields whose values differ between the two
+fn diff_fields(
+    mut before: Map<String, Value>,
+    mut after: Map<String, Value>,
+) -> (Map<String, Value>, Map<String, Value>) {
+    let unchanged: Vec<String> = before
+        .iter()
+        .filter(|(field, value)| after.get(*field) == Some(value))
+        .map(|(field, _)| field.clone())
+        .collect();
+
+    for field in unchanged {
+        before.remove(&field);
+        after.remove(&field);
+    }
+
+    (before, after)
+}
+
+// Narrows down the audit entries listed, each filter being left out unless given
+#[derive(Debug, Default)]
+pub struct AuditFilters {
+    pub actor_id: Option<Uuid>,
+    pub action: Option<AuditAction>,
+    pub entity_type: Option<EntityType>,
+    pub entity_id: Option<Uuid>,
+    pub request_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActorKind {
+    User,
+    ApiKey,
+    System,
+}
+
+impl Display for ActorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActorKind::User => write!(f, "user"),
+            ActorKind::ApiKey => write!(f, "api_key"),
+            ActorKind::System => write!(f, "system"),
+        }
+    }
+}
+
+impl ToSql for ActorKind {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.to_string().into())
+    }
+}
+
+impl FromStr for ActorKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "user" => Ok(ActorKind::User),
+            "api_key" => Ok(ActorKind::ApiKey),
+            "system" => Ok(ActorKind::System),
+            _ => Err(format!("{} is not a known value", s)),
+        }
+    }
+}
+
+impl FromSql for ActorKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|_| FromSqlError::InvalidType)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Borrow,
+    Return,
+    Renew,
+    // Brought back after being deleted
+    Restore,
+    // Removed for good once it had been deleted for long enough
+    Purge,
+    // Password of a user replaced, which is recorded without the password or its hash
+    ChangePassword,
+}
+
+impl Display for AuditAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditAction::Create => write!(f, "create"),
+            AuditAction::Update => write!(f, "update"),
+            AuditAction::Delete => write!(f, "delete"),
+            AuditAction::Borrow => write!(f, "borrow"),
+            AuditAction::Return => write!(f, "return"),
+            AuditAction::Renew => write!(f, "renew"),
+            AuditAction::Restore => write!(f, "restore"),
+            AuditAction::Purge => write!(f, "purge"),
+            AuditAction::ChangePassword => write!(f, "change_password"),
+        }
+    }
+}
+
+impl ToSql for AuditAction {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.to_string().into())
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "create" => Ok(AuditAction::Create),
+            "update" => Ok(AuditAction::Update),
+            "delete" => Ok(AuditAction::Delete),
+            "borrow" => Ok(AuditAction::Borrow),
+            "return" => Ok(AuditAction::Return),
+            "renew" => Ok(AuditAction::Renew),
+            "restore" => Ok(AuditAction::Restore),
+            "purge" => Ok(AuditAction::Purge),
+            "change_password" => Ok(AuditAction::ChangePassword),
+            _ => Err(format!("{} is not a known value", s)),
+        }
+    }
+}
+
+impl FromSql for AuditAction {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|_| FromSqlError::InvalidType)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityType {
+    Book,
+    Author,
+    Category,
+    Item,
+    User,
+    UserRole,
+    Loan,
+    Hold,
+    Fine,
+    ApiKey,
+}
+
+impl Display for EntityType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntityType::Book => write!(f, "book"),
+            EntityType::Author => write!(f, "author"),
+            EntityType::Category => write!(f, "category"),
+            EntityType::Item => write!(f, "item"),
+            EntityType::User => write!(f, "user"),
+            EntityType::UserRole => write!(f, "user_role"),
+            EntityType::Loan => write!(f, "loan"),
+            EntityType::Hold => write!(f, "hold"),
+            EntityType::Fine => write!(f, "fine"),
+            EntityType::ApiKey => write!(f, "api_key"),
+        }
+    }
+}
+
+impl ToSql for EntityType {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.to_string().into())
+    }
+}
+
+impl FromStr for EntityType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "book" => Ok(EntityType::Book),
+            "author" => Ok(EntityType::Author),
+            "category" => Ok(EntityType::Category),
+            "item" => Ok(EntityType::Item),
+            "user" => Ok(EntityType::User),
+            "user_role" => Ok(EntityType::UserRole),
+            "loan" => Ok(EntityType::Loan),
+            "hold" => Ok(EntityType::Hold),
+            "fine" => Ok(EntityType::Fine),
+            "api_key" => Ok(EntityType::ApiKey),
+            _ => Err(format!("{} is not a known value", s)),
+        }
+    }
+}
+
+impl FromSql for EntityType {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|_| FromSqlError::InvalidType)
+    }
+}
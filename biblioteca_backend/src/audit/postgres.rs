@@ -0,0 +1,104 @@
+use postgres::{GenericClient, Row};
+use serde_json::Value;
+
+use crate::{
+    query::{ListParams, Page},
+    repository::{postgres::QueryBuilder, PostgresRepository, Result},
+};
+
+use super::{
+    model::{AuditEntry, AuditFilters},
+    repository::AuditRepository,
+};
+
+const AUDIT_COLUMNS: &str = "id, occurred_at, actor_kind, actor_id, action, entity_type,
+    entity_id, before, after, request_id";
+
+const AUDIT_SORT_COLUMNS: &[(&str, &str)] = &[("occurred_at", "occurred_at")];
+
+impl AuditRepository for PostgresRepository {
+    fn list_audit_entries(
+        &self,
+        filters: &AuditFilters,
+        params: &ListParams,
+    ) -> Result<Page<AuditEntry>> {
+        self.with_client(|client| {
+            let mut query = QueryBuilder::new(&format!("SELECT {} FROM audit_log", AUDIT_COLUMNS));
+            if let Some(actor_id) = filters.actor_id {
+                query.condition("actor_id = ?", actor_id);
+            }
+            if let Some(action) = filters.action {
+                query.condition("action = ?", action);
+            }
+            if let Some(entity_type) = filters.entity_type {
+                query.condition("entity_type = ?", entity_type);
+            }
+            if let Some(entity_id) = filters.entity_id {
+                query.condition("entity_id = ?", entity_id);
+            }
+            if let Some(request_id) = &filters.request_id {
+                query.condition("request_id = ?", request_id.clone());
+            }
+            if let Some(from) = filters.from {
+                query.condition("occurred_at >= ?", from);
+            }
+            if let Some(to) = filters.to {
+                query.condition("occurred_at < ?", to);
+            }
+            query.paginate(params, AUDIT_SORT_COLUMNS, "rowid");
+
+            let entries = client
+                .query(&query.sql(), &query.params())?
+                .iter()
+                .map(audit_entry_from_row)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Page::new(entries, query.count(client)?, params))
+        })
+    }
+}
+
+// Records the entry as part of the transaction of the change it describes
+pub(crate) fn add_audit_entry(
+    client: &mut impl GenericClient,
+    entry: &AuditEntry,
+) -> Result<(), postgres::Error> {
+    client.execute(
+        "INSERT INTO audit_log (id, occurred_at, actor_kind, actor_id, action, entity_type, entity_id, before, after, request_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        &[
+            &entry.id,
+            &entry.occurred_at,
+            &entry.actor_kind,
+            &entry.actor_id,
+            &entry.action,
+            &entry.entity_type,
+            &entry.entity_id,
+            &entry.before.as_ref().map(|before| before.to_string()),
+            &entry.after.as_ref().map(|after| after.to_string()),
+            &entry.request_id,
+        ],
+    )?;
+
+    Ok(())
+}
+
+fn audit_entry_from_row(row: &Row) -> Result<AuditEntry, postgres::Error> {
+    Ok(AuditEntry {
+        id: row.try_get(0)?,
+        occurred_at: row.try_get(1)?,
+        actor_kind: row.try_get(2)?,
+        actor_id: row.try_get(3)?,
+        action: row.try_get(4)?,
+        entity_type: row.try_get(5)?,
+        entity_id: row.try_get(6)?,
+        before: parse_json(row.try_get(7)?),
+        after: parse_json(row.try_get(8)?),
+        request_id: row.try_get(9)?,
+    })
+}
+
+// Reads the JSON the fields of an entity are stored as
+fn parse_json(value: Option<String>) -> Option<Value> {
+    value.and_then(|value| serde_json::from_str(&value).ok())
+}
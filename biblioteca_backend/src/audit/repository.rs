@@ -0,0 +1,30 @@
+use crate::{
+    query::{ListParams, ListSpec, Page},
+    repository::Result,
+};
+
+use super::model::{AuditEntry, AuditFilters};
+
+// Filters and sort fields supported when listing audit entries
+pub const AUDIT_LIST: ListSpec = ListSpec {
+    filters: &[
+        "actor_id",
+        "action",
+        "entity_type",
+        "entity_id",
+        "request_id",
+        "from",
+        "to",
+    ],
+    sort_fields: &["occurred_at"],
+};
+
+// Storage of the audit log, which the other repositories append to as they make changes
+pub trait AuditRepository: Send + Sync {
+    // Lists the entries in the order they were recorded
+    fn list_audit_entries(
+        &self,
+        filters: &AuditFilters,
+        params: &ListParams,
+    ) -> Result<Page<AuditEntry>>;
+}
@@ -0,0 +1,117 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{app::AppState, error::Error, repository::RepositoryError};
+
+use super::{
+    error::AuthError,
+    model::{ApiKey, CreateApiKeyRequest, IssuedApiKey},
+    token::{api_key_prefix, generate_api_key, hash_api_key},
+};
+
+pub fn api_keys_router() -> Router<AppState> {
+    Router::new()
+        .route("/auth/keys", get(list_api_keys))
+        .route("/auth/keys", post(create_api_key))
+        .route("/auth/keys/:id", delete(revoke_api_key))
+        .route("/auth/keys/:id/rotate", post(rotate_api_key))
+}
+
+// Lists the API keys, without the keys themselves
+async fn list_api_keys(state: State<AppState>) -> Result<Json<Vec<ApiKey>>, Error> {
+    tracing::debug!("GET /auth/keys");
+
+    match state.auth.list_api_keys() {
+        Ok(api_keys) => Ok(Json(api_keys)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Issues a new API key, which is only shown in the response
+async fn create_api_key(
+    state: State<AppState>,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> Result<Json<IssuedApiKey>, Error> {
+    tracing::debug!("POST /auth/keys with name {:?}", payload.name);
+
+    let now = Utc::now();
+
+    if payload
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= now)
+    {
+        return Err(Error::bad_request(AuthError::ExpiryInPast.to_string()));
+    }
+
+    if !state.auth.is_api_key_name_valid(&payload.name).unwrap() {
+        return Err(Error::bad_request(AuthError::ApiKeyNameTaken.to_string()));
+    }
+
+    let key = generate_api_key();
+    let api_key = ApiKey {
+        id: Uuid::new_v4(),
+        name: payload.name,
+        prefix: api_key_prefix(&key),
+        key_hash: hash_api_key(&key),
+        permissions: payload.permissions,
+        created_at: now,
+        expires_at: payload.expires_at,
+        last_used_at: None,
+    };
+
+    match state.auth.add_api_key(api_key) {
+        Ok(api_key) => Ok(Json(IssuedApiKey { api_key, key })),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Replaces the key of an API key, keeping its name and permissions. The previous key stops
+// being accepted right away.
+async fn rotate_api_key(
+    state: State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<IssuedApiKey>, Error> {
+    tracing::debug!("POST /auth/keys/:id/rotate with id: {:?}", id);
+
+    let key = generate_api_key();
+
+    let result = state
+        .auth
+        .set_api_key_hash(id, &api_key_prefix(&key), &hash_api_key(&key))
+        .and_then(|()| state.auth.get_api_key(id));
+
+    match result {
+        Ok(api_key) => Ok(Json(IssuedApiKey { api_key, key })),
+        Err(RepositoryError::NotFound) => Err(Error::not_found()),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Revokes an API key, so that it can no longer be used
+async fn revoke_api_key(state: State<AppState>, Path(id): Path<Uuid>) -> Result<StatusCode, Error> {
+    tracing::debug!("DELETE /auth/keys with id: {:?}", id);
+
+    match state.auth.delete_api_key(id) {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(RepositoryError::NotFound) => Err(Error::not_found()),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::{app::AppState, error::Error, repository::RepositoryError, users::model::FullUser};
+
+use super::{
+    error::AuthError,
+    extractor::{AuthUser, Principal},
+    model::{LoginRequest, LoginResponse, Session},
+    password::verify_password,
+};
+
+pub fn auth_router() -> Router<AppState> {
+    Router::new()
+        .route("/auth/login", post(login))
+        .route("/auth/logout", post(logout))
+        .route("/auth/me", get(get_current_user))
+}
+
+pub async fn login(
+    state: State<AppState>,
+    Json(payload): Json<LoginRequest>,
+) -> Result<Json<LoginResponse>, Error> {
+    tracing::debug!("POST /auth/login for username {:?}", payload.username);
+
+    let credentials = match state.auth.get_credentials(&payload.username) {
+        Ok(credentials) => credentials,
+        Err(RepositoryError::NotFound) => {
+            return Err(Error::unauthorized(
+                AuthError::InvalidCredentials.to_string(),
+            ))
+        }
+        Err(err) => {
+            tracing::warn!("{}", err);
+            return Err(Error::server_issue());
+        }
+    };
+
+    // Users without a password cannot log in at all
+    let is_valid = credentials
+        .password_hash
+        .is_some_and(|password_hash| verify_password(&payload.password, &password_hash));
+    if !is_valid {
+        return Err(Error::unauthorized(
+            AuthError::InvalidCredentials.to_string(),
+        ));
+    }
+
+    // Clear out the sessions that can no longer be used, so that they do not pile up
+    let now = Utc::now();
+    state.auth.delete_expired_sessions(now).unwrap();
+
+    let session = Session {
+        id: Uuid::new_v4(),
+        user_id: credentials.user_id,
+        created_at: now,
+        expires_at: now + Duration::hours(i64::from(state.auth_settings.session_ttl_hours)),
+    };
+
+    match state.auth.add_session(session) {
+        Ok(session) => Ok(Json(LoginResponse {
+            token: state.tokens.sign(session.id),
+            user_id: session.user_id,
+            expires_at: session.expires_at,
+        })),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Ends the session of the token, so that none of its tokens can be used anymore
+pub async fn logout(state: State<AppState>, auth_user: AuthUser) -> Result<StatusCode, Error> {
+    let Principal::User {
+        user_id,
+        session_id,
+    } = auth_user.principal
+    else {
+        return Err(Error::forbidden(AuthError::NotLoggedInUser.to_string()));
+    };
+
+    tracing::debug!("POST /auth/logout for user_id {:?}", user_id);
+
+    match state.auth.delete_session(session_id) {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+pub async fn get_current_user(
+    state: State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<FullUser>, Error> {
+    let Some(user_id) = auth_user.user_id() else {
+        return Err(Error::forbidden(AuthError::NotLoggedInUser.to_string()));
+    };
+
+    tracing::debug!("GET /auth/me for user_id {:?}", user_id);
+
+    match state.users.get_user(user_id) {
+        Ok(user) => Ok(Json(user)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::not_found())
+        }
+    }
+}
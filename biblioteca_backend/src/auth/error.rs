@@ -0,0 +1,57 @@
+use std::fmt;
+
+use crate::{repository::RepositoryError, users::model::Permission};
+
+#[derive(thiserror::Error, Debug)]
+pub enum AuthError {
+    DatabaseError(#[from] RepositoryError),
+    InvalidCredentials,
+    MissingToken,
+    InvalidToken,
+    SessionExpired,
+    PasswordTooShort(usize),
+    PermissionDenied(Permission),
+    NotOwnAccount,
+    NotLoggedInUser,
+    UserIdRequired,
+    ApiKeyExpired,
+    ApiKeyNameTaken,
+    ExpiryInPast,
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AuthError::DatabaseError(..) => {
+                write!(f, "there was an error in accessing the database")
+            }
+            AuthError::InvalidCredentials => write!(f, "username or password is incorrect"),
+            AuthError::MissingToken => write!(f, "request requires a bearer token"),
+            AuthError::InvalidToken => write!(f, "token is not valid"),
+            AuthError::SessionExpired => write!(f, "session has expired or been logged out"),
+            AuthError::PasswordTooShort(min) => {
+                write!(f, "password must be at least {} characters long", min)
+            }
+            AuthError::PermissionDenied(permission) => {
+                write!(f, "request requires the {} permission", permission)
+            }
+            AuthError::NotOwnAccount => write!(
+                f,
+                "request can only be made for the authenticated user without the {} permission",
+                Permission::CirculationStaff
+            ),
+            AuthError::NotLoggedInUser => {
+                write!(f, "request can only be made by a logged in user")
+            }
+            AuthError::UserIdRequired => {
+                write!(
+                    f,
+                    "request made with an API key must give the user_id it acts for"
+                )
+            }
+            AuthError::ApiKeyExpired => write!(f, "API key has expired"),
+            AuthError::ApiKeyNameTaken => write!(f, "API key with this name already exists"),
+            AuthError::ExpiryInPast => write!(f, "expires_at must be in the future"),
+        }
+    }
+}
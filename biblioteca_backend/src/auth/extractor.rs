@@ -0,0 +1,230 @@
+use std::fmt;
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts},
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{app::AppState, error::Error, repository::RepositoryError, users::model::Permission};
+
+use super::{
+    error::AuthError,
+    token::{hash_api_key, API_KEY_PREFIX},
+};
+
+// User or machine client the bearer token of the request was issued to, rejecting requests
+// without a valid one
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub principal: Principal,
+    pub permissions: Vec<Permission>,
+}
+
+// Who made an authenticated request
+#[derive(Debug, Clone)]
+pub enum Principal {
+    // User logged in with the session
+    User { user_id: Uuid, session_id: Uuid },
+    // Machine client with the API key
+    ApiKey { key_id: Uuid, name: String },
+}
+
+impl fmt::Display for Principal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Principal::User { user_id, .. } => write!(f, "user {}", user_id),
+            Principal::ApiKey { key_id, name } => write!(f, "API key {} ({})", name, key_id),
+        }
+    }
+}
+
+impl AuthUser {
+    // Gets the id of the logged in user, which requests made with an API key do not have
+    pub fn user_id(&self) -> Option<Uuid> {
+        match self.principal {
+            Principal::User { user_id, .. } => Some(user_id),
+            Principal::ApiKey { .. } => None,
+        }
+    }
+
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.permissions.contains(&permission)
+    }
+
+    pub fn require_permission(&self, permission: Permission) -> Result<(), Error> {
+        if !self.has_permission(permission) {
+            return Err(Error::forbidden(
+                AuthError::PermissionDenied(permission).to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Gets the user a request acts for, which is the authenticated user unless it names another
+    // user, which only circulation staff may act for. Requests made with an API key always have
+    // to name the user.
+    pub fn acting_for(&self, user_id: Option<Uuid>) -> Result<Uuid, Error> {
+        let own_user_id = self.user_id();
+
+        match user_id {
+            Some(user_id) if Some(user_id) != own_user_id => {
+                if !self.has_permission(Permission::CirculationStaff) {
+                    return Err(Error::forbidden(AuthError::NotOwnAccount.to_string()));
+                }
+
+                Ok(user_id)
+            }
+            _ => {
+                own_user_id.ok_or_else(|| Error::bad_request(AuthError::UserIdRequired.to_string()))
+            }
+        }
+    }
+}
+
+// User the bearer token of the request was issued to, if the request has one. Requests with a
+// token that is not valid are still rejected.
+#[derive(Debug, Clone)]
+pub struct OptionalAuthUser(pub Option<AuthUser>);
+
+impl OptionalAuthUser {
+    // Requests without a token are rejected as unauthenticated, before the permission is checked
+    pub fn require_permission(&self, permission: Permission) -> Result<(), Error> {
+        match &self.0 {
+            Some(auth_user) => auth_user.require_permission(permission),
+            None => Err(Error::unauthorized(AuthError::MissingToken.to_string())),
+        }
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Error> {
+        // Requests that went through the authorization middleware were already authenticated
+        if let Some(auth_user) = parts.extensions.get::<AuthUser>() {
+            return Ok(auth_user.clone());
+        }
+
+        match bearer_token(parts)? {
+            Some(token) => authenticate(state, token),
+            None => Err(Error::unauthorized(AuthError::MissingToken.to_string())),
+        }
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<AppState> for OptionalAuthUser {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Error> {
+        if let Some(auth_user) = parts.extensions.get::<AuthUser>() {
+            return Ok(OptionalAuthUser(Some(auth_user.clone())));
+        }
+
+        match bearer_token(parts)? {
+            Some(token) => Ok(OptionalAuthUser(Some(authenticate(state, token)?))),
+            None => Ok(OptionalAuthUser(None)),
+        }
+    }
+}
+
+// Gets the token of the `Authorization: Bearer <token>` header, if the request has the header
+fn bearer_token(parts: &Parts) -> Result<Option<&str>, Error> {
+    let Some(header) = parts.headers.get(AUTHORIZATION) else {
+        return Ok(None);
+    };
+
+    header
+        .to_str()
+        .ok()
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .map(|token| Some(token.trim()))
+        .ok_or_else(|| Error::unauthorized(AuthError::InvalidToken.to_string()))
+}
+
+fn authenticate(state: &AppState, token: &str) -> Result<AuthUser, Error> {
+    // Tokens of sessions always have a signature after a dot, which API keys never have
+    if token.starts_with(API_KEY_PREFIX) && !token.contains('.') {
+        return authenticate_api_key(state, token);
+    }
+
+    let session_id = state
+        .tokens
+        .verify(token)
+        .ok_or_else(|| Error::unauthorized(AuthError::InvalidToken.to_string()))?;
+
+    let session = match state.auth.get_session(session_id) {
+        Ok(session) => session,
+        Err(RepositoryError::NotFound) => {
+            return Err(Error::unauthorized(AuthError::SessionExpired.to_string()))
+        }
+        Err(err) => {
+            tracing::warn!("{}", err);
+            return Err(Error::server_issue());
+        }
+    };
+
+    if session.expires_at <= Utc::now() {
+        return Err(Error::unauthorized(AuthError::SessionExpired.to_string()));
+    }
+
+    // Sessions of deleted users are ended, but one may still be in use as the user is deleted
+    let user = match state.users.get_user(session.user_id) {
+        Ok(user) => user,
+        Err(RepositoryError::NotFound) => {
+            return Err(Error::unauthorized(AuthError::SessionExpired.to_string()))
+        }
+        Err(err) => {
+            tracing::warn!("{}", err);
+            return Err(Error::server_issue());
+        }
+    };
+
+    Ok(AuthUser {
+        principal: Principal::User {
+            user_id: session.user_id,
+            session_id: session.id,
+        },
+        permissions: user.permissions(Utc::now()),
+    })
+}
+
+fn authenticate_api_key(state: &AppState, key: &str) -> Result<AuthUser, Error> {
+    let api_key = match state.auth.get_api_key_by_hash(&hash_api_key(key)) {
+        Ok(api_key) => api_key,
+        Err(RepositoryError::NotFound) => {
+            return Err(Error::unauthorized(AuthError::InvalidToken.to_string()))
+        }
+        Err(err) => {
+            tracing::warn!("{}", err);
+            return Err(Error::server_issue());
+        }
+    };
+
+    let now = Utc::now();
+
+    if api_key
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= now)
+    {
+        return Err(Error::unauthorized(AuthError::ApiKeyExpired.to_string()));
+    }
+
+    // Failing to record the use of the key is no reason to refuse the request
+    if let Err(err) = state.auth.set_api_key_last_used(api_key.id, now) {
+        tracing::warn!("{}", err);
+    }
+
+    Ok(AuthUser {
+        principal: Principal::ApiKey {
+            key_id: api_key.id,
+            name: api_key.name,
+        },
+        permissions: api_key.permissions,
+    })
+}
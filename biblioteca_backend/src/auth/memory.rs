@@ -0,0 +1,196 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    audit::{
+        context::AuditContext,
+        model::{AuditAction, AuditEntry, EntityType},
+    },
+    repository::{MemoryRepository, RepositoryError, Result},
+};
+
+use super::{
+    model::{ApiKey, Credentials, Session},
+    repository::AuthRepository,
+};
+
+impl AuthRepository for MemoryRepository {
+    fn get_credentials(&self, username: &str) -> Result<Credentials> {
+        let data = self.data();
+
+        let user = data
+            .users
+            .iter()
+            .find(|user| user.username == username && user.deleted_at.is_none())
+            .ok_or(RepositoryError::NotFound)?;
+
+        Ok(Credentials {
+            user_id: user.id,
+            password_hash: data.password_hashes.get(&user.id).cloned(),
+        })
+    }
+
+    fn set_password_hash(&self, user_id: Uuid, password_hash: &str) -> Result<()> {
+        let mut data = self.data();
+
+        if !data.users.iter().any(|user| user.id == user_id) {
+            return Err(RepositoryError::NotFound);
+        }
+
+        data.password_hashes
+            .insert(user_id, password_hash.to_string());
+
+        data.audit_entries.push(AuditEntry::new::<()>(
+            &AuditContext::current(),
+            AuditAction::ChangePassword,
+            EntityType::User,
+            user_id,
+            None,
+            None,
+        ));
+
+        Ok(())
+    }
+
+    fn add_session(&self, session: Session) -> Result<Session> {
+        self.data().sessions.push(session.clone());
+
+        Ok(session)
+    }
+
+    fn get_session(&self, id: Uuid) -> Result<Session> {
+        let data = self.data();
+
+        data.sessions
+            .iter()
+            .find(|session| {
+                session.id == id && data.users.iter().any(|user| user.id == session.user_id)
+            })
+            .cloned()
+            .ok_or(RepositoryError::NotFound)
+    }
+
+    fn delete_session(&self, id: Uuid) -> Result<()> {
+        self.data().sessions.retain(|session| session.id != id);
+
+        Ok(())
+    }
+
+    fn delete_expired_sessions(&self, now: DateTime<Utc>) -> Result<()> {
+        self.data()
+            .sessions
+            .retain(|session| session.expires_at > now);
+
+        Ok(())
+    }
+
+    fn add_api_key(&self, api_key: ApiKey) -> Result<ApiKey> {
+        let mut data = self.data();
+
+        data.api_keys.push(api_key.clone());
+
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Create,
+            EntityType::ApiKey,
+            api_key.id,
+            None,
+            Some(&api_key),
+        ));
+
+        Ok(api_key)
+    }
+
+    fn list_api_keys(&self) -> Result<Vec<ApiKey>> {
+        Ok(self.data().api_keys.clone())
+    }
+
+    fn get_api_key(&self, id: Uuid) -> Result<ApiKey> {
+        self.data()
+            .api_keys
+            .iter()
+            .find(|api_key| api_key.id == id)
+            .cloned()
+            .ok_or(RepositoryError::NotFound)
+    }
+
+    fn get_api_key_by_hash(&self, key_hash: &str) -> Result<ApiKey> {
+        self.data()
+            .api_keys
+            .iter()
+            .find(|api_key| api_key.key_hash == key_hash)
+            .cloned()
+            .ok_or(RepositoryError::NotFound)
+    }
+
+    fn is_api_key_name_valid(&self, name: &str) -> Result<bool> {
+        Ok(!self
+            .data()
+            .api_keys
+            .iter()
+            .any(|api_key| api_key.name == name))
+    }
+
+    fn set_api_key_hash(&self, id: Uuid, prefix: &str, key_hash: &str) -> Result<()> {
+        let mut data = self.data();
+
+        let api_key = data
+            .api_keys
+            .iter_mut()
+            .find(|api_key| api_key.id == id)
+            .ok_or(RepositoryError::NotFound)?;
+
+        let before = api_key.clone();
+        api_key.prefix = prefix.to_string();
+        api_key.key_hash = key_hash.to_string();
+        let after = api_key.clone();
+
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Update,
+            EntityType::ApiKey,
+            id,
+            Some(&before),
+            Some(&after),
+        ));
+
+        Ok(())
+    }
+
+    fn set_api_key_last_used(&self, id: Uuid, last_used_at: DateTime<Utc>) -> Result<()> {
+        if let Some(api_key) = self
+            .data()
+            .api_keys
+            .iter_mut()
+            .find(|api_key| api_key.id == id)
+        {
+            api_key.last_used_at = Some(last_used_at);
+        }
+
+        Ok(())
+    }
+
+    fn delete_api_key(&self, id: Uuid) -> Result<()> {
+        let mut data = self.data();
+
+        let api_key = data
+            .api_keys
+            .iter()
+            .find(|api_key| api_key.id == id)
+            .cloned()
+            .ok_or(RepositoryError::NotFound)?;
+
+        data.api_keys.retain(|api_key| api_key.id != id);
+
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Delete,
+            EntityType::ApiKey,
+            id,
+            Some(&api_key),
+            None,
+        ));
+
+        Ok(())
+    }
+}
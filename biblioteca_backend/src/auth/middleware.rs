@@ -0,0 +1,74 @@
+use axum::{
+    extract::{FromRequestParts, State},
+    http::{Method, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::{app::AppState, audit::context::AuditContext, users::model::Permission};
+
+use super::extractor::AuthUser;
+
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+// Who may make the requests of a router, checked before they reach its handlers
+#[derive(Debug, Clone, Copy)]
+pub enum Access {
+    // Anyone may read, while changes require the permission
+    PublicRead(Permission),
+    // Every request requires the permission
+    Restricted(Permission),
+    // Every request requires a logged in user, whose handlers check what the user may act on
+    LoggedIn,
+}
+
+// Authenticates the requests of a router and checks them against its access, passing the
+// authenticated user on to the handlers. What the handlers log and change is tagged with who
+// made the request and its id, taken from its `X-Request-Id` header if it has one.
+pub async fn authorize<B>(
+    State((state, access)): State<(AppState, Access)>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    if let Access::PublicRead(_) = access {
+        if matches!(*request.method(), Method::GET | Method::HEAD) {
+            return next.run(request).await;
+        }
+    }
+
+    let (mut parts, body) = request.into_parts();
+
+    let auth_user = match AuthUser::from_request_parts(&mut parts, &state).await {
+        Ok(auth_user) => auth_user,
+        Err(err) => return err.into_response(),
+    };
+
+    if let Access::PublicRead(permission) | Access::Restricted(permission) = access {
+        if let Err(err) = auth_user.require_permission(permission) {
+            tracing::debug!("{} was refused: {}", auth_user.principal, err);
+            return err.into_response();
+        }
+    }
+
+    let request_id = parts
+        .headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|request_id| request_id.to_str().ok())
+        .map(|request_id| request_id.to_string())
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let span = tracing::info_span!(
+        "request",
+        principal = %auth_user.principal,
+        request_id = %request_id
+    );
+    let context = AuditContext::new(&auth_user.principal, request_id);
+    parts.extensions.insert(auth_user);
+
+    context
+        .scope(next.run(Request::from_parts(parts, body)).instrument(span))
+        .await
+}
@@ -0,0 +1,14 @@
+pub mod api_keys;
+pub mod controller;
+pub mod error;
+pub mod extractor;
+pub mod middleware;
+pub mod model;
+pub mod password;
+pub mod repository;
+pub mod token;
+
+mod db;
+mod memory;
+#[cfg(feature = "postgres")]
+mod postgres;
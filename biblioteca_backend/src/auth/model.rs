@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::users::model::Permission;
+
+// A login of a user, which the tokens issued for it refer to until it expires or is logged out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+// Password hash of a user, which is missing for users that have not been given a password
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    pub user_id: Uuid,
+    pub password_hash: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoginResponse {
+    pub token: String,
+    pub user_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+}
+
+// Key a machine client authenticates with in place of a user, which only the hash of is stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: Uuid,
+    // Name of the client, which the requests it makes are logged under
+    pub name: String,
+    // First characters of the key, to tell keys apart without storing the keys themselves
+    pub prefix: String,
+    #[serde(skip)]
+    pub key_hash: String,
+    pub permissions: Vec<Permission>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+// API key along with the key itself, which is only ever shown when it is issued or rotated
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IssuedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthSettings {
+    // Key the tokens are signed with, which is generated anew on every start if not given
+    pub token_secret: Option<String>,
+
+    // Number of hours a login lasts before it has to be made again
+    pub session_ttl_hours: u32,
+}
+
+impl Default for AuthSettings {
+    fn default() -> Self {
+        AuthSettings {
+            token_secret: None,
+            session_ttl_hours: 24,
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use chrono::{DateTime, Utc};
+use postgres::{GenericClient, Row};
+use uuid::Uuid;
+
+use crate::{
+    audit::{
+        context::AuditContext,
+        model::{AuditAction, AuditEntry, EntityType},
+        postgres::add_audit_entry,
+    },
+    repository::{PostgresRepository, RepositoryError, Result},
+    users::model::{join_permissions, split_permissions},
+};
+
+use super::{
+    model::{ApiKey, Credentials, Session},
+    repository::AuthRepository,
+};
+
+impl AuthRepository for PostgresRepository {
+    fn get_credentials(&self, username: &str) -> Result<Credentials> {
+        self.with_client(|client| {
+            let row = client
+                .query_opt(
+                    "SELECT id, password_hash FROM users WHERE username = $1 AND deleted_at IS NULL",
+                    &[&username],
+                )?
+                .ok_or(RepositoryError::NotFound)?;
+
+            Ok(Credentials {
+                user_id: row.try_get(0)?,
+                password_hash: row.try_get(1)?,
+            })
+        })
+    }
+
+    fn set_password_hash(&self, user_id: Uuid, password_hash: &str) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            let num_updated = tx.execute(
+                "UPDATE users SET password_hash = $1 WHERE id = $2",
+                &[&password_hash, &user_id],
+            )?;
+
+            if num_updated == 0 {
+                return Err(RepositoryError::NotFound);
+            }
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new::<()>(
+                    &context,
+                    AuditAction::ChangePassword,
+                    EntityType::User,
+                    user_id,
+                    None,
+                    None,
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn add_session(&self, session: Session) -> Result<Session> {
+        self.with_client(|client| {
+            client.execute(
+                "INSERT INTO sessions (id, user_id, created_at, expires_at) VALUES ($1, $2, $3, $4)",
+                &[
+                    &session.id,
+                    &session.user_id,
+                    &session.created_at,
+                    &session.expires_at,
+                ],
+            )?;
+
+            Ok(session)
+        })
+    }
+
+    fn get_session(&self, id: Uuid) -> Result<Session> {
+        self.with_client(|client| {
+            let row = client
+                .query_opt(
+                    "SELECT a.id, a.user_id, a.created_at, a.expires_at FROM sessions a
+                    JOIN users b ON a.user_id = b.id
+                    WHERE a.id = $1",
+                    &[&id],
+                )?
+                .ok_or(RepositoryError::NotFound)?;
+
+            Ok(session_from_row(&row)?)
+        })
+    }
+
+    fn delete_session(&self, id: Uuid) -> Result<()> {
+        self.with_client(|client| {
+            client.execute("DELETE FROM sessions WHERE id = $1", &[&id])?;
+
+            Ok(())
+        })
+    }
+
+    fn delete_expired_sessions(&self, now: DateTime<Utc>) -> Result<()> {
+        self.with_client(|client| {
+            client.execute("DELETE FROM sessions WHERE expires_at <= $1", &[&now])?;
+
+            Ok(())
+        })
+    }
+
+    fn add_api_key(&self, api_key: ApiKey) -> Result<ApiKey> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            tx.execute(
+                "INSERT INTO api_keys (id, name, prefix, key_hash, permissions, created_at, expires_at, last_used_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+                &[
+                    &api_key.id,
+                    &api_key.name,
+                    &api_key.prefix,
+                    &api_key.key_hash,
+                    &join_permissions(&api_key.permissions),
+                    &api_key.created_at,
+                    &api_key.expires_at,
+                    &api_key.last_used_at,
+                ],
+            )?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Create,
+                    EntityType::ApiKey,
+                    api_key.id,
+                    None,
+                    Some(&api_key),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(api_key)
+        })
+    }
+
+    fn list_api_keys(&self) -> Result<Vec<ApiKey>> {
+        self.with_client(|client| {
+            let rows = client.query(&format!("{} ORDER BY created_at", API_KEY_SELECT), &[])?;
+
+            Ok(rows
+                .iter()
+                .map(api_key_from_row)
+                .collect::<Result<Vec<_>, _>>()?)
+        })
+    }
+
+    fn get_api_key(&self, id: Uuid) -> Result<ApiKey> {
+        self.with_client(|client| fetch_api_key(client, id))
+    }
+
+    fn get_api_key_by_hash(&self, key_hash: &str) -> Result<ApiKey> {
+        self.with_client(|client| {
+            let row = client
+                .query_opt(
+                    &format!("{} WHERE key_hash = $1", API_KEY_SELECT),
+                    &[&key_hash],
+                )?
+                .ok_or(RepositoryError::NotFound)?;
+
+            Ok(api_key_from_row(&row)?)
+        })
+    }
+
+    fn is_api_key_name_valid(&self, name: &str) -> Result<bool> {
+        self.with_client(|client| {
+            let count: i64 = client
+                .query_one("SELECT COUNT(*) FROM api_keys WHERE name = $1", &[&name])?
+                .try_get(0)?;
+
+            Ok(count == 0)
+        })
+    }
+
+    fn set_api_key_hash(&self, id: Uuid, prefix: &str, key_hash: &str) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            let before = fetch_api_key(&mut tx, id)?;
+
+            tx.execute(
+                "UPDATE api_keys SET prefix = $1, key_hash = $2 WHERE id = $3",
+                &[&prefix, &key_hash, &id],
+            )?;
+
+            let after = fetch_api_key(&mut tx, id)?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Update,
+                    EntityType::ApiKey,
+                    id,
+                    Some(&before),
+                    Some(&after),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn set_api_key_last_used(&self, id: Uuid, last_used_at: DateTime<Utc>) -> Result<()> {
+        self.with_client(|client| {
+            client.execute(
+                "UPDATE api_keys SET last_used_at = $1 WHERE id = $2",
+                &[&last_used_at, &id],
+            )?;
+
+            Ok(())
+        })
+    }
+
+    fn delete_api_key(&self, id: Uuid) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            let api_key = fetch_api_key(&mut tx, id)?;
+
+            tx.execute("DELETE FROM api_keys WHERE id = $1", &[&id])?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Delete,
+                    EntityType::ApiKey,
+                    id,
+                    Some(&api_key),
+                    None,
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+}
+
+const API_KEY_SELECT: &str = "SELECT id, name, prefix, key_hash, permissions, created_at, expires_at, last_used_at FROM api_keys";
+
+fn fetch_api_key(client: &mut impl GenericClient, id: Uuid) -> Result<ApiKey> {
+    let row = client
+        .query_opt(&format!("{} WHERE id = $1", API_KEY_SELECT), &[&id])?
+        .ok_or(RepositoryError::NotFound)?;
+
+    Ok(api_key_from_row(&row)?)
+}
+
+fn api_key_from_row(row: &Row) -> Result<ApiKey, postgres::Error> {
+    Ok(ApiKey {
+        id: row.try_get(0)?,
+        name: row.try_get(1)?,
+        prefix: row.try_get(2)?,
+        key_hash: row.try_get(3)?,
+        permissions: split_permissions(row.try_get(4)?),
+        created_at: row.try_get(5)?,
+        expires_at: row.try_get(6)?,
+        last_used_at: row.try_get(7)?,
+    })
+}
+
+fn session_from_row(row: &Row) -> Result<Session, postgres::Error> {
+    Ok(Session {
+        id: row.try_get(0)?,
+        user_id: row.try_get(1)?,
+        created_at: row.try_get(2)?,
+        expires_at: row.try_get(3)?,
+    })
+}
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::repository::Result;
+
+use super::model::{ApiKey, Credentials, Session};
+
+// Storage of the passwords of users, the sessions they log in with and the API keys of machine
+// clients
+pub trait AuthRepository: Send + Sync {
+    fn get_credentials(&self, username: &str) -> Result<Credentials>;
+
+    fn set_password_hash(&self, user_id: Uuid, password_hash: &str) -> Result<()>;
+
+    fn add_session(&self, session: Session) -> Result<Session>;
+
+    // Gets the session, as long as its user still exists
+    fn get_session(&self, id: Uuid) -> Result<Session>;
+
+    fn delete_session(&self, id: Uuid) -> Result<()>;
+
+    fn delete_expired_sessions(&self, now: DateTime<Utc>) -> Result<()>;
+
+    fn add_api_key(&self, api_key: ApiKey) -> Result<ApiKey>;
+
+    fn list_api_keys(&self) -> Result<Vec<ApiKey>>;
+
+    fn get_api_key(&self, id: Uuid) -> Result<ApiKey>;
+
+    fn get_api_key_by_hash(&self, key_hash: &str) -> Result<ApiKey>;
+
+    fn is_api_key_name_valid(&self, name: &str) -> Result<bool>;
+
+    // Replaces the key of the API key, which stops the previous key from being accepted
+    fn set_api_key_hash(&self, id: Uuid, prefix: &str, key_hash: &str) -> Result<()>;
+
+    fn set_api_key_last_used(&self, id: Uuid, last_used_at: DateTime<Utc>) -> Result<()>;
+
+    fn delete_api_key(&self, id: Uuid) -> Result<()>;
+}
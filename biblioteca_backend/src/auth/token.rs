@@ -0,0 +1,78 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Start of every API key, which tells them apart from the tokens of sessions
+pub const API_KEY_PREFIX: &str = "bib_";
+
+// Number of characters of an API key kept to tell it apart from other keys
+const API_KEY_PREFIX_LENGTH: usize = 12;
+
+// Signs the ids of sessions into bearer tokens, so that tokens cannot be made up without the
+// key. The session itself is still looked up, so that logging out revokes its tokens.
+#[derive(Clone)]
+pub struct TokenSigner {
+    key: Vec<u8>,
+}
+
+impl TokenSigner {
+    // Creates a signer with the secret, or with a random key if there is none
+    pub fn new(secret: Option<&str>) -> Self {
+        let key = match secret {
+            Some(secret) => secret.as_bytes().to_vec(),
+            None => rand::random::<[u8; 32]>().to_vec(),
+        };
+
+        TokenSigner { key }
+    }
+
+    pub fn sign(&self, session_id: Uuid) -> String {
+        let signature = self.mac(session_id).finalize().into_bytes();
+
+        format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(session_id.as_bytes()),
+            URL_SAFE_NO_PAD.encode(signature)
+        )
+    }
+
+    // Gets the id of the session the token was signed for, if its signature is valid
+    pub fn verify(&self, token: &str) -> Option<Uuid> {
+        let (session_id, signature) = token.split_once('.')?;
+
+        let session_id = Uuid::from_slice(&URL_SAFE_NO_PAD.decode(session_id).ok()?).ok()?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+
+        self.mac(session_id)
+            .verify_slice(&signature)
+            .ok()
+            .map(|_| session_id)
+    }
+
+    fn mac(&self, session_id: Uuid) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).unwrap();
+        mac.update(session_id.as_bytes());
+        mac
+    }
+}
+
+// Generates a new API key, which is random enough that hashing it without a salt is safe
+pub fn generate_api_key() -> String {
+    format!(
+        "{}{}",
+        API_KEY_PREFIX,
+        URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+    )
+}
+
+pub fn hash_api_key(key: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(key.as_bytes()))
+}
+
+// Gets the characters of the API key that are shown in place of it
+pub fn api_key_prefix(key: &str) -> String {
+    key.chars().take(API_KEY_PREFIX_LENGTH).collect()
+}
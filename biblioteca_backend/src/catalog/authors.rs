@@ -1,30 +1,31 @@
 use std::{collections::HashMap, str::FromStr};
 
 use crate::app::AppState;
-use crate::catalog::db::{
-    delete_author_from_db, get_author_from_db, list_authors_from_db, update_author_in_db,
-};
+use crate::auth::extractor::OptionalAuthUser;
+use crate::catalog::repository::{AUTHOR_LIST, BOOK_LIST};
 use crate::error::Error;
+use crate::query::{ListParams, Page};
+use crate::repository::RepositoryError;
+use crate::users::model::Permission;
 
 use axum::routing::{delete, get, post, put};
 use axum::Router;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{StatusCode, Uri},
     Json,
 };
 use uuid::Uuid;
 
-use super::{
-    db::add_author_to_db,
-    model::{Author, CreateAuthorRequest, UpdateAuthorRequest},
-};
+use super::model::{Author, Book, CreateAuthorRequest, UpdateAuthorRequest};
 
 pub fn authors_router() -> Router<AppState> {
     Router::new()
         .route("/authors/:id", get(get_author))
         .route("/authors/:id", delete(delete_author))
         .route("/authors/:id", put(update_author))
+        .route("/authors/:id/restore", post(restore_author))
+        .route("/authors/:id/books", get(list_author_books))
         .route("/authors", get(list_authors))
         .route("/authors", post(create_author))
 }
@@ -32,7 +33,7 @@ pub fn authors_router() -> Router<AppState> {
 async fn get_author(state: State<AppState>, Path(id): Path<String>) -> Result<Json<Author>, Error> {
     tracing::debug!("GET /authors with id: {:?}", id);
 
-    match get_author_from_db(state, Uuid::from_str(&id).unwrap()).await {
+    match state.catalog.get_author(Uuid::from_str(&id).unwrap()) {
         Ok(author) => Ok(Json(author)),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -43,11 +44,20 @@ async fn get_author(state: State<AppState>, Path(id): Path<String>) -> Result<Js
 
 async fn list_authors(
     state: State<AppState>,
+    auth_user: OptionalAuthUser,
+    uri: Uri,
     Query(params): Query<HashMap<String, String>>,
-) -> Result<Json<Vec<Author>>, Error> {
+) -> Result<Json<Page<Author>>, Error> {
     tracing::debug!("GET /authors with query params: {:?}", params);
 
-    match list_authors_from_db(state, params).await {
+    let params = ListParams::parse(&uri, params, &AUTHOR_LIST)
+        .map_err(|err| Error::bad_request(err.to_string()))?;
+
+    if params.include_deleted() {
+        auth_user.require_permission(Permission::CatalogWrite)?;
+    }
+
+    match state.catalog.list_authors(&params) {
         Ok(authors) => Ok(Json(authors)),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -56,6 +66,39 @@ async fn list_authors(
     }
 }
 
+async fn list_author_books(
+    state: State<AppState>,
+    auth_user: OptionalAuthUser,
+    Path(id): Path<Uuid>,
+    uri: Uri,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Page<Book>>, Error> {
+    tracing::debug!(
+        "GET /authors/:id/books with id: {:?} and query params: {:?}",
+        id,
+        params
+    );
+
+    let params = ListParams::parse(&uri, params, &BOOK_LIST)
+        .map_err(|err| Error::bad_request(err.to_string()))?;
+
+    if params.include_deleted() {
+        auth_user.require_permission(Permission::CatalogWrite)?;
+    }
+
+    if !state.catalog.is_author_exists(id).unwrap() {
+        return Err(Error::not_found());
+    }
+
+    match state.catalog.list_books_of_author(id, &params) {
+        Ok(books) => Ok(Json(books)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
 async fn create_author(
     state: State<AppState>,
     Json(payload): Json<CreateAuthorRequest>,
@@ -67,9 +110,10 @@ async fn create_author(
         name: payload.name,
         description: payload.description,
         country: payload.country,
+        deleted_at: None,
     };
 
-    match add_author_to_db(state, author).await {
+    match state.catalog.add_author(author) {
         Ok(author) => Ok(Json(author)),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -84,7 +128,7 @@ async fn delete_author(
 ) -> Result<StatusCode, Error> {
     tracing::debug!("DELETE /authors with id: {:?}", id);
 
-    match delete_author_from_db(state, Uuid::from_str(&id).unwrap()).await {
+    match state.catalog.delete_author(Uuid::from_str(&id).unwrap()) {
         Ok(()) => Ok(StatusCode::NO_CONTENT),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -93,6 +137,22 @@ async fn delete_author(
     }
 }
 
+async fn restore_author(
+    state: State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Author>, Error> {
+    tracing::debug!("POST /authors/:id/restore with id: {:?}", id);
+
+    match state.catalog.restore_author(id) {
+        Ok(author) => Ok(Json(author)),
+        Err(RepositoryError::NotFound) => Err(Error::not_found()),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
 async fn update_author(
     state: State<AppState>,
     Path(id): Path<String>,
@@ -105,9 +165,10 @@ async fn update_author(
         name: payload.name,
         description: payload.description,
         country: payload.country,
+        deleted_at: None,
     };
 
-    match update_author_in_db(state, author).await {
+    match state.catalog.update_author(author) {
         Ok(()) => Ok(StatusCode::NO_CONTENT),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -1,12 +1,13 @@
 use crate::app::AppState;
-use crate::catalog::db::is_author_exists_in_db;
+use crate::auth::extractor::OptionalAuthUser;
 use crate::catalog::error::CatalogError;
+use crate::catalog::repository::BOOK_LIST;
+use crate::users::model::Permission;
 
 use super::super::error::Error;
-use super::db::{
-    add_book_to_db, delete_book_from_db, get_book_from_db, list_books_from_db, update_book_in_db,
-};
-use super::model::{Book, CreateBookRequest, UpdateBookRequest};
+use super::model::{Book, BookContributorRequest, CreateBookRequest, UpdateBookRequest};
+use crate::query::{ListParams, Page};
+use crate::repository::RepositoryError;
 
 use axum::extract::State;
 use axum::routing::{delete, get, post, put};
@@ -17,7 +18,7 @@ use std::{collections::HashMap, str::FromStr};
 
 use axum::{
     extract::{Path, Query},
-    http::StatusCode,
+    http::{StatusCode, Uri},
     Json,
 };
 
@@ -26,6 +27,7 @@ pub fn books_router() -> Router<AppState> {
         .route("/books/:id", get(get_book))
         .route("/books/:id", delete(delete_book))
         .route("/books/:id", put(update_book))
+        .route("/books/:id/restore", post(restore_book))
         .route("/books", get(list_books))
         .route("/books", post(create_book))
 }
@@ -34,7 +36,7 @@ pub fn books_router() -> Router<AppState> {
 async fn get_book(state: State<AppState>, Path(id): Path<String>) -> Result<Json<Book>, Error> {
     tracing::debug!("GET /books with id: {:?}", id);
 
-    match get_book_from_db(state, Uuid::from_str(&id).unwrap()).await {
+    match state.catalog.get_book(Uuid::from_str(&id).unwrap()) {
         Ok(book) => Ok(Json(book)),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -43,14 +45,23 @@ async fn get_book(state: State<AppState>, Path(id): Path<String>) -> Result<Json
     }
 }
 
-// Retrieves all books
+// Retrieves all books, where only catalog staff may include the deleted ones
 async fn list_books(
     state: State<AppState>,
+    auth_user: OptionalAuthUser,
+    uri: Uri,
     Query(params): Query<HashMap<String, String>>,
-) -> Result<Json<Vec<Book>>, Error> {
+) -> Result<Json<Page<Book>>, Error> {
     tracing::debug!("GET /books with query params: {:?}", params);
 
-    match list_books_from_db(state, params).await {
+    let params = ListParams::parse(&uri, params, &BOOK_LIST)
+        .map_err(|err| Error::bad_request(err.to_string()))?;
+
+    if params.include_deleted() {
+        auth_user.require_permission(Permission::CatalogWrite)?;
+    }
+
+    match state.catalog.list_books(&params) {
         Ok(books) => Ok(Json(books)),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -70,13 +81,27 @@ async fn create_book(
         name: payload.name,
         description: payload.description,
         language: payload.language,
+        contributors: Vec::new(),
+        categories: Vec::new(),
+        total_copies: 0,
+        available_copies: 0,
+        deleted_at: None,
     };
 
-    if !is_author_exists_in_db(&state, payload.author_id).unwrap() {
-        return Err(Error::bad_request(CatalogError::AuthorNotFound.to_string()));
+    validate_contributors(&state, &payload.contributors)?;
+
+    for category_id in payload.category_ids.iter() {
+        if !state.catalog.is_category_exists(*category_id).unwrap() {
+            return Err(Error::bad_request(
+                CatalogError::CategoryNotFound.to_string(),
+            ));
+        }
     }
 
-    match add_book_to_db(state, book, payload.author_id).await {
+    match state
+        .catalog
+        .add_book(book, &payload.contributors, &payload.category_ids)
+    {
         Ok(book) => Ok(Json(book)),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -85,11 +110,17 @@ async fn create_book(
     }
 }
 
-// Deletes a specific book
+// Deletes a specific book, unless any of its copies are on loan
 async fn delete_book(state: State<AppState>, Path(id): Path<String>) -> Result<StatusCode, Error> {
     tracing::debug!("DELETE /books with id: {:?}", id);
 
-    match delete_book_from_db(state, Uuid::from_str(&id).unwrap()).await {
+    let id = Uuid::from_str(&id).unwrap();
+
+    if state.library.get_num_borrowed_of_book(id).unwrap() > 0 {
+        return Err(Error::bad_request(CatalogError::BookOnLoan.to_string()));
+    }
+
+    match state.catalog.delete_book(id) {
         Ok(()) => Ok(StatusCode::NO_CONTENT),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -98,6 +129,20 @@ async fn delete_book(state: State<AppState>, Path(id): Path<String>) -> Result<S
     }
 }
 
+// Restores a specific book that was deleted
+async fn restore_book(state: State<AppState>, Path(id): Path<Uuid>) -> Result<Json<Book>, Error> {
+    tracing::debug!("POST /books/:id/restore with id: {:?}", id);
+
+    match state.catalog.restore_book(id) {
+        Ok(book) => Ok(Json(book)),
+        Err(RepositoryError::NotFound) => Err(Error::not_found()),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
 // Updates a specific book
 async fn update_book(
     state: State<AppState>,
@@ -111,13 +156,27 @@ async fn update_book(
         name: payload.name,
         description: payload.description,
         language: payload.language,
+        contributors: Vec::new(),
+        categories: Vec::new(),
+        total_copies: 0,
+        available_copies: 0,
+        deleted_at: None,
     };
 
-    if !is_author_exists_in_db(&state, payload.author_id).unwrap() {
-        return Err(Error::bad_request(CatalogError::AuthorNotFound.to_string()));
+    validate_contributors(&state, &payload.contributors)?;
+
+    for category_id in payload.category_ids.iter() {
+        if !state.catalog.is_category_exists(*category_id).unwrap() {
+            return Err(Error::bad_request(
+                CatalogError::CategoryNotFound.to_string(),
+            ));
+        }
     }
 
-    match update_book_in_db(state, book, payload.author_id).await {
+    match state
+        .catalog
+        .update_book(book, &payload.contributors, &payload.category_ids)
+    {
         Ok(()) => Ok(StatusCode::NO_CONTENT),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -125,3 +184,34 @@ async fn update_book(
         }
     }
 }
+
+// Checks that a book has contributors, and that each of them is a known author listed once per role
+fn validate_contributors(
+    state: &State<AppState>,
+    contributors: &[BookContributorRequest],
+) -> Result<(), Error> {
+    if contributors.is_empty() {
+        return Err(Error::bad_request(CatalogError::NoContributors.to_string()));
+    }
+
+    for (index, contributor) in contributors.iter().enumerate() {
+        if !state
+            .catalog
+            .is_author_exists(contributor.author_id)
+            .unwrap()
+        {
+            return Err(Error::bad_request(CatalogError::AuthorNotFound.to_string()));
+        }
+
+        if contributors[..index]
+            .iter()
+            .any(|other| other.author_id == contributor.author_id && other.role == contributor.role)
+        {
+            return Err(Error::bad_request(
+                CatalogError::DuplicateContributor.to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use crate::app::AppState;
+use crate::catalog::error::CatalogError;
+use crate::catalog::repository::CATEGORY_LIST;
+use crate::error::Error;
+use crate::query::{ListParams, Page};
+
+use axum::routing::{delete, get, post, put};
+use axum::Router;
+use axum::{
+    extract::{Path, Query, State},
+    http::{StatusCode, Uri},
+    Json,
+};
+use uuid::Uuid;
+
+use super::model::{Category, CreateCategoryRequest, UpdateCategoryRequest};
+
+pub fn categories_router() -> Router<AppState> {
+    Router::new()
+        .route("/categories/:id", get(get_category))
+        .route("/categories/:id", delete(delete_category))
+        .route("/categories/:id", put(update_category))
+        .route("/categories", get(list_categories))
+        .route("/categories", post(create_category))
+}
+
+// Retrieves a specific category, by id
+async fn get_category(
+    state: State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Category>, Error> {
+    tracing::debug!("GET /categories with id: {:?}", id);
+
+    match state.catalog.get_category(id) {
+        Ok(category) => Ok(Json(category)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::not_found())
+        }
+    }
+}
+
+// Retrieves all categories
+async fn list_categories(
+    state: State<AppState>,
+    uri: Uri,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Page<Category>>, Error> {
+    tracing::debug!("GET /categories with query params: {:?}", params);
+
+    let params = ListParams::parse(&uri, params, &CATEGORY_LIST)
+        .map_err(|err| Error::bad_request(err.to_string()))?;
+
+    match state.catalog.list_categories(&params) {
+        Ok(categories) => Ok(Json(categories)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Creates a new category
+async fn create_category(
+    state: State<AppState>,
+    Json(payload): Json<CreateCategoryRequest>,
+) -> Result<Json<Category>, Error> {
+    tracing::debug!("POST /categories with params: {:?}", payload);
+
+    if !state
+        .catalog
+        .is_category_name_valid(&payload.name, None)
+        .unwrap()
+    {
+        return Err(Error::bad_request(
+            CatalogError::CategoryAlreadyExists.to_string(),
+        ));
+    }
+
+    let category = Category {
+        id: Uuid::new_v4(),
+        name: payload.name,
+    };
+
+    match state.catalog.add_category(category) {
+        Ok(category) => Ok(Json(category)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Deletes a specific category, removing it from all books
+async fn delete_category(
+    state: State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, Error> {
+    tracing::debug!("DELETE /categories with id: {:?}", id);
+
+    match state.catalog.delete_category(id) {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Updates a specific category
+async fn update_category(
+    state: State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateCategoryRequest>,
+) -> Result<StatusCode, Error> {
+    tracing::debug!("PUT /categories with id: {:?}", id);
+
+    if !state
+        .catalog
+        .is_category_name_valid(&payload.name, Some(id))
+        .unwrap()
+    {
+        return Err(Error::bad_request(
+            CatalogError::CategoryAlreadyExists.to_string(),
+        ));
+    }
+
+    let category = Category {
+        id,
+        name: payload.name,
+    };
+
+    match state.catalog.update_category(category) {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
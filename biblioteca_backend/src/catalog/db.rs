@@ -1,246 +1,1033 @@
-use std::collections::HashMap;
-
-use axum::extract::State;
-use rusqlite::Result;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, Params, Row};
 use uuid::Uuid;
 
-use crate::app::AppState;
+use crate::{
+    audit::{
+        context::AuditContext,
+        db::add_audit_entry,
+        model::{AuditAction, AuditEntry, EntityType},
+    },
+    library::db::{cancel_holds_of_book, pass_item_to_next_hold},
+    query::{Filters, ListParams, Page},
+    repository::{like_pattern, sqlite::QueryBuilder, RepositoryError, Result, SqliteRepository},
+};
+
+use super::{
+    model::{
+        highlight_snippet, Author, Book, BookContributor, BookContributorRequest, Category, Item,
+        ItemStatus, SearchResult, SearchResultKind,
+    },
+    repository::CatalogRepository,
+};
+
+// Columns selected for a book, including the number of copies held of it, which leaves out the
+// withdrawn ones
+const BOOK_COLUMNS: &str = "id, name, description, language,
+    (SELECT COUNT(*) FROM items WHERE items.book_id = books.id AND items.status != 'withdrawn'),
+    (SELECT COUNT(*) FROM items WHERE items.book_id = books.id AND items.status = 'available'),
+    deleted_at";
+
+const AUTHOR_COLUMNS: &str = "id, name, description, country, deleted_at";
 
-use super::model::{Author, Book};
+// Columns of the sort fields of books, authors and categories
+const BOOK_SORT_COLUMNS: &[(&str, &str)] = &[("name", "name"), ("language", "language")];
+const AUTHOR_SORT_COLUMNS: &[(&str, &str)] = &[("name", "name"), ("country", "country")];
+const CATEGORY_SORT_COLUMNS: &[(&str, &str)] = &[("name", "name")];
 
-pub async fn list_books_from_db(
-    State(state): State<AppState>,
-    params: HashMap<String, String>,
-) -> Result<Vec<Book>> {
-    let conn = state.db_pool.get().unwrap();
+// Ranks search results with names weighted over descriptions
+const SEARCH_ORDER: &str = "bm25(catalog_search, 0.0, 0.0, 10.0, 1.0)";
 
-    let mut stmt_string = String::from("SELECT * FROM books WHERE 1=1");
+impl CatalogRepository for SqliteRepository {
+    fn list_books(&self, params: &ListParams) -> Result<Page<Book>> {
+        let conn = self.pool.get().unwrap();
 
-    if params.contains_key("name") {
-        stmt_string.push_str(&format!(
-            " AND name LIKE '%{}%'",
-            params.get("name").unwrap()
-        ));
+        let mut query = QueryBuilder::new(&format!("SELECT {} FROM books", BOOK_COLUMNS));
+        push_book_filters(&mut query, &params.filters);
+        query
+            .exclude_deleted("deleted_at", params)
+            .paginate(params, BOOK_SORT_COLUMNS, "rowid");
+
+        let books = query_books(&conn, &query.sql(), query.params())?;
+
+        Ok(Page::new(books, query.count(&conn)?, params))
     }
 
-    if params.contains_key("language") {
-        stmt_string.push_str(&format!(
-            " AND language LIKE '%{}%'",
-            params.get("language").unwrap()
-        ));
+    fn list_books_of_author(&self, author_id: Uuid, params: &ListParams) -> Result<Page<Book>> {
+        let conn = self.pool.get().unwrap();
+
+        let mut query = QueryBuilder::new(&format!("SELECT {} FROM books", BOOK_COLUMNS));
+        query.condition(
+            "id IN (SELECT book_id FROM map_books_to_authors WHERE author_id = ?)",
+            author_id,
+        );
+        push_book_filters(&mut query, &params.filters);
+        query
+            .exclude_deleted("deleted_at", params)
+            .paginate(params, BOOK_SORT_COLUMNS, "rowid");
+
+        let books = query_books(&conn, &query.sql(), query.params())?;
+
+        Ok(Page::new(books, query.count(&conn)?, params))
     }
 
-    let mut stmt = conn.prepare(&stmt_string)?;
+    fn get_book(&self, id: Uuid) -> Result<Book> {
+        Ok(fetch_book(&self.pool.get().unwrap(), id)?)
+    }
 
-    let books = stmt
-        .query_map([], |row| {
-            Ok(Book {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                language: row.get(3)?,
-            })
-        })?
-        .map(|book| book.unwrap())
-        .collect();
+    fn add_book(
+        &self,
+        book: Book,
+        contributors: &[BookContributorRequest],
+        category_ids: &[Uuid],
+    ) -> Result<Book> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
 
-    Ok(books)
-}
+        // Use transaction to ensure both statements complete
+        let tx = conn.transaction()?;
 
-pub async fn get_book_from_db(State(state): State<AppState>, id: Uuid) -> Result<Book> {
-    state
-        .db_pool
-        .get()
-        .unwrap()
-        .query_row("SELECT * FROM books WHERE id = $1", [id], |row| {
-            Ok(Book {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                language: row.get(3)?,
-            })
-        })
-}
+        // Add the book itself
+        tx.execute(
+            "INSERT INTO books (id, name, description, language) VALUES (?1, ?2, ?3, ?4)",
+            (&book.id, &book.name, &book.description, &book.language),
+        )?;
 
-pub async fn add_book_to_db(
-    State(state): State<AppState>,
-    book: Book,
-    author_id: Uuid,
-) -> Result<Book, rusqlite::Error> {
-    let mut conn = state.db_pool.get().unwrap();
+        // Add links between contributing authors and book
+        add_contributors_of_book(&tx, book.id, contributors)?;
 
-    // Use transaction to ensure both statements complete
-    let tx = conn.transaction()?;
+        // Add links between categories and book
+        for category_id in category_ids.iter() {
+            tx.execute(
+                "INSERT INTO map_books_to_categories (book_id, category_id) VALUES (?1, ?2)",
+                (&book.id, category_id),
+            )?;
+        }
 
-    // Add the book itself
-    tx.execute(
-        "INSERT INTO books (id, name, description, language) VALUES (?1, ?2, ?3, ?4)",
-        (&book.id, &book.name, &book.description, &book.language),
-    )?;
+        let book = fetch_book(&tx, book.id)?;
 
-    // Add link between author and book
-    match tx.execute(
-        "INSERT INTO map_books_to_authors (book_id, author_id) VALUES (?1, ?2)",
-        (&book.id, author_id),
-    ) {
-        Ok(_it) => {}
-        Err(err) => return Err(err),
-    };
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Create,
+                EntityType::Book,
+                book.id,
+                None,
+                Some(&book),
+            ),
+        )?;
 
-    tx.commit()?;
+        tx.commit()?;
 
-    Ok(book)
-}
+        Ok(book)
+    }
 
-pub async fn delete_book_from_db(State(state): State<AppState>, id: Uuid) -> Result<()> {
-    state
-        .db_pool
-        .get()
-        .unwrap()
-        .execute("DELETE FROM books WHERE id = $1", [id])?;
+    fn update_book(
+        &self,
+        book: Book,
+        contributors: &[BookContributorRequest],
+        category_ids: &[Uuid],
+    ) -> Result<()> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
 
-    Ok(())
-}
+        // Nothing to update if the book does not exist
+        let Some(before) = fetch_book(&tx, book.id).optional()? else {
+            return Ok(());
+        };
 
-pub async fn update_book_in_db(
-    State(state): State<AppState>,
-    book: Book,
-    author_id: Uuid,
-) -> Result<(), rusqlite::Error> {
-    let mut conn = state.db_pool.get().unwrap();
-    let tx = conn.transaction()?;
-
-    // Update entry
-    tx.execute(
-        "UPDATE books
-        SET name = $1,
-            description = $2,
-            language = $3
-        WHERE
-            id = $4;
-        ",
-        (book.name, book.description, book.language, book.id),
-    )?;
+        // Update entry
+        tx.execute(
+            "UPDATE books
+            SET name = $1,
+                description = $2,
+                language = $3
+            WHERE
+                id = $4;
+            ",
+            (book.name, book.description, book.language, book.id),
+        )?;
 
-    // Update association
-    match tx.execute(
-        "UPDATE map_books_to_authors
-        SET author_id = $1
-        WHERE book_id = $2",
-        (author_id, book.id),
-    ) {
-        Ok(_) => {}
-        Err(err) => return Err(err),
-    };
+        // Replace contributor associations
+        tx.execute(
+            "DELETE FROM map_books_to_authors WHERE book_id = $1",
+            [book.id],
+        )?;
 
-    tx.commit()?;
+        add_contributors_of_book(&tx, book.id, contributors)?;
 
-    Ok(())
-}
+        // Replace category associations
+        tx.execute(
+            "DELETE FROM map_books_to_categories WHERE book_id = $1",
+            [book.id],
+        )?;
+
+        for category_id in category_ids.iter() {
+            tx.execute(
+                "INSERT INTO map_books_to_categories (book_id, category_id) VALUES (?1, ?2)",
+                (book.id, category_id),
+            )?;
+        }
+
+        let after = fetch_book(&tx, book.id)?;
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Update,
+                EntityType::Book,
+                book.id,
+                Some(&before),
+                Some(&after),
+            ),
+        )?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn delete_book(&self, id: Uuid) -> Result<()> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        if let Some(book) = fetch_book(&tx, id).optional()? {
+            tx.execute(
+                "UPDATE books SET deleted_at = $1 WHERE id = $2",
+                (Utc::now(), id),
+            )?;
+
+            add_audit_entry(
+                &tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Delete,
+                    EntityType::Book,
+                    id,
+                    Some(&book),
+                    None,
+                ),
+            )?;
+
+            cancel_holds_of_book(&tx, &context, id)?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
 
-pub async fn list_authors_from_db(
-    State(state): State<AppState>,
-    params: HashMap<String, String>,
-) -> Result<Vec<Author>> {
-    let conn = state.db_pool.get().unwrap();
+    fn restore_book(&self, id: Uuid) -> Result<Book> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
 
-    let mut stmt_string = String::from("SELECT * FROM authors WHERE 1=1");
+        let num_restored = tx.execute(
+            "UPDATE books SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL",
+            [id],
+        )?;
 
-    if params.contains_key("name") {
-        stmt_string.push_str(&format!(
-            " AND name LIKE '%{}%'",
-            params.get("name").unwrap()
-        ));
+        if num_restored == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+
+        let book = fetch_book(&tx, id)?;
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Restore,
+                EntityType::Book,
+                id,
+                None,
+                Some(&book),
+            ),
+        )?;
+
+        tx.commit()?;
+
+        Ok(book)
+    }
+
+    fn purge_deleted_books(&self, deleted_before: DateTime<Utc>) -> Result<u32> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        let books = query_books(
+            &tx,
+            &format!(
+                "SELECT {} FROM books WHERE deleted_at < $1
+                AND NOT EXISTS (SELECT 1 FROM map_users_to_borrowed_books WHERE book_id = books.id)
+                AND NOT EXISTS (SELECT 1 FROM holds WHERE book_id = books.id)",
+                BOOK_COLUMNS
+            ),
+            [deleted_before],
+        )?;
+
+        for book in books.iter() {
+            tx.execute("DELETE FROM books WHERE id = $1", [book.id])?;
+
+            add_audit_entry(
+                &tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Purge,
+                    EntityType::Book,
+                    book.id,
+                    Some(book),
+                    None,
+                ),
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(books.len() as u32)
     }
 
-    if params.contains_key("country") {
-        stmt_string.push_str(&format!(
-            " AND country LIKE '%{}%'",
-            params.get("country").unwrap()
-        ));
+    fn is_book_exists(&self, id: Uuid) -> Result<bool> {
+        let count = self.pool.get().unwrap().query_row::<i32, _, _>(
+            "SELECT COUNT(*) FROM books WHERE id = $1 AND deleted_at IS NULL",
+            [id],
+            |row| row.get(0),
+        )?;
+
+        Ok(count > 0)
     }
 
-    let mut stmt = conn.prepare(&stmt_string)?;
+    fn list_authors(&self, params: &ListParams) -> Result<Page<Author>> {
+        let conn = self.pool.get().unwrap();
+
+        let mut query = QueryBuilder::new(&format!("SELECT {} FROM authors", AUTHOR_COLUMNS));
+        query
+            .contains("name", params.filters.get("name"))
+            .contains("country", params.filters.get("country"))
+            .exclude_deleted("deleted_at", params)
+            .paginate(params, AUTHOR_SORT_COLUMNS, "rowid");
+
+        let mut stmt = conn.prepare(&query.sql())?;
 
-    let authors = stmt
-        .query_map([], |row| {
-            Ok(Author {
-                id: row.get(0)?,
+        let authors = stmt
+            .query_map(query.params(), author_from_row)?
+            .map(|author| author.unwrap())
+            .collect();
+
+        Ok(Page::new(authors, query.count(&conn)?, params))
+    }
+
+    fn get_author(&self, id: Uuid) -> Result<Author> {
+        Ok(fetch_author(&self.pool.get().unwrap(), id)?)
+    }
+
+    fn add_author(&self, author: Author) -> Result<Author> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO authors (id, name, description, country) VALUES (?1, ?2, ?3, ?4)",
+            (
+                &author.id,
+                &author.name,
+                &author.description,
+                &author.country,
+            ),
+        )?;
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Create,
+                EntityType::Author,
+                author.id,
+                None,
+                Some(&author),
+            ),
+        )?;
+
+        tx.commit()?;
+
+        Ok(author)
+    }
+
+    fn update_author(&self, author: Author) -> Result<()> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        let Some(before) = fetch_author(&tx, author.id).optional()? else {
+            return Ok(());
+        };
+
+        tx.execute(
+            "UPDATE authors
+            SET name = $1,
+                description = $2,
+                country = $3
+            WHERE
+                id = $5;
+            ",
+            (
+                &author.name,
+                &author.description,
+                &author.country,
+                &author.id,
+            ),
+        )?;
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Update,
+                EntityType::Author,
+                author.id,
+                Some(&before),
+                Some(&author),
+            ),
+        )?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn delete_author(&self, id: Uuid) -> Result<()> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        if let Some(author) = fetch_author(&tx, id).optional()? {
+            tx.execute(
+                "UPDATE authors SET deleted_at = $1 WHERE id = $2",
+                (Utc::now(), id),
+            )?;
+
+            add_audit_entry(
+                &tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Delete,
+                    EntityType::Author,
+                    id,
+                    Some(&author),
+                    None,
+                ),
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn restore_author(&self, id: Uuid) -> Result<Author> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        let num_restored = tx.execute(
+            "UPDATE authors SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL",
+            [id],
+        )?;
+
+        if num_restored == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+
+        let author = fetch_author(&tx, id)?;
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Restore,
+                EntityType::Author,
+                id,
+                None,
+                Some(&author),
+            ),
+        )?;
+
+        tx.commit()?;
+
+        Ok(author)
+    }
+
+    fn purge_deleted_authors(&self, deleted_before: DateTime<Utc>) -> Result<u32> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        let authors = {
+            let mut stmt = tx.prepare(&format!(
+                "SELECT {} FROM authors WHERE deleted_at < $1
+                AND NOT EXISTS (SELECT 1 FROM map_books_to_authors WHERE author_id = authors.id)",
+                AUTHOR_COLUMNS
+            ))?;
+            let authors = stmt
+                .query_map([deleted_before], author_from_row)?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            authors
+        };
+
+        for author in authors.iter() {
+            tx.execute("DELETE FROM authors WHERE id = $1", [author.id])?;
+
+            add_audit_entry(
+                &tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Purge,
+                    EntityType::Author,
+                    author.id,
+                    Some(author),
+                    None,
+                ),
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(authors.len() as u32)
+    }
+
+    fn is_author_exists(&self, id: Uuid) -> Result<bool> {
+        let count = self.pool.get().unwrap().query_row::<i32, _, _>(
+            "SELECT COUNT(*) FROM authors WHERE id = $1 AND deleted_at IS NULL",
+            [id],
+            |row| row.get(0),
+        )?;
+
+        Ok(count > 0)
+    }
+
+    fn list_categories(&self, params: &ListParams) -> Result<Page<Category>> {
+        let conn = self.pool.get().unwrap();
+
+        let mut query = QueryBuilder::new("SELECT * FROM categories");
+        query.contains("name", params.filters.get("name")).paginate(
+            params,
+            CATEGORY_SORT_COLUMNS,
+            "name",
+        );
+
+        let mut stmt = conn.prepare(&query.sql())?;
+
+        let categories = stmt
+            .query_map(query.params(), category_from_row)?
+            .map(|category| category.unwrap())
+            .collect();
+
+        Ok(Page::new(categories, query.count(&conn)?, params))
+    }
+
+    fn get_category(&self, id: Uuid) -> Result<Category> {
+        Ok(fetch_category(&self.pool.get().unwrap(), id)?)
+    }
+
+    fn add_category(&self, category: Category) -> Result<Category> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO categories (id, name) VALUES (?1, ?2)",
+            (&category.id, &category.name),
+        )?;
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Create,
+                EntityType::Category,
+                category.id,
+                None,
+                Some(&category),
+            ),
+        )?;
+
+        tx.commit()?;
+
+        Ok(category)
+    }
+
+    fn update_category(&self, category: Category) -> Result<()> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        let Some(before) = fetch_category(&tx, category.id).optional()? else {
+            return Ok(());
+        };
+
+        tx.execute(
+            "UPDATE categories
+            SET name = $1
+            WHERE
+                id = $2;
+            ",
+            (&category.name, &category.id),
+        )?;
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Update,
+                EntityType::Category,
+                category.id,
+                Some(&before),
+                Some(&category),
+            ),
+        )?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn delete_category(&self, id: Uuid) -> Result<()> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        if let Some(category) = fetch_category(&tx, id).optional()? {
+            tx.execute("DELETE FROM categories WHERE id = $1", [id])?;
+
+            add_audit_entry(
+                &tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Delete,
+                    EntityType::Category,
+                    id,
+                    Some(&category),
+                    None,
+                ),
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn is_category_exists(&self, id: Uuid) -> Result<bool> {
+        let count = self.pool.get().unwrap().query_row::<i32, _, _>(
+            "SELECT COUNT(*) FROM categories WHERE id = $1",
+            [id],
+            |row| row.get(0),
+        )?;
+
+        Ok(count > 0)
+    }
+
+    fn is_category_name_valid(&self, name: &str, category_id: Option<Uuid>) -> Result<bool> {
+        let count = self.pool.get().unwrap().query_row::<i32, _, _>(
+            "SELECT COUNT(*) FROM categories WHERE name = $1 AND id IS NOT $2",
+            (name, category_id),
+            |row| row.get(0),
+        )?;
+
+        Ok(count == 0)
+    }
+
+    fn list_items_of_book(&self, book_id: Uuid) -> Result<Vec<Item>> {
+        let conn = self.pool.get().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT id, book_id, barcode, shelf_location, condition, status FROM items
+            WHERE book_id = $1
+            ORDER BY barcode",
+        )?;
+
+        let items = stmt
+            .query_map([book_id], item_from_row)?
+            .map(|item| item.unwrap())
+            .collect();
+
+        Ok(items)
+    }
+
+    fn get_item(&self, id: Uuid) -> Result<Item> {
+        Ok(fetch_item(&self.pool.get().unwrap(), id)?)
+    }
+
+    fn add_item(&self, item: Item) -> Result<Item> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        insert_item(&tx, &context, &item)?;
+
+        tx.commit()?;
+
+        Ok(item)
+    }
+
+    fn add_shelved_item(&self, item: Item, hold_pickup_days: u32) -> Result<Item> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        insert_item(&tx, &context, &item)?;
+        pass_item_to_next_hold(&tx, &context, item.book_id, item.id, hold_pickup_days)?;
+        let item = fetch_item(&tx, item.id)?;
+
+        tx.commit()?;
+
+        Ok(item)
+    }
+
+    fn update_item(&self, item: Item, hold_pickup_days: u32) -> Result<()> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        let Some(before) = fetch_item(&tx, item.id).optional()? else {
+            return Ok(());
+        };
+
+        tx.execute(
+            "UPDATE items
+            SET barcode = $1,
+                shelf_location = $2,
+                condition = $3,
+                status = $4
+            WHERE
+                id = $5;
+            ",
+            (
+                &item.barcode,
+                &item.shelf_location,
+                &item.condition,
+                &item.status,
+                &item.id,
+            ),
+        )?;
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Update,
+                EntityType::Item,
+                item.id,
+                Some(&before),
+                Some(&item),
+            ),
+        )?;
+
+        if item.status == ItemStatus::Available {
+            pass_item_to_next_hold(&tx, &context, item.book_id, item.id, hold_pickup_days)?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn delete_item(&self, id: Uuid) -> Result<()> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        if let Some(item) = fetch_item(&tx, id).optional()? {
+            if item.status == ItemStatus::Withdrawn {
+                return Ok(());
+            }
+
+            tx.execute(
+                "UPDATE items SET status = $1 WHERE id = $2",
+                (ItemStatus::Withdrawn, id),
+            )?;
+
+            add_audit_entry(
+                &tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Delete,
+                    EntityType::Item,
+                    id,
+                    Some(&item),
+                    None,
+                ),
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn is_barcode_valid(&self, barcode: &str, item_id: Option<Uuid>) -> Result<bool> {
+        let count = self.pool.get().unwrap().query_row::<i32, _, _>(
+            "SELECT COUNT(*) FROM items WHERE barcode = $1 AND id IS NOT $2",
+            (barcode, item_id),
+            |row| row.get(0),
+        )?;
+
+        Ok(count == 0)
+    }
+
+    fn search_catalog(
+        &self,
+        terms: &[String],
+        kind: Option<SearchResultKind>,
+        params: &ListParams,
+    ) -> Result<Page<SearchResult>> {
+        let conn = self.pool.get().unwrap();
+
+        let mut query = QueryBuilder::new(
+            "SELECT kind, entity_id, name,
+                snippet(catalog_search, -1, char(2), char(3), '...', 16)
+            FROM catalog_search",
+        );
+        query.condition("catalog_search MATCH ?", to_match_query(terms));
+        if let Some(kind) = kind {
+            query.condition("kind = ?", kind);
+        }
+        query.paginate(params, &[], SEARCH_ORDER);
+
+        let mut stmt = conn.prepare(&query.sql())?;
+
+        let results = stmt
+            .query_map(query.params(), |row| {
+                Ok(SearchResult {
+                    kind: row.get(0)?,
+                    id: row.get(1)?,
+                    name: row.get(2)?,
+                    snippet: highlight_snippet(&row.get::<_, String>(3)?),
+                })
+            })?
+            .map(|result| result.unwrap())
+            .collect();
+
+        Ok(Page::new(results, query.count(&conn)?, params))
+    }
+}
+
+// Adds the supported book search filters to a `books` query
+fn push_book_filters(query: &mut QueryBuilder, filters: &Filters) {
+    query
+        .contains("name", filters.get("name"))
+        .contains("language", filters.get("language"));
+
+    if let Some(category) = filters.get("category") {
+        query.condition(
+            "id IN (
+                SELECT a.book_id FROM map_books_to_categories a, categories b
+                WHERE a.category_id = b.id AND b.name LIKE ? ESCAPE '\\'
+            )",
+            like_pattern(category),
+        );
+    }
+}
+
+fn query_books<P: Params>(
+    conn: &Connection,
+    stmt_string: &str,
+    params: P,
+) -> rusqlite::Result<Vec<Book>> {
+    let mut stmt = conn.prepare(stmt_string)?;
+
+    let books: Vec<Book> = stmt
+        .query_map(params, book_from_row)?
+        .map(|book| book.unwrap())
+        .collect();
+
+    books
+        .into_iter()
+        .map(|mut book| {
+            book.contributors = get_contributors_of_book(conn, book.id)?;
+            book.categories = get_categories_of_book(conn, book.id)?;
+            Ok(book)
+        })
+        .collect()
+}
+
+fn fetch_book(conn: &Connection, id: Uuid) -> rusqlite::Result<Book> {
+    let mut book = conn.query_row(
+        &format!(
+            "SELECT {} FROM books WHERE id = $1 AND deleted_at IS NULL",
+            BOOK_COLUMNS
+        ),
+        [id],
+        book_from_row,
+    )?;
+
+    book.contributors = get_contributors_of_book(conn, book.id)?;
+    book.categories = get_categories_of_book(conn, book.id)?;
+
+    Ok(book)
+}
+
+fn book_from_row(row: &Row) -> rusqlite::Result<Book> {
+    Ok(Book {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        language: row.get(3)?,
+        contributors: Vec::new(),
+        categories: Vec::new(),
+        total_copies: row.get(4)?,
+        available_copies: row.get(5)?,
+        deleted_at: row.get(6)?,
+    })
+}
+
+fn get_contributors_of_book(
+    conn: &Connection,
+    book_id: Uuid,
+) -> rusqlite::Result<Vec<BookContributor>> {
+    let mut stmt = conn.prepare(
+        "SELECT b.id, b.name, a.role FROM map_books_to_authors a, authors b
+        WHERE a.author_id = b.id AND a.book_id = $1 AND b.deleted_at IS NULL
+        ORDER BY a.position",
+    )?;
+
+    let contributors = stmt
+        .query_map([book_id], |row| {
+            Ok(BookContributor {
+                author_id: row.get(0)?,
                 name: row.get(1)?,
-                description: row.get(2)?,
-                country: row.get(3)?,
+                role: row.get(2)?,
             })
         })?
-        .map(|author| author.unwrap())
+        .map(|contributor| contributor.unwrap())
         .collect();
 
-    Ok(authors)
+    Ok(contributors)
 }
 
-pub async fn get_author_from_db(State(state): State<AppState>, id: Uuid) -> Result<Author> {
-    state
-        .db_pool
-        .get()
-        .unwrap()
-        .query_row("SELECT * FROM authors WHERE id = $1", [id], |row| {
-            Ok(Author {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                description: row.get(2)?,
-                country: row.get(3)?,
-            })
-        })
+// Links the contributors to the book, in the order that they are given
+fn add_contributors_of_book(
+    conn: &Connection,
+    book_id: Uuid,
+    contributors: &[BookContributorRequest],
+) -> rusqlite::Result<()> {
+    for (position, contributor) in contributors.iter().enumerate() {
+        conn.execute(
+            "INSERT INTO map_books_to_authors (book_id, author_id, role, position) VALUES (?1, ?2, ?3, ?4)",
+            (book_id, contributor.author_id, contributor.role, position),
+        )?;
+    }
+
+    Ok(())
 }
 
-pub async fn add_author_to_db(State(state): State<AppState>, author: Author) -> Result<Author> {
-    state.db_pool.get().unwrap().execute(
-        "INSERT INTO authors (id, name, description, country) VALUES (?1, ?2, ?3, ?4)",
-        (
-            &author.id,
-            &author.name,
-            &author.description,
-            &author.country,
-        ),
+fn get_categories_of_book(conn: &Connection, book_id: Uuid) -> rusqlite::Result<Vec<Category>> {
+    let mut stmt = conn.prepare(
+        "SELECT b.id, b.name FROM map_books_to_categories a, categories b
+        WHERE a.category_id = b.id AND a.book_id = $1
+        ORDER BY b.name",
     )?;
 
-    Ok(author)
+    let categories = stmt
+        .query_map([book_id], category_from_row)?
+        .map(|category| category.unwrap())
+        .collect();
+
+    Ok(categories)
 }
 
-pub async fn delete_author_from_db(State(state): State<AppState>, id: Uuid) -> Result<()> {
-    state
-        .db_pool
-        .get()
-        .unwrap()
-        .execute("DELETE FROM authors WHERE id = $1", [id])?;
+fn fetch_author(conn: &Connection, id: Uuid) -> rusqlite::Result<Author> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM authors WHERE id = $1 AND deleted_at IS NULL",
+            AUTHOR_COLUMNS
+        ),
+        [id],
+        author_from_row,
+    )
+}
 
-    Ok(())
+fn author_from_row(row: &Row) -> rusqlite::Result<Author> {
+    Ok(Author {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        country: row.get(3)?,
+        deleted_at: row.get(4)?,
+    })
 }
 
-pub async fn update_author_in_db(State(state): State<AppState>, author: Author) -> Result<()> {
-    state.db_pool.get().unwrap().execute(
-        "UPDATE authors
-        SET name = $1,
-            description = $2,
-            country = $3
-        WHERE
-            id = $5;
-        ",
-        (author.name, author.description, author.country, author.id),
+fn fetch_category(conn: &Connection, id: Uuid) -> rusqlite::Result<Category> {
+    conn.query_row(
+        "SELECT * FROM categories WHERE id = $1",
+        [id],
+        category_from_row,
+    )
+}
+
+fn category_from_row(row: &Row) -> rusqlite::Result<Category> {
+    Ok(Category {
+        id: row.get(0)?,
+        name: row.get(1)?,
+    })
+}
+
+// Adds the copy along with the record of its creation
+fn insert_item(conn: &Connection, context: &AuditContext, item: &Item) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO items (id, book_id, barcode, shelf_location, condition, status) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        (
+            &item.id,
+            &item.book_id,
+            &item.barcode,
+            &item.shelf_location,
+            &item.condition,
+            &item.status,
+        ),
     )?;
 
-    Ok(())
+    add_audit_entry(
+        conn,
+        &AuditEntry::new(
+            context,
+            AuditAction::Create,
+            EntityType::Item,
+            item.id,
+            None,
+            Some(item),
+        ),
+    )
 }
 
-pub fn is_author_exists_in_db(
-    State(state): &State<AppState>,
-    author_id: Uuid,
-) -> Result<bool, rusqlite::Error> {
-    match state.db_pool.get().unwrap().query_row::<i32, _, _>(
-        "SELECT COUNT(*) FROM authors WHERE id = $1",
-        [author_id],
-        |row| row.get(0),
-    ) {
-        Ok(count) => Ok(count > 0),
-        Err(err) => Err(err),
-    }
+fn fetch_item(conn: &Connection, id: Uuid) -> rusqlite::Result<Item> {
+    conn.query_row(
+        "SELECT id, book_id, barcode, shelf_location, condition, status FROM items WHERE id = $1",
+        [id],
+        item_from_row,
+    )
+}
+
+fn item_from_row(row: &Row) -> rusqlite::Result<Item> {
+    Ok(Item {
+        id: row.get(0)?,
+        book_id: row.get(1)?,
+        barcode: row.get(2)?,
+        shelf_location: row.get(3)?,
+        condition: row.get(4)?,
+        status: row.get(5)?,
+    })
+}
+
+// Turns each term of a search into a quoted prefix term, so that user input is never read as
+// FTS5 query syntax and every term has to match
+fn to_match_query(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect::<Vec<String>>()
+        .join(" ")
 }
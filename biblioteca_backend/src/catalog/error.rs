@@ -1,9 +1,24 @@
 use std::fmt;
 
+use crate::repository::RepositoryError;
+
 #[derive(thiserror::Error, Debug)]
 pub enum CatalogError {
-    DatabaseError(#[from] rusqlite::Error),
+    DatabaseError(#[from] RepositoryError),
     AuthorNotFound,
+    NoContributors,
+    DuplicateContributor,
+    CategoryNotFound,
+    CategoryAlreadyExists,
+    BookNotFound,
+    BookOnLoan,
+    BarcodeAlreadyExists,
+    ItemOnLoan,
+    ItemOnHold,
+    ItemStatusNotEditable,
+    ItemWithdrawn,
+    SearchQueryMissing,
+    InvalidSearchKind,
 }
 
 impl fmt::Display for CatalogError {
@@ -13,6 +28,34 @@ impl fmt::Display for CatalogError {
                 write!(f, "there was an error in accessing the database")
             }
             CatalogError::AuthorNotFound => write!(f, "author does not exist in catalog"),
+            CatalogError::NoContributors => write!(f, "book must have at least one contributor"),
+            CatalogError::DuplicateContributor => {
+                write!(f, "author is listed more than once with the same role")
+            }
+            CatalogError::CategoryNotFound => write!(f, "category does not exist in catalog"),
+            CatalogError::CategoryAlreadyExists => {
+                write!(f, "category with the same name already exists")
+            }
+            CatalogError::BookNotFound => write!(f, "book does not exist in catalog"),
+            CatalogError::BookOnLoan => write!(f, "book has copies currently on loan"),
+            CatalogError::BarcodeAlreadyExists => {
+                write!(f, "item with the same barcode already exists")
+            }
+            CatalogError::ItemOnLoan => write!(f, "item is currently on loan"),
+            CatalogError::ItemOnHold => write!(f, "item is currently on hold for a user"),
+            CatalogError::ItemStatusNotEditable => {
+                write!(
+                    f,
+                    "item can only be marked as borrowed or on hold through the library, or withdrawn by deleting it"
+                )
+            }
+            CatalogError::ItemWithdrawn => write!(f, "item has been withdrawn"),
+            CatalogError::SearchQueryMissing => {
+                write!(f, "search must have a query with at least one word")
+            }
+            CatalogError::InvalidSearchKind => {
+                write!(f, "search kind must be one of book, author or category")
+            }
         }
     }
 }
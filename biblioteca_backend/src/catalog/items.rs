@@ -0,0 +1,198 @@
+use crate::app::AppState;
+use crate::catalog::error::CatalogError;
+use crate::error::Error;
+
+use axum::routing::{delete, get, post, put};
+use axum::Router;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use uuid::Uuid;
+
+use super::model::{CreateItemRequest, Item, ItemStatus, UpdateItemRequest};
+
+pub fn items_router() -> Router<AppState> {
+    Router::new()
+        .route("/books/:id/items", get(list_book_items))
+        .route("/books/:id/items", post(create_item))
+        .route("/items/:id", get(get_item))
+        .route("/items/:id", delete(delete_item))
+        .route("/items/:id", put(update_item))
+}
+
+// Retrieves all copies of a specific book
+async fn list_book_items(
+    state: State<AppState>,
+    Path(book_id): Path<Uuid>,
+) -> Result<Json<Vec<Item>>, Error> {
+    tracing::debug!("GET /books/:id/items with id: {:?}", book_id);
+
+    if !state.catalog.is_book_exists(book_id).unwrap() {
+        return Err(Error::not_found());
+    }
+
+    match state.catalog.list_items_of_book(book_id) {
+        Ok(items) => Ok(Json(items)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Adds a new copy of a specific book, setting it aside for the first patron waiting for the book
+async fn create_item(
+    state: State<AppState>,
+    Path(book_id): Path<Uuid>,
+    Json(payload): Json<CreateItemRequest>,
+) -> Result<Json<Item>, Error> {
+    tracing::debug!(
+        "POST /books/:id/items with id: {:?} and params: {:?}",
+        book_id,
+        payload
+    );
+
+    if !state.catalog.is_book_exists(book_id).unwrap() {
+        return Err(Error::bad_request(CatalogError::BookNotFound.to_string()));
+    }
+
+    if !state
+        .catalog
+        .is_barcode_valid(&payload.barcode, None)
+        .unwrap()
+    {
+        return Err(Error::bad_request(
+            CatalogError::BarcodeAlreadyExists.to_string(),
+        ));
+    }
+
+    let item = Item {
+        id: Uuid::new_v4(),
+        book_id,
+        barcode: payload.barcode,
+        shelf_location: payload.shelf_location,
+        condition: payload.condition,
+        status: ItemStatus::Available,
+    };
+
+    match state
+        .catalog
+        .add_shelved_item(item, state.library_settings.hold_pickup_days)
+    {
+        Ok(item) => Ok(Json(item)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Retrieves a specific copy, by id
+async fn get_item(state: State<AppState>, Path(id): Path<Uuid>) -> Result<Json<Item>, Error> {
+    tracing::debug!("GET /items with id: {:?}", id);
+
+    match state.catalog.get_item(id) {
+        Ok(item) => Ok(Json(item)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::not_found())
+        }
+    }
+}
+
+// Withdraws a specific copy, as long as it is not on loan, keeping it for the loans and holds
+// that refer to it
+async fn delete_item(state: State<AppState>, Path(id): Path<Uuid>) -> Result<StatusCode, Error> {
+    tracing::debug!("DELETE /items with id: {:?}", id);
+
+    if let Ok(item) = state.catalog.get_item(id) {
+        match item.status {
+            ItemStatus::Borrowed => {
+                return Err(Error::bad_request(CatalogError::ItemOnLoan.to_string()))
+            }
+            ItemStatus::OnHold => {
+                return Err(Error::bad_request(CatalogError::ItemOnHold.to_string()))
+            }
+            _ => {}
+        }
+    }
+
+    match state.catalog.delete_item(id) {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Updates a specific copy, setting it aside for the first patron waiting for the book if it is
+// put back on the shelf; loans and holds are tracked by the library instead
+async fn update_item(
+    state: State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateItemRequest>,
+) -> Result<StatusCode, Error> {
+    tracing::debug!("PUT /items with id: {:?}", id);
+
+    let existing_item = match state.catalog.get_item(id) {
+        Ok(item) => item,
+        Err(err) => {
+            tracing::warn!("{}", err);
+            return Err(Error::not_found());
+        }
+    };
+
+    // Withdrawn copies are kept only for the loans and holds that refer to them
+    if existing_item.status == ItemStatus::Withdrawn {
+        return Err(Error::bad_request(CatalogError::ItemWithdrawn.to_string()));
+    }
+
+    if existing_item.status != payload.status {
+        match existing_item.status {
+            ItemStatus::Borrowed => {
+                return Err(Error::bad_request(CatalogError::ItemOnLoan.to_string()))
+            }
+            ItemStatus::OnHold => {
+                return Err(Error::bad_request(CatalogError::ItemOnHold.to_string()))
+            }
+            _ => {}
+        }
+
+        if let ItemStatus::Borrowed | ItemStatus::OnHold | ItemStatus::Withdrawn = payload.status {
+            return Err(Error::bad_request(
+                CatalogError::ItemStatusNotEditable.to_string(),
+            ));
+        }
+    }
+
+    if !state
+        .catalog
+        .is_barcode_valid(&payload.barcode, Some(id))
+        .unwrap()
+    {
+        return Err(Error::bad_request(
+            CatalogError::BarcodeAlreadyExists.to_string(),
+        ));
+    }
+
+    let item = Item {
+        id,
+        book_id: existing_item.book_id,
+        barcode: payload.barcode,
+        shelf_location: payload.shelf_location,
+        condition: payload.condition,
+        status: payload.status,
+    };
+
+    let hold_pickup_days = state.library_settings.hold_pickup_days;
+    match state.catalog.update_item(item, hold_pickup_days) {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
@@ -0,0 +1,918 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    audit::{
+        context::AuditContext,
+        model::{AuditAction, AuditEntry, EntityType},
+    },
+    library::memory::{cancel_holds_of_book, pass_item_to_next_hold},
+    query::{ListParams, Page},
+    repository::{
+        memory::{contains_term, paginate, BookContributorLink, MemoryData},
+        MemoryRepository, RepositoryError, Result,
+    },
+};
+
+use super::{
+    model::{
+        highlight_snippet, Author, Book, BookContributor, BookContributorRequest, Category, Item,
+        ItemStatus, SearchResult, SearchResultKind, SNIPPET_MATCH_END, SNIPPET_MATCH_START,
+    },
+    repository::CatalogRepository,
+};
+
+// Search ranking weighs matches in names over those in descriptions, as the index does
+const NAME_WEIGHT: u32 = 10;
+
+impl CatalogRepository for MemoryRepository {
+    fn list_books(&self, params: &ListParams) -> Result<Page<Book>> {
+        let data = self.data();
+
+        let books = data
+            .books
+            .iter()
+            .filter(|book| params.include_deleted() || book.deleted_at.is_none())
+            .filter(|book| is_book_matching(&data, book, params))
+            .map(|book| full_book(&data, book))
+            .collect();
+
+        Ok(paginate(books, params, compare_books))
+    }
+
+    fn list_books_of_author(&self, author_id: Uuid, params: &ListParams) -> Result<Page<Book>> {
+        let data = self.data();
+
+        let books = data
+            .books
+            .iter()
+            .filter(|book| {
+                data.book_contributors
+                    .iter()
+                    .any(|link| link.book_id == book.id && link.author_id == author_id)
+            })
+            .filter(|book| params.include_deleted() || book.deleted_at.is_none())
+            .filter(|book| is_book_matching(&data, book, params))
+            .map(|book| full_book(&data, book))
+            .collect();
+
+        Ok(paginate(books, params, compare_books))
+    }
+
+    fn get_book(&self, id: Uuid) -> Result<Book> {
+        let data = self.data();
+
+        match find_book(&data, id) {
+            Some(book) => Ok(full_book(&data, book)),
+            None => Err(RepositoryError::NotFound),
+        }
+    }
+
+    fn add_book(
+        &self,
+        book: Book,
+        contributors: &[BookContributorRequest],
+        category_ids: &[Uuid],
+    ) -> Result<Book> {
+        let mut data = self.data();
+
+        if data.books.iter().any(|existing| existing.id == book.id) {
+            return Err(RepositoryError::Backend("book already exists".to_string()));
+        }
+
+        link_book(&mut data, book.id, contributors, category_ids);
+        data.books.push(book);
+
+        let book = full_book(&data, data.books.last().unwrap());
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Create,
+            EntityType::Book,
+            book.id,
+            None,
+            Some(&book),
+        ));
+
+        Ok(book)
+    }
+
+    fn update_book(
+        &self,
+        book: Book,
+        contributors: &[BookContributorRequest],
+        category_ids: &[Uuid],
+    ) -> Result<()> {
+        let mut data = self.data();
+
+        // Nothing to update if the book does not exist
+        let Some(before) = find_book(&data, book.id).map(|existing| full_book(&data, existing))
+        else {
+            return Ok(());
+        };
+
+        let existing = data
+            .books
+            .iter_mut()
+            .find(|existing| existing.id == book.id)
+            .unwrap();
+        existing.name = book.name;
+        existing.description = book.description;
+        existing.language = book.language;
+
+        data.book_contributors
+            .retain(|link| link.book_id != book.id);
+        data.book_categories
+            .retain(|(book_id, _)| *book_id != book.id);
+        link_book(&mut data, book.id, contributors, category_ids);
+
+        let after = find_book(&data, book.id).map(|existing| full_book(&data, existing));
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Update,
+            EntityType::Book,
+            book.id,
+            Some(&before),
+            after.as_ref(),
+        ));
+
+        Ok(())
+    }
+
+    fn delete_book(&self, id: Uuid) -> Result<()> {
+        let mut data = self.data();
+
+        if let Some(book) = find_book(&data, id) {
+            let book = full_book(&data, book);
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Delete,
+                EntityType::Book,
+                id,
+                Some(&book),
+                None,
+            ));
+
+            let book = data.books.iter_mut().find(|book| book.id == id).unwrap();
+            book.deleted_at = Some(Utc::now());
+
+            cancel_holds_of_book(&mut data, id);
+        }
+
+        Ok(())
+    }
+
+    fn restore_book(&self, id: Uuid) -> Result<Book> {
+        let mut data = self.data();
+
+        let book = data
+            .books
+            .iter_mut()
+            .find(|book| book.id == id && book.deleted_at.is_some())
+            .ok_or(RepositoryError::NotFound)?;
+        book.deleted_at = None;
+
+        let book = full_book(&data, find_book(&data, id).unwrap());
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Restore,
+            EntityType::Book,
+            id,
+            None,
+            Some(&book),
+        ));
+
+        Ok(book)
+    }
+
+    fn purge_deleted_books(&self, deleted_before: DateTime<Utc>) -> Result<u32> {
+        let mut data = self.data();
+
+        let books: Vec<Book> = data
+            .books
+            .iter()
+            .filter(|book| {
+                book.deleted_at
+                    .is_some_and(|deleted_at| deleted_at < deleted_before)
+                    && !data
+                        .borrow_entries
+                        .iter()
+                        .any(|entry| entry.book_id == book.id)
+                    && !data.holds.iter().any(|hold| hold.book_id == book.id)
+            })
+            .map(|book| full_book(&data, book))
+            .collect();
+
+        for book in books.iter() {
+            let id = book.id;
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Purge,
+                EntityType::Book,
+                id,
+                Some(book),
+                None,
+            ));
+
+            data.books.retain(|book| book.id != id);
+            data.book_contributors.retain(|link| link.book_id != id);
+            data.book_categories.retain(|(book_id, _)| *book_id != id);
+            data.items.retain(|item| item.book_id != id);
+        }
+
+        Ok(books.len() as u32)
+    }
+
+    fn is_book_exists(&self, id: Uuid) -> Result<bool> {
+        Ok(find_book(&self.data(), id).is_some())
+    }
+
+    fn list_authors(&self, params: &ListParams) -> Result<Page<Author>> {
+        let authors = self
+            .data()
+            .authors
+            .iter()
+            .filter(|author| {
+                (params.include_deleted() || author.deleted_at.is_none())
+                    && contains_term(&author.name, params.filters.get("name"))
+                    && contains_term(&author.country, params.filters.get("country"))
+            })
+            .cloned()
+            .collect();
+
+        Ok(paginate(authors, params, |a, b, field| match field {
+            "name" => a.name.cmp(&b.name),
+            _ => a.country.cmp(&b.country),
+        }))
+    }
+
+    fn get_author(&self, id: Uuid) -> Result<Author> {
+        find_author(&self.data(), id)
+            .cloned()
+            .ok_or(RepositoryError::NotFound)
+    }
+
+    fn add_author(&self, author: Author) -> Result<Author> {
+        let mut data = self.data();
+
+        data.authors.push(author.clone());
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Create,
+            EntityType::Author,
+            author.id,
+            None,
+            Some(&author),
+        ));
+
+        Ok(author)
+    }
+
+    fn update_author(&self, author: Author) -> Result<()> {
+        let mut data = self.data();
+
+        if let Some(existing) = data
+            .authors
+            .iter_mut()
+            .find(|existing| existing.id == author.id && existing.deleted_at.is_none())
+        {
+            let before = std::mem::replace(existing, author.clone());
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Update,
+                EntityType::Author,
+                author.id,
+                Some(&before),
+                Some(&author),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn delete_author(&self, id: Uuid) -> Result<()> {
+        let mut data = self.data();
+
+        if let Some(author) = find_author(&data, id).cloned() {
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Delete,
+                EntityType::Author,
+                id,
+                Some(&author),
+                None,
+            ));
+
+            let author = data
+                .authors
+                .iter_mut()
+                .find(|author| author.id == id)
+                .unwrap();
+            author.deleted_at = Some(Utc::now());
+        }
+
+        Ok(())
+    }
+
+    fn restore_author(&self, id: Uuid) -> Result<Author> {
+        let mut data = self.data();
+
+        let author = data
+            .authors
+            .iter_mut()
+            .find(|author| author.id == id && author.deleted_at.is_some())
+            .ok_or(RepositoryError::NotFound)?;
+        author.deleted_at = None;
+
+        let author = author.clone();
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Restore,
+            EntityType::Author,
+            id,
+            None,
+            Some(&author),
+        ));
+
+        Ok(author)
+    }
+
+    fn purge_deleted_authors(&self, deleted_before: DateTime<Utc>) -> Result<u32> {
+        let mut data = self.data();
+
+        let authors: Vec<Author> = data
+            .authors
+            .iter()
+            .filter(|author| {
+                author
+                    .deleted_at
+                    .is_some_and(|deleted_at| deleted_at < deleted_before)
+                    && !data
+                        .book_contributors
+                        .iter()
+                        .any(|link| link.author_id == author.id)
+            })
+            .cloned()
+            .collect();
+
+        for author in authors.iter() {
+            let id = author.id;
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Purge,
+                EntityType::Author,
+                id,
+                Some(author),
+                None,
+            ));
+
+            data.authors.retain(|author| author.id != id);
+        }
+
+        Ok(authors.len() as u32)
+    }
+
+    fn is_author_exists(&self, id: Uuid) -> Result<bool> {
+        Ok(find_author(&self.data(), id).is_some())
+    }
+
+    fn list_categories(&self, params: &ListParams) -> Result<Page<Category>> {
+        let mut categories: Vec<Category> = self
+            .data()
+            .categories
+            .iter()
+            .filter(|category| contains_term(&category.name, params.filters.get("name")))
+            .cloned()
+            .collect();
+
+        // Categories are listed by name unless sorted otherwise
+        categories.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(paginate(categories, params, |a, b, _| a.name.cmp(&b.name)))
+    }
+
+    fn get_category(&self, id: Uuid) -> Result<Category> {
+        self.data()
+            .categories
+            .iter()
+            .find(|category| category.id == id)
+            .cloned()
+            .ok_or(RepositoryError::NotFound)
+    }
+
+    fn add_category(&self, category: Category) -> Result<Category> {
+        let mut data = self.data();
+
+        if data
+            .categories
+            .iter()
+            .any(|existing| existing.name == category.name)
+        {
+            return Err(RepositoryError::Backend(
+                "category name must be unique".to_string(),
+            ));
+        }
+
+        data.categories.push(category.clone());
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Create,
+            EntityType::Category,
+            category.id,
+            None,
+            Some(&category),
+        ));
+
+        Ok(category)
+    }
+
+    fn update_category(&self, category: Category) -> Result<()> {
+        let mut data = self.data();
+
+        if data
+            .categories
+            .iter()
+            .any(|existing| existing.name == category.name && existing.id != category.id)
+        {
+            return Err(RepositoryError::Backend(
+                "category name must be unique".to_string(),
+            ));
+        }
+
+        if let Some(existing) = data
+            .categories
+            .iter_mut()
+            .find(|existing| existing.id == category.id)
+        {
+            let before = std::mem::replace(existing, category.clone());
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Update,
+                EntityType::Category,
+                category.id,
+                Some(&before),
+                Some(&category),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn delete_category(&self, id: Uuid) -> Result<()> {
+        let mut data = self.data();
+
+        if let Some(category) = data
+            .categories
+            .iter()
+            .find(|category| category.id == id)
+            .cloned()
+        {
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Delete,
+                EntityType::Category,
+                id,
+                Some(&category),
+                None,
+            ));
+        }
+
+        data.categories.retain(|category| category.id != id);
+        data.book_categories
+            .retain(|(_, category_id)| *category_id != id);
+
+        Ok(())
+    }
+
+    fn is_category_exists(&self, id: Uuid) -> Result<bool> {
+        Ok(self
+            .data()
+            .categories
+            .iter()
+            .any(|category| category.id == id))
+    }
+
+    fn is_category_name_valid(&self, name: &str, category_id: Option<Uuid>) -> Result<bool> {
+        Ok(!self
+            .data()
+            .categories
+            .iter()
+            .any(|category| category.name == name && Some(category.id) != category_id))
+    }
+
+    fn list_items_of_book(&self, book_id: Uuid) -> Result<Vec<Item>> {
+        let mut items: Vec<Item> = self
+            .data()
+            .items
+            .iter()
+            .filter(|item| item.book_id == book_id)
+            .cloned()
+            .collect();
+
+        items.sort_by(|a, b| a.barcode.cmp(&b.barcode));
+
+        Ok(items)
+    }
+
+    fn get_item(&self, id: Uuid) -> Result<Item> {
+        self.data()
+            .items
+            .iter()
+            .find(|item| item.id == id)
+            .cloned()
+            .ok_or(RepositoryError::NotFound)
+    }
+
+    fn add_item(&self, item: Item) -> Result<Item> {
+        insert_item(&mut self.data(), &item)?;
+
+        Ok(item)
+    }
+
+    fn add_shelved_item(&self, item: Item, hold_pickup_days: u32) -> Result<Item> {
+        let mut data = self.data();
+
+        insert_item(&mut data, &item)?;
+        pass_item_to_next_hold(&mut data, item.book_id, item.id, hold_pickup_days);
+
+        Ok(data
+            .items
+            .iter()
+            .find(|existing| existing.id == item.id)
+            .cloned()
+            .unwrap())
+    }
+
+    fn update_item(&self, item: Item, hold_pickup_days: u32) -> Result<()> {
+        let mut data = self.data();
+
+        if data
+            .items
+            .iter()
+            .any(|existing| existing.barcode == item.barcode && existing.id != item.id)
+        {
+            return Err(RepositoryError::Backend(
+                "item barcode must be unique".to_string(),
+            ));
+        }
+
+        if let Some(existing) = data
+            .items
+            .iter_mut()
+            .find(|existing| existing.id == item.id)
+        {
+            let before = existing.clone();
+            existing.barcode = item.barcode;
+            existing.shelf_location = item.shelf_location;
+            existing.condition = item.condition;
+            existing.status = item.status;
+
+            let after = existing.clone();
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Update,
+                EntityType::Item,
+                item.id,
+                Some(&before),
+                Some(&after),
+            ));
+        }
+
+        if item.status == ItemStatus::Available {
+            pass_item_to_next_hold(&mut data, item.book_id, item.id, hold_pickup_days);
+        }
+
+        Ok(())
+    }
+
+    fn delete_item(&self, id: Uuid) -> Result<()> {
+        let mut data = self.data();
+
+        let Some(item) = data
+            .items
+            .iter_mut()
+            .find(|item| item.id == id && item.status != ItemStatus::Withdrawn)
+        else {
+            return Ok(());
+        };
+
+        let before = item.clone();
+        item.status = ItemStatus::Withdrawn;
+
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Delete,
+            EntityType::Item,
+            id,
+            Some(&before),
+            None,
+        ));
+
+        Ok(())
+    }
+
+    fn is_barcode_valid(&self, barcode: &str, item_id: Option<Uuid>) -> Result<bool> {
+        Ok(!self
+            .data()
+            .items
+            .iter()
+            .any(|item| item.barcode == barcode && Some(item.id) != item_id))
+    }
+
+    fn search_catalog(
+        &self,
+        terms: &[String],
+        kind: Option<SearchResultKind>,
+        params: &ListParams,
+    ) -> Result<Page<SearchResult>> {
+        let data = self.data();
+
+        let entries = data
+            .books
+            .iter()
+            .filter(|book| book.deleted_at.is_none())
+            .map(|book| {
+                (
+                    SearchResultKind::Book,
+                    book.id,
+                    book.name.as_str(),
+                    book.description.as_str(),
+                )
+            })
+            .chain(
+                data.authors
+                    .iter()
+                    .filter(|author| author.deleted_at.is_none())
+                    .map(|author| {
+                        (
+                            SearchResultKind::Author,
+                            author.id,
+                            author.name.as_str(),
+                            author.description.as_str(),
+                        )
+                    }),
+            )
+            .chain(data.categories.iter().map(|category| {
+                (
+                    SearchResultKind::Category,
+                    category.id,
+                    category.name.as_str(),
+                    "",
+                )
+            }));
+
+        let term_tokens: Vec<Vec<String>> = terms
+            .iter()
+            .map(|term| tokenize(term).into_iter().map(|token| token.2).collect())
+            .filter(|tokens: &Vec<String>| !tokens.is_empty())
+            .collect();
+
+        let mut results: Vec<(u32, SearchResult)> = entries
+            .filter(|(entry_kind, ..)| kind.is_none_or(|kind| kind == *entry_kind))
+            .filter_map(|(kind, id, name, description)| {
+                let name_matches = match_tokens(name, &term_tokens);
+                let description_matches = match_tokens(description, &term_tokens);
+
+                // Every term has to match either the name or the description
+                let is_matching = (0..term_tokens.len()).all(|index| {
+                    name_matches.iter().any(|(term, _)| *term == index)
+                        || description_matches.iter().any(|(term, _)| *term == index)
+                });
+                if term_tokens.is_empty() || !is_matching {
+                    return None;
+                }
+
+                let score =
+                    name_matches.len() as u32 * NAME_WEIGHT + description_matches.len() as u32;
+                let snippet = if name_matches.is_empty() {
+                    highlight(description, &description_matches)
+                } else {
+                    highlight(name, &name_matches)
+                };
+
+                Some((
+                    score,
+                    SearchResult {
+                        kind,
+                        id,
+                        name: name.to_string(),
+                        snippet,
+                    },
+                ))
+            })
+            .collect();
+
+        results.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+        let results = results.into_iter().map(|(_, result)| result).collect();
+
+        Ok(paginate(results, params, |_, _, _| {
+            std::cmp::Ordering::Equal
+        }))
+    }
+}
+
+// Finds the book with the id, unless it is deleted
+// Adds the copy along with the record of its creation, as long as its barcode is not taken
+fn insert_item(data: &mut MemoryData, item: &Item) -> Result<()> {
+    if data
+        .items
+        .iter()
+        .any(|existing| existing.barcode == item.barcode)
+    {
+        return Err(RepositoryError::Backend(
+            "item barcode must be unique".to_string(),
+        ));
+    }
+
+    data.items.push(item.clone());
+    data.audit_entries.push(AuditEntry::new(
+        &AuditContext::current(),
+        AuditAction::Create,
+        EntityType::Item,
+        item.id,
+        None,
+        Some(item),
+    ));
+
+    Ok(())
+}
+
+fn find_book(data: &MemoryData, id: Uuid) -> Option<&Book> {
+    data.books
+        .iter()
+        .find(|book| book.id == id && book.deleted_at.is_none())
+}
+
+// Finds the author with the id, unless they are deleted
+fn find_author(data: &MemoryData, id: Uuid) -> Option<&Author> {
+    data.authors
+        .iter()
+        .find(|author| author.id == id && author.deleted_at.is_none())
+}
+
+// Checks whether the book matches the supported book search filters
+fn is_book_matching(data: &MemoryData, book: &Book, params: &ListParams) -> bool {
+    let is_category_matching = match params.filters.get("category") {
+        Some(_) => data
+            .book_categories
+            .iter()
+            .filter(|(book_id, _)| *book_id == book.id)
+            .filter_map(|(_, category_id)| {
+                data.categories
+                    .iter()
+                    .find(|category| category.id == *category_id)
+            })
+            .any(|category| contains_term(&category.name, params.filters.get("category"))),
+        None => true,
+    };
+
+    is_category_matching
+        && contains_term(&book.name, params.filters.get("name"))
+        && contains_term(&book.language, params.filters.get("language"))
+}
+
+fn compare_books(a: &Book, b: &Book, field: &str) -> std::cmp::Ordering {
+    match field {
+        "name" => a.name.cmp(&b.name),
+        _ => a.language.cmp(&b.language),
+    }
+}
+
+// Links the contributors, in the order that they are given, and categories to the book
+fn link_book(
+    data: &mut MemoryData,
+    book_id: Uuid,
+    contributors: &[BookContributorRequest],
+    category_ids: &[Uuid],
+) {
+    for contributor in contributors.iter() {
+        data.book_contributors.push(BookContributorLink {
+            book_id,
+            author_id: contributor.author_id,
+            role: contributor.role,
+        });
+    }
+
+    for category_id in category_ids.iter() {
+        data.book_categories.push((book_id, *category_id));
+    }
+}
+
+// Fills in the contributors, categories and number of copies of a stored book
+fn full_book(data: &MemoryData, book: &Book) -> Book {
+    let contributors = data
+        .book_contributors
+        .iter()
+        .filter(|link| link.book_id == book.id)
+        .filter_map(|link| {
+            let author = data
+                .authors
+                .iter()
+                .find(|author| author.id == link.author_id && author.deleted_at.is_none())?;
+
+            Some(BookContributor {
+                author_id: author.id,
+                name: author.name.clone(),
+                role: link.role,
+            })
+        })
+        .collect();
+
+    let mut categories: Vec<Category> = data
+        .book_categories
+        .iter()
+        .filter(|(book_id, _)| *book_id == book.id)
+        .filter_map(|(_, category_id)| {
+            data.categories
+                .iter()
+                .find(|category| category.id == *category_id)
+                .cloned()
+        })
+        .collect();
+    categories.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let items = data
+        .items
+        .iter()
+        .filter(|item| item.book_id == book.id && item.status != ItemStatus::Withdrawn);
+
+    Book {
+        contributors,
+        categories,
+        total_copies: items.clone().count() as u32,
+        available_copies: items
+            .filter(|item| item.status == ItemStatus::Available)
+            .count() as u32,
+        ..book.clone()
+    }
+}
+
+// Splits text into its lowercased words, along with where each of them starts and ends
+fn tokenize(text: &str) -> Vec<(usize, usize, String)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+
+    for (index, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, c.is_alphanumeric()) {
+            (None, true) => start = Some(index),
+            (Some(token_start), false) => {
+                tokens.push((token_start, index, text[token_start..index].to_lowercase()));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    tokens
+}
+
+// Finds the words of the text matching each term, as the index of the term and the span of
+// the words; the words of a term have to follow each other, with the last one as a prefix
+fn match_tokens(text: &str, term_tokens: &[Vec<String>]) -> Vec<(usize, (usize, usize))> {
+    let tokens = tokenize(text);
+    let mut matches = Vec::new();
+
+    for (term_index, term) in term_tokens.iter().enumerate() {
+        for window in tokens.windows(term.len()) {
+            let is_matching = window.iter().zip(term.iter()).enumerate().all(
+                |(index, ((_, _, token), term_token))| {
+                    if index == term.len() - 1 {
+                        token.starts_with(term_token.as_str())
+                    } else {
+                        token == term_token
+                    }
+                },
+            );
+
+            if is_matching {
+                matches.push((term_index, (window[0].0, window[term.len() - 1].1)));
+            }
+        }
+    }
+
+    matches
+}
+
+// Marks every matched span of the text, like the snippets of the index do
+fn highlight(text: &str, matches: &[(usize, (usize, usize))]) -> String {
+    let mut spans: Vec<(usize, usize)> = matches.iter().map(|(_, span)| *span).collect();
+    spans.sort();
+
+    let mut snippet = String::new();
+    let mut position = 0;
+
+    for (start, end) in spans {
+        if start < position {
+            continue;
+        }
+
+        snippet.push_str(&text[position..start]);
+        snippet.push(SNIPPET_MATCH_START);
+        snippet.push_str(&text[start..end]);
+        snippet.push(SNIPPET_MATCH_END);
+        position = end;
+    }
+
+    snippet.push_str(&text[position..]);
+    highlight_snippet(&snippet)
+}
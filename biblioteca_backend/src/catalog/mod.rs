@@ -1,6 +1,13 @@
 pub mod authors;
 pub mod books;
+pub mod categories;
+pub mod items;
 pub mod model;
+pub mod repository;
+pub mod search;
 
 mod db;
 mod error;
+mod memory;
+#[cfg(feature = "postgres")]
+mod postgres;
@@ -1,30 +1,108 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{
+    types::{FromSql, FromSqlError, FromSqlResult, ValueRef},
+    ToSql,
+};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Book {
     pub id: Uuid,
     pub name: String,
     pub description: String,
     pub language: String,
+
+    pub contributors: Vec<BookContributor>,
+    pub categories: Vec<Category>,
+
+    pub total_copies: u32,
+    pub available_copies: u32,
+
+    // Set once the book is deleted, until it is restored or purged
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Category {
     pub id: Uuid,
     pub name: String,
 }
 
-#[derive(Serialize)]
-pub struct BookAuthor {
-    pub book_id: Uuid,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContributorRole {
+    #[default]
+    Author,
+    Editor,
+    Translator,
+    Illustrator,
+}
+
+impl Display for ContributorRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContributorRole::Author => write!(f, "author"),
+            ContributorRole::Editor => write!(f, "editor"),
+            ContributorRole::Translator => write!(f, "translator"),
+            ContributorRole::Illustrator => write!(f, "illustrator"),
+        }
+    }
+}
+
+impl ToSql for ContributorRole {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(self.to_string().into())
+    }
+}
+
+impl FromStr for ContributorRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "author" => Ok(ContributorRole::Author),
+            "editor" => Ok(ContributorRole::Editor),
+            "translator" => Ok(ContributorRole::Translator),
+            "illustrator" => Ok(ContributorRole::Illustrator),
+            _ => Err(format!("{} is not a known value", s)),
+        }
+    }
+}
+
+impl FromSql for ContributorRole {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|_| FromSqlError::InvalidType)
+    }
+}
+
+// An author's contribution to a book, as returned with the book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookContributor {
     pub author_id: Uuid,
+    pub name: String,
+    pub role: ContributorRole,
+}
+
+// An author's contribution to a book, as given when creating or updating the book
+#[derive(Debug, Deserialize)]
+pub struct BookContributorRequest {
+    pub author_id: Uuid,
+
+    #[serde(default)]
+    pub role: ContributorRole,
 }
 
 #[derive(Serialize)]
 pub struct BookCategory {
     pub book_id: Uuid,
-    pub genre_id: Uuid,
+    pub category_id: Uuid,
 }
 
 #[derive(Debug, Deserialize)]
@@ -33,7 +111,10 @@ pub struct CreateBookRequest {
     pub description: String,
     pub language: String,
 
-    pub author_id: Uuid,
+    pub contributors: Vec<BookContributorRequest>,
+
+    #[serde(default)]
+    pub category_ids: Vec<Uuid>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -42,15 +123,22 @@ pub struct UpdateBookRequest {
     pub description: String,
     pub language: String,
 
-    pub author_id: Uuid,
+    pub contributors: Vec<BookContributorRequest>,
+
+    #[serde(default)]
+    pub category_ids: Vec<Uuid>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Author {
     pub id: Uuid,
     pub name: String,
     pub description: String,
     pub country: String,
+
+    // Set once the author is deleted, until they are restored or purged
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -66,3 +154,232 @@ pub struct UpdateAuthorRequest {
     pub description: String,
     pub country: String,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCategoryRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateCategoryRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemCondition {
+    New,
+    #[default]
+    Good,
+    Fair,
+    Poor,
+    Damaged,
+}
+
+impl Display for ItemCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ItemCondition::New => write!(f, "new"),
+            ItemCondition::Good => write!(f, "good"),
+            ItemCondition::Fair => write!(f, "fair"),
+            ItemCondition::Poor => write!(f, "poor"),
+            ItemCondition::Damaged => write!(f, "damaged"),
+        }
+    }
+}
+
+impl ToSql for ItemCondition {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(self.to_string().into())
+    }
+}
+
+impl FromStr for ItemCondition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "new" => Ok(ItemCondition::New),
+            "good" => Ok(ItemCondition::Good),
+            "fair" => Ok(ItemCondition::Fair),
+            "poor" => Ok(ItemCondition::Poor),
+            "damaged" => Ok(ItemCondition::Damaged),
+            _ => Err(format!("{} is not a known value", s)),
+        }
+    }
+}
+
+impl FromSql for ItemCondition {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|_| FromSqlError::InvalidType)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemStatus {
+    #[default]
+    Available,
+    Borrowed,
+    Lost,
+    Maintenance,
+    #[serde(rename = "on_hold")]
+    OnHold,
+    // Taken out of circulation, but kept for the loans and holds that refer to it
+    Withdrawn,
+}
+
+impl Display for ItemStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ItemStatus::Available => write!(f, "available"),
+            ItemStatus::Borrowed => write!(f, "borrowed"),
+            ItemStatus::Lost => write!(f, "lost"),
+            ItemStatus::Maintenance => write!(f, "maintenance"),
+            ItemStatus::OnHold => write!(f, "on_hold"),
+            ItemStatus::Withdrawn => write!(f, "withdrawn"),
+        }
+    }
+}
+
+impl ToSql for ItemStatus {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(self.to_string().into())
+    }
+}
+
+impl FromStr for ItemStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "available" => Ok(ItemStatus::Available),
+            "borrowed" => Ok(ItemStatus::Borrowed),
+            "lost" => Ok(ItemStatus::Lost),
+            "maintenance" => Ok(ItemStatus::Maintenance),
+            "on_hold" => Ok(ItemStatus::OnHold),
+            "withdrawn" => Ok(ItemStatus::Withdrawn),
+            _ => Err(format!("{} is not a known value", s)),
+        }
+    }
+}
+
+impl FromSql for ItemStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|_| FromSqlError::InvalidType)
+    }
+}
+
+// A physical, lendable copy of a book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Item {
+    pub id: Uuid,
+    pub book_id: Uuid,
+    pub barcode: String,
+    pub shelf_location: String,
+    pub condition: ItemCondition,
+    pub status: ItemStatus,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateItemRequest {
+    pub barcode: String,
+    pub shelf_location: String,
+
+    #[serde(default)]
+    pub condition: ItemCondition,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateItemRequest {
+    pub barcode: String,
+    pub shelf_location: String,
+    pub condition: ItemCondition,
+    pub status: ItemStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchResultKind {
+    Book,
+    Author,
+    Category,
+}
+
+impl Display for SearchResultKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchResultKind::Book => write!(f, "book"),
+            SearchResultKind::Author => write!(f, "author"),
+            SearchResultKind::Category => write!(f, "category"),
+        }
+    }
+}
+
+impl ToSql for SearchResultKind {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(self.to_string().into())
+    }
+}
+
+impl FromStr for SearchResultKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "book" => Ok(SearchResultKind::Book),
+            "author" => Ok(SearchResultKind::Author),
+            "category" => Ok(SearchResultKind::Category),
+            _ => Err(format!("{} is not a known value", s)),
+        }
+    }
+}
+
+impl FromSql for SearchResultKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|_| FromSqlError::InvalidType)
+    }
+}
+
+// A catalog entry matching a search, with the matched text highlighted in its snippet
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub kind: SearchResultKind,
+    pub id: Uuid,
+    pub name: String,
+    pub snippet: String,
+}
+
+// Delimiters of the matched text in the snippets of the index, which are only turned into
+// highlight tags once the rest of the snippet has been escaped
+pub(crate) const SNIPPET_MATCH_START: char = '\u{2}';
+pub(crate) const SNIPPET_MATCH_END: char = '\u{3}';
+
+// Escapes the snippet as HTML, so that the only markup in it is the highlighting of its matches
+pub(crate) fn highlight_snippet(snippet: &str) -> String {
+    let mut highlighted = String::with_capacity(snippet.len());
+
+    for c in snippet.chars() {
+        match c {
+            SNIPPET_MATCH_START => highlighted.push_str("<mark>"),
+            SNIPPET_MATCH_END => highlighted.push_str("</mark>"),
+            '&' => highlighted.push_str("&amp;"),
+            '<' => highlighted.push_str("&lt;"),
+            '>' => highlighted.push_str("&gt;"),
+            '"' => highlighted.push_str("&quot;"),
+            '\'' => highlighted.push_str("&#39;"),
+            c => highlighted.push(c),
+        }
+    }
+
+    highlighted
+}
@@ -0,0 +1,1142 @@
+use chrono::{DateTime, Utc};
+use postgres::{types::ToSql, GenericClient, Row};
+use uuid::Uuid;
+
+use crate::{
+    audit::{
+        context::AuditContext,
+        model::{AuditAction, AuditEntry, EntityType},
+        postgres::add_audit_entry,
+    },
+    library::postgres::{cancel_holds_of_book, pass_item_to_next_hold},
+    query::{Filters, ListParams, Page},
+    repository::{
+        like_pattern, postgres::QueryBuilder, PostgresRepository, RepositoryError, Result,
+    },
+};
+
+use super::{
+    model::{
+        highlight_snippet, Author, Book, BookContributor, BookContributorRequest, Category, Item,
+        ItemStatus, SearchResult, SearchResultKind,
+    },
+    repository::CatalogRepository,
+};
+
+// Columns selected for a book, including the number of copies held of it, which leaves out the
+// withdrawn ones
+const BOOK_COLUMNS: &str = "id, name, description, language,
+    (SELECT COUNT(*) FROM items WHERE items.book_id = books.id AND items.status != 'withdrawn'),
+    (SELECT COUNT(*) FROM items WHERE items.book_id = books.id AND items.status = 'available'),
+    deleted_at";
+
+const AUTHOR_COLUMNS: &str = "id, name, description, country, deleted_at";
+
+const ITEM_COLUMNS: &str = "id, book_id, barcode, shelf_location, condition, status";
+
+// Columns of the sort fields of books, authors and categories, compared byte by byte like
+// SQLite does
+const BOOK_SORT_COLUMNS: &[(&str, &str)] = &[
+    ("name", "name COLLATE \"C\""),
+    ("language", "language COLLATE \"C\""),
+];
+const AUTHOR_SORT_COLUMNS: &[(&str, &str)] = &[
+    ("name", "name COLLATE \"C\""),
+    ("country", "country COLLATE \"C\""),
+];
+const CATEGORY_SORT_COLUMNS: &[(&str, &str)] = &[("name", "name COLLATE \"C\"")];
+
+// Matches the search query against the index, ranking names over descriptions by the weights
+// they are indexed with
+const SEARCH_FROM: &str = "FROM catalog_search, to_tsquery('simple', $1) AS query
+    WHERE document @@ query AND ($2::TEXT IS NULL OR kind = $2)";
+
+impl CatalogRepository for PostgresRepository {
+    fn list_books(&self, params: &ListParams) -> Result<Page<Book>> {
+        self.with_client(|client| {
+            let mut query = QueryBuilder::new(&format!("SELECT {} FROM books", BOOK_COLUMNS));
+            push_book_filters(&mut query, &params.filters);
+            query.exclude_deleted("deleted_at", params).paginate(
+                params,
+                BOOK_SORT_COLUMNS,
+                "rowid",
+            );
+
+            let books = query_books(client, &query.sql(), &query.params())?;
+
+            Ok(Page::new(books, query.count(client)?, params))
+        })
+    }
+
+    fn list_books_of_author(&self, author_id: Uuid, params: &ListParams) -> Result<Page<Book>> {
+        self.with_client(|client| {
+            let mut query = QueryBuilder::new(&format!("SELECT {} FROM books", BOOK_COLUMNS));
+            query.condition(
+                "id IN (SELECT book_id FROM map_books_to_authors WHERE author_id = ?)",
+                author_id,
+            );
+            push_book_filters(&mut query, &params.filters);
+            query.exclude_deleted("deleted_at", params).paginate(
+                params,
+                BOOK_SORT_COLUMNS,
+                "rowid",
+            );
+
+            let books = query_books(client, &query.sql(), &query.params())?;
+
+            Ok(Page::new(books, query.count(client)?, params))
+        })
+    }
+
+    fn get_book(&self, id: Uuid) -> Result<Book> {
+        self.with_client(|client| fetch_book(client, id)?.ok_or(RepositoryError::NotFound))
+    }
+
+    fn add_book(
+        &self,
+        book: Book,
+        contributors: &[BookContributorRequest],
+        category_ids: &[Uuid],
+    ) -> Result<Book> {
+        // Read before handing over to the client, which runs outside of the request task
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            // Use transaction to ensure both statements complete
+            let mut tx = client.transaction()?;
+
+            // Add the book itself
+            tx.execute(
+                "INSERT INTO books (id, name, description, language) VALUES ($1, $2, $3, $4)",
+                &[&book.id, &book.name, &book.description, &book.language],
+            )?;
+
+            // Add links between contributing authors and book
+            add_contributors_of_book(&mut tx, book.id, contributors)?;
+
+            // Add links between categories and book
+            for category_id in category_ids.iter() {
+                tx.execute(
+                    "INSERT INTO map_books_to_categories (book_id, category_id) VALUES ($1, $2)",
+                    &[&book.id, category_id],
+                )?;
+            }
+
+            let book = fetch_book(&mut tx, book.id)?.ok_or(RepositoryError::NotFound)?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Create,
+                    EntityType::Book,
+                    book.id,
+                    None,
+                    Some(&book),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(book)
+        })
+    }
+
+    fn update_book(
+        &self,
+        book: Book,
+        contributors: &[BookContributorRequest],
+        category_ids: &[Uuid],
+    ) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            // Nothing to update if the book does not exist
+            let Some(before) = fetch_book(&mut tx, book.id)? else {
+                return Ok(());
+            };
+
+            // Update entry
+            tx.execute(
+                "UPDATE books
+                SET name = $1,
+                    description = $2,
+                    language = $3
+                WHERE
+                    id = $4",
+                &[&book.name, &book.description, &book.language, &book.id],
+            )?;
+
+            // Replace contributor associations
+            tx.execute(
+                "DELETE FROM map_books_to_authors WHERE book_id = $1",
+                &[&book.id],
+            )?;
+
+            add_contributors_of_book(&mut tx, book.id, contributors)?;
+
+            // Replace category associations
+            tx.execute(
+                "DELETE FROM map_books_to_categories WHERE book_id = $1",
+                &[&book.id],
+            )?;
+
+            for category_id in category_ids.iter() {
+                tx.execute(
+                    "INSERT INTO map_books_to_categories (book_id, category_id) VALUES ($1, $2)",
+                    &[&book.id, category_id],
+                )?;
+            }
+
+            let after = fetch_book(&mut tx, book.id)?.ok_or(RepositoryError::NotFound)?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Update,
+                    EntityType::Book,
+                    book.id,
+                    Some(&before),
+                    Some(&after),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn delete_book(&self, id: Uuid) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            if let Some(book) = fetch_book(&mut tx, id)? {
+                tx.execute(
+                    "UPDATE books SET deleted_at = $1 WHERE id = $2",
+                    &[&Utc::now(), &id],
+                )?;
+
+                add_audit_entry(
+                    &mut tx,
+                    &AuditEntry::new(
+                        &context,
+                        AuditAction::Delete,
+                        EntityType::Book,
+                        id,
+                        Some(&book),
+                        None,
+                    ),
+                )?;
+
+                cancel_holds_of_book(&mut tx, &context, id)?;
+            }
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn restore_book(&self, id: Uuid) -> Result<Book> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            let num_restored = tx.execute(
+                "UPDATE books SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL",
+                &[&id],
+            )?;
+
+            if num_restored == 0 {
+                return Err(RepositoryError::NotFound);
+            }
+
+            let book = fetch_book(&mut tx, id)?.ok_or(RepositoryError::NotFound)?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Restore,
+                    EntityType::Book,
+                    id,
+                    None,
+                    Some(&book),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(book)
+        })
+    }
+
+    fn purge_deleted_books(&self, deleted_before: DateTime<Utc>) -> Result<u32> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            let books = query_books(
+                &mut tx,
+                &format!(
+                    "SELECT {} FROM books WHERE deleted_at < $1
+                AND NOT EXISTS (SELECT 1 FROM map_users_to_borrowed_books WHERE book_id = books.id)
+                AND NOT EXISTS (SELECT 1 FROM holds WHERE book_id = books.id)",
+                    BOOK_COLUMNS
+                ),
+                &[&deleted_before],
+            )?;
+
+            for book in books.iter() {
+                tx.execute("DELETE FROM books WHERE id = $1", &[&book.id])?;
+
+                add_audit_entry(
+                    &mut tx,
+                    &AuditEntry::new(
+                        &context,
+                        AuditAction::Purge,
+                        EntityType::Book,
+                        book.id,
+                        Some(book),
+                        None,
+                    ),
+                )?;
+            }
+
+            tx.commit()?;
+
+            Ok(books.len() as u32)
+        })
+    }
+
+    fn is_book_exists(&self, id: Uuid) -> Result<bool> {
+        self.with_client(|client| {
+            let count = count_rows(
+                client,
+                "SELECT COUNT(*) FROM books WHERE id = $1 AND deleted_at IS NULL",
+                &[&id],
+            )?;
+
+            Ok(count > 0)
+        })
+    }
+
+    fn list_authors(&self, params: &ListParams) -> Result<Page<Author>> {
+        self.with_client(|client| {
+            let mut query = QueryBuilder::new(&format!("SELECT {} FROM authors", AUTHOR_COLUMNS));
+            query
+                .contains("name", params.filters.get("name"))
+                .contains("country", params.filters.get("country"))
+                .exclude_deleted("deleted_at", params)
+                .paginate(params, AUTHOR_SORT_COLUMNS, "rowid");
+
+            let authors = client
+                .query(&query.sql(), &query.params())?
+                .iter()
+                .map(author_from_row)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Page::new(authors, query.count(client)?, params))
+        })
+    }
+
+    fn get_author(&self, id: Uuid) -> Result<Author> {
+        self.with_client(|client| fetch_author(client, id)?.ok_or(RepositoryError::NotFound))
+    }
+
+    fn add_author(&self, author: Author) -> Result<Author> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            tx.execute(
+                "INSERT INTO authors (id, name, description, country) VALUES ($1, $2, $3, $4)",
+                &[
+                    &author.id,
+                    &author.name,
+                    &author.description,
+                    &author.country,
+                ],
+            )?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Create,
+                    EntityType::Author,
+                    author.id,
+                    None,
+                    Some(&author),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(author)
+        })
+    }
+
+    fn update_author(&self, author: Author) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            let Some(before) = fetch_author(&mut tx, author.id)? else {
+                return Ok(());
+            };
+
+            tx.execute(
+                "UPDATE authors
+                SET name = $1,
+                    description = $2,
+                    country = $3
+                WHERE
+                    id = $4",
+                &[
+                    &author.name,
+                    &author.description,
+                    &author.country,
+                    &author.id,
+                ],
+            )?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Update,
+                    EntityType::Author,
+                    author.id,
+                    Some(&before),
+                    Some(&author),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn delete_author(&self, id: Uuid) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            if let Some(author) = fetch_author(&mut tx, id)? {
+                tx.execute(
+                    "UPDATE authors SET deleted_at = $1 WHERE id = $2",
+                    &[&Utc::now(), &id],
+                )?;
+
+                add_audit_entry(
+                    &mut tx,
+                    &AuditEntry::new(
+                        &context,
+                        AuditAction::Delete,
+                        EntityType::Author,
+                        id,
+                        Some(&author),
+                        None,
+                    ),
+                )?;
+            }
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn restore_author(&self, id: Uuid) -> Result<Author> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            let num_restored = tx.execute(
+                "UPDATE authors SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL",
+                &[&id],
+            )?;
+
+            if num_restored == 0 {
+                return Err(RepositoryError::NotFound);
+            }
+
+            let author = fetch_author(&mut tx, id)?.ok_or(RepositoryError::NotFound)?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Restore,
+                    EntityType::Author,
+                    id,
+                    None,
+                    Some(&author),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(author)
+        })
+    }
+
+    fn purge_deleted_authors(&self, deleted_before: DateTime<Utc>) -> Result<u32> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            let authors = tx
+                .query(
+                    &format!(
+                        "SELECT {} FROM authors WHERE deleted_at < $1
+                AND NOT EXISTS (SELECT 1 FROM map_books_to_authors WHERE author_id = authors.id)",
+                        AUTHOR_COLUMNS
+                    ),
+                    &[&deleted_before],
+                )?
+                .iter()
+                .map(author_from_row)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for author in authors.iter() {
+                tx.execute("DELETE FROM authors WHERE id = $1", &[&author.id])?;
+
+                add_audit_entry(
+                    &mut tx,
+                    &AuditEntry::new(
+                        &context,
+                        AuditAction::Purge,
+                        EntityType::Author,
+                        author.id,
+                        Some(author),
+                        None,
+                    ),
+                )?;
+            }
+
+            tx.commit()?;
+
+            Ok(authors.len() as u32)
+        })
+    }
+
+    fn is_author_exists(&self, id: Uuid) -> Result<bool> {
+        self.with_client(|client| {
+            let count = count_rows(
+                client,
+                "SELECT COUNT(*) FROM authors WHERE id = $1 AND deleted_at IS NULL",
+                &[&id],
+            )?;
+
+            Ok(count > 0)
+        })
+    }
+
+    fn list_categories(&self, params: &ListParams) -> Result<Page<Category>> {
+        self.with_client(|client| {
+            let mut query = QueryBuilder::new("SELECT id, name FROM categories");
+            query.contains("name", params.filters.get("name")).paginate(
+                params,
+                CATEGORY_SORT_COLUMNS,
+                "name COLLATE \"C\"",
+            );
+
+            let categories = client
+                .query(&query.sql(), &query.params())?
+                .iter()
+                .map(category_from_row)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Page::new(categories, query.count(client)?, params))
+        })
+    }
+
+    fn get_category(&self, id: Uuid) -> Result<Category> {
+        self.with_client(|client| fetch_category(client, id)?.ok_or(RepositoryError::NotFound))
+    }
+
+    fn add_category(&self, category: Category) -> Result<Category> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            tx.execute(
+                "INSERT INTO categories (id, name) VALUES ($1, $2)",
+                &[&category.id, &category.name],
+            )?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Create,
+                    EntityType::Category,
+                    category.id,
+                    None,
+                    Some(&category),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(category)
+        })
+    }
+
+    fn update_category(&self, category: Category) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            let Some(before) = fetch_category(&mut tx, category.id)? else {
+                return Ok(());
+            };
+
+            tx.execute(
+                "UPDATE categories SET name = $1 WHERE id = $2",
+                &[&category.name, &category.id],
+            )?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Update,
+                    EntityType::Category,
+                    category.id,
+                    Some(&before),
+                    Some(&category),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn delete_category(&self, id: Uuid) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            if let Some(category) = fetch_category(&mut tx, id)? {
+                tx.execute("DELETE FROM categories WHERE id = $1", &[&id])?;
+
+                add_audit_entry(
+                    &mut tx,
+                    &AuditEntry::new(
+                        &context,
+                        AuditAction::Delete,
+                        EntityType::Category,
+                        id,
+                        Some(&category),
+                        None,
+                    ),
+                )?;
+            }
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn is_category_exists(&self, id: Uuid) -> Result<bool> {
+        self.with_client(|client| {
+            let count = count_rows(
+                client,
+                "SELECT COUNT(*) FROM categories WHERE id = $1",
+                &[&id],
+            )?;
+
+            Ok(count > 0)
+        })
+    }
+
+    fn is_category_name_valid(&self, name: &str, category_id: Option<Uuid>) -> Result<bool> {
+        self.with_client(|client| {
+            let count = count_rows(
+                client,
+                "SELECT COUNT(*) FROM categories WHERE name = $1 AND id IS DISTINCT FROM $2",
+                &[&name, &category_id],
+            )?;
+
+            Ok(count == 0)
+        })
+    }
+
+    fn list_items_of_book(&self, book_id: Uuid) -> Result<Vec<Item>> {
+        self.with_client(|client| {
+            let items = client
+                .query(
+                    &format!(
+                        "SELECT {} FROM items WHERE book_id = $1 ORDER BY barcode COLLATE \"C\"",
+                        ITEM_COLUMNS
+                    ),
+                    &[&book_id],
+                )?
+                .iter()
+                .map(item_from_row)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(items)
+        })
+    }
+
+    fn get_item(&self, id: Uuid) -> Result<Item> {
+        self.with_client(|client| fetch_item(client, id)?.ok_or(RepositoryError::NotFound))
+    }
+
+    fn add_item(&self, item: Item) -> Result<Item> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            insert_item(&mut tx, &context, &item)?;
+
+            tx.commit()?;
+
+            Ok(item)
+        })
+    }
+
+    fn add_shelved_item(&self, item: Item, hold_pickup_days: u32) -> Result<Item> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            insert_item(&mut tx, &context, &item)?;
+            pass_item_to_next_hold(&mut tx, &context, item.book_id, item.id, hold_pickup_days)?;
+            let item = fetch_item(&mut tx, item.id)?.ok_or(RepositoryError::NotFound)?;
+
+            tx.commit()?;
+
+            Ok(item)
+        })
+    }
+
+    fn update_item(&self, item: Item, hold_pickup_days: u32) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            let Some(before) = fetch_item(&mut tx, item.id)? else {
+                return Ok(());
+            };
+
+            tx.execute(
+                "UPDATE items
+                SET barcode = $1,
+                    shelf_location = $2,
+                    condition = $3,
+                    status = $4
+                WHERE
+                    id = $5",
+                &[
+                    &item.barcode,
+                    &item.shelf_location,
+                    &item.condition,
+                    &item.status,
+                    &item.id,
+                ],
+            )?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Update,
+                    EntityType::Item,
+                    item.id,
+                    Some(&before),
+                    Some(&item),
+                ),
+            )?;
+
+            if item.status == ItemStatus::Available {
+                pass_item_to_next_hold(&mut tx, &context, item.book_id, item.id, hold_pickup_days)?;
+            }
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn delete_item(&self, id: Uuid) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            if let Some(item) = fetch_item(&mut tx, id)? {
+                if item.status == ItemStatus::Withdrawn {
+                    return Ok(());
+                }
+
+                tx.execute(
+                    "UPDATE items SET status = $1 WHERE id = $2",
+                    &[&ItemStatus::Withdrawn, &id],
+                )?;
+
+                add_audit_entry(
+                    &mut tx,
+                    &AuditEntry::new(
+                        &context,
+                        AuditAction::Delete,
+                        EntityType::Item,
+                        id,
+                        Some(&item),
+                        None,
+                    ),
+                )?;
+            }
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn is_barcode_valid(&self, barcode: &str, item_id: Option<Uuid>) -> Result<bool> {
+        self.with_client(|client| {
+            let count = count_rows(
+                client,
+                "SELECT COUNT(*) FROM items WHERE barcode = $1 AND id IS DISTINCT FROM $2",
+                &[&barcode, &item_id],
+            )?;
+
+            Ok(count == 0)
+        })
+    }
+
+    fn search_catalog(
+        &self,
+        terms: &[String],
+        kind: Option<SearchResultKind>,
+        params: &ListParams,
+    ) -> Result<Page<SearchResult>> {
+        self.with_client(|client| {
+            let search_params: [&(dyn ToSql + Sync); 2] = [&to_tsquery(terms), &kind];
+
+            // The snippet is taken from the name if it matches, otherwise from the description.
+            // The parser would take anything between angle brackets for a tag and drop it, so
+            // they are swapped for control characters around the headline and swapped back after
+            let results = client
+                .query(
+                    &format!(
+                        "SELECT kind, entity_id, name,
+                            translate(
+                                ts_headline(
+                                    'simple',
+                                    translate(
+                                        CASE WHEN to_tsvector('simple', translate(name, '<>', '  ')) @@ query
+                                            THEN name ELSE description END,
+                                        '<>',
+                                        chr(14) || chr(15)
+                                    ),
+                                    query,
+                                    'StartSel=' || chr(2) || ', StopSel=' || chr(3) || ', MaxWords=16, MinWords=8'
+                                ),
+                                chr(14) || chr(15),
+                                '<>'
+                            )
+                        {}
+                        ORDER BY ts_rank(document, query) DESC, name COLLATE \"C\"
+                        LIMIT {} OFFSET {}",
+                        SEARCH_FROM, params.limit, params.offset
+                    ),
+                    &search_params,
+                )?
+                .iter()
+                .map(|row| {
+                    Ok(SearchResult {
+                        kind: row.try_get(0)?,
+                        id: row.try_get(1)?,
+                        name: row.try_get(2)?,
+                        snippet: highlight_snippet(row.try_get(3)?),
+                    })
+                })
+                .collect::<Result<Vec<_>, postgres::Error>>()?;
+
+            let total = count_rows(
+                client,
+                &format!("SELECT COUNT(*) {}", SEARCH_FROM),
+                &search_params,
+            )?;
+
+            Ok(Page::new(results, total, params))
+        })
+    }
+}
+
+// Adds the supported book search filters to a `books` query
+fn push_book_filters(query: &mut QueryBuilder, filters: &Filters) {
+    query
+        .contains("name", filters.get("name"))
+        .contains("language", filters.get("language"));
+
+    if let Some(category) = filters.get("category") {
+        query.condition(
+            "id IN (
+                SELECT a.book_id FROM map_books_to_categories a, categories b
+                WHERE a.category_id = b.id AND b.name ILIKE ? ESCAPE '\\'
+            )",
+            like_pattern(category),
+        );
+    }
+}
+
+fn count_rows(
+    client: &mut impl GenericClient,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<u32, postgres::Error> {
+    let count: i64 = client.query_one(query, params)?.try_get(0)?;
+
+    Ok(count as u32)
+}
+
+fn query_books(
+    client: &mut impl GenericClient,
+    query: &str,
+    params: &[&(dyn ToSql + Sync)],
+) -> Result<Vec<Book>, postgres::Error> {
+    let books = client
+        .query(query, params)?
+        .iter()
+        .map(book_from_row)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    books
+        .into_iter()
+        .map(|mut book| {
+            book.contributors = get_contributors_of_book(client, book.id)?;
+            book.categories = get_categories_of_book(client, book.id)?;
+            Ok(book)
+        })
+        .collect()
+}
+
+fn fetch_book(client: &mut impl GenericClient, id: Uuid) -> Result<Option<Book>, postgres::Error> {
+    let Some(row) = client.query_opt(
+        &format!(
+            "SELECT {} FROM books WHERE id = $1 AND deleted_at IS NULL",
+            BOOK_COLUMNS
+        ),
+        &[&id],
+    )?
+    else {
+        return Ok(None);
+    };
+
+    let mut book = book_from_row(&row)?;
+    book.contributors = get_contributors_of_book(client, book.id)?;
+    book.categories = get_categories_of_book(client, book.id)?;
+
+    Ok(Some(book))
+}
+
+fn book_from_row(row: &Row) -> Result<Book, postgres::Error> {
+    Ok(Book {
+        id: row.try_get(0)?,
+        name: row.try_get(1)?,
+        description: row.try_get(2)?,
+        language: row.try_get(3)?,
+        contributors: Vec::new(),
+        categories: Vec::new(),
+        total_copies: row.try_get::<_, i64>(4)? as u32,
+        available_copies: row.try_get::<_, i64>(5)? as u32,
+        deleted_at: row.try_get(6)?,
+    })
+}
+
+fn get_contributors_of_book(
+    client: &mut impl GenericClient,
+    book_id: Uuid,
+) -> Result<Vec<BookContributor>, postgres::Error> {
+    client
+        .query(
+            "SELECT b.id, b.name, a.role FROM map_books_to_authors a, authors b
+            WHERE a.author_id = b.id AND a.book_id = $1 AND b.deleted_at IS NULL
+            ORDER BY a.position",
+            &[&book_id],
+        )?
+        .iter()
+        .map(|row| {
+            Ok(BookContributor {
+                author_id: row.try_get(0)?,
+                name: row.try_get(1)?,
+                role: row.try_get(2)?,
+            })
+        })
+        .collect()
+}
+
+// Links the contributors to the book, in the order that they are given
+fn add_contributors_of_book(
+    client: &mut impl GenericClient,
+    book_id: Uuid,
+    contributors: &[BookContributorRequest],
+) -> Result<(), postgres::Error> {
+    for (position, contributor) in contributors.iter().enumerate() {
+        client.execute(
+            "INSERT INTO map_books_to_authors (book_id, author_id, role, position) VALUES ($1, $2, $3, $4)",
+            &[&book_id, &contributor.author_id, &contributor.role, &(position as i32)],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn get_categories_of_book(
+    client: &mut impl GenericClient,
+    book_id: Uuid,
+) -> Result<Vec<Category>, postgres::Error> {
+    client
+        .query(
+            "SELECT b.id, b.name FROM map_books_to_categories a, categories b
+            WHERE a.category_id = b.id AND a.book_id = $1
+            ORDER BY b.name COLLATE \"C\"",
+            &[&book_id],
+        )?
+        .iter()
+        .map(category_from_row)
+        .collect()
+}
+
+fn fetch_author(
+    client: &mut impl GenericClient,
+    id: Uuid,
+) -> Result<Option<Author>, postgres::Error> {
+    client
+        .query_opt(
+            &format!(
+                "SELECT {} FROM authors WHERE id = $1 AND deleted_at IS NULL",
+                AUTHOR_COLUMNS
+            ),
+            &[&id],
+        )?
+        .map(|row| author_from_row(&row))
+        .transpose()
+}
+
+fn author_from_row(row: &Row) -> Result<Author, postgres::Error> {
+    Ok(Author {
+        id: row.try_get(0)?,
+        name: row.try_get(1)?,
+        description: row.try_get(2)?,
+        country: row.try_get(3)?,
+        deleted_at: row.try_get(4)?,
+    })
+}
+
+fn fetch_category(
+    client: &mut impl GenericClient,
+    id: Uuid,
+) -> Result<Option<Category>, postgres::Error> {
+    client
+        .query_opt("SELECT id, name FROM categories WHERE id = $1", &[&id])?
+        .map(|row| category_from_row(&row))
+        .transpose()
+}
+
+fn category_from_row(row: &Row) -> Result<Category, postgres::Error> {
+    Ok(Category {
+        id: row.try_get(0)?,
+        name: row.try_get(1)?,
+    })
+}
+
+// Adds the copy along with the record of its creation
+fn insert_item(
+    client: &mut impl GenericClient,
+    context: &AuditContext,
+    item: &Item,
+) -> Result<(), postgres::Error> {
+    client.execute(
+        "INSERT INTO items (id, book_id, barcode, shelf_location, condition, status) VALUES ($1, $2, $3, $4, $5, $6)",
+        &[
+            &item.id,
+            &item.book_id,
+            &item.barcode,
+            &item.shelf_location,
+            &item.condition,
+            &item.status,
+        ],
+    )?;
+
+    add_audit_entry(
+        client,
+        &AuditEntry::new(
+            context,
+            AuditAction::Create,
+            EntityType::Item,
+            item.id,
+            None,
+            Some(item),
+        ),
+    )
+}
+
+fn fetch_item(client: &mut impl GenericClient, id: Uuid) -> Result<Option<Item>, postgres::Error> {
+    client
+        .query_opt(
+            &format!("SELECT {} FROM items WHERE id = $1", ITEM_COLUMNS),
+            &[&id],
+        )?
+        .map(|row| item_from_row(&row))
+        .transpose()
+}
+
+fn item_from_row(row: &Row) -> Result<Item, postgres::Error> {
+    Ok(Item {
+        id: row.try_get(0)?,
+        book_id: row.try_get(1)?,
+        barcode: row.try_get(2)?,
+        shelf_location: row.try_get(3)?,
+        condition: row.try_get(4)?,
+        status: row.try_get(5)?,
+    })
+}
+
+// Turns each term of a search into a prefix match of the words it is made of, in the order
+// they appear, so that user input is never read as query syntax and every term has to match
+fn to_tsquery(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|term| {
+            let words: Vec<String> = term
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .map(|word| format!("'{}'", word))
+                .collect();
+
+            format!("{}:*", words.join(" <-> "))
+        })
+        .collect::<Vec<String>>()
+        .join(" & ")
+}
@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    query::{ListParams, ListSpec, Page},
+    repository::Result,
+};
+
+use super::model::{
+    Author, Book, BookContributorRequest, Category, Item, SearchResult, SearchResultKind,
+};
+
+// Filters and sort fields supported when listing books
+pub const BOOK_LIST: ListSpec = ListSpec {
+    filters: &["name", "language", "category", "include_deleted"],
+    sort_fields: &["name", "language"],
+};
+
+// Filters and sort fields supported when listing authors
+pub const AUTHOR_LIST: ListSpec = ListSpec {
+    filters: &["name", "country", "include_deleted"],
+    sort_fields: &["name", "country"],
+};
+
+// Filters and sort fields supported when listing categories
+pub const CATEGORY_LIST: ListSpec = ListSpec {
+    filters: &["name"],
+    sort_fields: &["name"],
+};
+
+// Filters supported when searching the catalog, which is always ranked by relevance
+pub const SEARCH_LIST: ListSpec = ListSpec {
+    filters: &["q", "kind"],
+    sort_fields: &[],
+};
+
+// Storage of the books, authors, categories and copies that make up the catalog
+pub trait CatalogRepository: Send + Sync {
+    // Lists the books that are not deleted, unless the list params include them
+    fn list_books(&self, params: &ListParams) -> Result<Page<Book>>;
+
+    fn list_books_of_author(&self, author_id: Uuid, params: &ListParams) -> Result<Page<Book>>;
+
+    fn get_book(&self, id: Uuid) -> Result<Book>;
+
+    // Adds the book along with its contributors, in the order they are given, and categories
+    fn add_book(
+        &self,
+        book: Book,
+        contributors: &[BookContributorRequest],
+        category_ids: &[Uuid],
+    ) -> Result<Book>;
+
+    // Updates the book, replacing its contributors and categories; does nothing if it does not exist
+    fn update_book(
+        &self,
+        book: Book,
+        contributors: &[BookContributorRequest],
+        category_ids: &[Uuid],
+    ) -> Result<()>;
+
+    // Marks the book as deleted, keeping it along with its copies, loans and holds until it is
+    // purged; deleted books are treated as missing everywhere else
+    fn delete_book(&self, id: Uuid) -> Result<()>;
+
+    // Brings back a deleted book, failing if there is no deleted book with the id
+    fn restore_book(&self, id: Uuid) -> Result<Book>;
+
+    // Removes the books deleted before the time for good, along with their copies, skipping the
+    // books that loans or holds still refer to, and returns how many were removed
+    fn purge_deleted_books(&self, deleted_before: DateTime<Utc>) -> Result<u32>;
+
+    fn is_book_exists(&self, id: Uuid) -> Result<bool>;
+
+    // Lists the authors that are not deleted, unless the list params include them
+    fn list_authors(&self, params: &ListParams) -> Result<Page<Author>>;
+
+    fn get_author(&self, id: Uuid) -> Result<Author>;
+
+    fn add_author(&self, author: Author) -> Result<Author>;
+
+    fn update_author(&self, author: Author) -> Result<()>;
+
+    // Marks the author as deleted, hiding them from the contributors of their books
+    fn delete_author(&self, id: Uuid) -> Result<()>;
+
+    // Brings back a deleted author, failing if there is no deleted author with the id
+    fn restore_author(&self, id: Uuid) -> Result<Author>;
+
+    // Removes the authors deleted before the time for good, skipping the authors still credited
+    // on a book, and returns how many were removed
+    fn purge_deleted_authors(&self, deleted_before: DateTime<Utc>) -> Result<u32>;
+
+    fn is_author_exists(&self, id: Uuid) -> Result<bool>;
+
+    fn list_categories(&self, params: &ListParams) -> Result<Page<Category>>;
+
+    fn get_category(&self, id: Uuid) -> Result<Category>;
+
+    fn add_category(&self, category: Category) -> Result<Category>;
+
+    fn update_category(&self, category: Category) -> Result<()>;
+
+    // Deletes the category, removing it from all books
+    fn delete_category(&self, id: Uuid) -> Result<()>;
+
+    fn is_category_exists(&self, id: Uuid) -> Result<bool>;
+
+    // Checks that no other category than the given one already has the name
+    fn is_category_name_valid(&self, name: &str, category_id: Option<Uuid>) -> Result<bool>;
+
+    fn list_items_of_book(&self, book_id: Uuid) -> Result<Vec<Item>>;
+
+    fn get_item(&self, id: Uuid) -> Result<Item>;
+
+    fn add_item(&self, item: Item) -> Result<Item>;
+
+    // Adds a new copy to the shelf, setting it aside for the first patron waiting for its book
+    fn add_shelved_item(&self, item: Item, hold_pickup_days: u32) -> Result<Item>;
+
+    // Updates the copy, setting it aside for the first patron waiting for its book if it is put
+    // back on the shelf
+    fn update_item(&self, item: Item, hold_pickup_days: u32) -> Result<()>;
+
+    // Withdraws the copy, keeping it for the loans and holds that refer to it
+    fn delete_item(&self, id: Uuid) -> Result<()>;
+
+    // Checks that no other copy than the given one already has the barcode
+    fn is_barcode_valid(&self, barcode: &str, item_id: Option<Uuid>) -> Result<bool>;
+
+    // Searches the names and descriptions of the catalog for entries with a word starting with
+    // each of the terms, names weighing more than descriptions in their ranking
+    fn search_catalog(
+        &self,
+        terms: &[String],
+        kind: Option<SearchResultKind>,
+        params: &ListParams,
+    ) -> Result<Page<SearchResult>>;
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+
+use crate::app::AppState;
+use crate::catalog::error::CatalogError;
+use crate::catalog::repository::SEARCH_LIST;
+use crate::error::Error;
+use crate::query::{ListParams, Page};
+
+use axum::routing::get;
+use axum::Router;
+use axum::{
+    extract::{Query, State},
+    http::Uri,
+    Json,
+};
+
+use super::model::{SearchResult, SearchResultKind};
+
+pub fn search_router() -> Router<AppState> {
+    Router::new().route("/search", get(search_catalog))
+}
+
+// Searches the names and descriptions of books, authors and categories by keyword
+async fn search_catalog(
+    state: State<AppState>,
+    uri: Uri,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Page<SearchResult>>, Error> {
+    tracing::debug!("GET /search with query params: {:?}", params);
+
+    let params = ListParams::parse(&uri, params, &SEARCH_LIST)
+        .map_err(|err| Error::bad_request(err.to_string()))?;
+
+    let terms = match params.filters.get("q").map(|q| to_search_terms(q)) {
+        Some(terms) if !terms.is_empty() => terms,
+        _ => {
+            return Err(Error::bad_request(
+                CatalogError::SearchQueryMissing.to_string(),
+            ))
+        }
+    };
+
+    let kind = match params.filters.get("kind").map(|kind| kind.as_str()) {
+        Some("book") => Some(SearchResultKind::Book),
+        Some("author") => Some(SearchResultKind::Author),
+        Some("category") => Some(SearchResultKind::Category),
+        Some(_) => {
+            return Err(Error::bad_request(
+                CatalogError::InvalidSearchKind.to_string(),
+            ))
+        }
+        None => None,
+    };
+
+    match state.catalog.search_catalog(&terms, kind, &params) {
+        Ok(results) => Ok(Json(results)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Splits a search into the words that every result has to match, skipping any without a
+// letter or digit to match on
+fn to_search_terms(q: &str) -> Vec<String> {
+    q.split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .map(|word| word.to_string())
+        .collect()
+}
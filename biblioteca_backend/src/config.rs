@@ -0,0 +1,377 @@
+use std::{
+    fmt, fs, io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+
+use clap::{Args, ValueEnum};
+use serde::Deserialize;
+
+use crate::{
+    auth::model::AuthSettings, database::DEFAULT_POOL_SIZE, library::model::LibrarySettings,
+};
+
+// Config file read when none is given, if it exists
+pub const DEFAULT_CONFIG_PATH: &str = "biblioteca.toml";
+
+// Shortest secret the session tokens can be signed with
+pub const MIN_TOKEN_SECRET_LENGTH: usize = 32;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    ReadError(PathBuf, io::Error),
+    ParseError(PathBuf, toml::de::Error),
+    InvalidValue(&'static str, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::ReadError(path, err) => {
+                write!(f, "could not read config file {}: {}", path.display(), err)
+            }
+            ConfigError::ParseError(path, err) => {
+                write!(f, "invalid config file {}: {}", path.display(), err)
+            }
+            ConfigError::InvalidValue(key, reason) => {
+                write!(f, "invalid config value for {}: {}", key, reason)
+            }
+        }
+    }
+}
+
+// Settings of the application, read from a TOML file with the same sections
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub log: LogConfig,
+    pub library: LibrarySettings,
+    pub auth: AuthSettings,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind_address: SocketAddr,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            bind_address: SocketAddr::from(([127, 0, 0, 1], 3000)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    // Path of the SQLite database, used unless a PostgreSQL URL is given
+    pub path: String,
+
+    // Connection string of a PostgreSQL database to use instead of the SQLite file
+    pub url: Option<String>,
+
+    // Maximum number of connections kept open to the database
+    pub pool_size: u32,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig {
+            path: String::from("library.sqlite"),
+            url: None,
+            pool_size: DEFAULT_POOL_SIZE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub level: LogLevel,
+    pub format: LogFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    #[default]
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl From<LogLevel> for tracing::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Trace => tracing::Level::TRACE,
+            LogLevel::Debug => tracing::Level::DEBUG,
+            LogLevel::Info => tracing::Level::INFO,
+            LogLevel::Warn => tracing::Level::WARN,
+            LogLevel::Error => tracing::Level::ERROR,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    // Human-readable lines, for running the application by hand
+    #[default]
+    Full,
+    // Shorter human-readable lines
+    Compact,
+    // One JSON object per line, for log collectors
+    Json,
+}
+
+// Settings given on the command line or through `BIBLIOTECA_*` environment variables, which
+// take precedence over the config file
+#[derive(Debug, Clone, Default, Args)]
+pub struct ConfigOverrides {
+    /// Path of the TOML config file [default: biblioteca.toml, if it exists]
+    #[arg(long, global = true, env = "BIBLIOTECA_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Address the server listens on
+    #[arg(long, global = true, env = "BIBLIOTECA_BIND_ADDRESS")]
+    pub bind_address: Option<SocketAddr>,
+
+    /// Path of the SQLite database
+    #[arg(long, global = true, env = "BIBLIOTECA_DATABASE_PATH")]
+    pub database_path: Option<String>,
+
+    /// Connection string of a PostgreSQL database to use instead of SQLite
+    #[arg(long, global = true, env = "BIBLIOTECA_DATABASE_URL")]
+    pub database_url: Option<String>,
+
+    /// Maximum number of connections kept open to the database
+    #[arg(long, global = true, env = "BIBLIOTECA_POOL_SIZE")]
+    pub pool_size: Option<u32>,
+
+    /// Most verbose level of the logs
+    #[arg(long, global = true, env = "BIBLIOTECA_LOG_LEVEL")]
+    pub log_level: Option<LogLevel>,
+
+    /// Format of the logs
+    #[arg(long, global = true, env = "BIBLIOTECA_LOG_FORMAT")]
+    pub log_format: Option<LogFormat>,
+
+    /// Number of days a returned copy stays on the hold shelf
+    #[arg(long, global = true, env = "BIBLIOTECA_HOLD_PICKUP_DAYS")]
+    pub hold_pickup_days: Option<u32>,
+
+    /// Outstanding balance above which users can no longer borrow books
+    #[arg(long, global = true, env = "BIBLIOTECA_MAX_OUTSTANDING_FINES_CENTS")]
+    pub max_outstanding_fines_cents: Option<i64>,
+
+    /// Loan period of user roles created without one
+    #[arg(long, global = true, env = "BIBLIOTECA_DEFAULT_LOAN_PERIOD_DAYS")]
+    pub default_loan_period_days: Option<i32>,
+
+    /// Number of renewals of user roles created without one
+    #[arg(long, global = true, env = "BIBLIOTECA_DEFAULT_MAX_RENEWALS")]
+    pub default_max_renewals: Option<i32>,
+
+    /// Daily fine of user roles created without one
+    #[arg(long, global = true, env = "BIBLIOTECA_DEFAULT_FINE_PER_DAY_CENTS")]
+    pub default_fine_per_day_cents: Option<i32>,
+
+    /// Fine cap of user roles created without one
+    #[arg(long, global = true, env = "BIBLIOTECA_DEFAULT_FINE_CAP_CENTS")]
+    pub default_fine_cap_cents: Option<i32>,
+
+    /// Number of days deleted books, authors and users are kept before they are purged
+    #[arg(long, global = true, env = "BIBLIOTECA_DELETED_RETENTION_DAYS")]
+    pub deleted_retention_days: Option<u32>,
+
+    /// Secret the session tokens are signed with, at least 32 characters long
+    #[arg(
+        long,
+        global = true,
+        env = "BIBLIOTECA_TOKEN_SECRET",
+        hide_env_values = true
+    )]
+    pub token_secret: Option<String>,
+
+    /// Number of hours a login lasts
+    #[arg(long, global = true, env = "BIBLIOTECA_SESSION_TTL_HOURS")]
+    pub session_ttl_hours: Option<u32>,
+}
+
+impl Config {
+    // Reads the config file, if there is one, and applies the overrides on top of it
+    pub fn load(overrides: &ConfigOverrides) -> Result<Config, ConfigError> {
+        let mut config = match &overrides.config {
+            Some(path) => Config::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Config::from_file(Path::new(DEFAULT_CONFIG_PATH))?
+            }
+            None => Config::default(),
+        };
+
+        config.apply(overrides);
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Config, ConfigError> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| ConfigError::ReadError(path.to_path_buf(), err))?;
+
+        toml::from_str(&contents).map_err(|err| ConfigError::ParseError(path.to_path_buf(), err))
+    }
+
+    fn apply(&mut self, overrides: &ConfigOverrides) {
+        let ConfigOverrides {
+            config: _,
+            bind_address,
+            database_path,
+            database_url,
+            pool_size,
+            log_level,
+            log_format,
+            hold_pickup_days,
+            max_outstanding_fines_cents,
+            default_loan_period_days,
+            default_max_renewals,
+            default_fine_per_day_cents,
+            default_fine_cap_cents,
+            deleted_retention_days,
+            token_secret,
+            session_ttl_hours,
+        } = overrides.clone();
+
+        let library = &mut self.library;
+
+        override_with(&mut self.server.bind_address, bind_address);
+        override_with(&mut self.database.path, database_path);
+        override_with(&mut self.database.url, database_url.map(Some));
+        override_with(&mut self.database.pool_size, pool_size);
+        override_with(&mut self.log.level, log_level);
+        override_with(&mut self.log.format, log_format);
+        override_with(&mut library.hold_pickup_days, hold_pickup_days);
+        override_with(
+            &mut library.max_outstanding_fines_cents,
+            max_outstanding_fines_cents,
+        );
+        override_with(
+            &mut library.default_loan_period_days,
+            default_loan_period_days,
+        );
+        override_with(&mut library.default_max_renewals, default_max_renewals);
+        override_with(
+            &mut library.default_fine_per_day_cents,
+            default_fine_per_day_cents,
+        );
+        override_with(&mut library.default_fine_cap_cents, default_fine_cap_cents);
+        override_with(&mut library.deleted_retention_days, deleted_retention_days);
+        override_with(&mut self.auth.token_secret, token_secret.map(Some));
+        override_with(&mut self.auth.session_ttl_hours, session_ttl_hours);
+    }
+
+    // Checks the values that parse but cannot be used, so that the application fails at startup
+    // rather than once they are needed
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.database.path.trim().is_empty() {
+            return Err(ConfigError::InvalidValue(
+                "database.path",
+                "must not be empty".to_string(),
+            ));
+        }
+
+        if self.database.pool_size == 0 {
+            return Err(ConfigError::InvalidValue(
+                "database.pool_size",
+                "must be positive integer".to_string(),
+            ));
+        }
+
+        if self.database.url.is_some() && cfg!(not(feature = "postgres")) {
+            return Err(ConfigError::InvalidValue(
+                "database.url",
+                "PostgreSQL is only supported when built with the `postgres` feature".to_string(),
+            ));
+        }
+
+        let library = &self.library;
+
+        if library.hold_pickup_days == 0 {
+            return Err(ConfigError::InvalidValue(
+                "library.hold_pickup_days",
+                "must be positive integer".to_string(),
+            ));
+        }
+
+        if library.max_outstanding_fines_cents < 0 {
+            return Err(ConfigError::InvalidValue(
+                "library.max_outstanding_fines_cents",
+                "must be zero or positive integer".to_string(),
+            ));
+        }
+
+        if library.default_loan_period_days <= 0 {
+            return Err(ConfigError::InvalidValue(
+                "library.default_loan_period_days",
+                "must be positive integer".to_string(),
+            ));
+        }
+
+        if library.default_max_renewals < 0 {
+            return Err(ConfigError::InvalidValue(
+                "library.default_max_renewals",
+                "must be zero or positive integer".to_string(),
+            ));
+        }
+
+        if library.default_fine_per_day_cents < 0 {
+            return Err(ConfigError::InvalidValue(
+                "library.default_fine_per_day_cents",
+                "must be zero or positive integer".to_string(),
+            ));
+        }
+
+        if library.default_fine_cap_cents < 0 {
+            return Err(ConfigError::InvalidValue(
+                "library.default_fine_cap_cents",
+                "must be zero or positive integer".to_string(),
+            ));
+        }
+
+        if self.auth.session_ttl_hours == 0 {
+            return Err(ConfigError::InvalidValue(
+                "auth.session_ttl_hours",
+                "must be positive integer".to_string(),
+            ));
+        }
+
+        if self
+            .auth
+            .token_secret
+            .as_ref()
+            .is_some_and(|secret| secret.len() < MIN_TOKEN_SECRET_LENGTH)
+        {
+            return Err(ConfigError::InvalidValue(
+                "auth.token_secret",
+                format!(
+                    "must be at least {} characters long",
+                    MIN_TOKEN_SECRET_LENGTH
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+fn override_with<T>(value: &mut T, replacement: Option<T>) {
+    if let Some(replacement) = replacement {
+        *value = replacement;
+    }
+}
@@ -0,0 +1,458 @@
+// A versioned change to the schema, applied in order of its version
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub sql: &'static str,
+}
+
+// Every migration of the schema, in the order they are applied. Migrations that have been
+// released must never be edited, and any change to the schema has to be a new migration.
+pub const MIGRATIONS: &[Migration] = &[
+    // Databases created before migrations were tracked already have these tables, which
+    // the `IF NOT EXISTS` keeps as they are
+    Migration {
+        version: 1,
+        description: "create books, authors, users and borrowing tables",
+        sql: "
+            CREATE TABLE IF NOT EXISTS books (
+                id              BLOB PRIMARY KEY,
+                name            TEXT NOT NULL,
+                description     TEXT NOT NULL,
+                language        TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS authors (
+                id              BLOB PRIMARY KEY,
+                name            TEXT NOT NULL,
+                description     TEXT,
+                country         TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS map_books_to_authors (
+                book_id     BLOB PRIMARY KEY,
+                author_id   BLOB NOT NULL,
+                CONSTRAINT fk_books
+                    FOREIGN KEY(book_id) REFERENCES books(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_authors
+                    FOREIGN KEY(author_id) REFERENCES authors(id)
+                    ON DELETE CASCADE
+            );
+
+            CREATE TABLE IF NOT EXISTS user_roles (
+                id                      BLOB PRIMARY KEY,
+                name                    TEXT NOT NULL,
+                num_borrowable_books    INT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS users (
+                id              BLOB PRIMARY KEY,
+                username        TEXT UNIQUE NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS map_users_to_user_roles (
+                user_id             BLOB NOT NULL,
+                user_role_id        BLOB NOT NULL,
+                CONSTRAINT fk_users
+                    FOREIGN KEY(user_id) REFERENCES users(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_user_roles
+                    FOREIGN KEY(user_role_id) REFERENCES user_roles(id)
+                    ON DELETE CASCADE
+            );
+
+            CREATE TABLE IF NOT EXISTS map_users_to_borrowed_books (
+                id              BLOB NOT NULL,
+                user_id         BLOB NOT NULL,
+                book_id         BLOB NOT NULL,
+                timestamp       DATE NOT NULL,
+                action          TEXT NOT NULL,
+                CONSTRAINT fk_users
+                    FOREIGN KEY (user_id) REFERENCES users(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_books
+                    FOREIGN KEY (book_id) REFERENCES books(id)
+                    ON DELETE CASCADE
+            );
+        ",
+    },
+    Migration {
+        version: 2,
+        description: "add categories of books",
+        sql: "
+            CREATE TABLE categories (
+                id              BLOB PRIMARY KEY,
+                name            TEXT UNIQUE NOT NULL
+            );
+
+            CREATE TABLE map_books_to_categories (
+                book_id         BLOB NOT NULL,
+                category_id     BLOB NOT NULL,
+                PRIMARY KEY (book_id, category_id),
+                CONSTRAINT fk_books
+                    FOREIGN KEY(book_id) REFERENCES books(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_categories
+                    FOREIGN KEY(category_id) REFERENCES categories(id)
+                    ON DELETE CASCADE
+            );
+        ",
+    },
+    // Existing books have a single author, which is kept as their only contributor
+    Migration {
+        version: 3,
+        description: "add roles and positions to the contributors of books",
+        sql: "
+            CREATE TABLE map_books_to_authors_new (
+                book_id     BLOB NOT NULL,
+                author_id   BLOB NOT NULL,
+                role        TEXT NOT NULL,
+                position    INT NOT NULL,
+                PRIMARY KEY (book_id, author_id, role),
+                CONSTRAINT fk_books
+                    FOREIGN KEY(book_id) REFERENCES books(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_authors
+                    FOREIGN KEY(author_id) REFERENCES authors(id)
+                    ON DELETE CASCADE
+            );
+
+            INSERT INTO map_books_to_authors_new (book_id, author_id, role, position)
+                SELECT book_id, author_id, 'author', 0 FROM map_books_to_authors;
+
+            DROP TABLE map_books_to_authors;
+            ALTER TABLE map_books_to_authors_new RENAME TO map_books_to_authors;
+        ",
+    },
+    // Books were lent as a single copy before, so each of them is given one
+    Migration {
+        version: 4,
+        description: "add physical copies of books",
+        sql: "
+            CREATE TABLE items (
+                id              BLOB PRIMARY KEY,
+                book_id         BLOB NOT NULL,
+                barcode         TEXT UNIQUE NOT NULL,
+                shelf_location  TEXT NOT NULL,
+                condition       TEXT NOT NULL,
+                status          TEXT NOT NULL,
+                CONSTRAINT fk_books
+                    FOREIGN KEY(book_id) REFERENCES books(id)
+                    ON DELETE CASCADE
+            );
+
+            INSERT INTO items (id, book_id, barcode, shelf_location, condition, status)
+                SELECT randomblob(16), id, lower(hex(id)), '', 'good',
+                    CASE WHEN EXISTS (
+                        SELECT 1 FROM map_users_to_borrowed_books a
+                        WHERE a.book_id = books.id
+                        AND a.action = 'Borrowed'
+                        AND a.id NOT IN (
+                            SELECT b.id FROM map_users_to_borrowed_books b WHERE b.action = 'Returned'
+                        )
+                    ) THEN 'borrowed' ELSE 'available' END
+                FROM books;
+        ",
+    },
+    Migration {
+        version: 5,
+        description: "add loan periods, renewals and fines to user roles",
+        sql: "
+            ALTER TABLE user_roles ADD COLUMN loan_period_days INT NOT NULL DEFAULT 14;
+            ALTER TABLE user_roles ADD COLUMN max_renewals INT NOT NULL DEFAULT 2;
+            ALTER TABLE user_roles ADD COLUMN fine_per_day_cents INT NOT NULL DEFAULT 0;
+            ALTER TABLE user_roles ADD COLUMN fine_cap_cents INT NOT NULL DEFAULT 0;
+        ",
+    },
+    // Existing loans are tied to the copy given to their book, and are due one loan period
+    // after they were borrowed
+    Migration {
+        version: 6,
+        description: "add copies, due dates and renewals to loans",
+        sql: "
+            CREATE TABLE map_users_to_borrowed_books_new (
+                id              BLOB NOT NULL,
+                user_id         BLOB NOT NULL,
+                book_id         BLOB NOT NULL,
+                item_id         BLOB NOT NULL,
+                timestamp       DATE NOT NULL,
+                due_at          DATE NOT NULL,
+                renewals        INT NOT NULL DEFAULT 0,
+                action          TEXT NOT NULL,
+                CONSTRAINT fk_users
+                    FOREIGN KEY (user_id) REFERENCES users(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_books
+                    FOREIGN KEY (book_id) REFERENCES books(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_items
+                    FOREIGN KEY (item_id) REFERENCES items(id)
+                    ON DELETE CASCADE
+            );
+
+            INSERT INTO map_users_to_borrowed_books_new
+                (id, user_id, book_id, item_id, timestamp, due_at, renewals, action)
+                SELECT a.id, a.user_id, a.book_id,
+                    (SELECT i.id FROM items i WHERE i.book_id = a.book_id ORDER BY i.rowid LIMIT 1),
+                    a.timestamp,
+                    strftime(
+                        '%Y-%m-%d %H:%M:%f+00:00',
+                        (SELECT MIN(b.timestamp) FROM map_users_to_borrowed_books b WHERE b.id = a.id),
+                        '+' || COALESCE((
+                            SELECT r.loan_period_days FROM map_users_to_user_roles m
+                            JOIN user_roles r ON m.user_role_id = r.id
+                            WHERE m.user_id = a.user_id
+                        ), 14) || ' days'
+                    ),
+                    0, a.action
+                FROM map_users_to_borrowed_books a;
+
+            DROP TABLE map_users_to_borrowed_books;
+            ALTER TABLE map_users_to_borrowed_books_new RENAME TO map_users_to_borrowed_books;
+        ",
+    },
+    Migration {
+        version: 7,
+        description: "add holds on books",
+        sql: "
+            CREATE TABLE holds (
+                id              BLOB PRIMARY KEY,
+                book_id         BLOB NOT NULL,
+                user_id         BLOB NOT NULL,
+                item_id         BLOB,
+                status          TEXT NOT NULL,
+                created_at      DATE NOT NULL,
+                expires_at      DATE,
+                CONSTRAINT fk_books
+                    FOREIGN KEY (book_id) REFERENCES books(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_users
+                    FOREIGN KEY (user_id) REFERENCES users(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_items
+                    FOREIGN KEY (item_id) REFERENCES items(id)
+                    ON DELETE SET NULL
+            );
+        ",
+    },
+    Migration {
+        version: 8,
+        description: "add ledger of fines",
+        sql: "
+            CREATE TABLE fines (
+                id              BLOB PRIMARY KEY,
+                user_id         BLOB NOT NULL,
+                loan_id         BLOB,
+                kind            TEXT NOT NULL,
+                amount_cents    INT NOT NULL,
+                reason          TEXT,
+                created_at      DATE NOT NULL,
+                CONSTRAINT fk_users
+                    FOREIGN KEY (user_id) REFERENCES users(id)
+                    ON DELETE CASCADE
+            );
+        ",
+    },
+    // The triggers keep the index in sync with every change to the books, authors and
+    // categories, after it is filled with the ones already in the catalog
+    Migration {
+        version: 9,
+        description: "add full-text search index of the catalog",
+        sql: "
+            CREATE VIRTUAL TABLE catalog_search USING fts5(
+                kind UNINDEXED,
+                entity_id UNINDEXED,
+                name,
+                description,
+                tokenize = 'unicode61 remove_diacritics 2'
+            );
+
+            CREATE TRIGGER books_search_insert AFTER INSERT ON books BEGIN
+                INSERT INTO catalog_search (kind, entity_id, name, description)
+                VALUES ('book', new.id, new.name, new.description);
+            END;
+            CREATE TRIGGER books_search_update AFTER UPDATE ON books BEGIN
+                UPDATE catalog_search SET name = new.name, description = new.description
+                WHERE kind = 'book' AND entity_id = old.id;
+            END;
+            CREATE TRIGGER books_search_delete AFTER DELETE ON books BEGIN
+                DELETE FROM catalog_search WHERE kind = 'book' AND entity_id = old.id;
+            END;
+
+            CREATE TRIGGER authors_search_insert AFTER INSERT ON authors BEGIN
+                INSERT INTO catalog_search (kind, entity_id, name, description)
+                VALUES ('author', new.id, new.name, COALESCE(new.description, ''));
+            END;
+            CREATE TRIGGER authors_search_update AFTER UPDATE ON authors BEGIN
+                UPDATE catalog_search SET name = new.name, description = COALESCE(new.description, '')
+                WHERE kind = 'author' AND entity_id = old.id;
+            END;
+            CREATE TRIGGER authors_search_delete AFTER DELETE ON authors BEGIN
+                DELETE FROM catalog_search WHERE kind = 'author' AND entity_id = old.id;
+            END;
+
+            CREATE TRIGGER categories_search_insert AFTER INSERT ON categories BEGIN
+                INSERT INTO catalog_search (kind, entity_id, name, description)
+                VALUES ('category', new.id, new.name, '');
+            END;
+            CREATE TRIGGER categories_search_update AFTER UPDATE ON categories BEGIN
+                UPDATE catalog_search SET name = new.name
+                WHERE kind = 'category' AND entity_id = old.id;
+            END;
+            CREATE TRIGGER categories_search_delete AFTER DELETE ON categories BEGIN
+                DELETE FROM catalog_search WHERE kind = 'category' AND entity_id = old.id;
+            END;
+
+            INSERT INTO catalog_search (kind, entity_id, name, description)
+                SELECT 'book', id, name, description FROM books;
+            INSERT INTO catalog_search (kind, entity_id, name, description)
+                SELECT 'author', id, name, COALESCE(description, '') FROM authors;
+            INSERT INTO catalog_search (kind, entity_id, name, description)
+                SELECT 'category', id, name, '' FROM categories;
+        ",
+    },
+    // Users without a password cannot log in until they are given one
+    Migration {
+        version: 10,
+        description: "add passwords and sessions of users",
+        sql: "
+            ALTER TABLE users ADD COLUMN password_hash TEXT;
+
+            CREATE TABLE sessions (
+                id              BLOB PRIMARY KEY,
+                user_id         BLOB NOT NULL,
+                created_at      DATE NOT NULL,
+                expires_at      DATE NOT NULL,
+                CONSTRAINT fk_users
+                    FOREIGN KEY (user_id) REFERENCES users(id)
+                    ON DELETE CASCADE
+            );
+        ",
+    },
+    // Administrators created before roles had permissions keep being able to run the library.
+    // Their role is known by its id, as any other role could have been given the same name
+    Migration {
+        version: 11,
+        description: "add permissions to user roles",
+        sql: "
+            ALTER TABLE user_roles ADD COLUMN permissions TEXT NOT NULL DEFAULT '';
+
+            UPDATE user_roles SET permissions = 'catalog:write,users:admin,circulation:staff'
+                WHERE id = X'00000000000000000000000000000001';
+        ",
+    },
+    // Keys are looked up by their hash, as they are never stored themselves
+    Migration {
+        version: 12,
+        description: "add API keys of machine clients",
+        sql: "
+            CREATE TABLE api_keys (
+                id              BLOB PRIMARY KEY,
+                name            TEXT NOT NULL UNIQUE,
+                prefix          TEXT NOT NULL,
+                key_hash        TEXT NOT NULL UNIQUE,
+                permissions     TEXT NOT NULL DEFAULT '',
+                created_at      DATE NOT NULL,
+                expires_at      DATE,
+                last_used_at    DATE
+            );
+        ",
+    },
+    Migration {
+        version: 13,
+        description: "add profile fields to users",
+        sql: "
+            ALTER TABLE users ADD COLUMN display_name TEXT;
+            ALTER TABLE users ADD COLUMN email TEXT;
+        ",
+    },
+    // Users may hold several roles, each at most once and optionally until a given time
+    Migration {
+        version: 14,
+        description: "add expiry of the roles of users",
+        sql: "
+            ALTER TABLE map_users_to_user_roles ADD COLUMN expires_at DATE;
+            CREATE UNIQUE INDEX idx_map_users_to_user_roles
+                ON map_users_to_user_roles (user_id, user_role_id);
+        ",
+    },
+    // Users have a membership status, and existing users start out active
+    Migration {
+        version: 15,
+        description: "add membership status of users",
+        sql: "
+            ALTER TABLE users ADD COLUMN status TEXT NOT NULL DEFAULT 'active';
+            ALTER TABLE users ADD COLUMN membership_expires_at DATE;
+            ALTER TABLE users ADD COLUMN suspension_reason TEXT;
+            ALTER TABLE users ADD COLUMN suspended_until DATE;
+        ",
+    },
+    // Every change is recorded in the same transaction as the change itself, and the triggers
+    // keep the recorded entries from being changed or removed. Roles that administer users may
+    // read the log from the start.
+    Migration {
+        version: 16,
+        description: "add audit log",
+        sql: "
+            CREATE TABLE audit_log (
+                id              BLOB PRIMARY KEY,
+                occurred_at     DATE NOT NULL,
+                actor_kind      TEXT NOT NULL,
+                actor_id        BLOB,
+                action          TEXT NOT NULL,
+                entity_type     TEXT NOT NULL,
+                entity_id       BLOB NOT NULL,
+                before          TEXT,
+                after           TEXT,
+                request_id      TEXT
+            );
+
+            CREATE INDEX idx_audit_log_entity ON audit_log (entity_type, entity_id);
+
+            CREATE TRIGGER audit_log_no_update BEFORE UPDATE ON audit_log
+            BEGIN
+                SELECT RAISE(ABORT, 'audit log is append-only');
+            END;
+
+            CREATE TRIGGER audit_log_no_delete BEFORE DELETE ON audit_log
+            BEGIN
+                SELECT RAISE(ABORT, 'audit log is append-only');
+            END;
+
+            UPDATE user_roles SET permissions = permissions || ',audit:read'
+                WHERE ',' || permissions || ',' LIKE '%,users:admin,%';
+        ",
+    },
+    // Deleted books, authors and users are kept along with their history until they are purged,
+    // and the triggers take deleted books and authors out of the search index until they are
+    // restored
+    Migration {
+        version: 17,
+        description: "add soft deletion of books, authors and users",
+        sql: "
+            ALTER TABLE books ADD COLUMN deleted_at DATE;
+            ALTER TABLE authors ADD COLUMN deleted_at DATE;
+            ALTER TABLE users ADD COLUMN deleted_at DATE;
+
+            CREATE TRIGGER books_search_soft_delete AFTER UPDATE OF deleted_at ON books
+                WHEN new.deleted_at IS NOT NULL BEGIN
+                DELETE FROM catalog_search WHERE kind = 'book' AND entity_id = old.id;
+            END;
+            CREATE TRIGGER books_search_restore AFTER UPDATE OF deleted_at ON books
+                WHEN new.deleted_at IS NULL AND old.deleted_at IS NOT NULL BEGIN
+                INSERT INTO catalog_search (kind, entity_id, name, description)
+                VALUES ('book', new.id, new.name, new.description);
+            END;
+
+            CREATE TRIGGER authors_search_soft_delete AFTER UPDATE OF deleted_at ON authors
+                WHEN new.deleted_at IS NOT NULL BEGIN
+                DELETE FROM catalog_search WHERE kind = 'author' AND entity_id = old.id;
+            END;
+            CREATE TRIGGER authors_search_restore AFTER UPDATE OF deleted_at ON authors
+                WHEN new.deleted_at IS NULL AND old.deleted_at IS NOT NULL BEGIN
+                INSERT INTO catalog_search (kind, entity_id, name, description)
+                VALUES ('author', new.id, new.name, COALESCE(new.description, ''));
+            END;
+        ",
+    },
+];
@@ -0,0 +1,139 @@
+use std::fmt;
+
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::Connection;
+
+mod migrations;
+#[cfg(feature = "postgres")]
+mod postgres;
+
+#[cfg(feature = "postgres")]
+pub use self::postgres::{
+    get_postgres_schema_version, latest_postgres_schema_version, migrate_postgres_db,
+    setup_postgres_db, POSTGRES_MIGRATIONS,
+};
+pub use migrations::{Migration, MIGRATIONS};
+
+// Number of connections kept open to the database, unless configured otherwise
+pub const DEFAULT_POOL_SIZE: u32 = 10;
+
+#[derive(thiserror::Error, Debug)]
+pub enum MigrationError {
+    DatabaseError(#[from] rusqlite::Error),
+    DatabaseTooNew(u32, u32),
+    #[cfg(feature = "postgres")]
+    PostgresError(#[from] ::postgres::Error),
+    #[cfg(feature = "postgres")]
+    ConnectionError(#[from] r2d2::Error),
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MigrationError::DatabaseError(ref err) => {
+                write!(f, "there was an error in migrating the database: {}", err)
+            }
+            MigrationError::DatabaseTooNew(version, latest) => write!(
+                f,
+                "database is at schema version {}, which is newer than the latest known version {}",
+                version, latest
+            ),
+            #[cfg(feature = "postgres")]
+            MigrationError::PostgresError(ref err) => {
+                write!(f, "there was an error in migrating the database: {}", err)
+            }
+            #[cfg(feature = "postgres")]
+            MigrationError::ConnectionError(ref err) => {
+                write!(
+                    f,
+                    "there was an error in connecting to the database: {}",
+                    err
+                )
+            }
+        }
+    }
+}
+
+pub fn setup_db(
+    database_path: String,
+    pool_size: u32,
+) -> Result<Pool<SqliteConnectionManager>, MigrationError> {
+    tracing::debug!("Setting up our SQLite database...");
+
+    // Foreign keys are turned on for every connection rather than relying on how SQLite was
+    // built, as removing a row would otherwise leave behind the rows that refer to it
+    let manager = SqliteConnectionManager::file(database_path)
+        .with_init(|conn| conn.pragma_update(None, "foreign_keys", "ON"));
+    let pool = r2d2::Pool::builder()
+        .max_size(pool_size)
+        .build(manager)
+        .unwrap();
+
+    migrate_db(&mut pool.get().unwrap(), false)?;
+
+    tracing::debug!("Database setup complete! :)");
+    Ok(pool)
+}
+
+// Applies every pending migration in a single transaction, returning the ones applied.
+// On a dry run, the migrations are rolled back after they have been applied.
+pub fn migrate_db(
+    conn: &mut Connection,
+    dry_run: bool,
+) -> Result<Vec<&'static Migration>, MigrationError> {
+    let version = get_schema_version(conn)?;
+    let latest = latest_schema_version();
+
+    if version > latest {
+        return Err(MigrationError::DatabaseTooNew(version, latest));
+    }
+
+    let pending: Vec<&Migration> = MIGRATIONS
+        .iter()
+        .filter(|migration| migration.version > version)
+        .collect();
+
+    if pending.is_empty() {
+        tracing::debug!("Database is at the latest schema version {}", version);
+        return Ok(pending);
+    }
+
+    let tx = conn.transaction()?;
+
+    for migration in pending.iter() {
+        tracing::debug!(
+            "> Applying migration {}: {}...",
+            migration.version,
+            migration.description
+        );
+
+        tx.execute_batch(migration.sql)?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+    }
+
+    if dry_run {
+        tracing::debug!("Rolling back migrations of dry run...");
+        tx.rollback()?;
+    } else {
+        tx.commit()?;
+    }
+
+    Ok(pending)
+}
+
+// Copies the database into a new file at the backup path, as a consistent snapshot that can be
+// taken while the application is running
+pub fn backup_db(conn: &Connection, backup_path: &str) -> Result<(), rusqlite::Error> {
+    conn.execute("VACUUM INTO ?1", [backup_path])?;
+
+    Ok(())
+}
+
+pub fn get_schema_version(conn: &Connection) -> Result<u32, rusqlite::Error> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+pub fn latest_schema_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |migration| migration.version)
+}
@@ -0,0 +1,490 @@
+use postgres::{Client, NoTls};
+use r2d2::Pool;
+use r2d2_postgres::PostgresConnectionManager;
+
+use crate::repository::postgres::run_blocking;
+
+use super::{Migration, MigrationError};
+
+// Key of the advisory lock held while migrating, so that replicas starting together apply
+// the pending migrations only once
+const MIGRATION_LOCK_KEY: i64 = 0x6269_626c_696f;
+
+// Every migration of the PostgreSQL schema, in the order they are applied. PostgreSQL
+// databases start out at the schema SQLite databases have at version 9, so versions from then
+// on are shared between the two and every later change to the schema is made to both.
+pub const POSTGRES_MIGRATIONS: &[Migration] = &[
+    // SQLite orders unsorted lists by the implicit rowid of their tables, which the tables
+    // listed that way are given here. The triggers keep the search index in sync with every
+    // change to the books, authors and categories.
+    Migration {
+        version: 9,
+        description: "create the schema of the library",
+        sql: "
+            CREATE TABLE books (
+                id              UUID PRIMARY KEY,
+                name            TEXT NOT NULL,
+                description     TEXT NOT NULL,
+                language        TEXT NOT NULL,
+                rowid           BIGINT GENERATED ALWAYS AS IDENTITY
+            );
+
+            CREATE TABLE authors (
+                id              UUID PRIMARY KEY,
+                name            TEXT NOT NULL,
+                description     TEXT,
+                country         TEXT NOT NULL,
+                rowid           BIGINT GENERATED ALWAYS AS IDENTITY
+            );
+
+            CREATE TABLE map_books_to_authors (
+                book_id     UUID NOT NULL,
+                author_id   UUID NOT NULL,
+                role        TEXT NOT NULL,
+                position    INT NOT NULL,
+                PRIMARY KEY (book_id, author_id, role),
+                CONSTRAINT fk_books
+                    FOREIGN KEY(book_id) REFERENCES books(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_authors
+                    FOREIGN KEY(author_id) REFERENCES authors(id)
+                    ON DELETE CASCADE
+            );
+
+            CREATE TABLE categories (
+                id              UUID PRIMARY KEY,
+                name            TEXT UNIQUE NOT NULL
+            );
+
+            CREATE TABLE map_books_to_categories (
+                book_id         UUID NOT NULL,
+                category_id     UUID NOT NULL,
+                PRIMARY KEY (book_id, category_id),
+                CONSTRAINT fk_books
+                    FOREIGN KEY(book_id) REFERENCES books(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_categories
+                    FOREIGN KEY(category_id) REFERENCES categories(id)
+                    ON DELETE CASCADE
+            );
+
+            CREATE TABLE items (
+                id              UUID PRIMARY KEY,
+                book_id         UUID NOT NULL,
+                barcode         TEXT UNIQUE NOT NULL,
+                shelf_location  TEXT NOT NULL,
+                condition       TEXT NOT NULL,
+                status          TEXT NOT NULL,
+                CONSTRAINT fk_books
+                    FOREIGN KEY(book_id) REFERENCES books(id)
+                    ON DELETE CASCADE
+            );
+
+            CREATE TABLE user_roles (
+                id                      UUID PRIMARY KEY,
+                name                    TEXT NOT NULL,
+                num_borrowable_books    INT NOT NULL,
+                loan_period_days        INT NOT NULL DEFAULT 14,
+                max_renewals            INT NOT NULL DEFAULT 2,
+                fine_per_day_cents      INT NOT NULL DEFAULT 0,
+                fine_cap_cents          INT NOT NULL DEFAULT 0,
+                rowid                   BIGINT GENERATED ALWAYS AS IDENTITY
+            );
+
+            CREATE TABLE users (
+                id              UUID PRIMARY KEY,
+                username        TEXT UNIQUE NOT NULL,
+                rowid           BIGINT GENERATED ALWAYS AS IDENTITY
+            );
+
+            CREATE TABLE map_users_to_user_roles (
+                user_id             UUID NOT NULL,
+                user_role_id        UUID NOT NULL,
+                CONSTRAINT fk_users
+                    FOREIGN KEY(user_id) REFERENCES users(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_user_roles
+                    FOREIGN KEY(user_role_id) REFERENCES user_roles(id)
+                    ON DELETE CASCADE
+            );
+
+            CREATE TABLE map_users_to_borrowed_books (
+                id              UUID NOT NULL,
+                user_id         UUID NOT NULL,
+                book_id         UUID NOT NULL,
+                item_id         UUID NOT NULL,
+                timestamp       TIMESTAMPTZ NOT NULL,
+                due_at          TIMESTAMPTZ NOT NULL,
+                renewals        INT NOT NULL DEFAULT 0,
+                action          TEXT NOT NULL,
+                CONSTRAINT fk_users
+                    FOREIGN KEY (user_id) REFERENCES users(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_books
+                    FOREIGN KEY (book_id) REFERENCES books(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_items
+                    FOREIGN KEY (item_id) REFERENCES items(id)
+                    ON DELETE CASCADE
+            );
+
+            CREATE TABLE holds (
+                id              UUID PRIMARY KEY,
+                book_id         UUID NOT NULL,
+                user_id         UUID NOT NULL,
+                item_id         UUID,
+                status          TEXT NOT NULL,
+                created_at      TIMESTAMPTZ NOT NULL,
+                expires_at      TIMESTAMPTZ,
+                CONSTRAINT fk_books
+                    FOREIGN KEY (book_id) REFERENCES books(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_users
+                    FOREIGN KEY (user_id) REFERENCES users(id)
+                    ON DELETE CASCADE,
+                CONSTRAINT fk_items
+                    FOREIGN KEY (item_id) REFERENCES items(id)
+                    ON DELETE SET NULL
+            );
+
+            CREATE TABLE fines (
+                id              UUID PRIMARY KEY,
+                user_id         UUID NOT NULL,
+                loan_id         UUID,
+                kind            TEXT NOT NULL,
+                amount_cents    BIGINT NOT NULL,
+                reason          TEXT,
+                created_at      TIMESTAMPTZ NOT NULL,
+                CONSTRAINT fk_users
+                    FOREIGN KEY (user_id) REFERENCES users(id)
+                    ON DELETE CASCADE
+            );
+
+            CREATE TABLE catalog_search (
+                kind            TEXT NOT NULL,
+                entity_id       UUID NOT NULL,
+                name            TEXT NOT NULL,
+                description     TEXT NOT NULL,
+                document        TSVECTOR GENERATED ALWAYS AS (
+                    setweight(to_tsvector('simple', translate(name, '<>', '  ')), 'A') ||
+                    setweight(to_tsvector('simple', translate(description, '<>', '  ')), 'B')
+                ) STORED,
+                PRIMARY KEY (kind, entity_id)
+            );
+
+            CREATE INDEX catalog_search_document ON catalog_search USING GIN (document);
+
+            CREATE FUNCTION books_search_sync() RETURNS TRIGGER AS $$
+            BEGIN
+                IF TG_OP = 'INSERT' THEN
+                    INSERT INTO catalog_search (kind, entity_id, name, description)
+                    VALUES ('book', NEW.id, NEW.name, NEW.description);
+                ELSIF TG_OP = 'UPDATE' THEN
+                    UPDATE catalog_search SET name = NEW.name, description = NEW.description
+                    WHERE kind = 'book' AND entity_id = OLD.id;
+                ELSE
+                    DELETE FROM catalog_search WHERE kind = 'book' AND entity_id = OLD.id;
+                END IF;
+                RETURN NULL;
+            END;
+            $$ LANGUAGE plpgsql;
+
+            CREATE TRIGGER books_search AFTER INSERT OR UPDATE OR DELETE ON books
+                FOR EACH ROW EXECUTE FUNCTION books_search_sync();
+
+            CREATE FUNCTION authors_search_sync() RETURNS TRIGGER AS $$
+            BEGIN
+                IF TG_OP = 'INSERT' THEN
+                    INSERT INTO catalog_search (kind, entity_id, name, description)
+                    VALUES ('author', NEW.id, NEW.name, COALESCE(NEW.description, ''));
+                ELSIF TG_OP = 'UPDATE' THEN
+                    UPDATE catalog_search SET name = NEW.name, description = COALESCE(NEW.description, '')
+                    WHERE kind = 'author' AND entity_id = OLD.id;
+                ELSE
+                    DELETE FROM catalog_search WHERE kind = 'author' AND entity_id = OLD.id;
+                END IF;
+                RETURN NULL;
+            END;
+            $$ LANGUAGE plpgsql;
+
+            CREATE TRIGGER authors_search AFTER INSERT OR UPDATE OR DELETE ON authors
+                FOR EACH ROW EXECUTE FUNCTION authors_search_sync();
+
+            CREATE FUNCTION categories_search_sync() RETURNS TRIGGER AS $$
+            BEGIN
+                IF TG_OP = 'INSERT' THEN
+                    INSERT INTO catalog_search (kind, entity_id, name, description)
+                    VALUES ('category', NEW.id, NEW.name, '');
+                ELSIF TG_OP = 'UPDATE' THEN
+                    UPDATE catalog_search SET name = NEW.name
+                    WHERE kind = 'category' AND entity_id = OLD.id;
+                ELSE
+                    DELETE FROM catalog_search WHERE kind = 'category' AND entity_id = OLD.id;
+                END IF;
+                RETURN NULL;
+            END;
+            $$ LANGUAGE plpgsql;
+
+            CREATE TRIGGER categories_search AFTER INSERT OR UPDATE OR DELETE ON categories
+                FOR EACH ROW EXECUTE FUNCTION categories_search_sync();
+        ",
+    },
+    Migration {
+        version: 10,
+        description: "add passwords and sessions of users",
+        sql: "
+            ALTER TABLE users ADD COLUMN password_hash TEXT;
+
+            CREATE TABLE sessions (
+                id              UUID PRIMARY KEY,
+                user_id         UUID NOT NULL,
+                created_at      TIMESTAMPTZ NOT NULL,
+                expires_at      TIMESTAMPTZ NOT NULL,
+                CONSTRAINT fk_users
+                    FOREIGN KEY (user_id) REFERENCES users(id)
+                    ON DELETE CASCADE
+            );
+        ",
+    },
+    Migration {
+        version: 11,
+        description: "add permissions to user roles",
+        sql: "
+            ALTER TABLE user_roles ADD COLUMN permissions TEXT NOT NULL DEFAULT '';
+
+            UPDATE user_roles SET permissions = 'catalog:write,users:admin,circulation:staff'
+                WHERE id = '00000000-0000-0000-0000-000000000001';
+        ",
+    },
+    Migration {
+        version: 12,
+        description: "add API keys of machine clients",
+        sql: "
+            CREATE TABLE api_keys (
+                id              UUID PRIMARY KEY,
+                name            TEXT NOT NULL UNIQUE,
+                prefix          TEXT NOT NULL,
+                key_hash        TEXT NOT NULL UNIQUE,
+                permissions     TEXT NOT NULL DEFAULT '',
+                created_at      TIMESTAMPTZ NOT NULL,
+                expires_at      TIMESTAMPTZ,
+                last_used_at    TIMESTAMPTZ
+            );
+        ",
+    },
+    Migration {
+        version: 13,
+        description: "add profile fields to users",
+        sql: "
+            ALTER TABLE users ADD COLUMN display_name TEXT;
+            ALTER TABLE users ADD COLUMN email TEXT;
+        ",
+    },
+    // Users may hold several roles, each at most once and optionally until a given time
+    Migration {
+        version: 14,
+        description: "add expiry of the roles of users",
+        sql: "
+            ALTER TABLE map_users_to_user_roles ADD COLUMN expires_at TIMESTAMPTZ;
+            CREATE UNIQUE INDEX idx_map_users_to_user_roles
+                ON map_users_to_user_roles (user_id, user_role_id);
+        ",
+    },
+    // Users have a membership status, and existing users start out active
+    Migration {
+        version: 15,
+        description: "add membership status of users",
+        sql: "
+            ALTER TABLE users ADD COLUMN status TEXT NOT NULL DEFAULT 'active';
+            ALTER TABLE users ADD COLUMN membership_expires_at TIMESTAMPTZ;
+            ALTER TABLE users ADD COLUMN suspension_reason TEXT;
+            ALTER TABLE users ADD COLUMN suspended_until TIMESTAMPTZ;
+        ",
+    },
+    // Every change is recorded in the same transaction as the change itself, and the trigger
+    // keeps the recorded entries from being changed or removed. Roles that administer users may
+    // read the log from the start.
+    Migration {
+        version: 16,
+        description: "add audit log",
+        sql: "
+            CREATE TABLE audit_log (
+                id              UUID PRIMARY KEY,
+                occurred_at     TIMESTAMPTZ NOT NULL,
+                actor_kind      TEXT NOT NULL,
+                actor_id        UUID,
+                action          TEXT NOT NULL,
+                entity_type     TEXT NOT NULL,
+                entity_id       UUID NOT NULL,
+                before          TEXT,
+                after           TEXT,
+                request_id      TEXT,
+                rowid           BIGINT GENERATED ALWAYS AS IDENTITY
+            );
+
+            CREATE INDEX idx_audit_log_entity ON audit_log (entity_type, entity_id);
+
+            CREATE FUNCTION refuse_audit_log_change() RETURNS TRIGGER AS $$
+            BEGIN
+                RAISE EXCEPTION 'audit log is append-only';
+            END;
+            $$ LANGUAGE plpgsql;
+
+            CREATE TRIGGER audit_log_append_only BEFORE UPDATE OR DELETE ON audit_log
+                FOR EACH ROW EXECUTE FUNCTION refuse_audit_log_change();
+
+            UPDATE user_roles SET permissions = permissions || ',audit:read'
+                WHERE ',' || permissions || ',' LIKE '%,users:admin,%';
+        ",
+    },
+    // Deleted books, authors and users are kept along with their history until they are purged,
+    // and the triggers take deleted books and authors out of the search index until they are
+    // restored
+    Migration {
+        version: 17,
+        description: "add soft deletion of books, authors and users",
+        sql: "
+            ALTER TABLE books ADD COLUMN deleted_at TIMESTAMPTZ;
+            ALTER TABLE authors ADD COLUMN deleted_at TIMESTAMPTZ;
+            ALTER TABLE users ADD COLUMN deleted_at TIMESTAMPTZ;
+
+            CREATE OR REPLACE FUNCTION books_search_sync() RETURNS TRIGGER AS $$
+            BEGIN
+                IF TG_OP = 'INSERT' THEN
+                    INSERT INTO catalog_search (kind, entity_id, name, description)
+                    VALUES ('book', NEW.id, NEW.name, NEW.description);
+                ELSIF TG_OP = 'UPDATE' AND NEW.deleted_at IS NOT NULL THEN
+                    DELETE FROM catalog_search WHERE kind = 'book' AND entity_id = OLD.id;
+                ELSIF TG_OP = 'UPDATE' AND OLD.deleted_at IS NOT NULL THEN
+                    INSERT INTO catalog_search (kind, entity_id, name, description)
+                    VALUES ('book', NEW.id, NEW.name, NEW.description);
+                ELSIF TG_OP = 'UPDATE' THEN
+                    UPDATE catalog_search SET name = NEW.name, description = NEW.description
+                    WHERE kind = 'book' AND entity_id = OLD.id;
+                ELSE
+                    DELETE FROM catalog_search WHERE kind = 'book' AND entity_id = OLD.id;
+                END IF;
+                RETURN NULL;
+            END;
+            $$ LANGUAGE plpgsql;
+
+            CREATE OR REPLACE FUNCTION authors_search_sync() RETURNS TRIGGER AS $$
+            BEGIN
+                IF TG_OP = 'INSERT' THEN
+                    INSERT INTO catalog_search (kind, entity_id, name, description)
+                    VALUES ('author', NEW.id, NEW.name, COALESCE(NEW.description, ''));
+                ELSIF TG_OP = 'UPDATE' AND NEW.deleted_at IS NOT NULL THEN
+                    DELETE FROM catalog_search WHERE kind = 'author' AND entity_id = OLD.id;
+                ELSIF TG_OP = 'UPDATE' AND OLD.deleted_at IS NOT NULL THEN
+                    INSERT INTO catalog_search (kind, entity_id, name, description)
+                    VALUES ('author', NEW.id, NEW.name, COALESCE(NEW.description, ''));
+                ELSIF TG_OP = 'UPDATE' THEN
+                    UPDATE catalog_search SET name = NEW.name, description = COALESCE(NEW.description, '')
+                    WHERE kind = 'author' AND entity_id = OLD.id;
+                ELSE
+                    DELETE FROM catalog_search WHERE kind = 'author' AND entity_id = OLD.id;
+                END IF;
+                RETURN NULL;
+            END;
+            $$ LANGUAGE plpgsql;
+        ",
+    },
+];
+
+pub fn setup_postgres_db(
+    database_url: String,
+    pool_size: u32,
+) -> Result<Pool<PostgresConnectionManager<NoTls>>, MigrationError> {
+    tracing::debug!("Setting up our PostgreSQL database...");
+
+    let pool = run_blocking(move || {
+        let manager = PostgresConnectionManager::new(database_url.parse()?, NoTls);
+        let pool = r2d2::Pool::builder().max_size(pool_size).build(manager)?;
+
+        migrate_postgres_db(&mut *pool.get()?, false)?;
+
+        Ok::<_, MigrationError>(pool)
+    })?;
+
+    tracing::debug!("Database setup complete! :)");
+    Ok(pool)
+}
+
+// Applies every pending migration in a single transaction, returning the ones applied.
+// On a dry run, the migrations are rolled back after they have been applied.
+pub fn migrate_postgres_db(
+    client: &mut Client,
+    dry_run: bool,
+) -> Result<Vec<&'static Migration>, MigrationError> {
+    let mut tx = client.transaction()?;
+
+    // Other replicas wait here until the migrations have been applied
+    tx.execute("SELECT pg_advisory_xact_lock($1)", &[&MIGRATION_LOCK_KEY])?;
+
+    let version = get_postgres_schema_version(&mut tx)?;
+    let latest = latest_postgres_schema_version();
+
+    if version > latest {
+        return Err(MigrationError::DatabaseTooNew(version, latest));
+    }
+
+    let pending: Vec<&Migration> = POSTGRES_MIGRATIONS
+        .iter()
+        .filter(|migration| migration.version > version)
+        .collect();
+
+    if pending.is_empty() {
+        tracing::debug!("Database is at the latest schema version {}", version);
+        return Ok(pending);
+    }
+
+    tx.batch_execute("CREATE TABLE IF NOT EXISTS schema_version (version INT NOT NULL)")?;
+
+    for migration in pending.iter() {
+        tracing::debug!(
+            "> Applying migration {}: {}...",
+            migration.version,
+            migration.description
+        );
+
+        tx.batch_execute(migration.sql)?;
+        tx.execute("DELETE FROM schema_version", &[])?;
+        tx.execute(
+            "INSERT INTO schema_version (version) VALUES ($1)",
+            &[&(migration.version as i32)],
+        )?;
+    }
+
+    if dry_run {
+        tracing::debug!("Rolling back migrations of dry run...");
+        tx.rollback()?;
+    } else {
+        tx.commit()?;
+    }
+
+    Ok(pending)
+}
+
+// Reads the version from the `schema_version` table, which does not exist before the first
+// migration has been applied
+pub fn get_postgres_schema_version(
+    client: &mut impl postgres::GenericClient,
+) -> Result<u32, postgres::Error> {
+    let row = client.query_one("SELECT to_regclass('schema_version') IS NOT NULL", &[])?;
+
+    if !row.try_get::<_, bool>(0)? {
+        return Ok(0);
+    }
+
+    let version = client
+        .query_opt("SELECT version FROM schema_version", &[])?
+        .map_or(Ok(0), |row| row.try_get::<_, i32>(0))?;
+
+    Ok(version as u32)
+}
+
+pub fn latest_postgres_schema_version() -> u32 {
+    POSTGRES_MIGRATIONS
+        .last()
+        .map_or(0, |migration| migration.version)
+}
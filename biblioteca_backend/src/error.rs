@@ -7,9 +7,18 @@ pub enum Error {
     #[error("{0}")]
     BadRequest(#[from] BadRequest),
 
+    #[error("{0}")]
+    Unauthorized(#[from] Unauthorized),
+
+    #[error("{0}")]
+    Forbidden(#[from] Forbidden),
+
     #[error("{0}")]
     NotFound(#[from] NotFound),
 
+    #[error("{0}")]
+    Conflict(#[from] Conflict),
+
     #[error("{0}")]
     ServerIssue(#[from] ServerIssue),
 }
@@ -19,7 +28,10 @@ impl Error {
         match *self {
             // 4XXs
             Error::BadRequest(_) => (StatusCode::BAD_REQUEST, 40001),
+            Error::Unauthorized(_) => (StatusCode::UNAUTHORIZED, 40101),
+            Error::Forbidden(_) => (StatusCode::FORBIDDEN, 40301),
             Error::NotFound(_) => (StatusCode::NOT_FOUND, 40004),
+            Error::Conflict(_) => (StatusCode::CONFLICT, 40901),
 
             // 5XXs
             Error::ServerIssue(_) => (StatusCode::INTERNAL_SERVER_ERROR, 50001),
@@ -34,6 +46,18 @@ impl Error {
         Error::BadRequest(BadRequest { message })
     }
 
+    pub fn unauthorized(message: String) -> Self {
+        Error::Unauthorized(Unauthorized { message })
+    }
+
+    pub fn forbidden(message: String) -> Self {
+        Error::Forbidden(Forbidden { message })
+    }
+
+    pub fn conflict(message: String) -> Self {
+        Error::Conflict(Conflict { message })
+    }
+
     pub fn server_issue() -> Self {
         Error::ServerIssue(ServerIssue {})
     }
@@ -56,10 +80,28 @@ pub struct BadRequest {
     message: String,
 }
 
+#[derive(thiserror::Error, Debug)]
+#[error("Unauthorized: {message}")]
+pub struct Unauthorized {
+    message: String,
+}
+
+#[derive(thiserror::Error, Debug)]
+#[error("Forbidden: {message}")]
+pub struct Forbidden {
+    message: String,
+}
+
 #[derive(thiserror::Error, Debug)]
 #[error("Resource not found!")]
 pub struct NotFound {}
 
+#[derive(thiserror::Error, Debug)]
+#[error("Conflict: {message}")]
+pub struct Conflict {
+    message: String,
+}
+
 #[derive(thiserror::Error, Debug)]
 #[error("Internal server error -- check logs for more details!")]
 pub struct ServerIssue {}
@@ -1,6 +1,12 @@
+pub mod admin;
 pub mod app;
+pub mod audit;
+pub mod auth;
 pub mod catalog;
+pub mod config;
 pub mod database;
 pub mod error;
 pub mod library;
+pub mod query;
+pub mod repository;
 pub mod users;
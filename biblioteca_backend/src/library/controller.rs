@@ -1,92 +1,172 @@
+use std::collections::HashMap;
+
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    routing::post,
+    extract::{Path, Query, State},
+    http::{StatusCode, Uri},
+    routing::{get, post},
     Json, Router,
 };
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
 use crate::app::AppState;
 use crate::{
+    auth::extractor::AuthUser,
+    catalog::model::ItemStatus,
     error::Error,
     library::{
-        db::{
-            add_borrow_entry_to_db, get_latest_book_entry_from_db, get_num_borrowed_from_db,
-            get_num_user_can_borrow_from_db, is_book_exists_in_db, is_user_exists_in_db,
-        },
         error::LibraryError,
-        model::BookBorrowState,
+        model::{BorrowBookRequest, Loan, LoanFilters, LoanRecord},
+        repository::LOAN_LIST,
     },
+    query::{ListParams, Page, QueryError},
+    repository::RepositoryError,
+    users::model::{Permission, UserStatus},
 };
 
-use super::{db::add_return_entry_to_db, model::BorrowBookRequest};
-
 pub fn library_router() -> Router<AppState> {
     Router::new()
         .route("/books/:id/borrow", post(borrow_book))
         .route("/books/:id/return", post(return_book))
+        .route("/books/:id/renew", post(renew_book))
+        .route("/users/:id/loans", get(list_user_loans))
+        .route("/books/:id/loans", get(list_book_loans))
 }
 
 // TODO: Update all Path objects to be Uuid instead of string
 pub async fn borrow_book(
     state: State<AppState>,
+    auth_user: AuthUser,
     Path(book_id): Path<Uuid>,
     Json(payload): Json<BorrowBookRequest>,
-) -> Result<StatusCode, Error> {
+) -> Result<(StatusCode, Json<Loan>), Error> {
+    let user_id = auth_user.acting_for(payload.user_id)?;
+
     tracing::debug!(
         "POST /books/:id/borrow for user_id {:?} and book_id {:?}",
-        payload.user_id,
+        user_id,
         book_id
     );
 
     // Check existence of book_id
-    if !is_book_exists_in_db(&state, book_id).unwrap() {
+    if !state.catalog.is_book_exists(book_id).unwrap() {
         return Err(Error::bad_request(LibraryError::BookNotExists.to_string()));
     }
 
-    // Check existence of user_id
-    if !is_user_exists_in_db(&state, payload.user_id).unwrap() {
-        return Err(Error::bad_request(LibraryError::UserNotExists.to_string()));
+    // Check that the user exists and may borrow
+    check_user_is_active(&state, user_id)?;
+
+    // Check whether user owes too much in fines to borrow
+    let max_outstanding_fines_cents = state.library_settings.max_outstanding_fines_cents;
+    if state.library.get_fine_balance_of_user(user_id).unwrap() > max_outstanding_fines_cents {
+        return Err(Error::bad_request(
+            LibraryError::OutstandingFinesExceeded(max_outstanding_fines_cents).to_string(),
+        ));
     }
 
+    let hold_pickup_days = state.library_settings.hold_pickup_days;
+    state
+        .library
+        .expire_holds_of_book(book_id, hold_pickup_days)
+        .unwrap();
+
     // Check whether user has exceeded borrow limit
-    let num_borrowed = get_num_borrowed_from_db(&state, payload.user_id).unwrap();
-    let num_max_borrowable = get_num_user_can_borrow_from_db(&state, payload.user_id).unwrap();
+    let num_borrowed = state.library.get_num_borrowed(user_id).unwrap();
+    let num_max_borrowable = state.library.get_num_user_can_borrow(user_id).unwrap();
     if num_borrowed >= num_max_borrowable {
         return Err(Error::bad_request(
             LibraryError::NumBorrowableExceeded(num_max_borrowable).to_string(),
         ));
     }
 
-    // Check whether book is available for borrowing
-    match get_latest_book_entry_from_db(&state, book_id) {
-        Ok(entry) => {
-            let latest_entry = entry.state;
+    // Copies on the hold shelf are only lent to the patron they were set aside for
+    let held_item_id = match state.library.get_hold_of_user(book_id, user_id) {
+        Ok(hold) => hold.item_id,
+        Err(RepositoryError::NotFound) => None,
+        Err(err) => {
+            tracing::warn!("{}", err);
+            return Err(Error::server_issue());
+        }
+    };
+
+    // Patrons waiting in the queue are served before anyone who did not place a hold
+    if held_item_id.is_none()
+        && state
+            .library
+            .is_book_awaited_by_others(book_id, user_id)
+            .unwrap()
+    {
+        return Err(Error::conflict(
+            LibraryError::BookHasActiveHolds.to_string(),
+        ));
+    }
 
-            // If entry exists, check if it's "Borrowed"
-            match latest_entry {
-                // Can't borrow a borrowed book, return as error
-                BookBorrowState::Borrowed => {
+    // Find the copy of the book to lend out, preferring one set aside for the user
+    let item = match held_item_id.or(payload.item_id) {
+        Some(item_id) => match state.catalog.get_item(item_id) {
+            Ok(item) if item.book_id == book_id => item,
+            Ok(_) | Err(RepositoryError::NotFound) => {
+                return Err(Error::bad_request(LibraryError::ItemNotExists.to_string()))
+            }
+            Err(err) => {
+                tracing::warn!("{}", err);
+                return Err(Error::server_issue());
+            }
+        },
+        None => match state.library.get_available_item_of_book(book_id) {
+            Ok(item) => item,
+            Err(RepositoryError::NotFound) => {
+                // No copy on the shelf, either because there are none, or all are out or set aside
+                if state.library.get_num_items_of_book(book_id).unwrap() == 0 {
                     return Err(Error::bad_request(
-                        LibraryError::BookAlreadyBorrowed.to_string(),
-                    ))
+                        LibraryError::BookHasNoCopies.to_string(),
+                    ));
+                }
+
+                if state
+                    .library
+                    .get_num_items_of_book_with_status(book_id, ItemStatus::OnHold)
+                    .unwrap()
+                    > 0
+                {
+                    return Err(Error::bad_request(LibraryError::BookOnHold.to_string()));
                 }
-                // Latest entry is that it's returned, so we can borrow it
-                BookBorrowState::Returned => {}
+
+                return Err(Error::bad_request(
+                    LibraryError::BookAlreadyBorrowed.to_string(),
+                ));
             }
-        }
-        Err(err) => {
-            tracing::warn!("{}", err);
-            match err {
-                // No entry found, so it's OK
-                rusqlite::Error::QueryReturnedNoRows => {}
-                _ => return Err(Error::server_issue()),
+            Err(err) => {
+                tracing::warn!("{}", err);
+                return Err(Error::server_issue());
             }
+        },
+    };
+
+    // Check whether the copy is available for borrowing
+    match item.status {
+        ItemStatus::Available => {}
+        ItemStatus::OnHold if held_item_id == Some(item.id) => {}
+        ItemStatus::OnHold => return Err(Error::bad_request(LibraryError::BookOnHold.to_string())),
+        ItemStatus::Borrowed => {
+            return Err(Error::bad_request(
+                LibraryError::BookAlreadyBorrowed.to_string(),
+            ))
+        }
+        ItemStatus::Lost | ItemStatus::Maintenance | ItemStatus::Withdrawn => {
+            return Err(Error::bad_request(
+                LibraryError::ItemNotAvailable.to_string(),
+            ))
         }
     }
 
-    match add_borrow_entry_to_db(state, payload.user_id, book_id).await {
-        Ok(()) => Ok(StatusCode::ACCEPTED),
+    let loan_period_days = state.library.get_loan_period_of_user(user_id).unwrap();
+
+    match state
+        .library
+        .add_borrow_entry(user_id, book_id, item.id, loan_period_days)
+    {
+        Ok(loan) => Ok((StatusCode::ACCEPTED, Json(loan))),
         Err(err) => {
             tracing::warn!("{}", err);
             Err(Error::server_issue())
@@ -96,67 +176,277 @@ pub async fn borrow_book(
 
 pub async fn return_book(
     state: State<AppState>,
+    auth_user: AuthUser,
     Path(book_id): Path<Uuid>,
     Json(payload): Json<BorrowBookRequest>,
 ) -> Result<StatusCode, Error> {
+    let user_id = auth_user.acting_for(payload.user_id)?;
+
     tracing::debug!(
         "POST /books/:id/return for user_id {:?} and book_id {:?}",
-        payload.user_id,
+        user_id,
         book_id
     );
 
     // Check existence of book_id
-    if !is_book_exists_in_db(&state, book_id).unwrap() {
+    if !state.catalog.is_book_exists(book_id).unwrap() {
         return Err(Error::bad_request(LibraryError::BookNotExists.to_string()));
     }
 
     // Check existence of user_id
-    if !is_user_exists_in_db(&state, payload.user_id).unwrap() {
+    if !state.users.is_user_exists(user_id).unwrap() {
         return Err(Error::bad_request(LibraryError::UserNotExists.to_string()));
     }
 
-    let mut borrower_id = Uuid::nil();
-    let mut entry_id = Uuid::nil();
-
-    // Check if the book is currently returned
-    match get_latest_book_entry_from_db(&state, book_id) {
-        Ok(entry) => {
-            let latest_entry = entry.state;
-
-            // If entry exists, check if it's "Returned"
-            match latest_entry {
-                BookBorrowState::Borrowed => {}
-                BookBorrowState::Returned => {
+    // Find the user's outstanding borrow of the book
+    let borrow_entry =
+        match state
+            .library
+            .get_active_borrow_entry(book_id, user_id, payload.item_id)
+        {
+            Ok(entry) => entry,
+            Err(RepositoryError::NotFound) => {
+                // Either someone else has the book, or nobody does
+                if state.library.get_num_borrowed_of_book(book_id).unwrap() > 0 {
                     return Err(Error::bad_request(
-                        LibraryError::BookAlreadyReturned.to_string(),
-                    ))
+                        LibraryError::BookNotBorrowedByUser.to_string(),
+                    ));
                 }
+
+                return Err(Error::bad_request(
+                    LibraryError::BookAlreadyReturned.to_string(),
+                ));
             }
+            Err(err) => {
+                tracing::warn!("{}", err);
+                return Err(Error::server_issue());
+            }
+        };
+
+    // Overdue books accrue a daily fine, up to the cap of the user's role
+    let (fine_per_day_cents, fine_cap_cents) =
+        state.library.get_fine_rates_of_user(user_id).unwrap();
+    let fine_cents = calculate_overdue_fine(
+        borrow_entry.due_at,
+        Utc::now(),
+        fine_per_day_cents,
+        fine_cap_cents,
+    );
+
+    let hold_pickup_days = state.library_settings.hold_pickup_days;
 
-            borrower_id = entry.user_id;
-            entry_id = entry.id;
+    match state
+        .library
+        .add_return_entry(borrow_entry, hold_pickup_days, fine_cents)
+    {
+        Ok(()) => Ok(StatusCode::ACCEPTED),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Refuses users who do not exist or whose membership is not active, who can still return
+// what they have on loan but not borrow, renew or hold books
+pub(crate) fn check_user_is_active(state: &AppState, user_id: Uuid) -> Result<(), Error> {
+    let user = match state.users.get_user(user_id) {
+        Ok(user) => user,
+        Err(RepositoryError::NotFound) => {
+            return Err(Error::bad_request(LibraryError::UserNotExists.to_string()));
         }
         Err(err) => {
             tracing::warn!("{}", err);
-            match err {
-                rusqlite::Error::QueryReturnedNoRows => {}
-                _ => return Err(Error::server_issue()),
-            }
+            return Err(Error::server_issue());
         }
+    };
+
+    let error = match user.membership.status {
+        UserStatus::Active => return Ok(()),
+        UserStatus::Suspended => LibraryError::UserSuspended,
+        UserStatus::Expired => LibraryError::MembershipExpired,
+        UserStatus::Pending => LibraryError::MembershipPending,
+    };
+
+    Err(Error::bad_request(error.to_string()))
+}
+
+// Charges every started day past the due date, up to the cap
+fn calculate_overdue_fine(
+    due_at: DateTime<Utc>,
+    returned_at: DateTime<Utc>,
+    fine_per_day_cents: u32,
+    fine_cap_cents: u32,
+) -> i64 {
+    let overdue_seconds = (returned_at - due_at).num_seconds();
+    if overdue_seconds <= 0 {
+        return 0;
+    }
+
+    let overdue_days = (overdue_seconds + 86_399) / 86_400;
+    (overdue_days * i64::from(fine_per_day_cents)).min(i64::from(fine_cap_cents))
+}
+
+// Extends the due date of the user's current loan of the book
+pub async fn renew_book(
+    state: State<AppState>,
+    auth_user: AuthUser,
+    Path(book_id): Path<Uuid>,
+    Json(payload): Json<BorrowBookRequest>,
+) -> Result<(StatusCode, Json<Loan>), Error> {
+    let user_id = auth_user.acting_for(payload.user_id)?;
+
+    tracing::debug!(
+        "POST /books/:id/renew for user_id {:?} and book_id {:?}",
+        user_id,
+        book_id
+    );
+
+    // Check existence of book_id
+    if !state.catalog.is_book_exists(book_id).unwrap() {
+        return Err(Error::bad_request(LibraryError::BookNotExists.to_string()));
+    }
+
+    // Check that the user exists and may borrow
+    check_user_is_active(&state, user_id)?;
+
+    // Only the current borrower can renew the loan
+    let borrow_entry =
+        match state
+            .library
+            .get_active_borrow_entry(book_id, user_id, payload.item_id)
+        {
+            Ok(entry) => entry,
+            Err(RepositoryError::NotFound) => {
+                return Err(Error::bad_request(
+                    LibraryError::BookNotBorrowedByUser.to_string(),
+                ));
+            }
+            Err(err) => {
+                tracing::warn!("{}", err);
+                return Err(Error::server_issue());
+            }
+        };
+
+    // Overdue loans are returned instead, which charges their fine from the due date
+    if borrow_entry.due_at < Utc::now() {
+        return Err(Error::conflict(LibraryError::LoanOverdue.to_string()));
     }
 
-    //  Check whether the borrower is the same user
-    if payload.user_id != borrower_id {
+    // Patrons waiting for the book take priority over renewing it
+    state
+        .library
+        .expire_holds_of_book(book_id, state.library_settings.hold_pickup_days)
+        .unwrap();
+    if state
+        .library
+        .is_book_held_by_others(book_id, user_id)
+        .unwrap()
+    {
+        return Err(Error::conflict(
+            LibraryError::BookHasActiveHolds.to_string(),
+        ));
+    }
+
+    // Check whether the loan has been renewed too many times
+    let max_renewals = state.library.get_max_renewals_of_user(user_id).unwrap();
+    if borrow_entry.renewals >= max_renewals {
         return Err(Error::bad_request(
-            LibraryError::BookNotBorrowedByUser.to_string(),
+            LibraryError::RenewalLimitReached(max_renewals).to_string(),
         ));
     }
 
-    match add_return_entry_to_db(state, entry_id, payload.user_id, book_id).await {
-        Ok(()) => Ok(StatusCode::ACCEPTED),
+    let loan_period_days = state.library.get_loan_period_of_user(user_id).unwrap();
+
+    match state
+        .library
+        .renew_borrow_entry(borrow_entry, loan_period_days)
+    {
+        Ok(loan) => Ok((StatusCode::ACCEPTED, Json(loan))),
         Err(err) => {
             tracing::warn!("{}", err);
             Err(Error::server_issue())
         }
     }
 }
+
+// Lists the loans of the user, which are only their current ones unless another status is given
+pub async fn list_user_loans(
+    state: State<AppState>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+    uri: Uri,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Page<LoanRecord>>, Error> {
+    tracing::debug!(
+        "GET /users/:id/loans for user_id {:?} with query params: {:?}",
+        user_id,
+        params
+    );
+
+    auth_user.acting_for(Some(user_id))?;
+
+    let params = ListParams::parse(&uri, params, &LOAN_LIST)
+        .map_err(|err| Error::bad_request(err.to_string()))?;
+    let filters = parse_loan_filters(&params).map_err(|err| Error::bad_request(err.to_string()))?;
+
+    if !state.users.is_user_exists(user_id).unwrap() {
+        return Err(Error::not_found());
+    }
+
+    match state
+        .library
+        .list_loan_history_of_user(user_id, &filters, &params)
+    {
+        Ok(loans) => Ok(Json(loans)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Lists who borrowed copies of the book, such as to find who had it last when damage is found
+pub async fn list_book_loans(
+    state: State<AppState>,
+    auth_user: AuthUser,
+    Path(book_id): Path<Uuid>,
+    uri: Uri,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Page<LoanRecord>>, Error> {
+    tracing::debug!(
+        "GET /books/:id/loans for book_id {:?} with query params: {:?}",
+        book_id,
+        params
+    );
+
+    auth_user.require_permission(Permission::CirculationStaff)?;
+
+    let params = ListParams::parse(&uri, params, &LOAN_LIST)
+        .map_err(|err| Error::bad_request(err.to_string()))?;
+    let filters = parse_loan_filters(&params).map_err(|err| Error::bad_request(err.to_string()))?;
+
+    if !state.catalog.is_book_exists(book_id).unwrap() {
+        return Err(Error::not_found());
+    }
+
+    match state
+        .library
+        .list_loan_history_of_book(book_id, &filters, &params)
+    {
+        Ok(loans) => Ok(Json(loans)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Reads the status and date range of a loan history, listing only active loans by default
+fn parse_loan_filters(params: &ListParams) -> Result<LoanFilters, QueryError> {
+    Ok(LoanFilters {
+        status: params.filters.get_parsed("status")?.unwrap_or_default(),
+        from: params.filters.get_parsed("from")?,
+        to: params.filters.get_parsed("to")?,
+    })
+}
@@ -1,116 +1,915 @@
-use axum::extract::State;
-use chrono::prelude::*;
-use rusqlite::Result;
+use chrono::{prelude::*, Duration};
+use rusqlite::{Connection, OptionalExtension, Row, Transaction};
 use uuid::Uuid;
 
-use crate::app::AppState;
+use crate::{
+    audit::{
+        context::AuditContext,
+        db::add_audit_entry,
+        model::{AuditAction, AuditEntry, EntityType},
+    },
+    catalog::model::{Item, ItemStatus},
+    query::{ListParams, Page},
+    repository::{sqlite::QueryBuilder, Result, SqliteRepository},
+};
 
-use super::model::{BookBorrowEntry, BookBorrowState};
+use super::{
+    model::{
+        BookBorrowEntry, BookBorrowState, FineEntry, FineEntryKind, Hold, HoldStatus, Loan,
+        LoanFilters, LoanRecord, LoanStatus,
+    },
+    repository::LibraryRepository,
+};
 
-pub async fn add_borrow_entry_to_db(
-    State(state): State<AppState>,
-    user_id: Uuid,
-    book_id: Uuid,
-) -> Result<(), rusqlite::Error> {
-    let conn = state.db_pool.get().unwrap();
+const LOAN_SORT_COLUMNS: &[(&str, &str)] =
+    &[("borrowed_at", "a.timestamp"), ("due_at", "a.due_at")];
+
+// Roles of the user given as the first parameter that have not expired by the time given as the
+// second, which together make up what the user may do
+const ACTIVE_USER_ROLES: &str = "map_users_to_user_roles b
+    JOIN user_roles c ON b.user_role_id = c.id
+    WHERE b.user_id = $1 AND (b.expires_at IS NULL OR b.expires_at > $2)";
+
+impl LibraryRepository for SqliteRepository {
+    fn add_borrow_entry(
+        &self,
+        user_id: Uuid,
+        book_id: Uuid,
+        item_id: Uuid,
+        loan_period_days: u32,
+    ) -> Result<Loan> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+
+        let borrowed_at = Utc::now();
+        let loan = Loan {
+            id: Uuid::new_v4(),
+            book_id,
+            item_id,
+            user_id,
+            borrowed_at,
+            due_at: borrowed_at + Duration::days(loan_period_days.into()),
+            renewals: 0,
+        };
+
+        // Use transaction to keep the ledger and the copy's status in sync
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO map_users_to_borrowed_books (id, user_id, book_id, item_id, timestamp, due_at, action) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            (loan.id, loan.user_id, loan.book_id, loan.item_id, loan.borrowed_at, loan.due_at, BookBorrowState::Borrowed),
+        )?;
+
+        set_item_status(&tx, &context, item_id, ItemStatus::Borrowed)?;
+
+        // Borrowing the book fulfils any hold the user had on it
+        let fulfilled_hold = tx
+            .query_row(
+                "SELECT id, book_id, user_id, item_id, status, created_at, expires_at FROM holds WHERE book_id = ?1 AND user_id = ?2",
+                (book_id, user_id),
+                hold_from_row,
+            )
+            .optional()?;
+
+        if let Some(hold) = fulfilled_hold {
+            tx.execute("DELETE FROM holds WHERE id = ?1", [hold.id])?;
+            add_audit_entry(
+                &tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Delete,
+                    EntityType::Hold,
+                    hold.id,
+                    Some(&hold),
+                    None,
+                ),
+            )?;
+        }
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Borrow,
+                EntityType::Loan,
+                loan.id,
+                None,
+                Some(&loan),
+            ),
+        )?;
 
-    match conn.execute(
-        "INSERT INTO map_users_to_borrowed_books (id, user_id, book_id, timestamp, action) VALUES (?1, ?2, ?3, ?4, ?5)",
-        (Uuid::new_v4(), user_id, book_id, Utc::now(), BookBorrowState::Borrowed),
-    ) {
-        Ok(_) => Ok(()),
-        Err(err) => Err(err),
+        tx.commit()?;
+
+        Ok(loan)
     }
-}
 
-pub async fn add_return_entry_to_db(
-    State(state): State<AppState>,
-    entry_id: Uuid,
-    user_id: Uuid,
-    book_id: Uuid,
-) -> Result<(), rusqlite::Error> {
-    let conn = state.db_pool.get().unwrap();
+    fn add_return_entry(
+        &self,
+        borrow_entry: BookBorrowEntry,
+        hold_pickup_days: u32,
+        fine_cents: i64,
+    ) -> Result<()> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+
+        // Use transaction to keep the ledger and the copy's status in sync
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO map_users_to_borrowed_books (id, user_id, book_id, item_id, timestamp, due_at, renewals, action) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            (
+                borrow_entry.id,
+                borrow_entry.user_id,
+                borrow_entry.book_id,
+                borrow_entry.item_id,
+                Utc::now(),
+                borrow_entry.due_at,
+                borrow_entry.renewals,
+                BookBorrowState::Returned,
+            ),
+        )?;
+
+        pass_item_to_next_hold(
+            &tx,
+            &context,
+            borrow_entry.book_id,
+            borrow_entry.item_id,
+            hold_pickup_days,
+        )?;
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Return,
+                EntityType::Loan,
+                borrow_entry.id,
+                Some(&Loan::from(&borrow_entry)),
+                None,
+            ),
+        )?;
+
+        // Overdue returns accrue their fine along with the return itself
+        if fine_cents > 0 {
+            let fine = FineEntry {
+                id: Uuid::new_v4(),
+                user_id: borrow_entry.user_id,
+                loan_id: Some(borrow_entry.id),
+                kind: FineEntryKind::Fine,
+                amount_cents: fine_cents,
+                reason: None,
+                created_at: Utc::now(),
+            };
+
+            insert_fine_entry(&tx, &fine)?;
+
+            add_audit_entry(
+                &tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Create,
+                    EntityType::Fine,
+                    fine.id,
+                    None,
+                    Some(&fine),
+                ),
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn renew_borrow_entry(
+        &self,
+        borrow_entry: BookBorrowEntry,
+        loan_period_days: u32,
+    ) -> Result<Loan> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+
+        let before = Loan::from(&borrow_entry);
+        let loan = Loan {
+            due_at: borrow_entry.due_at.max(Utc::now()) + Duration::days(loan_period_days.into()),
+            renewals: borrow_entry.renewals + 1,
+            ..Loan::from(&borrow_entry)
+        };
+
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "UPDATE map_users_to_borrowed_books SET due_at = ?1, renewals = ?2 WHERE id = ?3 AND action = ?4",
+            (loan.due_at, loan.renewals, loan.id, BookBorrowState::Borrowed),
+        )?;
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Renew,
+                EntityType::Loan,
+                loan.id,
+                Some(&before),
+                Some(&loan),
+            ),
+        )?;
+
+        tx.commit()?;
+
+        Ok(loan)
+    }
+
+    fn get_active_borrow_entry(
+        &self,
+        book_id: Uuid,
+        user_id: Uuid,
+        item_id: Option<Uuid>,
+    ) -> Result<BookBorrowEntry> {
+        let borrow_entry = self.pool.get().unwrap().query_row(
+            "SELECT a.id, a.user_id, a.book_id, a.item_id, a.timestamp, a.due_at, a.renewals, a.action FROM map_users_to_borrowed_books a
+                    WHERE a.action = 'Borrowed'
+                    AND a.book_id = ?1
+                    AND a.user_id = ?2
+                    AND (?3 IS NULL OR a.item_id = ?3)
+                    AND a.id NOT IN (SELECT b.id FROM map_users_to_borrowed_books b WHERE b.action = 'Returned')
+                    ORDER BY a.timestamp",
+            (book_id, user_id, item_id),
+            borrow_entry_from_row,
+        )?;
+
+        Ok(borrow_entry)
+    }
+
+    fn list_loan_history_of_user(
+        &self,
+        user_id: Uuid,
+        filters: &LoanFilters,
+        params: &ListParams,
+    ) -> Result<Page<LoanRecord>> {
+        list_loan_history(
+            &self.pool.get().unwrap(),
+            "a.user_id",
+            user_id,
+            filters,
+            params,
+        )
+    }
+
+    fn list_loan_history_of_book(
+        &self,
+        book_id: Uuid,
+        filters: &LoanFilters,
+        params: &ListParams,
+    ) -> Result<Page<LoanRecord>> {
+        list_loan_history(
+            &self.pool.get().unwrap(),
+            "a.book_id",
+            book_id,
+            filters,
+            params,
+        )
+    }
+
+    fn get_num_borrowed(&self, user_id: Uuid) -> Result<u32> {
+        let num_borrowed = self.pool.get().unwrap().query_row::<u32, _, _>(
+            "SELECT COUNT(*) FROM map_users_to_borrowed_books a
+                    WHERE a.action = 'Borrowed'
+                    AND a.user_id = $1
+                    AND a.id NOT IN (SELECT b.id FROM map_users_to_borrowed_books b WHERE b.action = 'Returned')",
+            [user_id],
+            |row| row.get(0),
+        )?;
+
+        Ok(num_borrowed)
+    }
+
+    fn get_num_borrowed_of_book(&self, book_id: Uuid) -> Result<u32> {
+        let num_borrowed = self.pool.get().unwrap().query_row::<u32, _, _>(
+            "SELECT COUNT(*) FROM map_users_to_borrowed_books a
+                    WHERE a.action = 'Borrowed'
+                    AND a.book_id = $1
+                    AND a.id NOT IN (SELECT b.id FROM map_users_to_borrowed_books b WHERE b.action = 'Returned')",
+            [book_id],
+            |row| row.get(0),
+        )?;
+
+        Ok(num_borrowed)
+    }
+
+    fn get_num_user_can_borrow(&self, user_id: Uuid) -> Result<u32> {
+        let num_borrowable = self.pool.get().unwrap().query_row::<u32, _, _>(
+            &format!(
+                "SELECT COALESCE(MAX(c.num_borrowable_books), 0) FROM {}",
+                ACTIVE_USER_ROLES
+            ),
+            (user_id, Utc::now()),
+            |row| row.get(0),
+        )?;
+
+        Ok(num_borrowable)
+    }
+
+    fn get_max_num_borrowed_of_user_role(&self, user_role_id: Uuid) -> Result<u32> {
+        let num_borrowed = self.pool.get().unwrap().query_row::<u32, _, _>(
+            "SELECT COALESCE(MAX(loans.num_borrowed), 0) FROM (
+                SELECT a.user_id, COUNT(*) AS num_borrowed FROM map_users_to_borrowed_books a
+                JOIN map_users_to_user_roles b ON a.user_id = b.user_id
+                WHERE a.action = 'Borrowed'
+                AND b.user_role_id = $1
+                AND (b.expires_at IS NULL OR b.expires_at > $2)
+                AND a.id NOT IN (SELECT c.id FROM map_users_to_borrowed_books c WHERE c.action = 'Returned')
+                GROUP BY a.user_id
+            ) loans
+            WHERE loans.num_borrowed > COALESCE((
+                SELECT MAX(e.num_borrowable_books) FROM map_users_to_user_roles d
+                JOIN user_roles e ON d.user_role_id = e.id
+                WHERE d.user_id = loans.user_id
+                AND d.user_role_id != $1
+                AND (d.expires_at IS NULL OR d.expires_at > $2)
+            ), 0)",
+            (user_role_id, Utc::now()),
+            |row| row.get(0),
+        )?;
+
+        Ok(num_borrowed)
+    }
 
-    match conn.execute(
-        "INSERT INTO map_users_to_borrowed_books (id, user_id, book_id, timestamp, action) VALUES (?1, ?2, ?3, ?4, ?5)",
-        (entry_id, user_id, book_id, Utc::now(), BookBorrowState::Returned),
-    ) {
-        Ok(_) => Ok(()),
-        Err(err) => Err(err),
+    fn get_loan_period_of_user(&self, user_id: Uuid) -> Result<u32> {
+        let loan_period_days = self.pool.get().unwrap().query_row::<u32, _, _>(
+            &format!(
+                "SELECT COALESCE(MAX(c.loan_period_days), 0) FROM {}",
+                ACTIVE_USER_ROLES
+            ),
+            (user_id, Utc::now()),
+            |row| row.get(0),
+        )?;
+
+        Ok(loan_period_days)
+    }
+
+    fn get_max_renewals_of_user(&self, user_id: Uuid) -> Result<u32> {
+        let max_renewals = self.pool.get().unwrap().query_row::<u32, _, _>(
+            &format!(
+                "SELECT COALESCE(MAX(c.max_renewals), 0) FROM {}",
+                ACTIVE_USER_ROLES
+            ),
+            (user_id, Utc::now()),
+            |row| row.get(0),
+        )?;
+
+        Ok(max_renewals)
+    }
+
+    fn get_fine_rates_of_user(&self, user_id: Uuid) -> Result<(u32, u32)> {
+        let fine_rates = self
+            .pool
+            .get()
+            .unwrap()
+            .query_row(
+                &format!(
+                    "SELECT c.fine_per_day_cents, c.fine_cap_cents FROM {}
+                    ORDER BY c.fine_per_day_cents, c.fine_cap_cents
+                    LIMIT 1",
+                    ACTIVE_USER_ROLES
+                ),
+                (user_id, Utc::now()),
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        Ok(fine_rates.unwrap_or((0, 0)))
+    }
+
+    fn get_available_item_of_book(&self, book_id: Uuid) -> Result<Item> {
+        let item = self.pool.get().unwrap().query_row(
+            "SELECT id, book_id, barcode, shelf_location, condition, status FROM items
+                    WHERE book_id = $1 AND status = $2
+                    ORDER BY barcode",
+            (book_id, ItemStatus::Available),
+            item_from_row,
+        )?;
+
+        Ok(item)
+    }
+
+    fn get_num_items_of_book(&self, book_id: Uuid) -> Result<u32> {
+        let num_items = self.pool.get().unwrap().query_row::<u32, _, _>(
+            "SELECT COUNT(*) FROM items WHERE book_id = $1 AND status != $2",
+            (book_id, ItemStatus::Withdrawn),
+            |row| row.get(0),
+        )?;
+
+        Ok(num_items)
+    }
+
+    fn get_num_items_of_book_with_status(&self, book_id: Uuid, status: ItemStatus) -> Result<u32> {
+        let num_items = self.pool.get().unwrap().query_row::<u32, _, _>(
+            "SELECT COUNT(*) FROM items WHERE book_id = $1 AND status = $2",
+            (book_id, status),
+            |row| row.get(0),
+        )?;
+
+        Ok(num_items)
+    }
+
+    fn add_hold(&self, hold: Hold) -> Result<Hold> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO holds (id, book_id, user_id, item_id, status, created_at, expires_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            (
+                &hold.id,
+                &hold.book_id,
+                &hold.user_id,
+                &hold.item_id,
+                &hold.status,
+                &hold.created_at,
+                &hold.expires_at,
+            ),
+        )?;
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Create,
+                EntityType::Hold,
+                hold.id,
+                None,
+                Some(&hold),
+            ),
+        )?;
+
+        tx.commit()?;
+
+        Ok(hold)
+    }
+
+    fn list_holds_of_book(&self, book_id: Uuid) -> Result<Vec<Hold>> {
+        let conn = self.pool.get().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT id, book_id, user_id, item_id, status, created_at, expires_at FROM holds
+                    WHERE book_id = ?1
+                    ORDER BY created_at",
+        )?;
+
+        let holds = stmt
+            .query_map([book_id], hold_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(holds)
+    }
+
+    fn get_hold(&self, id: Uuid) -> Result<Hold> {
+        let hold = self.pool.get().unwrap().query_row(
+            "SELECT id, book_id, user_id, item_id, status, created_at, expires_at FROM holds WHERE id = ?1",
+            [id],
+            hold_from_row,
+        )?;
+
+        Ok(hold)
+    }
+
+    fn get_hold_of_user(&self, book_id: Uuid, user_id: Uuid) -> Result<Hold> {
+        let hold = self.pool.get().unwrap().query_row(
+            "SELECT id, book_id, user_id, item_id, status, created_at, expires_at FROM holds WHERE book_id = ?1 AND user_id = ?2",
+            (book_id, user_id),
+            hold_from_row,
+        )?;
+
+        Ok(hold)
+    }
+
+    fn delete_hold(&self, hold: Hold, hold_pickup_days: u32) -> Result<()> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+
+        let tx = conn.transaction()?;
+
+        tx.execute("DELETE FROM holds WHERE id = ?1", [hold.id])?;
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Delete,
+                EntityType::Hold,
+                hold.id,
+                Some(&hold),
+                None,
+            ),
+        )?;
+
+        if let Some(item_id) = hold.item_id {
+            pass_item_to_next_hold(&tx, &context, hold.book_id, item_id, hold_pickup_days)?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn expire_holds_of_book(&self, book_id: Uuid, hold_pickup_days: u32) -> Result<()> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+
+        let tx = conn.transaction()?;
+
+        let mut stmt = tx.prepare(
+            "SELECT id, book_id, user_id, item_id, status, created_at, expires_at FROM holds
+                    WHERE book_id = ?1 AND status = ?2 AND expires_at < ?3
+                    ORDER BY created_at",
+        )?;
+
+        let expired_holds = stmt
+            .query_map((book_id, HoldStatus::Ready, Utc::now()), hold_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        drop(stmt);
+
+        for hold in expired_holds {
+            tx.execute("DELETE FROM holds WHERE id = ?1", [hold.id])?;
+            add_audit_entry(
+                &tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Delete,
+                    EntityType::Hold,
+                    hold.id,
+                    Some(&hold),
+                    None,
+                ),
+            )?;
+
+            if let Some(item_id) = hold.item_id {
+                pass_item_to_next_hold(&tx, &context, hold.book_id, item_id, hold_pickup_days)?;
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn is_book_held_by_others(&self, book_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let count = self.pool.get().unwrap().query_row::<i32, _, _>(
+            "SELECT COUNT(*) FROM holds WHERE book_id = $1 AND user_id != $2",
+            (book_id, user_id),
+            |row| row.get(0),
+        )?;
+
+        Ok(count > 0)
+    }
+
+    fn is_book_awaited_by_others(&self, book_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let count = self.pool.get().unwrap().query_row::<i32, _, _>(
+            "SELECT COUNT(*) FROM holds WHERE book_id = $1 AND user_id != $2 AND status = $3",
+            (book_id, user_id, HoldStatus::Waiting),
+            |row| row.get(0),
+        )?;
+
+        Ok(count > 0)
+    }
+
+    fn list_fine_entries_of_user(&self, user_id: Uuid) -> Result<Vec<FineEntry>> {
+        let conn = self.pool.get().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT id, user_id, loan_id, kind, amount_cents, reason, created_at FROM fines
+                    WHERE user_id = ?1
+                    ORDER BY created_at",
+        )?;
+
+        let entries = stmt
+            .query_map([user_id], |row| {
+                Ok(FineEntry {
+                    id: row.get(0)?,
+                    user_id: row.get(1)?,
+                    loan_id: row.get(2)?,
+                    kind: row.get(3)?,
+                    amount_cents: row.get(4)?,
+                    reason: row.get(5)?,
+                    created_at: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(entries)
+    }
+
+    fn add_fine_entry(&self, entry: FineEntry) -> Result<FineEntry> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        insert_fine_entry(&tx, &entry)?;
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Create,
+                EntityType::Fine,
+                entry.id,
+                None,
+                Some(&entry),
+            ),
+        )?;
+
+        tx.commit()?;
+
+        Ok(entry)
+    }
+
+    fn get_fine_balance_of_user(&self, user_id: Uuid) -> Result<i64> {
+        let balance_cents = self.pool.get().unwrap().query_row::<i64, _, _>(
+            "SELECT COALESCE(SUM(CASE kind WHEN 'fine' THEN amount_cents ELSE -amount_cents END), 0) FROM fines
+                    WHERE user_id = $1",
+            [user_id],
+            |row| row.get(0),
+        )?;
+
+        Ok(balance_cents)
     }
 }
 
-pub fn get_latest_book_entry_from_db(
-    state: &State<AppState>,
-    book_id: Uuid,
-) -> Result<BookBorrowEntry, rusqlite::Error> {
-    state.db_pool.get().unwrap().query_row(
-        "SELECT * FROM map_users_to_borrowed_books WHERE book_id = ?1 ORDER BY timestamp DESC",
-        [book_id],
-        |row| {
-            Ok(BookBorrowEntry {
+// Lists the loans whose user or book in the column is the one given, each joined with the
+// return of its copy, if there was one
+fn list_loan_history(
+    conn: &Connection,
+    column: &str,
+    id: Uuid,
+    filters: &LoanFilters,
+    params: &ListParams,
+) -> Result<Page<LoanRecord>> {
+    let mut query = QueryBuilder::new(
+        "SELECT a.id, a.book_id, a.item_id, a.user_id, a.timestamp, a.due_at, a.renewals, b.timestamp FROM map_users_to_borrowed_books a
+        LEFT JOIN map_users_to_borrowed_books b ON b.id = a.id AND b.action = 'Returned'",
+    );
+    query
+        .fixed_condition("a.action = 'Borrowed'")
+        .condition(&format!("{} = ?", column), id);
+    match filters.status {
+        LoanStatus::Active => {
+            query.fixed_condition("b.id IS NULL");
+        }
+        LoanStatus::Returned => {
+            query.fixed_condition("b.id IS NOT NULL");
+        }
+        LoanStatus::All => {}
+    }
+    if let Some(from) = filters.from {
+        query.condition("(b.timestamp IS NULL OR b.timestamp >= ?)", from);
+    }
+    if let Some(to) = filters.to {
+        query.condition("a.timestamp < ?", to);
+    }
+    query.paginate(params, LOAN_SORT_COLUMNS, "a.timestamp DESC, a.id");
+
+    let mut stmt = conn.prepare(&query.sql())?;
+
+    let loans = stmt
+        .query_map(query.params(), |row| {
+            let loan = Loan {
                 id: row.get(0)?,
-                user_id: row.get(1)?,
-                book_id: row.get(2)?,
-                timestamp: row.get(3)?,
-                state: row.get(4)?,
-            })
-        },
-    )
+                book_id: row.get(1)?,
+                item_id: row.get(2)?,
+                user_id: row.get(3)?,
+                borrowed_at: row.get(4)?,
+                due_at: row.get(5)?,
+                renewals: row.get(6)?,
+            };
+
+            Ok(LoanRecord::new(loan, row.get(7)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    Ok(Page::new(loans, query.count(conn)?, params))
 }
 
-pub fn get_num_borrowed_from_db(
-    state: &State<AppState>,
-    user_id: Uuid,
-) -> Result<u32, rusqlite::Error> {
-    state.db_pool.get().unwrap().query_row::<u32, _, _>(
-        "SELECT COUNT(*) FROM map_users_to_borrowed_books a
-                WHERE a.action = 'Borrowed'
-                AND a.user_id = $1
-                AND a.id NOT IN (SELECT b.id FROM map_users_to_borrowed_books b WHERE b.action = 'Returned')", 
-            [user_id],
-            |row| row.get(0)
-    )
+fn insert_fine_entry(tx: &Transaction, entry: &FineEntry) -> rusqlite::Result<()> {
+    tx.execute(
+        "INSERT INTO fines (id, user_id, loan_id, kind, amount_cents, reason, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        (
+            &entry.id,
+            &entry.user_id,
+            &entry.loan_id,
+            &entry.kind,
+            &entry.amount_cents,
+            &entry.reason,
+            &entry.created_at,
+        ),
+    )?;
+
+    Ok(())
 }
 
-pub fn get_num_user_can_borrow_from_db(
-    state: &State<AppState>,
+// Cancels every hold of the user, passing the copies set aside for them on to the next patron
+pub(crate) fn cancel_holds_of_user(
+    tx: &Transaction,
+    context: &AuditContext,
     user_id: Uuid,
-) -> Result<u32, rusqlite::Error> {
-    state.db_pool.get().unwrap().query_row::<u32, _, _>(
-        "SELECT c.num_borrowable_books FROM users a
-                LEFT JOIN map_users_to_user_roles b ON a.id = b.user_id 
-                LEFT JOIN user_roles c ON b.user_role_id = c.id
-                WHERE a.id = $1",
-        [user_id],
-        |row| row.get(0),
-    )
+    hold_pickup_days: u32,
+) -> rusqlite::Result<()> {
+    let mut stmt = tx.prepare(
+        "SELECT id, book_id, user_id, item_id, status, created_at, expires_at FROM holds
+                WHERE user_id = ?1
+                ORDER BY created_at",
+    )?;
+
+    let holds = stmt
+        .query_map([user_id], hold_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    drop(stmt);
+
+    for hold in holds {
+        tx.execute("DELETE FROM holds WHERE id = ?1", [hold.id])?;
+        add_audit_entry(
+            tx,
+            &AuditEntry::new(
+                context,
+                AuditAction::Delete,
+                EntityType::Hold,
+                hold.id,
+                Some(&hold),
+                None,
+            ),
+        )?;
+
+        if let Some(item_id) = hold.item_id {
+            pass_item_to_next_hold(tx, context, hold.book_id, item_id, hold_pickup_days)?;
+        }
+    }
+
+    Ok(())
 }
 
-pub fn is_user_exists_in_db(
-    state: &State<AppState>,
-    user_id: Uuid,
-) -> Result<bool, rusqlite::Error> {
-    match state.db_pool.get().unwrap().query_row::<i32, _, _>(
-        "SELECT COUNT(*) FROM users WHERE id = $1",
-        [user_id],
-        |row| row.get(0),
-    ) {
-        Ok(count) => Ok(count == 1),
-        Err(err) => Err(err),
+// Cancels every hold of the book, putting the copies set aside for them back on the shelf
+pub(crate) fn cancel_holds_of_book(
+    tx: &Transaction,
+    context: &AuditContext,
+    book_id: Uuid,
+) -> rusqlite::Result<()> {
+    let mut stmt = tx.prepare(
+        "SELECT id, book_id, user_id, item_id, status, created_at, expires_at FROM holds
+                WHERE book_id = ?1
+                ORDER BY created_at",
+    )?;
+
+    let holds = stmt
+        .query_map([book_id], hold_from_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    drop(stmt);
+
+    for hold in holds {
+        tx.execute("DELETE FROM holds WHERE id = ?1", [hold.id])?;
+        add_audit_entry(
+            tx,
+            &AuditEntry::new(
+                context,
+                AuditAction::Delete,
+                EntityType::Hold,
+                hold.id,
+                Some(&hold),
+                None,
+            ),
+        )?;
+
+        if let Some(item_id) = hold.item_id {
+            set_item_status(tx, context, item_id, ItemStatus::Available)?;
+        }
     }
+
+    Ok(())
 }
 
-pub fn is_book_exists_in_db(
-    state: &State<AppState>,
+// Sets the copy aside for the first patron waiting in the queue, otherwise puts it back on the shelf
+pub(crate) fn pass_item_to_next_hold(
+    tx: &Transaction,
+    context: &AuditContext,
     book_id: Uuid,
-) -> Result<bool, rusqlite::Error> {
-    match state.db_pool.get().unwrap().query_row::<i32, _, _>(
-        "SELECT COUNT(*) FROM books WHERE id = $1",
-        [book_id],
-        |row| row.get(0),
-    ) {
-        Ok(count) => Ok(count == 1),
-        Err(err) => Err(err),
+    item_id: Uuid,
+    hold_pickup_days: u32,
+) -> rusqlite::Result<()> {
+    let next_hold = tx
+        .query_row(
+            "SELECT id, book_id, user_id, item_id, status, created_at, expires_at FROM holds
+                    WHERE book_id = ?1 AND status = ?2
+                    ORDER BY created_at
+                    LIMIT 1",
+            (book_id, HoldStatus::Waiting),
+            hold_from_row,
+        )
+        .optional()?;
+
+    let Some(before) = next_hold else {
+        return set_item_status(tx, context, item_id, ItemStatus::Available);
+    };
+
+    let hold = Hold {
+        item_id: Some(item_id),
+        status: HoldStatus::Ready,
+        expires_at: Some(Utc::now() + Duration::days(hold_pickup_days.into())),
+        ..before.clone()
+    };
+
+    tx.execute(
+        "UPDATE holds SET status = ?1, item_id = ?2, expires_at = ?3 WHERE id = ?4",
+        (&hold.status, &hold.item_id, &hold.expires_at, &hold.id),
+    )?;
+
+    add_audit_entry(
+        tx,
+        &AuditEntry::new(
+            context,
+            AuditAction::Update,
+            EntityType::Hold,
+            hold.id,
+            Some(&before),
+            Some(&hold),
+        ),
+    )?;
+
+    set_item_status(tx, context, item_id, ItemStatus::OnHold)
+}
+
+// Moves the copy to the status, auditing the change if it is not in that status already
+fn set_item_status(
+    tx: &Transaction,
+    context: &AuditContext,
+    item_id: Uuid,
+    status: ItemStatus,
+) -> rusqlite::Result<()> {
+    let before = tx.query_row(
+        "SELECT id, book_id, barcode, shelf_location, condition, status FROM items WHERE id = ?1",
+        [item_id],
+        item_from_row,
+    )?;
+
+    if before.status == status {
+        return Ok(());
     }
+
+    let item = Item {
+        status,
+        ..before.clone()
+    };
+
+    tx.execute(
+        "UPDATE items SET status = ?1 WHERE id = ?2",
+        (&item.status, &item.id),
+    )?;
+
+    add_audit_entry(
+        tx,
+        &AuditEntry::new(
+            context,
+            AuditAction::Update,
+            EntityType::Item,
+            item.id,
+            Some(&before),
+            Some(&item),
+        ),
+    )
+}
+
+fn borrow_entry_from_row(row: &Row) -> rusqlite::Result<BookBorrowEntry> {
+    Ok(BookBorrowEntry {
+        id: row.get(0)?,
+        user_id: row.get(1)?,
+        book_id: row.get(2)?,
+        item_id: row.get(3)?,
+        timestamp: row.get(4)?,
+        due_at: row.get(5)?,
+        renewals: row.get(6)?,
+        state: row.get(7)?,
+    })
+}
+
+fn item_from_row(row: &Row) -> rusqlite::Result<Item> {
+    Ok(Item {
+        id: row.get(0)?,
+        book_id: row.get(1)?,
+        barcode: row.get(2)?,
+        shelf_location: row.get(3)?,
+        condition: row.get(4)?,
+        status: row.get(5)?,
+    })
+}
+
+fn hold_from_row(row: &Row) -> rusqlite::Result<Hold> {
+    Ok(Hold {
+        id: row.get(0)?,
+        book_id: row.get(1)?,
+        user_id: row.get(2)?,
+        item_id: row.get(3)?,
+        status: row.get(4)?,
+        created_at: row.get(5)?,
+        expires_at: row.get(6)?,
+    })
 }
@@ -1,14 +1,34 @@
 use std::fmt;
 
+use crate::repository::RepositoryError;
+
 #[derive(thiserror::Error, Debug)]
 pub enum LibraryError {
-    DatabaseError(#[from] rusqlite::Error),
+    DatabaseError(#[from] RepositoryError),
     UserNotExists,
+    UserSuspended,
+    MembershipExpired,
+    MembershipPending,
     BookNotExists,
+    BookHasNoCopies,
+    ItemNotExists,
+    ItemNotAvailable,
     BookAlreadyBorrowed,
     BookAlreadyReturned,
     BookNotBorrowedByUser,
     NumBorrowableExceeded(u32),
+    LoansExceedRoleLimit(u32),
+    RenewalLimitReached(u32),
+    LoanOverdue,
+    BookOnHold,
+    BookAvailable,
+    BookHasActiveHolds,
+    BookAlreadyBorrowedByUser,
+    HoldAlreadyPlaced,
+    OutstandingFinesExceeded(i64),
+    InvalidAmount,
+    AmountExceedsBalance,
+    WaiverReasonMissing,
 }
 
 impl fmt::Display for LibraryError {
@@ -18,7 +38,15 @@ impl fmt::Display for LibraryError {
                 write!(f, "there was an error in accessing the database")
             }
             LibraryError::UserNotExists => write!(f, "user does not exist"),
+            LibraryError::UserSuspended => write!(f, "user is suspended"),
+            LibraryError::MembershipExpired => write!(f, "membership of user has expired"),
+            LibraryError::MembershipPending => {
+                write!(f, "membership of user has not been activated yet")
+            }
             LibraryError::BookNotExists => write!(f, "book does not exist"),
+            LibraryError::BookHasNoCopies => write!(f, "book has no copies that can be lent"),
+            LibraryError::ItemNotExists => write!(f, "item does not exist for given book"),
+            LibraryError::ItemNotAvailable => write!(f, "item is not available for borrowing"),
             LibraryError::BookAlreadyBorrowed => write!(f, "book has already been borrowed"),
             LibraryError::BookAlreadyReturned => write!(f, "book has already been returned"),
             LibraryError::BookNotBorrowedByUser => write!(f, "book was not borrowed by given user"),
@@ -27,6 +55,39 @@ impl fmt::Display for LibraryError {
                 "user has reached max num of borrowable books (max: {})",
                 max
             ),
+            LibraryError::LoansExceedRoleLimit(max) => write!(
+                f,
+                "user has more books on loan than the role allows (max: {})",
+                max
+            ),
+            LibraryError::RenewalLimitReached(max) => {
+                write!(f, "loan has reached max num of renewals (max: {})", max)
+            }
+            LibraryError::LoanOverdue => {
+                write!(
+                    f,
+                    "loan is overdue and has to be returned to settle its fine"
+                )
+            }
+            LibraryError::BookOnHold => write!(f, "book is on hold for another user"),
+            LibraryError::BookAvailable => write!(f, "book has copies available for borrowing"),
+            LibraryError::BookHasActiveHolds => {
+                write!(f, "book has active holds by other users")
+            }
+            LibraryError::BookAlreadyBorrowedByUser => {
+                write!(f, "book is currently borrowed by given user")
+            }
+            LibraryError::HoldAlreadyPlaced => write!(f, "user already has a hold on this book"),
+            LibraryError::OutstandingFinesExceeded(max) => write!(
+                f,
+                "user has outstanding fines over the allowed limit (max: {} cents)",
+                max
+            ),
+            LibraryError::InvalidAmount => write!(f, "amount must be a positive number of cents"),
+            LibraryError::AmountExceedsBalance => {
+                write!(f, "amount exceeds outstanding balance of user")
+            }
+            LibraryError::WaiverReasonMissing => write!(f, "waiver must have a reason"),
         }
     }
 }
@@ -0,0 +1,153 @@
+use axum::{
+    extract::{Path, State},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::extractor::AuthUser,
+    error::Error,
+    library::{
+        error::LibraryError,
+        model::{FineEntry, FineEntryKind, FineSummary, PaymentRequest, WaiverRequest},
+    },
+    users::model::Permission,
+};
+
+pub fn fines_router() -> Router<AppState> {
+    Router::new()
+        .route("/users/:id/fines", get(list_user_fines))
+        .route("/users/:id/payments", post(add_payment))
+        .route("/users/:id/waivers", post(add_waiver))
+}
+
+// Retrieves the user's fines ledger along with what they still owe
+async fn list_user_fines(
+    state: State<AppState>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<FineSummary>, Error> {
+    tracing::debug!("GET /users/:id/fines for user_id {:?}", user_id);
+
+    auth_user.acting_for(Some(user_id))?;
+
+    if !state.users.is_user_exists(user_id).unwrap() {
+        return Err(Error::not_found());
+    }
+
+    let balance_cents = state.library.get_fine_balance_of_user(user_id).unwrap();
+
+    match state.library.list_fine_entries_of_user(user_id) {
+        Ok(entries) => Ok(Json(FineSummary {
+            balance_cents,
+            entries,
+        })),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+async fn add_payment(
+    state: State<AppState>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<PaymentRequest>,
+) -> Result<Json<FineEntry>, Error> {
+    tracing::debug!(
+        "POST /users/:id/payments for user_id {:?} with params: {:?}",
+        user_id,
+        payload
+    );
+
+    // Payments are taken at the desk, so only staff record them
+    auth_user.require_permission(Permission::CirculationStaff)?;
+
+    validate_settlement(&state, user_id, payload.amount_cents)?;
+
+    let entry = FineEntry {
+        id: Uuid::new_v4(),
+        user_id,
+        loan_id: None,
+        kind: FineEntryKind::Payment,
+        amount_cents: payload.amount_cents,
+        reason: None,
+        created_at: Utc::now(),
+    };
+
+    match state.library.add_fine_entry(entry) {
+        Ok(entry) => Ok(Json(entry)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+async fn add_waiver(
+    state: State<AppState>,
+    auth_user: AuthUser,
+    Path(user_id): Path<Uuid>,
+    Json(payload): Json<WaiverRequest>,
+) -> Result<Json<FineEntry>, Error> {
+    tracing::debug!(
+        "POST /users/:id/waivers for user_id {:?} with params: {:?}",
+        user_id,
+        payload
+    );
+
+    auth_user.require_permission(Permission::CirculationStaff)?;
+
+    validate_settlement(&state, user_id, payload.amount_cents)?;
+
+    if payload.reason.trim().is_empty() {
+        return Err(Error::bad_request(
+            LibraryError::WaiverReasonMissing.to_string(),
+        ));
+    }
+
+    let entry = FineEntry {
+        id: Uuid::new_v4(),
+        user_id,
+        loan_id: None,
+        kind: FineEntryKind::Waiver,
+        amount_cents: payload.amount_cents,
+        reason: Some(payload.reason),
+        created_at: Utc::now(),
+    };
+
+    match state.library.add_fine_entry(entry) {
+        Ok(entry) => Ok(Json(entry)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Payments and waivers must settle part of what the user owes, and no more
+fn validate_settlement(
+    state: &State<AppState>,
+    user_id: Uuid,
+    amount_cents: i64,
+) -> Result<(), Error> {
+    if !state.users.is_user_exists(user_id).unwrap() {
+        return Err(Error::bad_request(LibraryError::UserNotExists.to_string()));
+    }
+
+    if amount_cents <= 0 {
+        return Err(Error::bad_request(LibraryError::InvalidAmount.to_string()));
+    }
+
+    if amount_cents > state.library.get_fine_balance_of_user(user_id).unwrap() {
+        return Err(Error::bad_request(
+            LibraryError::AmountExceedsBalance.to_string(),
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    app::AppState,
+    auth::extractor::AuthUser,
+    catalog::model::ItemStatus,
+    error::Error,
+    library::{
+        controller::check_user_is_active,
+        error::LibraryError,
+        model::{Hold, HoldStatus, PlaceHoldRequest},
+    },
+    users::model::Permission,
+};
+
+pub fn holds_router() -> Router<AppState> {
+    Router::new()
+        .route("/books/:id/holds", get(list_book_holds))
+        .route("/books/:id/holds", post(place_hold))
+        .route("/holds/:id", delete(cancel_hold))
+}
+
+// Lists the hold queue of a book, first in line first
+async fn list_book_holds(
+    state: State<AppState>,
+    auth_user: AuthUser,
+    Path(book_id): Path<Uuid>,
+) -> Result<Json<Vec<Hold>>, Error> {
+    tracing::debug!("GET /books/:id/holds for book_id {:?}", book_id);
+
+    // The queue shows who is waiting for the book, which only staff may see
+    auth_user.require_permission(Permission::CirculationStaff)?;
+
+    if !state.catalog.is_book_exists(book_id).unwrap() {
+        return Err(Error::not_found());
+    }
+
+    state
+        .library
+        .expire_holds_of_book(book_id, state.library_settings.hold_pickup_days)
+        .unwrap();
+
+    match state.library.list_holds_of_book(book_id) {
+        Ok(holds) => Ok(Json(holds)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Joins the back of the hold queue of a book that has no copies on the shelf
+async fn place_hold(
+    state: State<AppState>,
+    auth_user: AuthUser,
+    Path(book_id): Path<Uuid>,
+    Json(payload): Json<PlaceHoldRequest>,
+) -> Result<Json<Hold>, Error> {
+    let user_id = auth_user.acting_for(payload.user_id)?;
+
+    tracing::debug!(
+        "POST /books/:id/holds for user_id {:?} and book_id {:?}",
+        user_id,
+        book_id
+    );
+
+    // Check existence of book_id
+    if !state.catalog.is_book_exists(book_id).unwrap() {
+        return Err(Error::bad_request(LibraryError::BookNotExists.to_string()));
+    }
+
+    // Check that the user exists and may hold books
+    check_user_is_active(&state, user_id)?;
+
+    state
+        .library
+        .expire_holds_of_book(book_id, state.library_settings.hold_pickup_days)
+        .unwrap();
+
+    if state.library.get_hold_of_user(book_id, user_id).is_ok() {
+        return Err(Error::bad_request(
+            LibraryError::HoldAlreadyPlaced.to_string(),
+        ));
+    }
+
+    if state
+        .library
+        .get_active_borrow_entry(book_id, user_id, None)
+        .is_ok()
+    {
+        return Err(Error::bad_request(
+            LibraryError::BookAlreadyBorrowedByUser.to_string(),
+        ));
+    }
+
+    // Holds are only needed when there is nothing on the shelf to borrow
+    if state.library.get_num_items_of_book(book_id).unwrap() == 0 {
+        return Err(Error::bad_request(
+            LibraryError::BookHasNoCopies.to_string(),
+        ));
+    }
+
+    if state
+        .library
+        .get_num_items_of_book_with_status(book_id, ItemStatus::Available)
+        .unwrap()
+        > 0
+    {
+        return Err(Error::bad_request(LibraryError::BookAvailable.to_string()));
+    }
+
+    let hold = Hold {
+        id: Uuid::new_v4(),
+        book_id,
+        user_id,
+        item_id: None,
+        status: HoldStatus::Waiting,
+        created_at: Utc::now(),
+        expires_at: None,
+    };
+
+    match state.library.add_hold(hold) {
+        Ok(hold) => Ok(Json(hold)),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+async fn cancel_hold(
+    state: State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, Error> {
+    tracing::debug!("DELETE /holds with id: {:?}", id);
+
+    let hold = match state.library.get_hold(id) {
+        Ok(hold) => hold,
+        Err(err) => {
+            tracing::warn!("{}", err);
+            return Err(Error::not_found());
+        }
+    };
+
+    auth_user.acting_for(Some(hold.user_id))?;
+
+    let hold_pickup_days = state.library_settings.hold_pickup_days;
+
+    match state.library.delete_hold(hold, hold_pickup_days) {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
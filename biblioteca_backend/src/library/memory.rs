@@ -0,0 +1,685 @@
+use chrono::{prelude::*, Duration};
+use uuid::Uuid;
+
+use crate::{
+    audit::{
+        context::AuditContext,
+        model::{AuditAction, AuditEntry, EntityType},
+    },
+    catalog::model::{Item, ItemStatus},
+    query::{ListParams, Page},
+    repository::{
+        memory::{paginate, MemoryData, UserRoleLink},
+        MemoryRepository, RepositoryError, Result,
+    },
+    users::model::UserRole,
+};
+
+use super::{
+    model::{
+        BookBorrowEntry, BookBorrowState, FineEntry, FineEntryKind, Hold, HoldStatus, Loan,
+        LoanFilters, LoanRecord, LoanStatus,
+    },
+    repository::LibraryRepository,
+};
+
+impl LibraryRepository for MemoryRepository {
+    fn add_borrow_entry(
+        &self,
+        user_id: Uuid,
+        book_id: Uuid,
+        item_id: Uuid,
+        loan_period_days: u32,
+    ) -> Result<Loan> {
+        let mut data = self.data();
+
+        let borrowed_at = Utc::now();
+        let loan = Loan {
+            id: Uuid::new_v4(),
+            book_id,
+            item_id,
+            user_id,
+            borrowed_at,
+            due_at: borrowed_at + Duration::days(loan_period_days.into()),
+            renewals: 0,
+        };
+
+        data.borrow_entries.push(BookBorrowEntry {
+            id: loan.id,
+            book_id,
+            item_id,
+            user_id,
+            timestamp: loan.borrowed_at,
+            due_at: loan.due_at,
+            renewals: 0,
+            state: BookBorrowState::Borrowed,
+        });
+
+        set_item_status(&mut data, item_id, ItemStatus::Borrowed);
+
+        // Borrowing the book fulfils any hold the user had on it
+        if let Some(index) = data
+            .holds
+            .iter()
+            .position(|hold| hold.book_id == book_id && hold.user_id == user_id)
+        {
+            let hold = data.holds.remove(index);
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Delete,
+                EntityType::Hold,
+                hold.id,
+                Some(&hold),
+                None,
+            ));
+        }
+
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Borrow,
+            EntityType::Loan,
+            loan.id,
+            None,
+            Some(&loan),
+        ));
+
+        Ok(loan)
+    }
+
+    fn add_return_entry(
+        &self,
+        borrow_entry: BookBorrowEntry,
+        hold_pickup_days: u32,
+        fine_cents: i64,
+    ) -> Result<()> {
+        let mut data = self.data();
+
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Return,
+            EntityType::Loan,
+            borrow_entry.id,
+            Some(&Loan::from(&borrow_entry)),
+            None,
+        ));
+
+        let (loan_id, user_id, book_id, item_id) = (
+            borrow_entry.id,
+            borrow_entry.user_id,
+            borrow_entry.book_id,
+            borrow_entry.item_id,
+        );
+
+        data.borrow_entries.push(BookBorrowEntry {
+            timestamp: Utc::now(),
+            state: BookBorrowState::Returned,
+            ..borrow_entry
+        });
+
+        pass_item_to_next_hold(&mut data, book_id, item_id, hold_pickup_days);
+
+        // Overdue returns accrue their fine along with the return itself
+        if fine_cents > 0 {
+            let fine = FineEntry {
+                id: Uuid::new_v4(),
+                user_id,
+                loan_id: Some(loan_id),
+                kind: FineEntryKind::Fine,
+                amount_cents: fine_cents,
+                reason: None,
+                created_at: Utc::now(),
+            };
+
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Create,
+                EntityType::Fine,
+                fine.id,
+                None,
+                Some(&fine),
+            ));
+            data.fines.push(fine);
+        }
+
+        Ok(())
+    }
+
+    fn renew_borrow_entry(
+        &self,
+        borrow_entry: BookBorrowEntry,
+        loan_period_days: u32,
+    ) -> Result<Loan> {
+        let mut data = self.data();
+
+        let before = Loan::from(&borrow_entry);
+        let loan = Loan {
+            due_at: borrow_entry.due_at.max(Utc::now()) + Duration::days(loan_period_days.into()),
+            renewals: borrow_entry.renewals + 1,
+            ..Loan::from(&borrow_entry)
+        };
+
+        for entry in data.borrow_entries.iter_mut() {
+            if entry.id == loan.id && matches!(entry.state, BookBorrowState::Borrowed) {
+                entry.due_at = loan.due_at;
+                entry.renewals = loan.renewals;
+            }
+        }
+
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Renew,
+            EntityType::Loan,
+            loan.id,
+            Some(&before),
+            Some(&loan),
+        ));
+
+        Ok(loan)
+    }
+
+    fn get_active_borrow_entry(
+        &self,
+        book_id: Uuid,
+        user_id: Uuid,
+        item_id: Option<Uuid>,
+    ) -> Result<BookBorrowEntry> {
+        let data = self.data();
+
+        active_borrow_entries(&data)
+            .filter(|entry| {
+                entry.book_id == book_id
+                    && entry.user_id == user_id
+                    && item_id.is_none_or(|item_id| entry.item_id == item_id)
+            })
+            .min_by_key(|entry| entry.timestamp)
+            .cloned()
+            .ok_or(RepositoryError::NotFound)
+    }
+
+    fn list_loan_history_of_user(
+        &self,
+        user_id: Uuid,
+        filters: &LoanFilters,
+        params: &ListParams,
+    ) -> Result<Page<LoanRecord>> {
+        Ok(list_loan_history(
+            &self.data(),
+            |entry| entry.user_id == user_id,
+            filters,
+            params,
+        ))
+    }
+
+    fn list_loan_history_of_book(
+        &self,
+        book_id: Uuid,
+        filters: &LoanFilters,
+        params: &ListParams,
+    ) -> Result<Page<LoanRecord>> {
+        Ok(list_loan_history(
+            &self.data(),
+            |entry| entry.book_id == book_id,
+            filters,
+            params,
+        ))
+    }
+
+    fn get_num_borrowed(&self, user_id: Uuid) -> Result<u32> {
+        let data = self.data();
+
+        Ok(active_borrow_entries(&data)
+            .filter(|entry| entry.user_id == user_id)
+            .count() as u32)
+    }
+
+    fn get_num_borrowed_of_book(&self, book_id: Uuid) -> Result<u32> {
+        let data = self.data();
+
+        Ok(active_borrow_entries(&data)
+            .filter(|entry| entry.book_id == book_id)
+            .count() as u32)
+    }
+
+    fn get_num_user_can_borrow(&self, user_id: Uuid) -> Result<u32> {
+        Ok(active_user_roles_of_user(&self.data(), user_id, Utc::now())
+            .map(|user_role| user_role.num_borrowable_books as u32)
+            .max()
+            .unwrap_or(0))
+    }
+
+    fn get_max_num_borrowed_of_user_role(&self, user_role_id: Uuid) -> Result<u32> {
+        let data = self.data();
+        let now = Utc::now();
+
+        Ok(data
+            .user_role_links
+            .iter()
+            .filter(|link| link.user_role_id == user_role_id && is_link_active(link, now))
+            .map(|link| {
+                let num_borrowed = active_borrow_entries(&data)
+                    .filter(|entry| entry.user_id == link.user_id)
+                    .count() as u32;
+                let num_other_roles_allow = active_user_roles_of_user(&data, link.user_id, now)
+                    .filter(|user_role| user_role.id != user_role_id)
+                    .map(|user_role| user_role.num_borrowable_books as u32)
+                    .max()
+                    .unwrap_or(0);
+
+                if num_borrowed > num_other_roles_allow {
+                    num_borrowed
+                } else {
+                    0
+                }
+            })
+            .max()
+            .unwrap_or(0))
+    }
+
+    fn get_loan_period_of_user(&self, user_id: Uuid) -> Result<u32> {
+        Ok(active_user_roles_of_user(&self.data(), user_id, Utc::now())
+            .map(|user_role| user_role.loan_period_days as u32)
+            .max()
+            .unwrap_or(0))
+    }
+
+    fn get_max_renewals_of_user(&self, user_id: Uuid) -> Result<u32> {
+        Ok(active_user_roles_of_user(&self.data(), user_id, Utc::now())
+            .map(|user_role| user_role.max_renewals as u32)
+            .max()
+            .unwrap_or(0))
+    }
+
+    fn get_fine_rates_of_user(&self, user_id: Uuid) -> Result<(u32, u32)> {
+        let data = self.data();
+
+        Ok(active_user_roles_of_user(&data, user_id, Utc::now())
+            .map(|user_role| {
+                (
+                    user_role.fine_per_day_cents as u32,
+                    user_role.fine_cap_cents as u32,
+                )
+            })
+            .min()
+            .unwrap_or((0, 0)))
+    }
+
+    fn get_available_item_of_book(&self, book_id: Uuid) -> Result<Item> {
+        self.data()
+            .items
+            .iter()
+            .filter(|item| item.book_id == book_id && item.status == ItemStatus::Available)
+            .min_by(|a, b| a.barcode.cmp(&b.barcode))
+            .cloned()
+            .ok_or(RepositoryError::NotFound)
+    }
+
+    fn get_num_items_of_book(&self, book_id: Uuid) -> Result<u32> {
+        Ok(self
+            .data()
+            .items
+            .iter()
+            .filter(|item| item.book_id == book_id && item.status != ItemStatus::Withdrawn)
+            .count() as u32)
+    }
+
+    fn get_num_items_of_book_with_status(&self, book_id: Uuid, status: ItemStatus) -> Result<u32> {
+        Ok(self
+            .data()
+            .items
+            .iter()
+            .filter(|item| item.book_id == book_id && item.status == status)
+            .count() as u32)
+    }
+
+    fn add_hold(&self, hold: Hold) -> Result<Hold> {
+        let mut data = self.data();
+
+        data.holds.push(hold.clone());
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Create,
+            EntityType::Hold,
+            hold.id,
+            None,
+            Some(&hold),
+        ));
+
+        Ok(hold)
+    }
+
+    fn list_holds_of_book(&self, book_id: Uuid) -> Result<Vec<Hold>> {
+        let mut holds: Vec<Hold> = self
+            .data()
+            .holds
+            .iter()
+            .filter(|hold| hold.book_id == book_id)
+            .cloned()
+            .collect();
+
+        holds.sort_by_key(|hold| hold.created_at);
+
+        Ok(holds)
+    }
+
+    fn get_hold(&self, id: Uuid) -> Result<Hold> {
+        self.data()
+            .holds
+            .iter()
+            .find(|hold| hold.id == id)
+            .cloned()
+            .ok_or(RepositoryError::NotFound)
+    }
+
+    fn get_hold_of_user(&self, book_id: Uuid, user_id: Uuid) -> Result<Hold> {
+        self.data()
+            .holds
+            .iter()
+            .find(|hold| hold.book_id == book_id && hold.user_id == user_id)
+            .cloned()
+            .ok_or(RepositoryError::NotFound)
+    }
+
+    fn delete_hold(&self, hold: Hold, hold_pickup_days: u32) -> Result<()> {
+        let mut data = self.data();
+
+        data.holds.retain(|existing| existing.id != hold.id);
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Delete,
+            EntityType::Hold,
+            hold.id,
+            Some(&hold),
+            None,
+        ));
+
+        if let Some(item_id) = hold.item_id {
+            pass_item_to_next_hold(&mut data, hold.book_id, item_id, hold_pickup_days);
+        }
+
+        Ok(())
+    }
+
+    fn expire_holds_of_book(&self, book_id: Uuid, hold_pickup_days: u32) -> Result<()> {
+        let mut data = self.data();
+
+        let now = Utc::now();
+        let mut expired_holds: Vec<Hold> = data
+            .holds
+            .iter()
+            .filter(|hold| {
+                hold.book_id == book_id
+                    && hold.status == HoldStatus::Ready
+                    && hold.expires_at.is_some_and(|expires_at| expires_at < now)
+            })
+            .cloned()
+            .collect();
+
+        expired_holds.sort_by_key(|hold| hold.created_at);
+
+        for hold in expired_holds {
+            data.holds.retain(|existing| existing.id != hold.id);
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Delete,
+                EntityType::Hold,
+                hold.id,
+                Some(&hold),
+                None,
+            ));
+
+            if let Some(item_id) = hold.item_id {
+                pass_item_to_next_hold(&mut data, hold.book_id, item_id, hold_pickup_days);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_book_held_by_others(&self, book_id: Uuid, user_id: Uuid) -> Result<bool> {
+        Ok(self
+            .data()
+            .holds
+            .iter()
+            .any(|hold| hold.book_id == book_id && hold.user_id != user_id))
+    }
+
+    fn is_book_awaited_by_others(&self, book_id: Uuid, user_id: Uuid) -> Result<bool> {
+        Ok(self.data().holds.iter().any(|hold| {
+            hold.book_id == book_id && hold.user_id != user_id && hold.status == HoldStatus::Waiting
+        }))
+    }
+
+    fn list_fine_entries_of_user(&self, user_id: Uuid) -> Result<Vec<FineEntry>> {
+        let mut entries: Vec<FineEntry> = self
+            .data()
+            .fines
+            .iter()
+            .filter(|entry| entry.user_id == user_id)
+            .cloned()
+            .collect();
+
+        entries.sort_by_key(|entry| entry.created_at);
+
+        Ok(entries)
+    }
+
+    fn add_fine_entry(&self, entry: FineEntry) -> Result<FineEntry> {
+        let mut data = self.data();
+
+        data.fines.push(entry.clone());
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Create,
+            EntityType::Fine,
+            entry.id,
+            None,
+            Some(&entry),
+        ));
+
+        Ok(entry)
+    }
+
+    fn get_fine_balance_of_user(&self, user_id: Uuid) -> Result<i64> {
+        Ok(self
+            .data()
+            .fines
+            .iter()
+            .filter(|entry| entry.user_id == user_id)
+            .map(|entry| match entry.kind {
+                FineEntryKind::Fine => entry.amount_cents,
+                FineEntryKind::Payment | FineEntryKind::Waiver => -entry.amount_cents,
+            })
+            .sum())
+    }
+}
+
+// Borrowed entries of the ledger matching the predicate, each paired with the time its copy was
+// returned, if it was
+fn list_loan_history(
+    data: &MemoryData,
+    predicate: impl Fn(&BookBorrowEntry) -> bool,
+    filters: &LoanFilters,
+    params: &ListParams,
+) -> Page<LoanRecord> {
+    let mut records: Vec<LoanRecord> = data
+        .borrow_entries
+        .iter()
+        .filter(|entry| matches!(entry.state, BookBorrowState::Borrowed) && predicate(entry))
+        .map(|entry| {
+            let returned_at = data
+                .borrow_entries
+                .iter()
+                .find(|other| {
+                    other.id == entry.id && matches!(other.state, BookBorrowState::Returned)
+                })
+                .map(|other| other.timestamp);
+
+            LoanRecord::new(Loan::from(entry), returned_at)
+        })
+        .filter(|record| match filters.status {
+            LoanStatus::Active => record.returned_at.is_none(),
+            LoanStatus::Returned => record.returned_at.is_some(),
+            LoanStatus::All => true,
+        })
+        .filter(|record| {
+            filters.from.is_none_or(|from| {
+                record
+                    .returned_at
+                    .is_none_or(|returned_at| returned_at >= from)
+            }) && filters.to.is_none_or(|to| record.borrowed_at < to)
+        })
+        .collect();
+
+    records.sort_by(|a, b| b.borrowed_at.cmp(&a.borrowed_at).then(a.id.cmp(&b.id)));
+
+    paginate(records, params, |a, b, field| match field {
+        "due_at" => a.due_at.cmp(&b.due_at),
+        _ => a.borrowed_at.cmp(&b.borrowed_at),
+    })
+}
+
+// Borrowed entries of the ledger that have not been returned yet
+fn active_borrow_entries(data: &MemoryData) -> impl Iterator<Item = &BookBorrowEntry> {
+    data.borrow_entries.iter().filter(|entry| {
+        matches!(entry.state, BookBorrowState::Borrowed)
+            && !data.borrow_entries.iter().any(|other| {
+                other.id == entry.id && matches!(other.state, BookBorrowState::Returned)
+            })
+    })
+}
+
+// Roles of the user that have not expired by the given time
+fn active_user_roles_of_user(
+    data: &MemoryData,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> impl Iterator<Item = &UserRole> {
+    data.user_role_links
+        .iter()
+        .filter(move |link| link.user_id == user_id && is_link_active(link, now))
+        .filter_map(move |link| {
+            data.user_roles
+                .iter()
+                .find(|user_role| user_role.id == link.user_role_id)
+        })
+}
+
+pub(crate) fn is_link_active(link: &UserRoleLink, now: DateTime<Utc>) -> bool {
+    link.expires_at.is_none_or(|expires_at| expires_at > now)
+}
+
+// Moves the copy to the status, auditing the change if it is not in that status already
+fn set_item_status(data: &mut MemoryData, item_id: Uuid, status: ItemStatus) {
+    let Some(item) = data
+        .items
+        .iter_mut()
+        .find(|item| item.id == item_id && item.status != status)
+    else {
+        return;
+    };
+
+    let before = item.clone();
+    item.status = status;
+
+    let after = item.clone();
+    data.audit_entries.push(AuditEntry::new(
+        &AuditContext::current(),
+        AuditAction::Update,
+        EntityType::Item,
+        item_id,
+        Some(&before),
+        Some(&after),
+    ));
+}
+
+// Cancels every hold of the user, passing the copies set aside for them on to the next patron
+pub(crate) fn cancel_holds_of_user(data: &mut MemoryData, user_id: Uuid, hold_pickup_days: u32) {
+    let mut holds: Vec<Hold> = data
+        .holds
+        .iter()
+        .filter(|hold| hold.user_id == user_id)
+        .cloned()
+        .collect();
+
+    holds.sort_by_key(|hold| hold.created_at);
+
+    for hold in holds {
+        data.holds.retain(|existing| existing.id != hold.id);
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Delete,
+            EntityType::Hold,
+            hold.id,
+            Some(&hold),
+            None,
+        ));
+
+        if let Some(item_id) = hold.item_id {
+            pass_item_to_next_hold(data, hold.book_id, item_id, hold_pickup_days);
+        }
+    }
+}
+
+// Cancels every hold of the book, putting the copies set aside for them back on the shelf
+pub(crate) fn cancel_holds_of_book(data: &mut MemoryData, book_id: Uuid) {
+    let mut holds: Vec<Hold> = data
+        .holds
+        .iter()
+        .filter(|hold| hold.book_id == book_id)
+        .cloned()
+        .collect();
+
+    holds.sort_by_key(|hold| hold.created_at);
+
+    for hold in holds {
+        data.holds.retain(|existing| existing.id != hold.id);
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Delete,
+            EntityType::Hold,
+            hold.id,
+            Some(&hold),
+            None,
+        ));
+
+        if let Some(item_id) = hold.item_id {
+            set_item_status(data, item_id, ItemStatus::Available);
+        }
+    }
+}
+
+// Sets the copy aside for the first patron waiting in the queue, otherwise puts it back on the shelf
+pub(crate) fn pass_item_to_next_hold(
+    data: &mut MemoryData,
+    book_id: Uuid,
+    item_id: Uuid,
+    hold_pickup_days: u32,
+) {
+    let next_hold = data
+        .holds
+        .iter_mut()
+        .filter(|hold| hold.book_id == book_id && hold.status == HoldStatus::Waiting)
+        .min_by_key(|hold| hold.created_at);
+
+    match next_hold {
+        Some(hold) => {
+            let before = hold.clone();
+            hold.status = HoldStatus::Ready;
+            hold.item_id = Some(item_id);
+            hold.expires_at = Some(Utc::now() + Duration::days(hold_pickup_days.into()));
+
+            let after = hold.clone();
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Update,
+                EntityType::Hold,
+                after.id,
+                Some(&before),
+                Some(&after),
+            ));
+
+            set_item_status(data, item_id, ItemStatus::OnHold);
+        }
+        None => set_item_status(data, item_id, ItemStatus::Available),
+    }
+}
@@ -1,5 +1,11 @@
 pub mod controller;
+pub mod fines;
+pub mod holds;
 pub mod model;
+pub mod repository;
 
-mod db;
-mod error;
+pub(crate) mod db;
+pub(crate) mod error;
+pub(crate) mod memory;
+#[cfg(feature = "postgres")]
+pub(crate) mod postgres;
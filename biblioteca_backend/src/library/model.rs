@@ -5,10 +5,10 @@ use rusqlite::{
     types::{FromSql, FromSqlError, FromSqlResult, ValueRef},
     ToSql,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone)]
 pub enum BookBorrowState {
     Borrowed,
     Returned,
@@ -50,15 +50,284 @@ impl FromSql for BookBorrowState {
     }
 }
 
+#[derive(Clone)]
 pub struct BookBorrowEntry {
     pub id: Uuid,
     pub book_id: Uuid,
+    pub item_id: Uuid,
     pub user_id: Uuid,
     pub timestamp: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewals: u32,
     pub state: BookBorrowState,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Loan {
+    pub id: Uuid,
+    pub book_id: Uuid,
+    pub item_id: Uuid,
+    pub user_id: Uuid,
+    pub borrowed_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewals: u32,
+}
+
+impl From<&BookBorrowEntry> for Loan {
+    fn from(entry: &BookBorrowEntry) -> Self {
+        Loan {
+            id: entry.id,
+            book_id: entry.book_id,
+            item_id: entry.item_id,
+            user_id: entry.user_id,
+            borrowed_at: entry.timestamp,
+            due_at: entry.due_at,
+            renewals: entry.renewals,
+        }
+    }
+}
+
+// Loan paired with the return of its copy, if it was returned
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LoanRecord {
+    pub id: Uuid,
+    pub book_id: Uuid,
+    pub item_id: Uuid,
+    pub user_id: Uuid,
+    pub borrowed_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewals: u32,
+    pub returned_at: Option<DateTime<Utc>>,
+    // Whole days the copy was out for, counting up to now while it is still on loan
+    pub duration_days: i64,
+}
+
+impl LoanRecord {
+    pub fn new(loan: Loan, returned_at: Option<DateTime<Utc>>) -> Self {
+        let duration_days = (returned_at.unwrap_or_else(Utc::now) - loan.borrowed_at).num_days();
+
+        LoanRecord {
+            id: loan.id,
+            book_id: loan.book_id,
+            item_id: loan.item_id,
+            user_id: loan.user_id,
+            borrowed_at: loan.borrowed_at,
+            due_at: loan.due_at,
+            renewals: loan.renewals,
+            returned_at,
+            duration_days,
+        }
+    }
+}
+
+// Loans listed in a loan history, by whether their copies were returned
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LoanStatus {
+    #[default]
+    Active,
+    Returned,
+    All,
+}
+
+impl FromStr for LoanStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(LoanStatus::Active),
+            "returned" => Ok(LoanStatus::Returned),
+            "all" => Ok(LoanStatus::All),
+            _ => Err(format!("{} is not a known value", s)),
+        }
+    }
+}
+
+// Narrows down a loan history, where the date range keeps the loans whose copies were out at
+// any time during it
+#[derive(Debug, Default)]
+pub struct LoanFilters {
+    pub status: LoanStatus,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct BorrowBookRequest {
+    // User to act for, which is the authenticated user unless circulation staff give another
+    #[serde(default)]
+    pub user_id: Option<Uuid>,
+
+    // Specific copy to borrow or return, otherwise any suitable copy of the book is used
+    #[serde(default)]
+    pub item_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HoldStatus {
+    Waiting,
+    Ready,
+}
+
+impl Display for HoldStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HoldStatus::Waiting => write!(f, "waiting"),
+            HoldStatus::Ready => write!(f, "ready"),
+        }
+    }
+}
+
+impl ToSql for HoldStatus {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(self.to_string().into())
+    }
+}
+
+impl FromStr for HoldStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "waiting" => Ok(HoldStatus::Waiting),
+            "ready" => Ok(HoldStatus::Ready),
+            _ => Err(format!("{} is not a known value", s)),
+        }
+    }
+}
+
+impl FromSql for HoldStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|_| FromSqlError::InvalidType)
+    }
+}
+
+// A patron's place in the queue for a book; once ready, a copy is set aside until it expires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hold {
+    pub id: Uuid,
+    pub book_id: Uuid,
     pub user_id: Uuid,
+    pub item_id: Option<Uuid>,
+    pub status: HoldStatus,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlaceHoldRequest {
+    // User to place the hold for, which is the authenticated user unless staff give another
+    #[serde(default)]
+    pub user_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FineEntryKind {
+    Fine,
+    Payment,
+    Waiver,
+}
+
+impl Display for FineEntryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FineEntryKind::Fine => write!(f, "fine"),
+            FineEntryKind::Payment => write!(f, "payment"),
+            FineEntryKind::Waiver => write!(f, "waiver"),
+        }
+    }
+}
+
+impl ToSql for FineEntryKind {
+    fn to_sql(&self) -> rusqlite::Result<rusqlite::types::ToSqlOutput<'_>> {
+        Ok(self.to_string().into())
+    }
+}
+
+impl FromStr for FineEntryKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fine" => Ok(FineEntryKind::Fine),
+            "payment" => Ok(FineEntryKind::Payment),
+            "waiver" => Ok(FineEntryKind::Waiver),
+            _ => Err(format!("{} is not a known value", s)),
+        }
+    }
+}
+
+impl FromSql for FineEntryKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|_| FromSqlError::InvalidType)
+    }
+}
+
+// A line in a user's fines ledger; fines add to the balance, payments and waivers settle it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FineEntry {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub loan_id: Option<Uuid>,
+    pub kind: FineEntryKind,
+    pub amount_cents: i64,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FineSummary {
+    pub balance_cents: i64,
+    pub entries: Vec<FineEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PaymentRequest {
+    pub amount_cents: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WaiverRequest {
+    pub amount_cents: i64,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LibrarySettings {
+    // Number of days a returned copy stays on the hold shelf for the next patron
+    pub hold_pickup_days: u32,
+
+    // Outstanding balance above which a user can no longer borrow books
+    pub max_outstanding_fines_cents: i64,
+
+    // Loan policies given to user roles that are created without their own
+    pub default_loan_period_days: i32,
+    pub default_max_renewals: i32,
+    pub default_fine_per_day_cents: i32,
+    pub default_fine_cap_cents: i32,
+
+    // Number of days deleted books, authors and users can still be restored before they are purged
+    pub deleted_retention_days: u32,
+}
+
+impl Default for LibrarySettings {
+    fn default() -> Self {
+        LibrarySettings {
+            hold_pickup_days: 3,
+            max_outstanding_fines_cents: 500,
+            default_loan_period_days: 14,
+            default_max_renewals: 2,
+            default_fine_per_day_cents: 0,
+            default_fine_cap_cents: 0,
+            deleted_retention_days: 30,
+        }
+    }
 }
@@ -0,0 +1,965 @@
+use chrono::{prelude::*, Duration};
+use postgres::{Client, GenericClient, Row};
+use uuid::Uuid;
+
+use crate::{
+    audit::{
+        context::AuditContext,
+        model::{AuditAction, AuditEntry, EntityType},
+        postgres::add_audit_entry,
+    },
+    catalog::model::{Item, ItemStatus},
+    query::{ListParams, Page},
+    repository::{postgres::QueryBuilder, PostgresRepository, RepositoryError, Result},
+};
+
+use super::{
+    model::{
+        BookBorrowEntry, BookBorrowState, FineEntry, FineEntryKind, Hold, HoldStatus, Loan,
+        LoanFilters, LoanRecord, LoanStatus,
+    },
+    repository::LibraryRepository,
+};
+
+const LOAN_SORT_COLUMNS: &[(&str, &str)] =
+    &[("borrowed_at", "a.timestamp"), ("due_at", "a.due_at")];
+
+const HOLD_COLUMNS: &str = "id, book_id, user_id, item_id, status, created_at, expires_at";
+
+impl LibraryRepository for PostgresRepository {
+    fn add_borrow_entry(
+        &self,
+        user_id: Uuid,
+        book_id: Uuid,
+        item_id: Uuid,
+        loan_period_days: u32,
+    ) -> Result<Loan> {
+        // Read before handing over to the client, which runs outside of the request task
+        let context = AuditContext::current();
+
+        let borrowed_at = Utc::now();
+        let loan = Loan {
+            id: Uuid::new_v4(),
+            book_id,
+            item_id,
+            user_id,
+            borrowed_at,
+            due_at: borrowed_at + Duration::days(loan_period_days.into()),
+            renewals: 0,
+        };
+
+        self.with_client(|client| {
+            // Use transaction to keep the ledger and the copy's status in sync
+            let mut tx = client.transaction()?;
+
+            tx.execute(
+                "INSERT INTO map_users_to_borrowed_books (id, user_id, book_id, item_id, timestamp, due_at, action) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                &[&loan.id, &loan.user_id, &loan.book_id, &loan.item_id, &loan.borrowed_at, &loan.due_at, &BookBorrowState::Borrowed],
+            )?;
+
+            set_item_status(&mut tx, &context, item_id, ItemStatus::Borrowed)?;
+
+            // Borrowing the book fulfils any hold the user had on it
+            let fulfilled_hold = tx
+                .query_opt(
+                    &format!(
+                        "SELECT {} FROM holds WHERE book_id = $1 AND user_id = $2",
+                        HOLD_COLUMNS
+                    ),
+                    &[&book_id, &user_id],
+                )?
+                .map(|row| hold_from_row(&row))
+                .transpose()?;
+
+            if let Some(hold) = fulfilled_hold {
+                tx.execute("DELETE FROM holds WHERE id = $1", &[&hold.id])?;
+                add_audit_entry(
+                    &mut tx,
+                    &AuditEntry::new(
+                        &context,
+                        AuditAction::Delete,
+                        EntityType::Hold,
+                        hold.id,
+                        Some(&hold),
+                        None,
+                    ),
+                )?;
+            }
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Borrow,
+                    EntityType::Loan,
+                    loan.id,
+                    None,
+                    Some(&loan),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(loan)
+        })
+    }
+
+    fn add_return_entry(
+        &self,
+        borrow_entry: BookBorrowEntry,
+        hold_pickup_days: u32,
+        fine_cents: i64,
+    ) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            // Use transaction to keep the ledger and the copy's status in sync
+            let mut tx = client.transaction()?;
+
+            tx.execute(
+                "INSERT INTO map_users_to_borrowed_books (id, user_id, book_id, item_id, timestamp, due_at, renewals, action) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+                &[
+                    &borrow_entry.id,
+                    &borrow_entry.user_id,
+                    &borrow_entry.book_id,
+                    &borrow_entry.item_id,
+                    &Utc::now(),
+                    &borrow_entry.due_at,
+                    &(borrow_entry.renewals as i32),
+                    &BookBorrowState::Returned,
+                ],
+            )?;
+
+            pass_item_to_next_hold(
+                &mut tx,
+                &context,
+                borrow_entry.book_id,
+                borrow_entry.item_id,
+                hold_pickup_days,
+            )?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Return,
+                    EntityType::Loan,
+                    borrow_entry.id,
+                    Some(&Loan::from(&borrow_entry)),
+                    None,
+                ),
+            )?;
+
+            // Overdue returns accrue their fine along with the return itself
+            if fine_cents > 0 {
+                let fine = FineEntry {
+                    id: Uuid::new_v4(),
+                    user_id: borrow_entry.user_id,
+                    loan_id: Some(borrow_entry.id),
+                    kind: FineEntryKind::Fine,
+                    amount_cents: fine_cents,
+                    reason: None,
+                    created_at: Utc::now(),
+                };
+
+                insert_fine_entry(&mut tx, &fine)?;
+
+                add_audit_entry(
+                    &mut tx,
+                    &AuditEntry::new(
+                        &context,
+                        AuditAction::Create,
+                        EntityType::Fine,
+                        fine.id,
+                        None,
+                        Some(&fine),
+                    ),
+                )?;
+            }
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn renew_borrow_entry(
+        &self,
+        borrow_entry: BookBorrowEntry,
+        loan_period_days: u32,
+    ) -> Result<Loan> {
+        let context = AuditContext::current();
+
+        let before = Loan::from(&borrow_entry);
+        let loan = Loan {
+            due_at: borrow_entry.due_at.max(Utc::now()) + Duration::days(loan_period_days.into()),
+            renewals: borrow_entry.renewals + 1,
+            ..Loan::from(&borrow_entry)
+        };
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            tx.execute(
+                "UPDATE map_users_to_borrowed_books SET due_at = $1, renewals = $2 WHERE id = $3 AND action = $4",
+                &[&loan.due_at, &(loan.renewals as i32), &loan.id, &BookBorrowState::Borrowed],
+            )?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Renew,
+                    EntityType::Loan,
+                    loan.id,
+                    Some(&before),
+                    Some(&loan),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(loan)
+        })
+    }
+
+    fn get_active_borrow_entry(
+        &self,
+        book_id: Uuid,
+        user_id: Uuid,
+        item_id: Option<Uuid>,
+    ) -> Result<BookBorrowEntry> {
+        self.with_client(|client| {
+            let row = client
+                .query_opt(
+                    "SELECT a.id, a.user_id, a.book_id, a.item_id, a.timestamp, a.due_at, a.renewals, a.action FROM map_users_to_borrowed_books a
+                    WHERE a.action = 'Borrowed'
+                    AND a.book_id = $1
+                    AND a.user_id = $2
+                    AND ($3::UUID IS NULL OR a.item_id = $3)
+                    AND a.id NOT IN (SELECT b.id FROM map_users_to_borrowed_books b WHERE b.action = 'Returned')
+                    ORDER BY a.timestamp
+                    LIMIT 1",
+                    &[&book_id, &user_id, &item_id],
+                )?
+                .ok_or(RepositoryError::NotFound)?;
+
+            Ok(borrow_entry_from_row(&row)?)
+        })
+    }
+
+    fn list_loan_history_of_user(
+        &self,
+        user_id: Uuid,
+        filters: &LoanFilters,
+        params: &ListParams,
+    ) -> Result<Page<LoanRecord>> {
+        self.with_client(|client| list_loan_history(client, "a.user_id", user_id, filters, params))
+    }
+
+    fn list_loan_history_of_book(
+        &self,
+        book_id: Uuid,
+        filters: &LoanFilters,
+        params: &ListParams,
+    ) -> Result<Page<LoanRecord>> {
+        self.with_client(|client| list_loan_history(client, "a.book_id", book_id, filters, params))
+    }
+
+    fn get_num_borrowed(&self, user_id: Uuid) -> Result<u32> {
+        self.with_client(|client| {
+            let num_borrowed: i64 = client
+                .query_one(
+                    "SELECT COUNT(*) FROM map_users_to_borrowed_books a
+                    WHERE a.action = 'Borrowed'
+                    AND a.user_id = $1
+                    AND a.id NOT IN (SELECT b.id FROM map_users_to_borrowed_books b WHERE b.action = 'Returned')",
+                    &[&user_id],
+                )?
+                .try_get(0)?;
+
+            Ok(num_borrowed as u32)
+        })
+    }
+
+    fn get_num_borrowed_of_book(&self, book_id: Uuid) -> Result<u32> {
+        self.with_client(|client| {
+            let num_borrowed: i64 = client
+                .query_one(
+                    "SELECT COUNT(*) FROM map_users_to_borrowed_books a
+                    WHERE a.action = 'Borrowed'
+                    AND a.book_id = $1
+                    AND a.id NOT IN (SELECT b.id FROM map_users_to_borrowed_books b WHERE b.action = 'Returned')",
+                    &[&book_id],
+                )?
+                .try_get(0)?;
+
+            Ok(num_borrowed as u32)
+        })
+    }
+
+    fn get_num_user_can_borrow(&self, user_id: Uuid) -> Result<u32> {
+        self.aggregate_active_user_roles(user_id, "MAX(c.num_borrowable_books)")
+    }
+
+    fn get_max_num_borrowed_of_user_role(&self, user_role_id: Uuid) -> Result<u32> {
+        self.with_client(|client| {
+            let num_borrowed: i64 = client
+                .query_one(
+                    "SELECT COALESCE(MAX(loans.num_borrowed), 0) FROM (
+                        SELECT a.user_id, COUNT(*) AS num_borrowed FROM map_users_to_borrowed_books a
+                        JOIN map_users_to_user_roles b ON a.user_id = b.user_id
+                        WHERE a.action = 'Borrowed'
+                        AND b.user_role_id = $1
+                        AND (b.expires_at IS NULL OR b.expires_at > $2)
+                        AND a.id NOT IN (SELECT c.id FROM map_users_to_borrowed_books c WHERE c.action = 'Returned')
+                        GROUP BY a.user_id
+                    ) loans
+                    WHERE loans.num_borrowed > COALESCE((
+                        SELECT MAX(e.num_borrowable_books) FROM map_users_to_user_roles d
+                        JOIN user_roles e ON d.user_role_id = e.id
+                        WHERE d.user_id = loans.user_id
+                        AND d.user_role_id != $1
+                        AND (d.expires_at IS NULL OR d.expires_at > $2)
+                    ), 0)",
+                    &[&user_role_id, &Utc::now()],
+                )?
+                .try_get(0)?;
+
+            Ok(num_borrowed as u32)
+        })
+    }
+
+    fn get_loan_period_of_user(&self, user_id: Uuid) -> Result<u32> {
+        self.aggregate_active_user_roles(user_id, "MAX(c.loan_period_days)")
+    }
+
+    fn get_max_renewals_of_user(&self, user_id: Uuid) -> Result<u32> {
+        self.aggregate_active_user_roles(user_id, "MAX(c.max_renewals)")
+    }
+
+    fn get_fine_rates_of_user(&self, user_id: Uuid) -> Result<(u32, u32)> {
+        self.with_client(|client| {
+            let fine_rates = client
+                .query_opt(
+                    "SELECT c.fine_per_day_cents, c.fine_cap_cents FROM map_users_to_user_roles b
+                    JOIN user_roles c ON b.user_role_id = c.id
+                    WHERE b.user_id = $1 AND (b.expires_at IS NULL OR b.expires_at > $2)
+                    ORDER BY c.fine_per_day_cents, c.fine_cap_cents
+                    LIMIT 1",
+                    &[&user_id, &Utc::now()],
+                )?
+                .map(|row| -> Result<(u32, u32), postgres::Error> {
+                    Ok((
+                        row.try_get::<_, i32>(0)? as u32,
+                        row.try_get::<_, i32>(1)? as u32,
+                    ))
+                })
+                .transpose()?;
+
+            Ok(fine_rates.unwrap_or((0, 0)))
+        })
+    }
+
+    fn get_available_item_of_book(&self, book_id: Uuid) -> Result<Item> {
+        self.with_client(|client| {
+            let row = client
+                .query_opt(
+                    "SELECT id, book_id, barcode, shelf_location, condition, status FROM items
+                    WHERE book_id = $1 AND status = $2
+                    ORDER BY barcode COLLATE \"C\"
+                    LIMIT 1",
+                    &[&book_id, &ItemStatus::Available],
+                )?
+                .ok_or(RepositoryError::NotFound)?;
+
+            Ok(item_from_row(&row)?)
+        })
+    }
+
+    fn get_num_items_of_book(&self, book_id: Uuid) -> Result<u32> {
+        self.with_client(|client| {
+            let num_items: i64 = client
+                .query_one(
+                    "SELECT COUNT(*) FROM items WHERE book_id = $1 AND status != $2",
+                    &[&book_id, &ItemStatus::Withdrawn],
+                )?
+                .try_get(0)?;
+
+            Ok(num_items as u32)
+        })
+    }
+
+    fn get_num_items_of_book_with_status(&self, book_id: Uuid, status: ItemStatus) -> Result<u32> {
+        self.with_client(|client| {
+            let num_items: i64 = client
+                .query_one(
+                    "SELECT COUNT(*) FROM items WHERE book_id = $1 AND status = $2",
+                    &[&book_id, &status],
+                )?
+                .try_get(0)?;
+
+            Ok(num_items as u32)
+        })
+    }
+
+    fn add_hold(&self, hold: Hold) -> Result<Hold> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            tx.execute(
+                "INSERT INTO holds (id, book_id, user_id, item_id, status, created_at, expires_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                &[
+                    &hold.id,
+                    &hold.book_id,
+                    &hold.user_id,
+                    &hold.item_id,
+                    &hold.status,
+                    &hold.created_at,
+                    &hold.expires_at,
+                ],
+            )?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Create,
+                    EntityType::Hold,
+                    hold.id,
+                    None,
+                    Some(&hold),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(hold)
+        })
+    }
+
+    fn list_holds_of_book(&self, book_id: Uuid) -> Result<Vec<Hold>> {
+        self.with_client(|client| {
+            let holds = client
+                .query(
+                    &format!(
+                        "SELECT {} FROM holds WHERE book_id = $1 ORDER BY created_at",
+                        HOLD_COLUMNS
+                    ),
+                    &[&book_id],
+                )?
+                .iter()
+                .map(hold_from_row)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(holds)
+        })
+    }
+
+    fn get_hold(&self, id: Uuid) -> Result<Hold> {
+        self.with_client(|client| {
+            let row = client
+                .query_opt(
+                    &format!("SELECT {} FROM holds WHERE id = $1", HOLD_COLUMNS),
+                    &[&id],
+                )?
+                .ok_or(RepositoryError::NotFound)?;
+
+            Ok(hold_from_row(&row)?)
+        })
+    }
+
+    fn get_hold_of_user(&self, book_id: Uuid, user_id: Uuid) -> Result<Hold> {
+        self.with_client(|client| {
+            let row = client
+                .query_opt(
+                    &format!(
+                        "SELECT {} FROM holds WHERE book_id = $1 AND user_id = $2 LIMIT 1",
+                        HOLD_COLUMNS
+                    ),
+                    &[&book_id, &user_id],
+                )?
+                .ok_or(RepositoryError::NotFound)?;
+
+            Ok(hold_from_row(&row)?)
+        })
+    }
+
+    fn delete_hold(&self, hold: Hold, hold_pickup_days: u32) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            tx.execute("DELETE FROM holds WHERE id = $1", &[&hold.id])?;
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Delete,
+                    EntityType::Hold,
+                    hold.id,
+                    Some(&hold),
+                    None,
+                ),
+            )?;
+
+            if let Some(item_id) = hold.item_id {
+                pass_item_to_next_hold(&mut tx, &context, hold.book_id, item_id, hold_pickup_days)?;
+            }
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn expire_holds_of_book(&self, book_id: Uuid, hold_pickup_days: u32) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            let expired_holds = tx
+                .query(
+                    &format!(
+                        "SELECT {} FROM holds
+                        WHERE book_id = $1 AND status = $2 AND expires_at < $3
+                        ORDER BY created_at",
+                        HOLD_COLUMNS
+                    ),
+                    &[&book_id, &HoldStatus::Ready, &Utc::now()],
+                )?
+                .iter()
+                .map(hold_from_row)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for hold in expired_holds {
+                tx.execute("DELETE FROM holds WHERE id = $1", &[&hold.id])?;
+                add_audit_entry(
+                    &mut tx,
+                    &AuditEntry::new(
+                        &context,
+                        AuditAction::Delete,
+                        EntityType::Hold,
+                        hold.id,
+                        Some(&hold),
+                        None,
+                    ),
+                )?;
+
+                if let Some(item_id) = hold.item_id {
+                    pass_item_to_next_hold(
+                        &mut tx,
+                        &context,
+                        hold.book_id,
+                        item_id,
+                        hold_pickup_days,
+                    )?;
+                }
+            }
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn is_book_held_by_others(&self, book_id: Uuid, user_id: Uuid) -> Result<bool> {
+        self.with_client(|client| {
+            let count: i64 = client
+                .query_one(
+                    "SELECT COUNT(*) FROM holds WHERE book_id = $1 AND user_id != $2",
+                    &[&book_id, &user_id],
+                )?
+                .try_get(0)?;
+
+            Ok(count > 0)
+        })
+    }
+
+    fn is_book_awaited_by_others(&self, book_id: Uuid, user_id: Uuid) -> Result<bool> {
+        self.with_client(|client| {
+            let count: i64 = client
+                .query_one(
+                    "SELECT COUNT(*) FROM holds WHERE book_id = $1 AND user_id != $2 AND status = $3",
+                    &[&book_id, &user_id, &HoldStatus::Waiting],
+                )?
+                .try_get(0)?;
+
+            Ok(count > 0)
+        })
+    }
+
+    fn list_fine_entries_of_user(&self, user_id: Uuid) -> Result<Vec<FineEntry>> {
+        self.with_client(|client| {
+            let entries = client
+                .query(
+                    "SELECT id, user_id, loan_id, kind, amount_cents, reason, created_at FROM fines
+                    WHERE user_id = $1
+                    ORDER BY created_at",
+                    &[&user_id],
+                )?
+                .iter()
+                .map(|row| {
+                    Ok(FineEntry {
+                        id: row.try_get(0)?,
+                        user_id: row.try_get(1)?,
+                        loan_id: row.try_get(2)?,
+                        kind: row.try_get(3)?,
+                        amount_cents: row.try_get(4)?,
+                        reason: row.try_get(5)?,
+                        created_at: row.try_get(6)?,
+                    })
+                })
+                .collect::<Result<Vec<_>, postgres::Error>>()?;
+
+            Ok(entries)
+        })
+    }
+
+    fn add_fine_entry(&self, entry: FineEntry) -> Result<FineEntry> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            insert_fine_entry(&mut tx, &entry)?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Create,
+                    EntityType::Fine,
+                    entry.id,
+                    None,
+                    Some(&entry),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(entry)
+        })
+    }
+
+    fn get_fine_balance_of_user(&self, user_id: Uuid) -> Result<i64> {
+        self.with_client(|client| {
+            // Sums of BIGINT are NUMERIC, which is cast back to match the amounts
+            let balance_cents: i64 = client
+                .query_one(
+                    "SELECT COALESCE(SUM(CASE kind WHEN 'fine' THEN amount_cents ELSE -amount_cents END), 0)::BIGINT FROM fines
+                    WHERE user_id = $1",
+                    &[&user_id],
+                )?
+                .try_get(0)?;
+
+            Ok(balance_cents)
+        })
+    }
+}
+
+impl PostgresRepository {
+    // Aggregates a column over the roles of the user that have not expired, such as taking the
+    // largest of them, where a user without any such role has none of it
+    fn aggregate_active_user_roles(&self, user_id: Uuid, aggregate: &str) -> Result<u32> {
+        self.with_client(|client| {
+            let value: i32 = client
+                .query_one(
+                    &format!(
+                        "SELECT COALESCE({}, 0) FROM map_users_to_user_roles b
+                        JOIN user_roles c ON b.user_role_id = c.id
+                        WHERE b.user_id = $1 AND (b.expires_at IS NULL OR b.expires_at > $2)",
+                        aggregate
+                    ),
+                    &[&user_id, &Utc::now()],
+                )?
+                .try_get(0)?;
+
+            Ok(value as u32)
+        })
+    }
+}
+
+// Lists the loans whose user or book in the column is the one given, each joined with the
+// return of its copy, if there was one
+fn list_loan_history(
+    client: &mut Client,
+    column: &str,
+    id: Uuid,
+    filters: &LoanFilters,
+    params: &ListParams,
+) -> Result<Page<LoanRecord>> {
+    let mut query = QueryBuilder::new(
+        "SELECT a.id, a.book_id, a.item_id, a.user_id, a.timestamp, a.due_at, a.renewals, b.timestamp FROM map_users_to_borrowed_books a
+        LEFT JOIN map_users_to_borrowed_books b ON b.id = a.id AND b.action = 'Returned'",
+    );
+    query
+        .fixed_condition("a.action = 'Borrowed'")
+        .condition(&format!("{} = ?", column), id);
+    match filters.status {
+        LoanStatus::Active => {
+            query.fixed_condition("b.id IS NULL");
+        }
+        LoanStatus::Returned => {
+            query.fixed_condition("b.id IS NOT NULL");
+        }
+        LoanStatus::All => {}
+    }
+    if let Some(from) = filters.from {
+        query.condition("(b.timestamp IS NULL OR b.timestamp >= ?)", from);
+    }
+    if let Some(to) = filters.to {
+        query.condition("a.timestamp < ?", to);
+    }
+    query.paginate(params, LOAN_SORT_COLUMNS, "a.timestamp DESC, a.id");
+
+    let loans = client
+        .query(&query.sql(), &query.params())?
+        .iter()
+        .map(|row| {
+            let loan = Loan {
+                id: row.try_get(0)?,
+                book_id: row.try_get(1)?,
+                item_id: row.try_get(2)?,
+                user_id: row.try_get(3)?,
+                borrowed_at: row.try_get(4)?,
+                due_at: row.try_get(5)?,
+                renewals: row.try_get::<_, i32>(6)? as u32,
+            };
+
+            Ok(LoanRecord::new(loan, row.try_get(7)?))
+        })
+        .collect::<Result<Vec<_>, postgres::Error>>()?;
+
+    Ok(Page::new(loans, query.count(client)?, params))
+}
+
+fn insert_fine_entry(
+    client: &mut impl GenericClient,
+    entry: &FineEntry,
+) -> Result<(), postgres::Error> {
+    client.execute(
+        "INSERT INTO fines (id, user_id, loan_id, kind, amount_cents, reason, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        &[
+            &entry.id,
+            &entry.user_id,
+            &entry.loan_id,
+            &entry.kind,
+            &entry.amount_cents,
+            &entry.reason,
+            &entry.created_at,
+        ],
+    )?;
+
+    Ok(())
+}
+
+// Cancels every hold of the user, passing the copies set aside for them on to the next patron
+pub(crate) fn cancel_holds_of_user(
+    client: &mut impl GenericClient,
+    context: &AuditContext,
+    user_id: Uuid,
+    hold_pickup_days: u32,
+) -> Result<(), postgres::Error> {
+    let holds = client
+        .query(
+            &format!(
+                "SELECT {} FROM holds WHERE user_id = $1 ORDER BY created_at",
+                HOLD_COLUMNS
+            ),
+            &[&user_id],
+        )?
+        .iter()
+        .map(hold_from_row)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for hold in holds {
+        client.execute("DELETE FROM holds WHERE id = $1", &[&hold.id])?;
+        add_audit_entry(
+            client,
+            &AuditEntry::new(
+                context,
+                AuditAction::Delete,
+                EntityType::Hold,
+                hold.id,
+                Some(&hold),
+                None,
+            ),
+        )?;
+
+        if let Some(item_id) = hold.item_id {
+            pass_item_to_next_hold(client, context, hold.book_id, item_id, hold_pickup_days)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Cancels every hold of the book, putting the copies set aside for them back on the shelf
+pub(crate) fn cancel_holds_of_book(
+    client: &mut impl GenericClient,
+    context: &AuditContext,
+    book_id: Uuid,
+) -> Result<(), postgres::Error> {
+    let holds = client
+        .query(
+            &format!(
+                "SELECT {} FROM holds WHERE book_id = $1 ORDER BY created_at",
+                HOLD_COLUMNS
+            ),
+            &[&book_id],
+        )?
+        .iter()
+        .map(hold_from_row)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for hold in holds {
+        client.execute("DELETE FROM holds WHERE id = $1", &[&hold.id])?;
+        add_audit_entry(
+            client,
+            &AuditEntry::new(
+                context,
+                AuditAction::Delete,
+                EntityType::Hold,
+                hold.id,
+                Some(&hold),
+                None,
+            ),
+        )?;
+
+        if let Some(item_id) = hold.item_id {
+            set_item_status(client, context, item_id, ItemStatus::Available)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Sets the copy aside for the first patron waiting in the queue, otherwise puts it back on the shelf
+pub(crate) fn pass_item_to_next_hold(
+    client: &mut impl GenericClient,
+    context: &AuditContext,
+    book_id: Uuid,
+    item_id: Uuid,
+    hold_pickup_days: u32,
+) -> Result<(), postgres::Error> {
+    let next_hold = client
+        .query_opt(
+            &format!(
+                "SELECT {} FROM holds WHERE book_id = $1 AND status = $2 ORDER BY created_at LIMIT 1",
+                HOLD_COLUMNS
+            ),
+            &[&book_id, &HoldStatus::Waiting],
+        )?
+        .map(|row| hold_from_row(&row))
+        .transpose()?;
+
+    let Some(before) = next_hold else {
+        return set_item_status(client, context, item_id, ItemStatus::Available);
+    };
+
+    let hold = Hold {
+        item_id: Some(item_id),
+        status: HoldStatus::Ready,
+        expires_at: Some(Utc::now() + Duration::days(hold_pickup_days.into())),
+        ..before.clone()
+    };
+
+    client.execute(
+        "UPDATE holds SET status = $1, item_id = $2, expires_at = $3 WHERE id = $4",
+        &[&hold.status, &hold.item_id, &hold.expires_at, &hold.id],
+    )?;
+
+    add_audit_entry(
+        client,
+        &AuditEntry::new(
+            context,
+            AuditAction::Update,
+            EntityType::Hold,
+            hold.id,
+            Some(&before),
+            Some(&hold),
+        ),
+    )?;
+
+    set_item_status(client, context, item_id, ItemStatus::OnHold)
+}
+
+// Moves the copy to the status, auditing the change if it is not in that status already
+fn set_item_status(
+    client: &mut impl GenericClient,
+    context: &AuditContext,
+    item_id: Uuid,
+    status: ItemStatus,
+) -> Result<(), postgres::Error> {
+    let before = item_from_row(&client.query_one(
+        "SELECT id, book_id, barcode, shelf_location, condition, status FROM items WHERE id = $1",
+        &[&item_id],
+    )?)?;
+
+    if before.status == status {
+        return Ok(());
+    }
+
+    let item = Item {
+        status,
+        ..before.clone()
+    };
+
+    client.execute(
+        "UPDATE items SET status = $1 WHERE id = $2",
+        &[&item.status, &item.id],
+    )?;
+
+    add_audit_entry(
+        client,
+        &AuditEntry::new(
+            context,
+            AuditAction::Update,
+            EntityType::Item,
+            item.id,
+            Some(&before),
+            Some(&item),
+        ),
+    )
+}
+
+fn borrow_entry_from_row(row: &Row) -> Result<BookBorrowEntry, postgres::Error> {
+    Ok(BookBorrowEntry {
+        id: row.try_get(0)?,
+        user_id: row.try_get(1)?,
+        book_id: row.try_get(2)?,
+        item_id: row.try_get(3)?,
+        timestamp: row.try_get(4)?,
+        due_at: row.try_get(5)?,
+        renewals: row.try_get::<_, i32>(6)? as u32,
+        state: row.try_get(7)?,
+    })
+}
+
+fn item_from_row(row: &Row) -> Result<Item, postgres::Error> {
+    Ok(Item {
+        id: row.try_get(0)?,
+        book_id: row.try_get(1)?,
+        barcode: row.try_get(2)?,
+        shelf_location: row.try_get(3)?,
+        condition: row.try_get(4)?,
+        status: row.try_get(5)?,
+    })
+}
+
+fn hold_from_row(row: &Row) -> Result<Hold, postgres::Error> {
+    Ok(Hold {
+        id: row.try_get(0)?,
+        book_id: row.try_get(1)?,
+        user_id: row.try_get(2)?,
+        item_id: row.try_get(3)?,
+        status: row.try_get(4)?,
+        created_at: row.try_get(5)?,
+        expires_at: row.try_get(6)?,
+    })
+}
@@ -0,0 +1,125 @@
+use uuid::Uuid;
+
+use crate::{
+    catalog::model::{Item, ItemStatus},
+    query::{ListParams, ListSpec, Page},
+    repository::Result,
+};
+
+use super::model::{BookBorrowEntry, FineEntry, Hold, Loan, LoanFilters, LoanRecord};
+
+// Filters and sort fields supported when listing the loan history of a user or book
+pub const LOAN_LIST: ListSpec = ListSpec {
+    filters: &["status", "from", "to"],
+    sort_fields: &["borrowed_at", "due_at"],
+};
+
+// Storage of the loans, holds and fines of the library, along with the limits that the roles
+// of users place on them
+pub trait LibraryRepository: Send + Sync {
+    // Lends the copy to the user, fulfilling any hold they had on the book
+    fn add_borrow_entry(
+        &self,
+        user_id: Uuid,
+        book_id: Uuid,
+        item_id: Uuid,
+        loan_period_days: u32,
+    ) -> Result<Loan>;
+
+    // Records the return of a loan and any fine for it, passing the copy on to the next hold
+    fn add_return_entry(
+        &self,
+        borrow_entry: BookBorrowEntry,
+        hold_pickup_days: u32,
+        fine_cents: i64,
+    ) -> Result<()>;
+
+    // Extends the due date of an outstanding borrow by the loan period, counting from the later of
+    // its due date and now so that renewing early loses no days, and counts it as a renewal
+    fn renew_borrow_entry(
+        &self,
+        borrow_entry: BookBorrowEntry,
+        loan_period_days: u32,
+    ) -> Result<Loan>;
+
+    // Retrieves the user's outstanding borrow of the book, optionally of a specific copy
+    fn get_active_borrow_entry(
+        &self,
+        book_id: Uuid,
+        user_id: Uuid,
+        item_id: Option<Uuid>,
+    ) -> Result<BookBorrowEntry>;
+
+    // Lists the loans of the user along with their returns, most recently borrowed first
+    fn list_loan_history_of_user(
+        &self,
+        user_id: Uuid,
+        filters: &LoanFilters,
+        params: &ListParams,
+    ) -> Result<Page<LoanRecord>>;
+
+    // Lists the loans of copies of the book along with their returns, most recently borrowed
+    // first
+    fn list_loan_history_of_book(
+        &self,
+        book_id: Uuid,
+        filters: &LoanFilters,
+        params: &ListParams,
+    ) -> Result<Page<LoanRecord>>;
+
+    fn get_num_borrowed(&self, user_id: Uuid) -> Result<u32>;
+
+    fn get_num_borrowed_of_book(&self, book_id: Uuid) -> Result<u32>;
+
+    // Entitlements of a user combine those of their roles that have not expired, taking the
+    // largest allowance and the lowest fine rates of them. A user without any such role may not
+    // borrow at all.
+    fn get_num_user_can_borrow(&self, user_id: Uuid) -> Result<u32>;
+
+    // Retrieves the most outstanding loans that any one user given the role has, leaving out
+    // users whose other roles already allow them that many
+    fn get_max_num_borrowed_of_user_role(&self, user_role_id: Uuid) -> Result<u32>;
+
+    fn get_loan_period_of_user(&self, user_id: Uuid) -> Result<u32>;
+
+    fn get_max_renewals_of_user(&self, user_id: Uuid) -> Result<u32>;
+
+    // Retrieves the daily fine rate and the per-loan fine cap of the user, both taken from their
+    // unexpired role with the lowest daily rate, the lower cap breaking ties
+    fn get_fine_rates_of_user(&self, user_id: Uuid) -> Result<(u32, u32)>;
+
+    // Retrieves any copy of the book that is currently on the shelf
+    fn get_available_item_of_book(&self, book_id: Uuid) -> Result<Item>;
+
+    // Counts the copies of the book that have not been withdrawn
+    fn get_num_items_of_book(&self, book_id: Uuid) -> Result<u32>;
+
+    fn get_num_items_of_book_with_status(&self, book_id: Uuid, status: ItemStatus) -> Result<u32>;
+
+    fn add_hold(&self, hold: Hold) -> Result<Hold>;
+
+    // Retrieves the hold queue of a book, in the order it will be served
+    fn list_holds_of_book(&self, book_id: Uuid) -> Result<Vec<Hold>>;
+
+    fn get_hold(&self, id: Uuid) -> Result<Hold>;
+
+    fn get_hold_of_user(&self, book_id: Uuid, user_id: Uuid) -> Result<Hold>;
+
+    // Cancels a hold, passing any copy set aside for it on to the next patron
+    fn delete_hold(&self, hold: Hold, hold_pickup_days: u32) -> Result<()>;
+
+    // Drops ready holds whose pickup window has passed, passing their copies on to the next patron
+    fn expire_holds_of_book(&self, book_id: Uuid, hold_pickup_days: u32) -> Result<()>;
+
+    fn is_book_held_by_others(&self, book_id: Uuid, user_id: Uuid) -> Result<bool>;
+
+    // Checks whether patrons other than the user are still waiting in the queue for a copy
+    fn is_book_awaited_by_others(&self, book_id: Uuid, user_id: Uuid) -> Result<bool>;
+
+    fn list_fine_entries_of_user(&self, user_id: Uuid) -> Result<Vec<FineEntry>>;
+
+    fn add_fine_entry(&self, entry: FineEntry) -> Result<FineEntry>;
+
+    // Retrieves the amount the user still owes, i.e. fines less payments and waivers
+    fn get_fine_balance_of_user(&self, user_id: Uuid) -> Result<i64>;
+}
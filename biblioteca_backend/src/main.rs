@@ -1,30 +1,158 @@
-use std::net::SocketAddr;
+use std::{fs::File, path::PathBuf, process};
 
 use axum::extract::State;
+use clap::{Parser, Subcommand};
+use rusqlite::Connection;
 
-use crate::database::setup_db;
+use biblioteca_backend::{
+    admin::{
+        create_admin, purge_deleted,
+        seed::seed_library,
+        set_password,
+        transfer::{read_import, write_export, ImportSummary},
+    },
+    app::{self, AppState},
+    config::{Config, ConfigOverrides, LogConfig, LogFormat},
+    database::{backup_db, migrate_db, setup_db, MigrationError},
+};
+#[cfg(feature = "postgres")]
+use biblioteca_backend::{
+    database::{migrate_postgres_db, setup_postgres_db, Migration},
+    repository::postgres::run_blocking,
+};
 
-mod app;
-mod catalog;
-mod database;
-mod error;
-mod library;
-mod users;
+#[derive(Parser)]
+#[command(about = "Server of la-biblioteca, a library management system")]
+struct Cli {
+    #[command(flatten)]
+    config: ConfigOverrides,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Start the server, which is what runs when no command is given
+    Serve,
+
+    /// Apply the pending migrations of the database
+    Migrate {
+        /// List the pending migrations, checking that they apply without keeping them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Fill an empty database with demo data
+    Seed,
+
+    /// Add the catalog and users of an export that are not in the database yet
+    Import {
+        /// JSON file written by `export`
+        file: PathBuf,
+    },
+
+    /// Write the catalog and users of the database to a JSON file
+    Export {
+        /// File to write the export to
+        file: PathBuf,
+    },
+
+    /// Copy the SQLite database into a new file, while the server may be running
+    Backup {
+        /// File to write the backup to, which must not exist yet
+        file: PathBuf,
+    },
+
+    /// Create a user with the administrator role
+    CreateAdmin {
+        #[arg(long)]
+        username: String,
+
+        /// Password the administrator logs in with
+        #[arg(long, env = "BIBLIOTECA_PASSWORD", hide_env_values = true)]
+        password: String,
+    },
+
+    /// Set the password a user logs in with
+    SetPassword {
+        #[arg(long)]
+        username: String,
+
+        #[arg(long, env = "BIBLIOTECA_PASSWORD", hide_env_values = true)]
+        password: String,
+    },
+
+    /// Remove the books, authors and users deleted longer ago than the retention period
+    Purge,
+}
 
 #[tokio::main]
 async fn main() {
+    let cli = Cli::parse();
+
+    let config = match Config::load(&cli.config) {
+        Ok(config) => config,
+        Err(err) => exit_with_error(err),
+    };
+
     // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::DEBUG)
-        .init();
+    init_tracing(&config.log);
+
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => serve(config).await,
+        Command::Migrate { dry_run } => migrate(config, dry_run),
+        Command::Seed => {
+            let summary = seed_library(&create_state(config)).unwrap_or_else(exit_with_error);
+            print_summary("Added", &summary);
+        }
+        Command::Import { file } => {
+            let state = create_state(config);
+            let file = File::open(file).unwrap_or_else(exit_with_error);
+
+            let summary = read_import(&state, file).unwrap_or_else(exit_with_error);
+            print_summary("Imported", &summary);
+        }
+        Command::Export { file } => {
+            let state = create_state(config);
+            let file = File::create(file).unwrap_or_else(exit_with_error);
+
+            write_export(&state, file).unwrap_or_else(exit_with_error);
+        }
+        Command::Backup { file } => backup(config, file),
+        Command::CreateAdmin { username, password } => {
+            let user = create_admin(&create_state(config), &username, &password)
+                .unwrap_or_else(exit_with_error);
+            println!(
+                "Created administrator {} with id {}",
+                user.username, user.id
+            );
+        }
+        Command::SetPassword { username, password } => {
+            set_password(&create_state(config), &username, &password)
+                .unwrap_or_else(exit_with_error);
+            println!("Set password of {}", username);
+        }
+        Command::Purge => {
+            let summary = purge_deleted(&create_state(config)).unwrap_or_else(exit_with_error);
+            println!(
+                "Purged {} books, {} authors and {} users",
+                summary.books, summary.authors, summary.users
+            );
+        }
+    }
+}
+
+async fn serve(config: Config) {
+    let addr = config.server.bind_address;
 
-    let database_pool = setup_db(String::from("library.sqlite")).unwrap();
-    let state = app::create_new_state(database_pool);
+    if config.auth.token_secret.is_none() {
+        tracing::warn!("no token secret is configured, so logins will not survive a restart");
+    }
 
-    let app = app::create_app(State(state));
+    let app = app::create_app(State(create_state(config)));
 
-    // Run app using hyper, listens on port 3000
-    let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
+    // Run app using hyper, listening on the configured address
     tracing::debug!("la-biblioteca server listening on {}", addr);
 
     axum::Server::bind(&addr)
@@ -32,3 +160,92 @@ async fn main() {
         .await
         .unwrap();
 }
+
+// Connects to the configured database, applying its pending migrations
+fn create_state(config: Config) -> AppState {
+    let pool_size = config.database.pool_size;
+    let library_settings = config.library;
+    let auth_settings = config.auth;
+
+    match config.database.url {
+        #[cfg(feature = "postgres")]
+        Some(database_url) => {
+            let pool = setup_postgres_db(database_url, pool_size).unwrap_or_else(exit_with_error);
+            app::create_postgres_state(pool, library_settings, auth_settings)
+        }
+        _ => {
+            let pool = setup_db(config.database.path, pool_size).unwrap_or_else(exit_with_error);
+            app::create_new_state(pool, library_settings, auth_settings)
+        }
+    }
+}
+
+fn migrate(config: Config, dry_run: bool) {
+    let migrations = match config.database.url {
+        #[cfg(feature = "postgres")]
+        Some(database_url) => postgres_migrate(database_url, dry_run),
+        _ => Connection::open(&config.database.path)
+            .map_err(MigrationError::from)
+            .and_then(|mut conn| migrate_db(&mut conn, dry_run)),
+    }
+    .unwrap_or_else(exit_with_error);
+
+    if migrations.is_empty() {
+        println!("No pending migrations");
+    }
+
+    for migration in migrations {
+        println!("{}: {}", migration.version, migration.description);
+    }
+}
+
+fn backup(config: Config, file: PathBuf) {
+    if config.database.url.is_some() {
+        exit_with_error::<()>("backups of PostgreSQL databases are made with pg_dump");
+    }
+
+    let conn = Connection::open(&config.database.path).unwrap_or_else(exit_with_error);
+    backup_db(&conn, &file.to_string_lossy()).unwrap_or_else(exit_with_error);
+
+    println!("Backed up {} to {}", config.database.path, file.display());
+}
+
+fn print_summary(verb: &str, summary: &ImportSummary) {
+    println!(
+        "{} {} categories, {} authors, {} books, {} copies, {} user roles and {} users",
+        verb,
+        summary.categories,
+        summary.authors,
+        summary.books,
+        summary.items,
+        summary.user_roles,
+        summary.users
+    );
+}
+
+fn init_tracing(log: &LogConfig) {
+    let subscriber = tracing_subscriber::fmt().with_max_level(tracing::Level::from(log.level));
+
+    match log.format {
+        LogFormat::Full => subscriber.init(),
+        LogFormat::Compact => subscriber.compact().init(),
+        LogFormat::Json => subscriber.json().init(),
+    }
+}
+
+fn exit_with_error<T>(err: impl std::fmt::Display) -> T {
+    eprintln!("error: {}", err);
+    process::exit(1);
+}
+
+#[cfg(feature = "postgres")]
+fn postgres_migrate(
+    database_url: String,
+    dry_run: bool,
+) -> Result<Vec<&'static Migration>, MigrationError> {
+    run_blocking(move || {
+        let mut client = postgres::Client::connect(&database_url, postgres::NoTls)?;
+
+        migrate_postgres_db(&mut client, dry_run)
+    })
+}
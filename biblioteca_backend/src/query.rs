@@ -0,0 +1,214 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use axum::http::Uri;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_PAGE_LIMIT: u32 = 50;
+pub const MAX_PAGE_LIMIT: u32 = 200;
+
+#[derive(thiserror::Error, Debug)]
+pub enum QueryError {
+    UnknownFilter(String),
+    InvalidFilter(String),
+    UnknownSortField(String),
+    InvalidLimit,
+    InvalidOffset,
+    InvalidIncludeDeleted,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QueryError::UnknownFilter(key) => write!(f, "unknown filter parameter: {}", key),
+            QueryError::InvalidFilter(key) => {
+                write!(f, "invalid value of filter parameter: {}", key)
+            }
+            QueryError::UnknownSortField(field) => write!(f, "unknown sort field: {}", field),
+            QueryError::InvalidLimit => {
+                write!(f, "limit must be a number between 1 and {}", MAX_PAGE_LIMIT)
+            }
+            QueryError::InvalidOffset => write!(f, "offset must be a non-negative number"),
+            QueryError::InvalidIncludeDeleted => {
+                write!(f, "include_deleted must be either true or false")
+            }
+        }
+    }
+}
+
+// Describes what a list endpoint can be filtered and sorted by
+pub struct ListSpec {
+    pub filters: &'static [&'static str],
+    pub sort_fields: &'static [&'static str],
+}
+
+// A field to sort a list by, as given in its `sort` query param
+#[derive(Debug, Clone, Copy)]
+pub struct SortKey {
+    pub field: &'static str,
+    pub descending: bool,
+}
+
+// Query params of a list endpoint, split into its filters and the page requested
+#[derive(Debug)]
+pub struct ListParams {
+    pub filters: Filters,
+    pub sort: Vec<SortKey>,
+    pub limit: u32,
+    pub offset: u32,
+    path: String,
+    // Encoded query pairs of the request, kept when linking to other pages
+    query_pairs: Vec<String>,
+}
+
+impl ListParams {
+    pub fn parse(
+        uri: &Uri,
+        mut params: HashMap<String, String>,
+        spec: &ListSpec,
+    ) -> Result<Self, QueryError> {
+        let limit = match params.remove("limit") {
+            Some(limit) => match limit.parse::<u32>() {
+                Ok(limit) if (1..=MAX_PAGE_LIMIT).contains(&limit) => limit,
+                _ => return Err(QueryError::InvalidLimit),
+            },
+            None => DEFAULT_PAGE_LIMIT,
+        };
+
+        let offset = match params.remove("offset") {
+            Some(offset) => offset.parse::<u32>().or(Err(QueryError::InvalidOffset))?,
+            None => 0,
+        };
+
+        let mut sort = Vec::new();
+
+        if let Some(fields) = params.remove("sort") {
+            for field in fields.split(',') {
+                let (name, descending) = match field.strip_prefix('-') {
+                    Some(name) => (name, true),
+                    None => (field, false),
+                };
+
+                match spec.sort_fields.iter().find(|key| **key == name) {
+                    Some(field) => sort.push(SortKey { field, descending }),
+                    None => return Err(QueryError::UnknownSortField(field.to_string())),
+                }
+            }
+        }
+
+        let filters = Filters::parse(params, spec.filters)?;
+
+        if filters
+            .get("include_deleted")
+            .is_some_and(|value| value != "true" && value != "false")
+        {
+            return Err(QueryError::InvalidIncludeDeleted);
+        }
+
+        let query_pairs = uri
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter(|pair| {
+                !pair.is_empty() && !pair.starts_with("limit=") && !pair.starts_with("offset=")
+            })
+            .map(|pair| pair.to_string())
+            .collect();
+
+        Ok(ListParams {
+            filters,
+            sort,
+            limit,
+            offset,
+            path: uri.path().to_string(),
+            query_pairs,
+        })
+    }
+
+    // Params of a page of a list without filters or sorting, for listing outside of a request
+    pub fn page(limit: u32, offset: u32) -> Self {
+        ListParams {
+            filters: Filters::default(),
+            sort: Vec::new(),
+            limit,
+            offset,
+            path: String::new(),
+            query_pairs: Vec::new(),
+        }
+    }
+
+    // Checks whether the list should also have the records that were deleted but not purged yet
+    pub fn include_deleted(&self) -> bool {
+        self.filters
+            .get("include_deleted")
+            .is_some_and(|value| value == "true")
+    }
+
+    fn link(&self, offset: u32) -> String {
+        let mut pairs = self.query_pairs.clone();
+        pairs.push(format!("limit={}", self.limit));
+        pairs.push(format!("offset={}", offset));
+
+        format!("{}?{}", self.path, pairs.join("&"))
+    }
+}
+
+// A single page of a list endpoint's results
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u32,
+    pub limit: u32,
+    pub offset: u32,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, total: u32, params: &ListParams) -> Self {
+        let next_offset = params.offset.saturating_add(params.limit);
+        let next = (next_offset < total).then(|| params.link(next_offset));
+        let prev =
+            (params.offset > 0).then(|| params.link(params.offset.saturating_sub(params.limit)));
+
+        Page {
+            items,
+            total,
+            limit: params.limit,
+            offset: params.offset,
+            next,
+            prev,
+        }
+    }
+}
+
+// Query params of a list endpoint, restricted to the filters it supports
+#[derive(Debug, Default)]
+pub struct Filters(HashMap<String, String>);
+
+impl Filters {
+    pub fn parse(
+        params: HashMap<String, String>,
+        allowed_keys: &[&str],
+    ) -> Result<Self, QueryError> {
+        if let Some(key) = params
+            .keys()
+            .find(|key| !allowed_keys.contains(&key.as_str()))
+        {
+            return Err(QueryError::UnknownFilter(key.clone()));
+        }
+
+        Ok(Filters(params))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&String> {
+        self.0.get(key)
+    }
+
+    // Parses the value of a filter, if it was given
+    pub fn get_parsed<T: FromStr>(&self, key: &str) -> Result<Option<T>, QueryError> {
+        self.get(key)
+            .map(|value| value.parse())
+            .transpose()
+            .map_err(|_| QueryError::InvalidFilter(key.to_string()))
+    }
+}
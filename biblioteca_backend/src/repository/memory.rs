@@ -0,0 +1,115 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+};
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    audit::model::AuditEntry,
+    auth::model::{ApiKey, Session},
+    catalog::model::{Author, Book, Category, ContributorRole, Item},
+    library::model::{BookBorrowEntry, FineEntry, Hold},
+    query::{ListParams, Page},
+    users::model::{User, UserRole},
+};
+
+// Stores every domain in memory, for tests and trying out the app without a database
+#[derive(Default)]
+pub struct MemoryRepository {
+    data: Mutex<MemoryData>,
+}
+
+impl MemoryRepository {
+    pub fn new() -> Self {
+        MemoryRepository::default()
+    }
+
+    pub(crate) fn data(&self) -> MutexGuard<'_, MemoryData> {
+        self.data.lock().unwrap()
+    }
+}
+
+// Records of each domain, kept in the order they were added
+#[derive(Default)]
+pub(crate) struct MemoryData {
+    pub books: Vec<Book>,
+    pub authors: Vec<Author>,
+    pub categories: Vec<Category>,
+    pub items: Vec<Item>,
+    // Contributors of each book, in the order they are listed
+    pub book_contributors: Vec<BookContributorLink>,
+    pub book_categories: Vec<(Uuid, Uuid)>,
+
+    pub users: Vec<User>,
+    pub user_roles: Vec<UserRole>,
+    pub user_role_links: Vec<UserRoleLink>,
+
+    // Password hashes of the users that have been given one
+    pub password_hashes: HashMap<Uuid, String>,
+    pub sessions: Vec<Session>,
+    pub api_keys: Vec<ApiKey>,
+
+    // Ledger of loans, where a loan is outstanding until it has a returned entry
+    pub borrow_entries: Vec<BookBorrowEntry>,
+    pub holds: Vec<Hold>,
+    pub fines: Vec<FineEntry>,
+
+    // Every change made to the other records, oldest first
+    pub audit_entries: Vec<AuditEntry>,
+}
+
+pub(crate) struct BookContributorLink {
+    pub book_id: Uuid,
+    pub author_id: Uuid,
+    pub role: ContributorRole,
+}
+
+pub(crate) struct UserRoleLink {
+    pub user_id: Uuid,
+    pub user_role_id: Uuid,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+// Checks whether the value contains the term, ignoring case like a LIKE match does
+pub(crate) fn contains_term(value: &str, term: Option<&String>) -> bool {
+    match term {
+        Some(term) => value.to_lowercase().contains(&term.to_lowercase()),
+        None => true,
+    }
+}
+
+// Sorts the records by the sort fields of the list params, keeping their current order for
+// any ties, and returns the page of them requested
+pub(crate) fn paginate<T>(
+    mut records: Vec<T>,
+    params: &ListParams,
+    compare: impl Fn(&T, &T, &str) -> Ordering,
+) -> Page<T> {
+    records.sort_by(|a, b| {
+        params
+            .sort
+            .iter()
+            .map(|key| {
+                let ordering = compare(a, b, key.field);
+                if key.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+
+    let total = records.len() as u32;
+    let items = records
+        .into_iter()
+        .skip(params.offset as usize)
+        .take(params.limit as usize)
+        .collect();
+
+    Page::new(items, total, params)
+}
@@ -0,0 +1,59 @@
+use std::fmt;
+
+use crate::query::ListParams;
+
+pub mod memory;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+pub mod sqlite;
+
+#[cfg(feature = "postgres")]
+pub use self::postgres::PostgresRepository;
+pub use memory::MemoryRepository;
+pub use sqlite::SqliteRepository;
+
+#[derive(thiserror::Error, Debug)]
+pub enum RepositoryError {
+    NotFound,
+    Backend(String),
+}
+
+impl fmt::Display for RepositoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RepositoryError::NotFound => write!(f, "record does not exist in the repository"),
+            RepositoryError::Backend(err) => {
+                write!(f, "there was an error in accessing the repository: {}", err)
+            }
+        }
+    }
+}
+
+pub type Result<T, E = RepositoryError> = std::result::Result<T, E>;
+
+// Wraps a search term for a LIKE substring match, escaping its own wildcards
+pub fn like_pattern(term: &str) -> String {
+    let escaped = term
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    format!("%{}%", escaped)
+}
+
+// Orders by the column of each sort field of the list params, then by the order used without
+// one, which also breaks any ties
+pub fn order_by(params: &ListParams, sort_columns: &[(&str, &str)], default_order: &str) -> String {
+    let mut order_by: Vec<String> = params
+        .sort
+        .iter()
+        .filter_map(|key| {
+            let (_, column) = sort_columns.iter().find(|(field, _)| *field == key.field)?;
+            let direction = if key.descending { "DESC" } else { "ASC" };
+            Some(format!("{} {}", column, direction))
+        })
+        .collect();
+    order_by.push(default_order.to_string());
+
+    order_by.join(", ")
+}
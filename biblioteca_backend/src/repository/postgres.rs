@@ -0,0 +1,238 @@
+use std::{error::Error, panic, thread};
+
+use bytes::BytesMut;
+use postgres::{
+    types::{accepts, to_sql_checked, FromSql, IsNull, ToSql, Type},
+    Client, NoTls,
+};
+use r2d2::Pool;
+use r2d2_postgres::PostgresConnectionManager;
+
+use crate::{
+    audit::model::{ActorKind, AuditAction, EntityType},
+    catalog::model::{ContributorRole, ItemCondition, ItemStatus, SearchResultKind},
+    library::model::{BookBorrowState, FineEntryKind, HoldStatus},
+    query::ListParams,
+    users::model::UserStatus,
+};
+
+use super::{like_pattern, order_by, RepositoryError, Result};
+
+// Stores every domain in a PostgreSQL database, through a pool of its connections
+pub struct PostgresRepository {
+    // Only taken once the repository is dropped
+    pool: Option<Pool<PostgresConnectionManager<NoTls>>>,
+}
+
+impl PostgresRepository {
+    pub fn new(pool: Pool<PostgresConnectionManager<NoTls>>) -> Self {
+        PostgresRepository { pool: Some(pool) }
+    }
+
+    // Runs the statements of a call with a connection from the pool
+    pub fn with_client<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send,
+        F: FnOnce(&mut Client) -> Result<T> + Send,
+    {
+        let pool = self.pool.as_ref().unwrap();
+
+        run_blocking(|| {
+            let mut client = pool
+                .get()
+                .map_err(|err| RepositoryError::Backend(err.to_string()))?;
+
+            f(&mut client)
+        })
+    }
+}
+
+impl Drop for PostgresRepository {
+    // Closing the connections blocks on their runtimes as well
+    fn drop(&mut self) {
+        if let Some(pool) = self.pool.take() {
+            run_blocking(move || drop(pool));
+        }
+    }
+}
+
+// Runs the closure on a thread of its own. Each client blocks on a runtime of its own, which
+// panics when done from within the runtime of the app, so clients are only ever used this way.
+pub fn run_blocking<T, F>(f: F) -> T
+where
+    T: Send,
+    F: FnOnce() -> T + Send,
+{
+    thread::scope(|scope| match scope.spawn(f).join() {
+        Ok(result) => result,
+        Err(err) => panic::resume_unwind(err),
+    })
+}
+
+impl From<postgres::Error> for RepositoryError {
+    fn from(err: postgres::Error) -> Self {
+        RepositoryError::Backend(err.to_string())
+    }
+}
+
+// Stores the values of the model enums as the same text they are stored as in SQLite
+macro_rules! text_column {
+    ($($model:ty),*) => {
+        $(
+            impl ToSql for $model {
+                fn to_sql(
+                    &self,
+                    ty: &Type,
+                    out: &mut BytesMut,
+                ) -> std::result::Result<IsNull, Box<dyn Error + Sync + Send>> {
+                    self.to_string().to_sql(ty, out)
+                }
+
+                accepts!(TEXT, VARCHAR);
+                to_sql_checked!();
+            }
+
+            impl<'a> FromSql<'a> for $model {
+                fn from_sql(
+                    ty: &Type,
+                    raw: &'a [u8],
+                ) -> std::result::Result<Self, Box<dyn Error + Sync + Send>> {
+                    Ok(<&str>::from_sql(ty, raw)?.parse()?)
+                }
+
+                accepts!(TEXT, VARCHAR);
+            }
+        )*
+    };
+}
+
+text_column!(
+    ContributorRole,
+    ItemCondition,
+    ItemStatus,
+    SearchResultKind,
+    BookBorrowState,
+    FineEntryKind,
+    HoldStatus,
+    UserStatus,
+    ActorKind,
+    AuditAction,
+    EntityType
+);
+
+// Builds a SELECT statement whose conditions are all bound as parameters
+pub struct QueryBuilder {
+    select: String,
+    conditions: Vec<String>,
+    values: Vec<Box<dyn ToSql + Sync + Send>>,
+    order_by: Option<String>,
+    page: Option<(u32, u32)>,
+}
+
+impl QueryBuilder {
+    pub fn new(select: &str) -> Self {
+        QueryBuilder {
+            select: select.to_string(),
+            conditions: Vec::new(),
+            values: Vec::new(),
+            order_by: None,
+            page: None,
+        }
+    }
+
+    // Adds a condition with a single `?` placeholder for the given value, which is numbered
+    // after those of the conditions before it
+    pub fn condition<T: ToSql + Sync + Send + 'static>(
+        &mut self,
+        condition: &str,
+        value: T,
+    ) -> &mut Self {
+        self.values.push(Box::new(value));
+        self.conditions
+            .push(condition.replace('?', &format!("${}", self.values.len())));
+        self
+    }
+
+    // Adds a case-insensitive substring match of a column, if a term was given
+    pub fn contains(&mut self, column: &str, term: Option<&String>) -> &mut Self {
+        if let Some(term) = term {
+            self.condition(
+                &format!("{} ILIKE ? ESCAPE '\\'", column),
+                like_pattern(term),
+            );
+        }
+        self
+    }
+
+    // Adds a condition without any placeholders
+    pub fn fixed_condition(&mut self, condition: &str) -> &mut Self {
+        self.conditions.push(condition.to_string());
+        self
+    }
+
+    // Leaves out the rows deleted through the column, unless the list params include them
+    pub fn exclude_deleted(&mut self, column: &str, params: &ListParams) -> &mut Self {
+        if !params.include_deleted() {
+            self.fixed_condition(&format!("{} IS NULL", column));
+        }
+        self
+    }
+
+    // Orders and limits the query to the page requested in the list params, given the column
+    // of each sort field and the order used without one, which also breaks any ties
+    pub fn paginate(
+        &mut self,
+        params: &ListParams,
+        sort_columns: &[(&str, &str)],
+        default_order: &str,
+    ) -> &mut Self {
+        self.order_by = Some(order_by(params, sort_columns, default_order));
+        self.page = Some((params.limit, params.offset));
+        self
+    }
+
+    pub fn sql(&self) -> String {
+        let mut sql = self.filtered_sql();
+
+        if let Some(order_by) = &self.order_by {
+            sql.push_str(" ORDER BY ");
+            sql.push_str(order_by);
+        }
+
+        if let Some((limit, offset)) = self.page {
+            sql.push_str(&format!(" LIMIT {} OFFSET {}", limit, offset));
+        }
+
+        sql
+    }
+
+    // Counts every row matching the conditions, regardless of the page
+    pub fn count(&self, client: &mut Client) -> Result<u32, postgres::Error> {
+        let count: i64 = client
+            .query_one(
+                &format!("SELECT COUNT(*) FROM ({}) AS filtered", self.filtered_sql()),
+                &self.params(),
+            )?
+            .try_get(0)?;
+
+        Ok(count as u32)
+    }
+
+    fn filtered_sql(&self) -> String {
+        let mut sql = self.select.clone();
+
+        if !self.conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&self.conditions.join(" AND "));
+        }
+
+        sql
+    }
+
+    pub fn params(&self) -> Vec<&(dyn ToSql + Sync)> {
+        self.values
+            .iter()
+            .map(|value| value.as_ref() as &(dyn ToSql + Sync))
+            .collect()
+    }
+}
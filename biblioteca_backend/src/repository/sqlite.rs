@@ -0,0 +1,132 @@
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{params_from_iter, Connection, Params, ToSql};
+
+use crate::query::ListParams;
+
+use super::{like_pattern, order_by, RepositoryError};
+
+// Stores every domain in a SQLite database, through a pool of its connections
+pub struct SqliteRepository {
+    pub pool: Pool<SqliteConnectionManager>,
+}
+
+impl SqliteRepository {
+    pub fn new(pool: Pool<SqliteConnectionManager>) -> Self {
+        SqliteRepository { pool }
+    }
+}
+
+impl From<rusqlite::Error> for RepositoryError {
+    fn from(err: rusqlite::Error) -> Self {
+        match err {
+            rusqlite::Error::QueryReturnedNoRows => RepositoryError::NotFound,
+            err => RepositoryError::Backend(err.to_string()),
+        }
+    }
+}
+
+// Builds a SELECT statement whose conditions are all bound as parameters
+pub struct QueryBuilder {
+    select: String,
+    conditions: Vec<String>,
+    values: Vec<Box<dyn ToSql>>,
+    order_by: Option<String>,
+    page: Option<(u32, u32)>,
+}
+
+impl QueryBuilder {
+    pub fn new(select: &str) -> Self {
+        QueryBuilder {
+            select: select.to_string(),
+            conditions: Vec::new(),
+            values: Vec::new(),
+            order_by: None,
+            page: None,
+        }
+    }
+
+    // Adds a condition with a single `?` placeholder for the given value
+    pub fn condition<T: ToSql + 'static>(&mut self, condition: &str, value: T) -> &mut Self {
+        self.conditions.push(condition.to_string());
+        self.values.push(Box::new(value));
+        self
+    }
+
+    // Adds a case-insensitive substring match of a column, if a term was given
+    pub fn contains(&mut self, column: &str, term: Option<&String>) -> &mut Self {
+        if let Some(term) = term {
+            self.condition(
+                &format!("{} LIKE ? ESCAPE '\\'", column),
+                like_pattern(term),
+            );
+        }
+        self
+    }
+
+    // Adds a condition without any placeholders
+    pub fn fixed_condition(&mut self, condition: &str) -> &mut Self {
+        self.conditions.push(condition.to_string());
+        self
+    }
+
+    // Leaves out the rows deleted through the column, unless the list params include them
+    pub fn exclude_deleted(&mut self, column: &str, params: &ListParams) -> &mut Self {
+        if !params.include_deleted() {
+            self.fixed_condition(&format!("{} IS NULL", column));
+        }
+        self
+    }
+
+    // Orders and limits the query to the page requested in the list params, given the column
+    // of each sort field and the order used without one, which also breaks any ties
+    pub fn paginate(
+        &mut self,
+        params: &ListParams,
+        sort_columns: &[(&str, &str)],
+        default_order: &str,
+    ) -> &mut Self {
+        self.order_by = Some(order_by(params, sort_columns, default_order));
+        self.page = Some((params.limit, params.offset));
+        self
+    }
+
+    pub fn sql(&self) -> String {
+        let mut sql = self.filtered_sql();
+
+        if let Some(order_by) = &self.order_by {
+            sql.push_str(" ORDER BY ");
+            sql.push_str(order_by);
+        }
+
+        if let Some((limit, offset)) = self.page {
+            sql.push_str(&format!(" LIMIT {} OFFSET {}", limit, offset));
+        }
+
+        sql
+    }
+
+    // Counts every row matching the conditions, regardless of the page
+    pub fn count(&self, conn: &Connection) -> rusqlite::Result<u32> {
+        conn.query_row(
+            &format!("SELECT COUNT(*) FROM ({})", self.filtered_sql()),
+            self.params(),
+            |row| row.get(0),
+        )
+    }
+
+    fn filtered_sql(&self) -> String {
+        let mut sql = self.select.clone();
+
+        if !self.conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&self.conditions.join(" AND "));
+        }
+
+        sql
+    }
+
+    pub fn params(&self) -> impl Params + '_ {
+        params_from_iter(self.values.iter())
+    }
+}
@@ -2,37 +2,41 @@ use std::collections::HashMap;
 
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    routing::{delete, get, post},
+    http::{StatusCode, Uri},
+    routing::{delete, get, post, put},
     Json, Router,
 };
+use chrono::Utc;
 use uuid::Uuid;
 
+use crate::query::{ListParams, Page};
 use crate::{
     app::AppState,
-    users::db::{
-        add_user_role_to_db, delete_user_role_from_db, get_user_role_from_db,
-        is_username_valid_in_db,
-    },
-};
-use crate::{
+    auth::password::{hash_password, validate_password},
     error::Error,
-    users::db::{
-        add_user_to_db, delete_user_from_db, get_user_from_db, list_user_roles_from_db,
-        list_users_from_db,
-    },
+    library::error::LibraryError,
+    repository::RepositoryError,
+    users::repository::{USER_LIST, USER_ROLE_LIST},
 };
 
-use super::model::{CreateUserRequest, CreateUserRoleRequest, FullUser, User, UserRole};
+use super::model::{
+    CreateUserRequest, CreateUserRoleRequest, FullUser, GrantUserRoleRequest, Membership,
+    UpdateUserRequest, UpdateUserRoleRequest, User, UserRole, UserStatus,
+};
 
 pub fn users_router() -> Router<AppState> {
     Router::new()
         .route("/users/:id", get(get_user))
         .route("/users/:id", delete(delete_user))
+        .route("/users/:id", put(update_user))
+        .route("/users/:id/restore", post(restore_user))
+        .route("/users/:id/roles", post(grant_user_role))
+        .route("/users/:id/roles/:user_role_id", delete(revoke_user_role))
         .route("/users", get(list_users))
         .route("/users", post(add_user))
         .route("/users/roles/:id", get(get_user_role))
         .route("/users/roles/:id", delete(delete_user_role))
+        .route("/users/roles/:id", put(update_user_role))
         .route("/users/roles", post(add_user_role))
         .route("/users/roles", get(list_user_roles))
 }
@@ -43,7 +47,7 @@ pub async fn get_user(
 ) -> Result<Json<FullUser>, Error> {
     tracing::debug!("GET /users with id: {:?}", id);
 
-    match get_user_from_db(state, Uuid::parse_str(&id).unwrap()).await {
+    match state.users.get_user(Uuid::parse_str(&id).unwrap()) {
         Ok(user) => Ok(Json(user)),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -54,11 +58,15 @@ pub async fn get_user(
 
 pub async fn list_users(
     state: State<AppState>,
+    uri: Uri,
     Query(params): Query<HashMap<String, String>>,
-) -> Result<Json<Vec<FullUser>>, Error> {
+) -> Result<Json<Page<FullUser>>, Error> {
     tracing::debug!("GET /users with query params: {:?}", params);
 
-    match list_users_from_db(state).await {
+    let params = ListParams::parse(&uri, params, &USER_LIST)
+        .map_err(|err| Error::bad_request(err.to_string()))?;
+
+    match state.users.list_users(&params) {
         Ok(users) => Ok(Json(users)),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -71,36 +79,216 @@ pub async fn add_user(
     state: State<AppState>,
     Json(payload): Json<CreateUserRequest>,
 ) -> Result<Json<User>, Error> {
-    tracing::debug!("POST /users with params: {:?}", payload);
+    tracing::debug!("POST /users for username {:?}", payload.username);
+
+    if let Some(password) = &payload.password {
+        validate_password(password).map_err(|err| Error::bad_request(err.to_string()))?;
+    }
 
-    if !is_username_valid_in_db(&state, &payload.username).unwrap() {
+    if !state
+        .users
+        .is_username_valid(&payload.username, None)
+        .unwrap()
+    {
         return Err(Error::bad_request("username already exists".to_string()));
     }
 
+    // Suspensions and expiries come about later, so new users start out active or pending
+    if !matches!(payload.status, UserStatus::Active | UserStatus::Pending) {
+        return Err(Error::bad_request(
+            "user must start out active or pending".to_string(),
+        ));
+    }
+
     let user = User {
         id: Uuid::new_v4(),
         username: payload.username,
+        display_name: payload.display_name,
+        email: payload.email,
+        membership: Membership {
+            status: payload.status,
+            membership_expires_at: payload.membership_expires_at,
+            ..Default::default()
+        },
+        deleted_at: None,
     };
 
     let user_role_id = payload.user_role_id;
+    let password_hash = payload.password.as_deref().map(hash_password);
 
-    match add_user_to_db(state, user, user_role_id).await {
+    match state
+        .users
+        .add_user(user, user_role_id, password_hash.as_deref())
+    {
         Ok(user) => Ok(Json(user)),
         Err(err) => {
             tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Updates the username, profile and membership expiry of a user
+pub async fn update_user(
+    state: State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateUserRequest>,
+) -> Result<StatusCode, Error> {
+    tracing::debug!("PUT /users with id: {:?}", id);
+
+    if !state.users.is_user_exists(id).unwrap() {
+        return Err(Error::not_found());
+    }
 
+    if !state
+        .users
+        .is_username_valid(&payload.username, Some(id))
+        .unwrap()
+    {
+        return Err(Error::bad_request("username already exists".to_string()));
+    }
+
+    let user = User {
+        id,
+        username: payload.username,
+        display_name: payload.display_name,
+        email: payload.email,
+        membership: Membership {
+            membership_expires_at: payload.membership_expires_at,
+            ..Default::default()
+        },
+        deleted_at: None,
+    };
+
+    match state.users.update_user(user) {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(err) => {
+            tracing::warn!("{}", err);
             Err(Error::server_issue())
         }
     }
 }
 
+// Gives a user a role, optionally until it expires. Granting a role the user already holds
+// changes when it expires instead.
+pub async fn grant_user_role(
+    state: State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<GrantUserRoleRequest>,
+) -> Result<StatusCode, Error> {
+    tracing::debug!("POST /users/:id/roles with id: {:?}", id);
+
+    if !state.users.is_user_exists(id).unwrap() {
+        return Err(Error::not_found());
+    }
+
+    if let Err(err) = state.users.get_user_role(payload.user_role_id) {
+        tracing::warn!("{}", err);
+        return Err(Error::bad_request("user role does not exist".to_string()));
+    }
+
+    if payload
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(Error::bad_request(
+            "expiry of user role must be in the future".to_string(),
+        ));
+    }
+
+    match state
+        .users
+        .grant_user_role(id, payload.user_role_id, payload.expires_at)
+    {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Takes a role away from a user. Users keep at least one role, and cannot lose a role if the
+// roles left to them allow fewer books than they currently have on loan.
+pub async fn revoke_user_role(
+    state: State<AppState>,
+    Path((id, user_role_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, Error> {
+    tracing::debug!(
+        "DELETE /users/:id/roles with id: {:?} and role id: {:?}",
+        id,
+        user_role_id
+    );
+
+    let user = match state.users.get_user(id) {
+        Ok(user) => user,
+        Err(err) => {
+            tracing::warn!("{}", err);
+            return Err(Error::not_found());
+        }
+    };
+
+    let Some(revoked) = user
+        .user_roles
+        .iter()
+        .find(|assigned| assigned.user_role.id == user_role_id)
+    else {
+        return Err(Error::not_found());
+    };
+
+    // Roles that have expired do not count towards the one role a user keeps
+    let now = Utc::now();
+    if user.user_roles.len() == 1
+        || (revoked.is_active(now) && user.active_user_roles(now).count() == 1)
+    {
+        return Err(Error::bad_request(
+            "user must keep at least one role".to_string(),
+        ));
+    }
+
+    if revoked.is_active(now) {
+        let num_borrowable = user
+            .active_user_roles(now)
+            .filter(|user_role| user_role.id != user_role_id)
+            .map(|user_role| user_role.num_borrowable_books as u32)
+            .max()
+            .unwrap_or(0);
+
+        let num_borrowed = state.library.get_num_borrowed(id).unwrap();
+        if num_borrowed > num_borrowable {
+            return Err(Error::bad_request(
+                LibraryError::LoansExceedRoleLimit(num_borrowable).to_string(),
+            ));
+        }
+    }
+
+    match state.users.revoke_user_role(id, user_role_id) {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Deletes a user, unless they have books on loan. Their sessions end, their holds are cancelled
+// and their username stays taken until they are purged.
 pub async fn delete_user(
     state: State<AppState>,
     Path(id): Path<String>,
 ) -> Result<StatusCode, Error> {
     tracing::debug!("DELETE /users with id: {:?}", id);
 
-    match delete_user_from_db(state, Uuid::parse_str(&id).unwrap()).await {
+    let id = Uuid::parse_str(&id).unwrap();
+
+    if state.library.get_num_borrowed(id).unwrap() > 0 {
+        return Err(Error::bad_request(
+            "user has books currently on loan".to_string(),
+        ));
+    }
+
+    let hold_pickup_days = state.library_settings.hold_pickup_days;
+    match state.users.delete_user(id, hold_pickup_days) {
         Ok(()) => Ok(StatusCode::NO_CONTENT),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -109,13 +297,30 @@ pub async fn delete_user(
     }
 }
 
+// Restores a user that was deleted, who has to log in again
+pub async fn restore_user(
+    state: State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<FullUser>, Error> {
+    tracing::debug!("POST /users/:id/restore with id: {:?}", id);
+
+    match state.users.restore_user(id) {
+        Ok(user) => Ok(Json(user)),
+        Err(RepositoryError::NotFound) => Err(Error::not_found()),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
 pub async fn get_user_role(
     state: State<AppState>,
     Path(id): Path<String>,
 ) -> Result<Json<UserRole>, Error> {
     tracing::debug!("GET /users/roles with id: {:?}", id);
 
-    match get_user_role_from_db(state, Uuid::parse_str(&id).unwrap()).await {
+    match state.users.get_user_role(Uuid::parse_str(&id).unwrap()) {
         Ok(user_role) => Ok(Json(user_role)),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -124,10 +329,17 @@ pub async fn get_user_role(
     }
 }
 
-pub async fn list_user_roles(state: State<AppState>) -> Result<Json<Vec<UserRole>>, Error> {
-    tracing::debug!("GET /users/roles");
+pub async fn list_user_roles(
+    state: State<AppState>,
+    uri: Uri,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Json<Page<UserRole>>, Error> {
+    tracing::debug!("GET /users/roles with query params: {:?}", params);
 
-    match list_user_roles_from_db(state).await {
+    let params = ListParams::parse(&uri, params, &USER_ROLE_LIST)
+        .map_err(|err| Error::bad_request(err.to_string()))?;
+
+    match state.users.list_user_roles(&params) {
         Ok(user_roles) => Ok(Json(user_roles)),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -142,19 +354,34 @@ pub async fn add_user_role(
 ) -> Result<Json<UserRole>, Error> {
     tracing::debug!("POST /users/roles with params: {:?}", payload);
 
-    if payload.num_borrowable_books < 0 {
-        return Err(Error::bad_request(
-            "num_borrowable_books must be zero or positive integer".to_string(),
-        ));
-    }
+    let settings = &state.library_settings;
+    let loan_period_days = payload
+        .loan_period_days
+        .unwrap_or(settings.default_loan_period_days);
+    let max_renewals = payload
+        .max_renewals
+        .unwrap_or(settings.default_max_renewals);
+    let fine_per_day_cents = payload
+        .fine_per_day_cents
+        .unwrap_or(settings.default_fine_per_day_cents);
+    let fine_cap_cents = payload
+        .fine_cap_cents
+        .unwrap_or(settings.default_fine_cap_cents);
 
     let user_role = UserRole {
         id: Uuid::new_v4(),
         name: payload.name,
         num_borrowable_books: payload.num_borrowable_books,
+        loan_period_days,
+        max_renewals,
+        fine_per_day_cents,
+        fine_cap_cents,
+        permissions: payload.permissions,
     };
 
-    match add_user_role_to_db(state, user_role).await {
+    validate_user_role(&user_role)?;
+
+    match state.users.add_user_role(user_role) {
         Ok(user_role) => Ok(Json(user_role)),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -163,13 +390,73 @@ pub async fn add_user_role(
     }
 }
 
+// Updates a user role. The number of books it allows cannot be lowered below what any user given
+// the role currently has on loan, unless the other roles of the user already allow that many.
+pub async fn update_user_role(
+    state: State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateUserRoleRequest>,
+) -> Result<StatusCode, Error> {
+    tracing::debug!("PUT /users/roles with id: {:?}", id);
+
+    if let Err(err) = state.users.get_user_role(id) {
+        tracing::warn!("{}", err);
+        return Err(Error::not_found());
+    }
+
+    let user_role = UserRole {
+        id,
+        name: payload.name,
+        num_borrowable_books: payload.num_borrowable_books,
+        loan_period_days: payload.loan_period_days,
+        max_renewals: payload.max_renewals,
+        fine_per_day_cents: payload.fine_per_day_cents,
+        fine_cap_cents: payload.fine_cap_cents,
+        permissions: payload.permissions,
+    };
+
+    validate_user_role(&user_role)?;
+
+    let max_num_borrowed = state.library.get_max_num_borrowed_of_user_role(id).unwrap();
+    if max_num_borrowed > user_role.num_borrowable_books as u32 {
+        return Err(Error::bad_request(
+            LibraryError::LoansExceedRoleLimit(user_role.num_borrowable_books as u32).to_string(),
+        ));
+    }
+
+    match state.users.update_user_role(user_role) {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Deletes a user role, unless it is the only role of some user, as users keep at least one role,
+// or some user given the role has more books on loan than their other roles allow
 pub async fn delete_user_role(
     state: State<AppState>,
     Path(id): Path<String>,
 ) -> Result<StatusCode, Error> {
     tracing::debug!("DELETE /users/roles with id: {:?}", id);
 
-    match delete_user_role_from_db(state, Uuid::parse_str(&id).unwrap()).await {
+    let id = Uuid::parse_str(&id).unwrap();
+
+    if state.users.is_only_user_role_of_any_user(id).unwrap() {
+        return Err(Error::bad_request(
+            "user role is the only role of some users".to_string(),
+        ));
+    }
+
+    // Deleting the role leaves its users with what their other roles allow, as if it allowed none
+    if state.library.get_max_num_borrowed_of_user_role(id).unwrap() > 0 {
+        return Err(Error::bad_request(
+            LibraryError::LoansExceedRoleLimit(0).to_string(),
+        ));
+    }
+
+    match state.users.delete_user_role(id) {
         Ok(()) => Ok(StatusCode::NO_CONTENT),
         Err(err) => {
             tracing::warn!("{}", err);
@@ -177,3 +464,32 @@ pub async fn delete_user_role(
         }
     }
 }
+
+// Checks the limits and loan policies of a role that is about to be added or updated
+fn validate_user_role(user_role: &UserRole) -> Result<(), Error> {
+    if user_role.num_borrowable_books < 0 {
+        return Err(Error::bad_request(
+            "num_borrowable_books must be zero or positive integer".to_string(),
+        ));
+    }
+
+    if user_role.loan_period_days <= 0 {
+        return Err(Error::bad_request(
+            "loan_period_days must be positive integer".to_string(),
+        ));
+    }
+
+    if user_role.max_renewals < 0 {
+        return Err(Error::bad_request(
+            "max_renewals must be zero or positive integer".to_string(),
+        ));
+    }
+
+    if user_role.fine_per_day_cents < 0 || user_role.fine_cap_cents < 0 {
+        return Err(Error::bad_request(
+            "fine_per_day_cents and fine_cap_cents must be zero or positive integer".to_string(),
+        ));
+    }
+
+    Ok(())
+}
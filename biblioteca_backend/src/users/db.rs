@@ -1,171 +1,581 @@
-use axum::extract::State;
-use rusqlite::{Error, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, Row};
 use uuid::Uuid;
 
-use crate::app::AppState;
+use crate::{
+    audit::{
+        context::AuditContext,
+        db::add_audit_entry,
+        model::{AuditAction, AuditEntry, EntityType},
+    },
+    library::db::cancel_holds_of_user,
+    query::{ListParams, Page},
+    repository::{like_pattern, sqlite::QueryBuilder, RepositoryError, Result, SqliteRepository},
+};
 
-use super::model::{FullUser, User, UserRole};
+use super::{
+    model::{
+        join_permissions, split_permissions, AssignedUserRole, FullUser, Membership, User,
+        UserRole, UserStatus,
+    },
+    repository::UserRepository,
+};
 
-pub async fn list_users_from_db(State(state): State<AppState>) -> Result<Vec<FullUser>> {
-    let conn = state.db_pool.get().unwrap();
+const USER_COLUMNS: &str = "a.id, a.username, a.display_name, a.email, a.status,
+    a.membership_expires_at, a.suspension_reason, a.suspended_until, a.deleted_at";
 
-    let mut stmt = conn.prepare(
-        "
-        SELECT a.id as user_id, a.username, c.id as user_role_id, c.name, c.num_borrowable_books 
-        FROM users a, map_users_to_user_roles b, user_roles c 
-        WHERE a.id = b.user_id AND b.user_role_id = c.id",
-    )?;
+// Columns of the sort fields of users and user roles, where users are sorted by the first of
+// their role names
+const USER_SORT_COLUMNS: &[(&str, &str)] = &[
+    ("username", "a.username"),
+    (
+        "role",
+        "(SELECT MIN(c.name) FROM map_users_to_user_roles b
+        JOIN user_roles c ON b.user_role_id = c.id
+        WHERE b.user_id = a.id)",
+    ),
+];
+const USER_ROLE_SORT_COLUMNS: &[(&str, &str)] = &[
+    ("name", "name"),
+    ("num_borrowable_books", "num_borrowable_books"),
+    ("loan_period_days", "loan_period_days"),
+];
 
-    let users = stmt
-        .query_map([], |row| {
-            Ok(FullUser {
-                id: row.get(0)?,
-                username: row.get(1)?,
-                user_role: UserRole {
-                    id: row.get(2)?,
-                    name: row.get(3)?,
-                    num_borrowable_books: row.get(4)?,
-                },
-            })
-        })?
-        .map(|user| user.unwrap())
-        .collect();
+impl UserRepository for SqliteRepository {
+    fn list_users(&self, params: &ListParams) -> Result<Page<FullUser>> {
+        let conn = self.pool.get().unwrap();
 
-    Ok(users)
-}
+        let mut query = QueryBuilder::new(&format!("SELECT {} FROM users a", USER_COLUMNS));
+        query.contains("a.username", params.filters.get("username"));
+        if let Some(role) = params.filters.get("role") {
+            query.condition(
+                "EXISTS (SELECT 1 FROM map_users_to_user_roles b
+                JOIN user_roles c ON b.user_role_id = c.id
+                WHERE b.user_id = a.id AND c.name LIKE ? ESCAPE '\\')",
+                like_pattern(role),
+            );
+        }
+        query.exclude_deleted("a.deleted_at", params).paginate(
+            params,
+            USER_SORT_COLUMNS,
+            "a.rowid",
+        );
 
-pub async fn get_user_from_db(State(state): State<AppState>, id: Uuid) -> Result<FullUser> {
-    state.db_pool.get().unwrap().query_row(
-        "SELECT a.id as user_id, a.username, c.id as user_role_id, c.name, c.num_borrowable_books 
-        FROM users a, map_users_to_user_roles b, user_roles c 
-        WHERE a.id = b.user_id AND b.user_role_id = c.id
-        AND a.id = $1",
-        [id],
-        |row| {
-            Ok(FullUser {
-                id: row.get(0)?,
-                username: row.get(1)?,
-                user_role: UserRole {
-                    id: row.get(2)?,
-                    name: row.get(3)?,
-                    num_borrowable_books: row.get(4)?,
-                },
-            })
-        },
-    )
-}
+        let mut stmt = conn.prepare(&query.sql())?;
 
-pub async fn add_user_to_db(
-    State(state): State<AppState>,
-    user: User,
-    user_role_id: Uuid,
-) -> Result<User, Error> {
-    let mut conn = state.db_pool.get().unwrap();
+        let users = stmt
+            .query_map(query.params(), user_from_row)?
+            .map(|user| full_user(&conn, user?))
+            .collect::<rusqlite::Result<Vec<_>>>()?;
 
-    let tx = conn.transaction()?;
+        Ok(Page::new(users, query.count(&conn)?, params))
+    }
 
-    // Add the user itself
-    tx.execute(
-        "INSERT INTO users (id, username) VALUES (?1, ?2)",
-        (&user.id, &user.username),
-    )?;
+    fn get_user(&self, id: Uuid) -> Result<FullUser> {
+        Ok(fetch_user(&self.pool.get().unwrap(), id)?)
+    }
 
-    // Add the user's role association
-    tx.execute(
-        "INSERT INTO map_users_to_user_roles (user_id, user_role_id) VALUES (?1, ?2)",
-        (&user.id, user_role_id),
-    )?;
+    fn add_user(
+        &self,
+        user: User,
+        user_role_id: Uuid,
+        password_hash: Option<&str>,
+    ) -> Result<User> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
 
-    tx.commit()?;
+        let tx = conn.transaction()?;
 
-    Ok(user)
-}
+        // Add the user itself
+        tx.execute(
+            "INSERT INTO users (id, username, display_name, email, status, membership_expires_at, suspension_reason, suspended_until, password_hash)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            (
+                &user.id,
+                &user.username,
+                &user.display_name,
+                &user.email,
+                &user.membership.status,
+                &user.membership.membership_expires_at,
+                &user.membership.suspension_reason,
+                &user.membership.suspended_until,
+                password_hash,
+            ),
+        )?;
 
-pub async fn delete_user_from_db(State(state): State<AppState>, id: Uuid) -> Result<()> {
-    state
-        .db_pool
-        .get()
-        .unwrap()
-        .execute("DELETE FROM users WHERE id = $1", [id])?;
+        // Add the user's role association
+        tx.execute(
+            "INSERT INTO map_users_to_user_roles (user_id, user_role_id) VALUES (?1, ?2)",
+            (&user.id, user_role_id),
+        )?;
 
-    Ok(())
-}
+        let after = fetch_user(&tx, user.id)?;
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Create,
+                EntityType::User,
+                user.id,
+                None,
+                Some(&after),
+            ),
+        )?;
 
-pub async fn list_user_roles_from_db(State(state): State<AppState>) -> Result<Vec<UserRole>> {
-    let conn = state.db_pool.get().unwrap();
+        tx.commit()?;
 
-    let mut stmt = conn.prepare("SELECT * FROM user_roles")?;
+        Ok(user)
+    }
 
-    let user_roles = stmt
-        .query_map([], |row| {
-            Ok(UserRole {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                num_borrowable_books: row.get(2)?,
-            })
-        })?
-        .map(|user| user.unwrap())
-        .collect();
+    fn update_user(&self, user: User) -> Result<()> {
+        self.change_user(user.id, |conn| {
+            conn.execute(
+                "UPDATE users SET username = ?1, display_name = ?2, email = ?3, membership_expires_at = ?4
+                WHERE id = ?5",
+                (
+                    &user.username,
+                    &user.display_name,
+                    &user.email,
+                    &user.membership.membership_expires_at,
+                    &user.id,
+                ),
+            )?;
 
-    Ok(user_roles)
-}
+            Ok(())
+        })
+    }
 
-pub async fn get_user_role_from_db(State(state): State<AppState>, id: Uuid) -> Result<UserRole> {
-    state.db_pool.get().unwrap().query_row(
-        "SELECT * FROM user_roles WHERE user_roles.id = $1",
-        [id],
-        |row| {
-            Ok(UserRole {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                num_borrowable_books: row.get(2)?,
-            })
-        },
-    )
+    fn set_user_status(
+        &self,
+        id: Uuid,
+        status: UserStatus,
+        suspension_reason: Option<String>,
+        suspended_until: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        self.change_user(id, |conn| {
+            conn.execute(
+                "UPDATE users SET status = ?1, suspension_reason = ?2, suspended_until = ?3 WHERE id = ?4",
+                (status, suspension_reason, suspended_until, id),
+            )?;
+
+            Ok(())
+        })
+    }
+
+    fn grant_user_role(
+        &self,
+        user_id: Uuid,
+        user_role_id: Uuid,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        self.change_user(user_id, |conn| {
+            conn.execute(
+                "INSERT INTO map_users_to_user_roles (user_id, user_role_id, expires_at) VALUES (?1, ?2, ?3)
+                ON CONFLICT (user_id, user_role_id) DO UPDATE SET expires_at = excluded.expires_at",
+                (user_id, user_role_id, expires_at),
+            )?;
+
+            Ok(())
+        })
+    }
+
+    fn revoke_user_role(&self, user_id: Uuid, user_role_id: Uuid) -> Result<()> {
+        self.change_user(user_id, |conn| {
+            let num_deleted = conn.execute(
+                "DELETE FROM map_users_to_user_roles WHERE user_id = ?1 AND user_role_id = ?2",
+                (user_id, user_role_id),
+            )?;
+
+            if num_deleted == 0 {
+                return Err(RepositoryError::NotFound);
+            }
+
+            Ok(())
+        })
+    }
+
+    fn delete_user(&self, id: Uuid, hold_pickup_days: u32) -> Result<()> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        if let Some(user) = fetch_user(&tx, id).optional()? {
+            tx.execute(
+                "UPDATE users SET deleted_at = $1 WHERE id = $2",
+                (Utc::now(), id),
+            )?;
+            tx.execute("DELETE FROM sessions WHERE user_id = $1", [id])?;
+            cancel_holds_of_user(&tx, &context, id, hold_pickup_days)?;
+
+            add_audit_entry(
+                &tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Delete,
+                    EntityType::User,
+                    id,
+                    Some(&user),
+                    None,
+                ),
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn restore_user(&self, id: Uuid) -> Result<FullUser> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        let num_restored = tx.execute(
+            "UPDATE users SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL",
+            [id],
+        )?;
+
+        if num_restored == 0 {
+            return Err(RepositoryError::NotFound);
+        }
+
+        let user = fetch_user(&tx, id)?;
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Restore,
+                EntityType::User,
+                id,
+                None,
+                Some(&user),
+            ),
+        )?;
+
+        tx.commit()?;
+
+        Ok(user)
+    }
+
+    fn purge_deleted_users(&self, deleted_before: DateTime<Utc>) -> Result<u32> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        let users = {
+            let mut stmt = tx.prepare(&format!(
+                "SELECT {} FROM users a WHERE a.deleted_at < $1
+                AND NOT EXISTS (SELECT 1 FROM map_users_to_borrowed_books b WHERE b.user_id = a.id)
+                AND NOT EXISTS (SELECT 1 FROM holds b WHERE b.user_id = a.id)
+                AND NOT EXISTS (SELECT 1 FROM fines b WHERE b.user_id = a.id)",
+                USER_COLUMNS
+            ))?;
+            let users = stmt
+                .query_map([deleted_before], user_from_row)?
+                .map(|user| full_user(&tx, user?))
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+            users
+        };
+
+        for user in users.iter() {
+            tx.execute("DELETE FROM users WHERE id = $1", [user.id])?;
+
+            add_audit_entry(
+                &tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Purge,
+                    EntityType::User,
+                    user.id,
+                    Some(user),
+                    None,
+                ),
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(users.len() as u32)
+    }
+
+    fn is_user_exists(&self, id: Uuid) -> Result<bool> {
+        let count = self.pool.get().unwrap().query_row::<i32, _, _>(
+            "SELECT COUNT(*) FROM users WHERE id = $1 AND deleted_at IS NULL",
+            [id],
+            |row| row.get(0),
+        )?;
+
+        Ok(count == 1)
+    }
+
+    fn is_username_valid(&self, username: &str, user_id: Option<Uuid>) -> Result<bool> {
+        let count = self.pool.get().unwrap().query_row::<i32, _, _>(
+            "SELECT COUNT(*) FROM users WHERE username = $1 AND id IS NOT $2",
+            (username, user_id),
+            |row| row.get(0),
+        )?;
+
+        Ok(count == 0)
+    }
+
+    fn list_user_roles(&self, params: &ListParams) -> Result<Page<UserRole>> {
+        let conn = self.pool.get().unwrap();
+
+        let mut query = QueryBuilder::new(
+            "SELECT id, name, num_borrowable_books, loan_period_days, max_renewals, fine_per_day_cents, fine_cap_cents, permissions FROM user_roles",
+        );
+        query.contains("name", params.filters.get("name")).paginate(
+            params,
+            USER_ROLE_SORT_COLUMNS,
+            "rowid",
+        );
+
+        let mut stmt = conn.prepare(&query.sql())?;
+
+        let user_roles = stmt
+            .query_map(query.params(), user_role_from_row)?
+            .map(|user| user.unwrap())
+            .collect();
+
+        Ok(Page::new(user_roles, query.count(&conn)?, params))
+    }
+
+    fn get_user_role(&self, id: Uuid) -> Result<UserRole> {
+        Ok(fetch_user_role(&self.pool.get().unwrap(), id)?)
+    }
+
+    fn add_user_role(&self, user_role: UserRole) -> Result<UserRole> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO user_roles (id, name, num_borrowable_books, loan_period_days, max_renewals, fine_per_day_cents, fine_cap_cents, permissions) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            (
+                &user_role.id,
+                &user_role.name,
+                &user_role.num_borrowable_books,
+                &user_role.loan_period_days,
+                &user_role.max_renewals,
+                &user_role.fine_per_day_cents,
+                &user_role.fine_cap_cents,
+                &join_permissions(&user_role.permissions),
+            ),
+        )?;
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Create,
+                EntityType::UserRole,
+                user_role.id,
+                None,
+                Some(&user_role),
+            ),
+        )?;
+
+        tx.commit()?;
+
+        Ok(user_role)
+    }
+
+    fn update_user_role(&self, user_role: UserRole) -> Result<()> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        let Some(before) = fetch_user_role(&tx, user_role.id).optional()? else {
+            return Ok(());
+        };
+
+        tx.execute(
+            "UPDATE user_roles
+            SET name = ?1, num_borrowable_books = ?2, loan_period_days = ?3, max_renewals = ?4,
+                fine_per_day_cents = ?5, fine_cap_cents = ?6, permissions = ?7
+            WHERE id = ?8",
+            (
+                &user_role.name,
+                &user_role.num_borrowable_books,
+                &user_role.loan_period_days,
+                &user_role.max_renewals,
+                &user_role.fine_per_day_cents,
+                &user_role.fine_cap_cents,
+                &join_permissions(&user_role.permissions),
+                &user_role.id,
+            ),
+        )?;
+
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Update,
+                EntityType::UserRole,
+                user_role.id,
+                Some(&before),
+                Some(&user_role),
+            ),
+        )?;
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn delete_user_role(&self, id: Uuid) -> Result<()> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        if let Some(user_role) = fetch_user_role(&tx, id).optional()? {
+            tx.execute("DELETE FROM user_roles WHERE id = $1", [id])?;
+
+            add_audit_entry(
+                &tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Delete,
+                    EntityType::UserRole,
+                    id,
+                    Some(&user_role),
+                    None,
+                ),
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn is_only_user_role_of_any_user(&self, id: Uuid) -> Result<bool> {
+        let count = self.pool.get().unwrap().query_row::<i32, _, _>(
+            "SELECT COUNT(*) FROM map_users_to_user_roles a
+            WHERE a.user_role_id = $1 AND NOT EXISTS (
+                SELECT 1 FROM map_users_to_user_roles b
+                WHERE b.user_id = a.user_id AND b.user_role_id != a.user_role_id
+                AND (b.expires_at IS NULL OR b.expires_at > $2)
+            )",
+            (id, Utc::now()),
+            |row| row.get(0),
+        )?;
+
+        Ok(count > 0)
+    }
 }
 
-pub async fn add_user_role_to_db(
-    State(state): State<AppState>,
-    user_role: UserRole,
-) -> Result<UserRole, Error> {
-    let mut conn = state.db_pool.get().unwrap();
+impl SqliteRepository {
+    // Makes a change to the user within a transaction, recording the user before and after it
+    fn change_user(&self, id: Uuid, change: impl FnOnce(&Connection) -> Result<()>) -> Result<()> {
+        let context = AuditContext::current();
+        let mut conn = self.pool.get().unwrap();
+        let tx = conn.transaction()?;
+
+        let before = fetch_user(&tx, id)?;
+        change(&tx)?;
+        let after = fetch_user(&tx, id)?;
 
-    let tx = conn.transaction()?;
+        add_audit_entry(
+            &tx,
+            &AuditEntry::new(
+                &context,
+                AuditAction::Update,
+                EntityType::User,
+                id,
+                Some(&before),
+                Some(&after),
+            ),
+        )?;
 
-    tx.execute(
-        "INSERT INTO user_roles (id, name, num_borrowable_books) VALUES (?1, ?2, ?3)",
-        (
-            &user_role.id,
-            &user_role.name,
-            &user_role.num_borrowable_books,
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+fn fetch_user(conn: &Connection, id: Uuid) -> rusqlite::Result<FullUser> {
+    let user = conn.query_row(
+        &format!(
+            "SELECT {} FROM users a WHERE a.id = $1 AND a.deleted_at IS NULL",
+            USER_COLUMNS
         ),
+        [id],
+        user_from_row,
     )?;
 
-    tx.commit()?;
+    full_user(conn, user)
+}
 
-    Ok(user_role)
+fn user_from_row(row: &Row) -> rusqlite::Result<User> {
+    Ok(User {
+        id: row.get(0)?,
+        username: row.get(1)?,
+        display_name: row.get(2)?,
+        email: row.get(3)?,
+        membership: Membership {
+            status: row.get(4)?,
+            membership_expires_at: row.get(5)?,
+            suspension_reason: row.get(6)?,
+            suspended_until: row.get(7)?,
+        },
+        deleted_at: row.get(8)?,
+    })
 }
 
-pub async fn delete_user_role_from_db(State(state): State<AppState>, id: Uuid) -> Result<()> {
-    state
-        .db_pool
-        .get()
-        .unwrap()
-        .execute("DELETE FROM user_roles WHERE id = $1", [id])?;
+// Retrieves every role the user holds, including expired ones, to make up the full user
+fn full_user(conn: &Connection, user: User) -> rusqlite::Result<FullUser> {
+    let mut stmt = conn.prepare(
+        "SELECT c.id, c.name, c.num_borrowable_books, c.loan_period_days, c.max_renewals,
+        c.fine_per_day_cents, c.fine_cap_cents, c.permissions, b.expires_at
+        FROM map_users_to_user_roles b
+        JOIN user_roles c ON b.user_role_id = c.id
+        WHERE b.user_id = $1
+        ORDER BY c.name",
+    )?;
+
+    let user_roles = stmt
+        .query_map([user.id], |row| {
+            Ok(AssignedUserRole {
+                user_role: user_role_from_row(row)?,
+                expires_at: row.get(8)?,
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut membership = user.membership;
+    membership.refresh_status(Utc::now());
 
-    Ok(())
+    Ok(FullUser {
+        id: user.id,
+        username: user.username,
+        display_name: user.display_name,
+        email: user.email,
+        membership,
+        user_roles,
+        deleted_at: user.deleted_at,
+    })
 }
 
-pub fn is_username_valid_in_db(
-    State(state): &State<AppState>,
-    username: &String,
-) -> Result<bool, rusqlite::Error> {
-    match state.db_pool.get().unwrap().query_row::<i32, _, _>(
-        "SELECT COUNT(*) FROM users WHERE username = $1",
-        [username],
-        |row| row.get(0),
-    ) {
-        Ok(count) => Ok(count == 0),
-        Err(err) => Err(err),
-    }
+fn fetch_user_role(conn: &Connection, id: Uuid) -> rusqlite::Result<UserRole> {
+    conn.query_row(
+        "SELECT id, name, num_borrowable_books, loan_period_days, max_renewals, fine_per_day_cents, fine_cap_cents, permissions FROM user_roles WHERE user_roles.id = $1",
+        [id],
+        user_role_from_row,
+    )
+}
+
+fn user_role_from_row(row: &Row) -> rusqlite::Result<UserRole> {
+    Ok(UserRole {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        num_borrowable_books: row.get(2)?,
+        loan_period_days: row.get(3)?,
+        max_renewals: row.get(4)?,
+        fine_per_day_cents: row.get(5)?,
+        fine_cap_cents: row.get(6)?,
+        permissions: split_permissions(&row.get::<_, String>(7)?),
+    })
 }
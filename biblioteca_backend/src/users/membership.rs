@@ -0,0 +1,91 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::post,
+    Json, Router,
+};
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{app::AppState, error::Error, repository::RepositoryError};
+
+use super::model::{SuspendUserRequest, UserStatus};
+
+pub fn membership_router() -> Router<AppState> {
+    Router::new()
+        .route("/users/:id/suspend", post(suspend_user))
+        .route("/users/:id/reinstate", post(reinstate_user))
+}
+
+// Suspends a user, either until they are reinstated or until the given time
+async fn suspend_user(
+    state: State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<SuspendUserRequest>,
+) -> Result<StatusCode, Error> {
+    tracing::debug!("POST /users/:id/suspend with id: {:?}", id);
+
+    if !state.users.is_user_exists(id).unwrap() {
+        return Err(Error::not_found());
+    }
+
+    if payload.reason.trim().is_empty() {
+        return Err(Error::bad_request(
+            "suspension must have a reason".to_string(),
+        ));
+    }
+
+    if payload.until.is_some_and(|until| until <= Utc::now()) {
+        return Err(Error::bad_request(
+            "end of suspension must be in the future".to_string(),
+        ));
+    }
+
+    match state.users.set_user_status(
+        id,
+        UserStatus::Suspended,
+        Some(payload.reason),
+        payload.until,
+    ) {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
+
+// Makes a suspended or pending user active again. Users whose membership has expired need a
+// new expiry instead.
+async fn reinstate_user(state: State<AppState>, Path(id): Path<Uuid>) -> Result<StatusCode, Error> {
+    tracing::debug!("POST /users/:id/reinstate with id: {:?}", id);
+
+    let user = match state.users.get_user(id) {
+        Ok(user) => user,
+        Err(RepositoryError::NotFound) => return Err(Error::not_found()),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            return Err(Error::server_issue());
+        }
+    };
+
+    if !matches!(
+        user.membership.status,
+        UserStatus::Suspended | UserStatus::Pending
+    ) {
+        return Err(Error::bad_request(
+            "user is not suspended or pending".to_string(),
+        ));
+    }
+
+    match state
+        .users
+        .set_user_status(id, UserStatus::Active, None, None)
+    {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(err) => {
+            tracing::warn!("{}", err);
+            Err(Error::server_issue())
+        }
+    }
+}
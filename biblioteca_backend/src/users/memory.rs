@@ -0,0 +1,451 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    audit::{
+        context::AuditContext,
+        model::{AuditAction, AuditEntry, EntityType},
+    },
+    library::memory::{cancel_holds_of_user, is_link_active},
+    query::{ListParams, Page},
+    repository::{
+        memory::{contains_term, paginate, MemoryData, UserRoleLink},
+        MemoryRepository, RepositoryError, Result,
+    },
+};
+
+use super::{
+    model::{AssignedUserRole, FullUser, User, UserRole, UserStatus},
+    repository::UserRepository,
+};
+
+impl UserRepository for MemoryRepository {
+    fn list_users(&self, params: &ListParams) -> Result<Page<FullUser>> {
+        let data = self.data();
+
+        let users = data
+            .users
+            .iter()
+            .filter(|user| params.include_deleted() || user.deleted_at.is_none())
+            .map(|user| full_user(&data, user))
+            .filter(|user| {
+                contains_term(&user.username, params.filters.get("username"))
+                    && (params.filters.get("role").is_none()
+                        || user.user_roles.iter().any(|assigned| {
+                            contains_term(&assigned.user_role.name, params.filters.get("role"))
+                        }))
+            })
+            .collect();
+
+        // Users are sorted by the first of their role names
+        Ok(paginate(users, params, |a, b, field| match field {
+            "username" => a.username.cmp(&b.username),
+            _ => first_role_name(a).cmp(&first_role_name(b)),
+        }))
+    }
+
+    fn get_user(&self, id: Uuid) -> Result<FullUser> {
+        find_user(&self.data(), id).ok_or(RepositoryError::NotFound)
+    }
+
+    fn add_user(
+        &self,
+        user: User,
+        user_role_id: Uuid,
+        password_hash: Option<&str>,
+    ) -> Result<User> {
+        let mut data = self.data();
+
+        if data
+            .users
+            .iter()
+            .any(|existing| existing.username == user.username)
+        {
+            return Err(RepositoryError::Backend(
+                "username must be unique".to_string(),
+            ));
+        }
+
+        data.users.push(user.clone());
+        data.user_role_links.push(UserRoleLink {
+            user_id: user.id,
+            user_role_id,
+            expires_at: None,
+        });
+        if let Some(password_hash) = password_hash {
+            data.password_hashes
+                .insert(user.id, password_hash.to_string());
+        }
+
+        let after = find_user(&data, user.id);
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Create,
+            EntityType::User,
+            user.id,
+            None,
+            after.as_ref(),
+        ));
+
+        Ok(user)
+    }
+
+    fn update_user(&self, user: User) -> Result<()> {
+        self.change_user(user.id, |data| {
+            if data
+                .users
+                .iter()
+                .any(|existing| existing.username == user.username && existing.id != user.id)
+            {
+                return Err(RepositoryError::Backend(
+                    "username must be unique".to_string(),
+                ));
+            }
+
+            if let Some(existing) = data
+                .users
+                .iter_mut()
+                .find(|existing| existing.id == user.id)
+            {
+                existing.username = user.username;
+                existing.display_name = user.display_name;
+                existing.email = user.email;
+                existing.membership.membership_expires_at = user.membership.membership_expires_at;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn set_user_status(
+        &self,
+        id: Uuid,
+        status: UserStatus,
+        suspension_reason: Option<String>,
+        suspended_until: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        self.change_user(id, |data| {
+            if let Some(user) = data.users.iter_mut().find(|user| user.id == id) {
+                user.membership.status = status;
+                user.membership.suspension_reason = suspension_reason;
+                user.membership.suspended_until = suspended_until;
+            }
+
+            Ok(())
+        })
+    }
+
+    fn grant_user_role(
+        &self,
+        user_id: Uuid,
+        user_role_id: Uuid,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        self.change_user(user_id, |data| {
+            match data
+                .user_role_links
+                .iter_mut()
+                .find(|link| link.user_id == user_id && link.user_role_id == user_role_id)
+            {
+                Some(link) => link.expires_at = expires_at,
+                None => data.user_role_links.push(UserRoleLink {
+                    user_id,
+                    user_role_id,
+                    expires_at,
+                }),
+            }
+
+            Ok(())
+        })
+    }
+
+    fn revoke_user_role(&self, user_id: Uuid, user_role_id: Uuid) -> Result<()> {
+        self.change_user(user_id, |data| {
+            let num_links = data.user_role_links.len();
+            data.user_role_links
+                .retain(|link| !(link.user_id == user_id && link.user_role_id == user_role_id));
+
+            if data.user_role_links.len() == num_links {
+                return Err(RepositoryError::NotFound);
+            }
+
+            Ok(())
+        })
+    }
+
+    fn delete_user(&self, id: Uuid, hold_pickup_days: u32) -> Result<()> {
+        let mut data = self.data();
+
+        if let Some(user) = find_user(&data, id) {
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Delete,
+                EntityType::User,
+                id,
+                Some(&user),
+                None,
+            ));
+
+            if let Some(existing) = data.users.iter_mut().find(|user| user.id == id) {
+                existing.deleted_at = Some(Utc::now());
+            }
+            data.sessions.retain(|session| session.user_id != id);
+            cancel_holds_of_user(&mut data, id, hold_pickup_days);
+        }
+
+        Ok(())
+    }
+
+    fn restore_user(&self, id: Uuid) -> Result<FullUser> {
+        let mut data = self.data();
+
+        let existing = data
+            .users
+            .iter_mut()
+            .find(|user| user.id == id && user.deleted_at.is_some())
+            .ok_or(RepositoryError::NotFound)?;
+        existing.deleted_at = None;
+
+        let user = find_user(&data, id).ok_or(RepositoryError::NotFound)?;
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Restore,
+            EntityType::User,
+            id,
+            None,
+            Some(&user),
+        ));
+
+        Ok(user)
+    }
+
+    fn purge_deleted_users(&self, deleted_before: DateTime<Utc>) -> Result<u32> {
+        let mut data = self.data();
+
+        let users = data
+            .users
+            .iter()
+            .filter(|user| {
+                user.deleted_at
+                    .is_some_and(|deleted_at| deleted_at < deleted_before)
+                    && !data
+                        .borrow_entries
+                        .iter()
+                        .any(|entry| entry.user_id == user.id)
+                    && !data.holds.iter().any(|hold| hold.user_id == user.id)
+                    && !data.fines.iter().any(|entry| entry.user_id == user.id)
+            })
+            .map(|user| full_user(&data, user))
+            .collect::<Vec<_>>();
+
+        for user in users.iter() {
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Purge,
+                EntityType::User,
+                user.id,
+                Some(user),
+                None,
+            ));
+
+            data.users.retain(|existing| existing.id != user.id);
+            data.user_role_links.retain(|link| link.user_id != user.id);
+            data.password_hashes.remove(&user.id);
+        }
+
+        Ok(users.len() as u32)
+    }
+
+    fn is_user_exists(&self, id: Uuid) -> Result<bool> {
+        Ok(self
+            .data()
+            .users
+            .iter()
+            .any(|user| user.id == id && user.deleted_at.is_none()))
+    }
+
+    fn is_username_valid(&self, username: &str, user_id: Option<Uuid>) -> Result<bool> {
+        Ok(!self
+            .data()
+            .users
+            .iter()
+            .any(|user| user.username == username && Some(user.id) != user_id))
+    }
+
+    fn list_user_roles(&self, params: &ListParams) -> Result<Page<UserRole>> {
+        let user_roles = self
+            .data()
+            .user_roles
+            .iter()
+            .filter(|user_role| contains_term(&user_role.name, params.filters.get("name")))
+            .cloned()
+            .collect();
+
+        Ok(paginate(user_roles, params, |a, b, field| match field {
+            "name" => a.name.cmp(&b.name),
+            "num_borrowable_books" => a.num_borrowable_books.cmp(&b.num_borrowable_books),
+            _ => a.loan_period_days.cmp(&b.loan_period_days),
+        }))
+    }
+
+    fn get_user_role(&self, id: Uuid) -> Result<UserRole> {
+        self.data()
+            .user_roles
+            .iter()
+            .find(|user_role| user_role.id == id)
+            .cloned()
+            .ok_or(RepositoryError::NotFound)
+    }
+
+    fn add_user_role(&self, user_role: UserRole) -> Result<UserRole> {
+        let mut data = self.data();
+
+        data.user_roles.push(user_role.clone());
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Create,
+            EntityType::UserRole,
+            user_role.id,
+            None,
+            Some(&user_role),
+        ));
+
+        Ok(user_role)
+    }
+
+    fn update_user_role(&self, user_role: UserRole) -> Result<()> {
+        let mut data = self.data();
+
+        if let Some(existing) = data
+            .user_roles
+            .iter_mut()
+            .find(|existing| existing.id == user_role.id)
+        {
+            let before = std::mem::replace(existing, user_role.clone());
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Update,
+                EntityType::UserRole,
+                user_role.id,
+                Some(&before),
+                Some(&user_role),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn delete_user_role(&self, id: Uuid) -> Result<()> {
+        let mut data = self.data();
+
+        if let Some(user_role) = data
+            .user_roles
+            .iter()
+            .find(|user_role| user_role.id == id)
+            .cloned()
+        {
+            data.audit_entries.push(AuditEntry::new(
+                &AuditContext::current(),
+                AuditAction::Delete,
+                EntityType::UserRole,
+                id,
+                Some(&user_role),
+                None,
+            ));
+        }
+
+        data.user_roles.retain(|user_role| user_role.id != id);
+        data.user_role_links.retain(|link| link.user_role_id != id);
+
+        Ok(())
+    }
+
+    fn is_only_user_role_of_any_user(&self, id: Uuid) -> Result<bool> {
+        let data = self.data();
+        let now = Utc::now();
+
+        Ok(data
+            .user_role_links
+            .iter()
+            .filter(|link| link.user_role_id == id)
+            .any(|link| {
+                !data.user_role_links.iter().any(|other| {
+                    other.user_id == link.user_id
+                        && other.user_role_id != id
+                        && is_link_active(other, now)
+                })
+            }))
+    }
+}
+
+impl MemoryRepository {
+    // Makes a change to the user, recording the user before and after it. Nothing is recorded
+    // if the change fails.
+    fn change_user(
+        &self,
+        id: Uuid,
+        change: impl FnOnce(&mut MemoryData) -> Result<()>,
+    ) -> Result<()> {
+        let mut data = self.data();
+
+        let before = find_user(&data, id).ok_or(RepositoryError::NotFound)?;
+        change(&mut data)?;
+        let after = find_user(&data, id);
+
+        data.audit_entries.push(AuditEntry::new(
+            &AuditContext::current(),
+            AuditAction::Update,
+            EntityType::User,
+            id,
+            Some(&before),
+            after.as_ref(),
+        ));
+
+        Ok(())
+    }
+}
+
+fn find_user(data: &MemoryData, id: Uuid) -> Option<FullUser> {
+    data.users
+        .iter()
+        .find(|user| user.id == id && user.deleted_at.is_none())
+        .map(|user| full_user(data, user))
+}
+
+// Makes up the full user from every role they hold, including expired ones, sorted by name
+fn full_user(data: &MemoryData, user: &User) -> FullUser {
+    let mut user_roles = data
+        .user_role_links
+        .iter()
+        .filter(|link| link.user_id == user.id)
+        .filter_map(|link| {
+            data.user_roles
+                .iter()
+                .find(|user_role| user_role.id == link.user_role_id)
+                .map(|user_role| AssignedUserRole {
+                    user_role: user_role.clone(),
+                    expires_at: link.expires_at,
+                })
+        })
+        .collect::<Vec<_>>();
+    user_roles.sort_by(|a, b| a.user_role.name.cmp(&b.user_role.name));
+
+    let mut membership = user.membership.clone();
+    membership.refresh_status(Utc::now());
+
+    FullUser {
+        id: user.id,
+        username: user.username.clone(),
+        display_name: user.display_name.clone(),
+        email: user.email.clone(),
+        membership,
+        user_roles,
+        deleted_at: user.deleted_at,
+    }
+}
+
+fn first_role_name(user: &FullUser) -> Option<&str> {
+    user.user_roles
+        .first()
+        .map(|assigned| assigned.user_role.name.as_str())
+}
@@ -1,4 +1,9 @@
 pub mod controller;
+pub mod membership;
 pub mod model;
+pub mod repository;
 
 mod db;
+mod memory;
+#[cfg(feature = "postgres")]
+mod postgres;
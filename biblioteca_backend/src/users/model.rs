@@ -1,34 +1,324 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: Uuid,
     pub username: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(flatten)]
+    pub membership: Membership,
+    // Set once the user is deleted, until they are restored or purged
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+// Standing of a user with the library, which decides whether they may borrow, renew and place
+// holds
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Membership {
+    #[serde(default)]
+    pub status: UserStatus,
+    // Membership runs out at this time, if ever, unless it is renewed before then
+    #[serde(default)]
+    pub membership_expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub suspension_reason: Option<String>,
+    // Suspension is lifted at this time, or only once the user is reinstated if not given
+    #[serde(default)]
+    pub suspended_until: Option<DateTime<Utc>>,
+}
+
+impl Membership {
+    // Brings the status up to date with the time, lifting a suspension that has ended and
+    // expiring a membership that has run out
+    pub fn refresh_status(&mut self, now: DateTime<Utc>) {
+        if self.status == UserStatus::Suspended
+            && self.suspended_until.is_some_and(|until| until <= now)
+        {
+            self.status = UserStatus::Active;
+            self.suspension_reason = None;
+            self.suspended_until = None;
+        }
+
+        // Memberships are active again once they are renewed
+        let has_expired = self
+            .membership_expires_at
+            .is_some_and(|expires_at| expires_at <= now);
+        match self.status {
+            UserStatus::Active if has_expired => self.status = UserStatus::Expired,
+            UserStatus::Expired if !has_expired => self.status = UserStatus::Active,
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserStatus {
+    #[default]
+    Active,
+    Suspended,
+    Expired,
+    // Signed up, but not let in by staff yet
+    Pending,
+}
+
+impl Display for UserStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UserStatus::Active => write!(f, "active"),
+            UserStatus::Suspended => write!(f, "suspended"),
+            UserStatus::Expired => write!(f, "expired"),
+            UserStatus::Pending => write!(f, "pending"),
+        }
+    }
+}
+
+impl ToSql for UserStatus {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.to_string().into())
+    }
+}
+
+impl FromStr for UserStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(UserStatus::Active),
+            "suspended" => Ok(UserStatus::Suspended),
+            "expired" => Ok(UserStatus::Expired),
+            "pending" => Ok(UserStatus::Pending),
+            _ => Err(format!("{} is not a known value", s)),
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl FromSql for UserStatus {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|_| FromSqlError::InvalidType)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserRole {
     pub id: Uuid,
     pub name: String,
     pub num_borrowable_books: i32,
+    pub loan_period_days: i32,
+    pub max_renewals: i32,
+    pub fine_per_day_cents: i32,
+    pub fine_cap_cents: i32,
+
+    // What users given the role may do besides reading the catalog and managing their own loans
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Permission {
+    // Adding, updating and deleting books, authors, categories and copies
+    #[serde(rename = "catalog:write")]
+    CatalogWrite,
+    // Managing users and their roles
+    #[serde(rename = "users:admin")]
+    UsersAdmin,
+    // Lending, renewing and taking back books for other users, and managing their holds and fines
+    #[serde(rename = "circulation:staff")]
+    CirculationStaff,
+    // Reading the audit log of every change made to the library
+    #[serde(rename = "audit:read")]
+    AuditRead,
+}
+
+impl Permission {
+    pub const ALL: &'static [Permission] = &[
+        Permission::CatalogWrite,
+        Permission::UsersAdmin,
+        Permission::CirculationStaff,
+        Permission::AuditRead,
+    ];
+}
+
+impl Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Permission::CatalogWrite => write!(f, "catalog:write"),
+            Permission::UsersAdmin => write!(f, "users:admin"),
+            Permission::CirculationStaff => write!(f, "circulation:staff"),
+            Permission::AuditRead => write!(f, "audit:read"),
+        }
+    }
+}
+
+impl FromStr for Permission {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "catalog:write" => Ok(Permission::CatalogWrite),
+            "users:admin" => Ok(Permission::UsersAdmin),
+            "circulation:staff" => Ok(Permission::CirculationStaff),
+            "audit:read" => Ok(Permission::AuditRead),
+            _ => Err(format!("{} is not a known value", s)),
+        }
+    }
+}
+
+// Joins the permissions of a role into the comma-separated form they are stored in
+pub fn join_permissions(permissions: &[Permission]) -> String {
+    permissions
+        .iter()
+        .map(|permission| permission.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// Splits the stored permissions of a role, leaving out any that are no longer known
+pub fn split_permissions(permissions: &str) -> Vec<Permission> {
+    permissions
+        .split(',')
+        .filter_map(|permission| permission.parse().ok())
+        .collect()
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateUserRequest {
     pub username: String,
     pub user_role_id: Uuid,
+
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+
+    // Password the user logs in with, without which the user cannot log in until given one
+    #[serde(default)]
+    pub password: Option<String>,
+
+    // Users are either let in right away or left pending until staff reinstate them
+    #[serde(default)]
+    pub status: UserStatus,
+    #[serde(default)]
+    pub membership_expires_at: Option<DateTime<Utc>>,
+}
+
+// Replaces the username, profile and membership expiry of a user, clearing the fields left out
+#[derive(Debug, Deserialize)]
+pub struct UpdateUserRequest {
+    pub username: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub membership_expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SuspendUserRequest {
+    pub reason: String,
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+}
+
+// Gives a user a role, or changes when a role the user already holds expires
+#[derive(Debug, Deserialize)]
+pub struct GrantUserRoleRequest {
+    pub user_role_id: Uuid,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateUserRoleRequest {
     pub name: String,
     pub num_borrowable_books: i32,
+    // Loan policies left out fall back to the defaults of the library
+    pub loan_period_days: Option<i32>,
+    pub max_renewals: Option<i32>,
+    pub fine_per_day_cents: Option<i32>,
+    pub fine_cap_cents: Option<i32>,
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize)]
+pub struct UpdateUserRoleRequest {
+    pub name: String,
+    pub num_borrowable_books: i32,
+    pub loan_period_days: i32,
+    pub max_renewals: i32,
+    pub fine_per_day_cents: i32,
+    pub fine_cap_cents: i32,
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct FullUser {
     pub id: Uuid,
     pub username: String,
+    #[serde(default)]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(flatten)]
+    pub membership: Membership,
+    pub user_roles: Vec<AssignedUserRole>,
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl FullUser {
+    // Roles of the user that have not expired, which are the ones that count towards what the
+    // user may do
+    pub fn active_user_roles(&self, now: DateTime<Utc>) -> impl Iterator<Item = &UserRole> {
+        self.user_roles
+            .iter()
+            .filter(move |assigned| assigned.is_active(now))
+            .map(|assigned| &assigned.user_role)
+    }
+
+    // Permissions given by any of the user's roles that have not expired
+    pub fn permissions(&self, now: DateTime<Utc>) -> Vec<Permission> {
+        let mut permissions = Vec::new();
+
+        for permission in self
+            .active_user_roles(now)
+            .flat_map(|user_role| user_role.permissions.iter())
+        {
+            if !permissions.contains(permission) {
+                permissions.push(*permission);
+            }
+        }
+
+        permissions
+    }
+}
+
+// A role held by a user, optionally only until it expires
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AssignedUserRole {
+    #[serde(flatten)]
     pub user_role: UserRole,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl AssignedUserRole {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
 }
@@ -0,0 +1,629 @@
+use chrono::{DateTime, Utc};
+use postgres::{GenericClient, Row, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    audit::{
+        context::AuditContext,
+        model::{AuditAction, AuditEntry, EntityType},
+        postgres::add_audit_entry,
+    },
+    library::postgres::cancel_holds_of_user,
+    query::{ListParams, Page},
+    repository::{
+        like_pattern, postgres::QueryBuilder, PostgresRepository, RepositoryError, Result,
+    },
+};
+
+use super::{
+    model::{
+        join_permissions, split_permissions, AssignedUserRole, FullUser, Membership, User,
+        UserRole, UserStatus,
+    },
+    repository::UserRepository,
+};
+
+const USER_COLUMNS: &str = "a.id, a.username, a.display_name, a.email, a.status,
+    a.membership_expires_at, a.suspension_reason, a.suspended_until, a.deleted_at";
+
+const USER_ROLE_COLUMNS: &str = "id, name, num_borrowable_books, loan_period_days, max_renewals,
+    fine_per_day_cents, fine_cap_cents, permissions";
+
+// Columns of the sort fields of users and user roles, compared byte by byte like SQLite does,
+// where users are sorted by the first of their role names
+const USER_SORT_COLUMNS: &[(&str, &str)] = &[
+    ("username", "a.username COLLATE \"C\""),
+    (
+        "role",
+        "(SELECT MIN(c.name COLLATE \"C\") FROM map_users_to_user_roles b
+        JOIN user_roles c ON b.user_role_id = c.id
+        WHERE b.user_id = a.id)",
+    ),
+];
+const USER_ROLE_SORT_COLUMNS: &[(&str, &str)] = &[
+    ("name", "name COLLATE \"C\""),
+    ("num_borrowable_books", "num_borrowable_books"),
+    ("loan_period_days", "loan_period_days"),
+];
+
+impl UserRepository for PostgresRepository {
+    fn list_users(&self, params: &ListParams) -> Result<Page<FullUser>> {
+        self.with_client(|client| {
+            let mut query = QueryBuilder::new(&format!("SELECT {} FROM users a", USER_COLUMNS));
+            query.contains("a.username", params.filters.get("username"));
+            if let Some(role) = params.filters.get("role") {
+                query.condition(
+                    "EXISTS (SELECT 1 FROM map_users_to_user_roles b
+                    JOIN user_roles c ON b.user_role_id = c.id
+                    WHERE b.user_id = a.id AND c.name ILIKE ? ESCAPE '\\')",
+                    like_pattern(role),
+                );
+            }
+            query.exclude_deleted("a.deleted_at", params).paginate(
+                params,
+                USER_SORT_COLUMNS,
+                "a.rowid",
+            );
+
+            let users = client
+                .query(&query.sql(), &query.params())?
+                .iter()
+                .map(user_from_row)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let users = users
+                .into_iter()
+                .map(|user| full_user(client, user))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Page::new(users, query.count(client)?, params))
+        })
+    }
+
+    fn get_user(&self, id: Uuid) -> Result<FullUser> {
+        self.with_client(|client| fetch_user(client, id)?.ok_or(RepositoryError::NotFound))
+    }
+
+    fn add_user(
+        &self,
+        user: User,
+        user_role_id: Uuid,
+        password_hash: Option<&str>,
+    ) -> Result<User> {
+        // Read before handing over to the client, which runs outside of the request task
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            // Add the user itself
+            tx.execute(
+                "INSERT INTO users (id, username, display_name, email, status, membership_expires_at, suspension_reason, suspended_until, password_hash)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+                &[
+                    &user.id,
+                    &user.username,
+                    &user.display_name,
+                    &user.email,
+                    &user.membership.status,
+                    &user.membership.membership_expires_at,
+                    &user.membership.suspension_reason,
+                    &user.membership.suspended_until,
+                    &password_hash,
+                ],
+            )?;
+
+            // Add the user's role association
+            tx.execute(
+                "INSERT INTO map_users_to_user_roles (user_id, user_role_id) VALUES ($1, $2)",
+                &[&user.id, &user_role_id],
+            )?;
+
+            let after = fetch_user(&mut tx, user.id)?.ok_or(RepositoryError::NotFound)?;
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Create,
+                    EntityType::User,
+                    user.id,
+                    None,
+                    Some(&after),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(user)
+        })
+    }
+
+    fn update_user(&self, user: User) -> Result<()> {
+        self.change_user(user.id, |tx| {
+            tx.execute(
+                "UPDATE users SET username = $1, display_name = $2, email = $3, membership_expires_at = $4
+                WHERE id = $5",
+                &[
+                    &user.username,
+                    &user.display_name,
+                    &user.email,
+                    &user.membership.membership_expires_at,
+                    &user.id,
+                ],
+            )?;
+
+            Ok(())
+        })
+    }
+
+    fn set_user_status(
+        &self,
+        id: Uuid,
+        status: UserStatus,
+        suspension_reason: Option<String>,
+        suspended_until: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        self.change_user(id, |tx| {
+            tx.execute(
+                "UPDATE users SET status = $1, suspension_reason = $2, suspended_until = $3 WHERE id = $4",
+                &[&status, &suspension_reason, &suspended_until, &id],
+            )?;
+
+            Ok(())
+        })
+    }
+
+    fn grant_user_role(
+        &self,
+        user_id: Uuid,
+        user_role_id: Uuid,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        self.change_user(user_id, |tx| {
+            tx.execute(
+                "INSERT INTO map_users_to_user_roles (user_id, user_role_id, expires_at) VALUES ($1, $2, $3)
+                ON CONFLICT (user_id, user_role_id) DO UPDATE SET expires_at = excluded.expires_at",
+                &[&user_id, &user_role_id, &expires_at],
+            )?;
+
+            Ok(())
+        })
+    }
+
+    fn revoke_user_role(&self, user_id: Uuid, user_role_id: Uuid) -> Result<()> {
+        self.change_user(user_id, |tx| {
+            let num_deleted = tx.execute(
+                "DELETE FROM map_users_to_user_roles WHERE user_id = $1 AND user_role_id = $2",
+                &[&user_id, &user_role_id],
+            )?;
+
+            if num_deleted == 0 {
+                return Err(RepositoryError::NotFound);
+            }
+
+            Ok(())
+        })
+    }
+
+    fn delete_user(&self, id: Uuid, hold_pickup_days: u32) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            if let Some(user) = fetch_user(&mut tx, id)? {
+                tx.execute(
+                    "UPDATE users SET deleted_at = $1 WHERE id = $2",
+                    &[&Utc::now(), &id],
+                )?;
+                tx.execute("DELETE FROM sessions WHERE user_id = $1", &[&id])?;
+                cancel_holds_of_user(&mut tx, &context, id, hold_pickup_days)?;
+
+                add_audit_entry(
+                    &mut tx,
+                    &AuditEntry::new(
+                        &context,
+                        AuditAction::Delete,
+                        EntityType::User,
+                        id,
+                        Some(&user),
+                        None,
+                    ),
+                )?;
+            }
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn restore_user(&self, id: Uuid) -> Result<FullUser> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            let num_restored = tx.execute(
+                "UPDATE users SET deleted_at = NULL WHERE id = $1 AND deleted_at IS NOT NULL",
+                &[&id],
+            )?;
+
+            if num_restored == 0 {
+                return Err(RepositoryError::NotFound);
+            }
+
+            let user = fetch_user(&mut tx, id)?.ok_or(RepositoryError::NotFound)?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Restore,
+                    EntityType::User,
+                    id,
+                    None,
+                    Some(&user),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(user)
+        })
+    }
+
+    fn purge_deleted_users(&self, deleted_before: DateTime<Utc>) -> Result<u32> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            let users = tx
+                .query(
+                    &format!(
+                        "SELECT {} FROM users a WHERE a.deleted_at < $1
+                AND NOT EXISTS (SELECT 1 FROM map_users_to_borrowed_books b WHERE b.user_id = a.id)
+                AND NOT EXISTS (SELECT 1 FROM holds b WHERE b.user_id = a.id)
+                AND NOT EXISTS (SELECT 1 FROM fines b WHERE b.user_id = a.id)",
+                        USER_COLUMNS
+                    ),
+                    &[&deleted_before],
+                )?
+                .iter()
+                .map(user_from_row)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let users = users
+                .into_iter()
+                .map(|user| full_user(&mut tx, user))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            for user in users.iter() {
+                tx.execute("DELETE FROM users WHERE id = $1", &[&user.id])?;
+
+                add_audit_entry(
+                    &mut tx,
+                    &AuditEntry::new(
+                        &context,
+                        AuditAction::Purge,
+                        EntityType::User,
+                        user.id,
+                        Some(user),
+                        None,
+                    ),
+                )?;
+            }
+
+            tx.commit()?;
+
+            Ok(users.len() as u32)
+        })
+    }
+
+    fn is_user_exists(&self, id: Uuid) -> Result<bool> {
+        self.with_client(|client| {
+            let count: i64 = client
+                .query_one(
+                    "SELECT COUNT(*) FROM users WHERE id = $1 AND deleted_at IS NULL",
+                    &[&id],
+                )?
+                .try_get(0)?;
+
+            Ok(count == 1)
+        })
+    }
+
+    fn is_username_valid(&self, username: &str, user_id: Option<Uuid>) -> Result<bool> {
+        self.with_client(|client| {
+            let count: i64 = client
+                .query_one(
+                    "SELECT COUNT(*) FROM users WHERE username = $1 AND id IS DISTINCT FROM $2",
+                    &[&username, &user_id],
+                )?
+                .try_get(0)?;
+
+            Ok(count == 0)
+        })
+    }
+
+    fn list_user_roles(&self, params: &ListParams) -> Result<Page<UserRole>> {
+        self.with_client(|client| {
+            let mut query =
+                QueryBuilder::new(&format!("SELECT {} FROM user_roles", USER_ROLE_COLUMNS));
+            query.contains("name", params.filters.get("name")).paginate(
+                params,
+                USER_ROLE_SORT_COLUMNS,
+                "rowid",
+            );
+
+            let user_roles = client
+                .query(&query.sql(), &query.params())?
+                .iter()
+                .map(user_role_from_row)
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Ok(Page::new(user_roles, query.count(client)?, params))
+        })
+    }
+
+    fn get_user_role(&self, id: Uuid) -> Result<UserRole> {
+        self.with_client(|client| fetch_user_role(client, id)?.ok_or(RepositoryError::NotFound))
+    }
+
+    fn add_user_role(&self, user_role: UserRole) -> Result<UserRole> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            tx.execute(
+                "INSERT INTO user_roles (id, name, num_borrowable_books, loan_period_days, max_renewals, fine_per_day_cents, fine_cap_cents, permissions) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+                &[
+                    &user_role.id,
+                    &user_role.name,
+                    &user_role.num_borrowable_books,
+                    &user_role.loan_period_days,
+                    &user_role.max_renewals,
+                    &user_role.fine_per_day_cents,
+                    &user_role.fine_cap_cents,
+                    &join_permissions(&user_role.permissions),
+                ],
+            )?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Create,
+                    EntityType::UserRole,
+                    user_role.id,
+                    None,
+                    Some(&user_role),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(user_role)
+        })
+    }
+
+    fn update_user_role(&self, user_role: UserRole) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            let Some(before) = fetch_user_role(&mut tx, user_role.id)? else {
+                return Ok(());
+            };
+
+            tx.execute(
+                "UPDATE user_roles
+                SET name = $1, num_borrowable_books = $2, loan_period_days = $3, max_renewals = $4,
+                    fine_per_day_cents = $5, fine_cap_cents = $6, permissions = $7
+                WHERE id = $8",
+                &[
+                    &user_role.name,
+                    &user_role.num_borrowable_books,
+                    &user_role.loan_period_days,
+                    &user_role.max_renewals,
+                    &user_role.fine_per_day_cents,
+                    &user_role.fine_cap_cents,
+                    &join_permissions(&user_role.permissions),
+                    &user_role.id,
+                ],
+            )?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Update,
+                    EntityType::UserRole,
+                    user_role.id,
+                    Some(&before),
+                    Some(&user_role),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn delete_user_role(&self, id: Uuid) -> Result<()> {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            if let Some(user_role) = fetch_user_role(&mut tx, id)? {
+                tx.execute("DELETE FROM user_roles WHERE id = $1", &[&id])?;
+
+                add_audit_entry(
+                    &mut tx,
+                    &AuditEntry::new(
+                        &context,
+                        AuditAction::Delete,
+                        EntityType::UserRole,
+                        id,
+                        Some(&user_role),
+                        None,
+                    ),
+                )?;
+            }
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+
+    fn is_only_user_role_of_any_user(&self, id: Uuid) -> Result<bool> {
+        self.with_client(|client| {
+            let count: i64 = client
+                .query_one(
+                    "SELECT COUNT(*) FROM map_users_to_user_roles a
+                    WHERE a.user_role_id = $1 AND NOT EXISTS (
+                        SELECT 1 FROM map_users_to_user_roles b
+                        WHERE b.user_id = a.user_id AND b.user_role_id != a.user_role_id
+                        AND (b.expires_at IS NULL OR b.expires_at > $2)
+                    )",
+                    &[&id, &Utc::now()],
+                )?
+                .try_get(0)?;
+
+            Ok(count > 0)
+        })
+    }
+}
+
+impl PostgresRepository {
+    // Makes a change to the user within a transaction, recording the user before and after it
+    fn change_user<F>(&self, id: Uuid, change: F) -> Result<()>
+    where
+        F: FnOnce(&mut Transaction) -> Result<()> + Send,
+    {
+        let context = AuditContext::current();
+
+        self.with_client(|client| {
+            let mut tx = client.transaction()?;
+
+            let before = fetch_user(&mut tx, id)?.ok_or(RepositoryError::NotFound)?;
+            change(&mut tx)?;
+            let after = fetch_user(&mut tx, id)?.ok_or(RepositoryError::NotFound)?;
+
+            add_audit_entry(
+                &mut tx,
+                &AuditEntry::new(
+                    &context,
+                    AuditAction::Update,
+                    EntityType::User,
+                    id,
+                    Some(&before),
+                    Some(&after),
+                ),
+            )?;
+
+            tx.commit()?;
+
+            Ok(())
+        })
+    }
+}
+
+fn fetch_user(
+    client: &mut impl GenericClient,
+    id: Uuid,
+) -> Result<Option<FullUser>, postgres::Error> {
+    client
+        .query_opt(
+            &format!(
+                "SELECT {} FROM users a WHERE a.id = $1 AND a.deleted_at IS NULL",
+                USER_COLUMNS
+            ),
+            &[&id],
+        )?
+        .map(|row| full_user(client, user_from_row(&row)?))
+        .transpose()
+}
+
+fn user_from_row(row: &Row) -> Result<User, postgres::Error> {
+    Ok(User {
+        id: row.try_get(0)?,
+        username: row.try_get(1)?,
+        display_name: row.try_get(2)?,
+        email: row.try_get(3)?,
+        membership: Membership {
+            status: row.try_get(4)?,
+            membership_expires_at: row.try_get(5)?,
+            suspension_reason: row.try_get(6)?,
+            suspended_until: row.try_get(7)?,
+        },
+        deleted_at: row.try_get(8)?,
+    })
+}
+
+// Retrieves every role the user holds, including expired ones, to make up the full user
+fn full_user(client: &mut impl GenericClient, user: User) -> Result<FullUser, postgres::Error> {
+    let user_roles = client
+        .query(
+            "SELECT c.id, c.name, c.num_borrowable_books, c.loan_period_days, c.max_renewals,
+            c.fine_per_day_cents, c.fine_cap_cents, c.permissions, b.expires_at
+            FROM map_users_to_user_roles b
+            JOIN user_roles c ON b.user_role_id = c.id
+            WHERE b.user_id = $1
+            ORDER BY c.name COLLATE \"C\"",
+            &[&user.id],
+        )?
+        .iter()
+        .map(|row| {
+            Ok(AssignedUserRole {
+                user_role: user_role_from_row(row)?,
+                expires_at: row.try_get(8)?,
+            })
+        })
+        .collect::<Result<Vec<_>, postgres::Error>>()?;
+
+    let mut membership = user.membership;
+    membership.refresh_status(Utc::now());
+
+    Ok(FullUser {
+        id: user.id,
+        username: user.username,
+        display_name: user.display_name,
+        email: user.email,
+        membership,
+        user_roles,
+        deleted_at: user.deleted_at,
+    })
+}
+
+fn fetch_user_role(
+    client: &mut impl GenericClient,
+    id: Uuid,
+) -> Result<Option<UserRole>, postgres::Error> {
+    client
+        .query_opt(
+            &format!("SELECT {} FROM user_roles WHERE id = $1", USER_ROLE_COLUMNS),
+            &[&id],
+        )?
+        .map(|row| user_role_from_row(&row))
+        .transpose()
+}
+
+fn user_role_from_row(row: &Row) -> Result<UserRole, postgres::Error> {
+    Ok(UserRole {
+        id: row.try_get(0)?,
+        name: row.try_get(1)?,
+        num_borrowable_books: row.try_get(2)?,
+        loan_period_days: row.try_get(3)?,
+        max_renewals: row.try_get(4)?,
+        fine_per_day_cents: row.try_get(5)?,
+        fine_cap_cents: row.try_get(6)?,
+        permissions: split_permissions(row.try_get(7)?),
+    })
+}
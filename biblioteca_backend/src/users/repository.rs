@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    query::{ListParams, ListSpec, Page},
+    repository::Result,
+};
+
+use super::model::{FullUser, User, UserRole, UserStatus};
+
+// Filters and sort fields supported when listing users
+pub const USER_LIST: ListSpec = ListSpec {
+    filters: &["username", "role", "include_deleted"],
+    sort_fields: &["username", "role"],
+};
+
+// Filters and sort fields supported when listing user roles
+pub const USER_ROLE_LIST: ListSpec = ListSpec {
+    filters: &["name"],
+    sort_fields: &["name", "num_borrowable_books", "loan_period_days"],
+};
+
+// Storage of the users of the library and the roles they are given
+pub trait UserRepository: Send + Sync {
+    // Lists the users that are not deleted, unless the list params include them
+    fn list_users(&self, params: &ListParams) -> Result<Page<FullUser>>;
+
+    fn get_user(&self, id: Uuid) -> Result<FullUser>;
+
+    // Adds the user, giving them the role without an expiry, along with the hash of their
+    // password if they were given one
+    fn add_user(&self, user: User, user_role_id: Uuid, password_hash: Option<&str>)
+        -> Result<User>;
+
+    // Updates the username, profile and membership expiry of the user, leaving their status as
+    // it is
+    fn update_user(&self, user: User) -> Result<()>;
+
+    // Sets the status of the user, along with the reason for and end of any suspension
+    fn set_user_status(
+        &self,
+        id: Uuid,
+        status: UserStatus,
+        suspension_reason: Option<String>,
+        suspended_until: Option<DateTime<Utc>>,
+    ) -> Result<()>;
+
+    // Gives the user the role until it expires, if ever, replacing the expiry of a role the user
+    // already holds
+    fn grant_user_role(
+        &self,
+        user_id: Uuid,
+        user_role_id: Uuid,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<()>;
+
+    // Takes the role away from the user, failing if the user does not hold it
+    fn revoke_user_role(&self, user_id: Uuid, user_role_id: Uuid) -> Result<()>;
+
+    // Marks the user as deleted, ends their sessions and cancels their holds, passing the copies
+    // set aside for them on to the next patron. They are kept along with their loans and fines
+    // until they are purged. Deleted users are treated as missing everywhere else, but their
+    // usernames stay taken.
+    fn delete_user(&self, id: Uuid, hold_pickup_days: u32) -> Result<()>;
+
+    // Brings back a deleted user, failing if there is no deleted user with the id
+    fn restore_user(&self, id: Uuid) -> Result<FullUser>;
+
+    // Removes the users deleted before the time for good, skipping the users that loans, holds
+    // or fines still refer to, and returns how many were removed
+    fn purge_deleted_users(&self, deleted_before: DateTime<Utc>) -> Result<u32>;
+
+    fn is_user_exists(&self, id: Uuid) -> Result<bool>;
+
+    // Checks that no other user than the given one already has the username
+    fn is_username_valid(&self, username: &str, user_id: Option<Uuid>) -> Result<bool>;
+
+    fn list_user_roles(&self, params: &ListParams) -> Result<Page<UserRole>>;
+
+    fn get_user_role(&self, id: Uuid) -> Result<UserRole>;
+
+    fn add_user_role(&self, user_role: UserRole) -> Result<UserRole>;
+
+    fn update_user_role(&self, user_role: UserRole) -> Result<()>;
+
+    // Deletes the role, taking it away from the users given it
+    fn delete_user_role(&self, id: Uuid) -> Result<()>;
+
+    // Checks whether any user, deleted or not, holds no other role than the given one
+    fn is_only_user_role_of_any_user(&self, id: Uuid) -> Result<bool>;
+}
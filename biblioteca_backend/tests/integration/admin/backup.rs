@@ -0,0 +1,58 @@
+use std::fs::remove_file;
+
+use biblioteca_backend::database::{
+    backup_db, get_schema_version, latest_schema_version, setup_db, DEFAULT_POOL_SIZE,
+};
+use rusqlite::Connection;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn backup_database_successful() {
+    let database_path = "backup_database_successful.sqlite";
+    let backup_path = "backup_database_successful_backup.sqlite";
+
+    let pool = setup_db(database_path.to_string(), DEFAULT_POOL_SIZE).unwrap();
+    let conn = pool.get().unwrap();
+    conn.execute(
+        "INSERT INTO categories (id, name) VALUES (?1, 'Poetry')",
+        [Uuid::new_v4()],
+    )
+    .unwrap();
+
+    backup_db(&conn, backup_path).unwrap();
+
+    {
+        let backup = Connection::open(backup_path).unwrap();
+        let num_categories: i32 = backup
+            .query_row("SELECT COUNT(*) FROM categories", [], |row| row.get(0))
+            .unwrap();
+
+        assert_eq!(num_categories, 1);
+        assert_eq!(
+            get_schema_version(&backup).unwrap(),
+            latest_schema_version()
+        );
+    }
+
+    remove_file(database_path).unwrap();
+    remove_file(backup_path).unwrap();
+}
+
+#[tokio::test]
+async fn backup_database_existing_file_failure() {
+    let database_path = "backup_database_existing_file_failure.sqlite";
+    let backup_path = "backup_database_existing_file_failure_backup.sqlite";
+
+    let pool = setup_db(database_path.to_string(), DEFAULT_POOL_SIZE).unwrap();
+    std::fs::write(backup_path, "not a database").unwrap();
+
+    let result = backup_db(&pool.get().unwrap(), backup_path);
+
+    {
+        assert!(result.is_err());
+        assert_eq!(std::fs::read(backup_path).unwrap(), b"not a database");
+    }
+
+    remove_file(database_path).unwrap();
+    remove_file(backup_path).unwrap();
+}
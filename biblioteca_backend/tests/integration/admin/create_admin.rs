@@ -0,0 +1,131 @@
+use biblioteca_backend::{
+    admin::{create_admin, set_password, AdminError, ADMIN_ROLE_ID, ADMIN_ROLE_NAME},
+    audit::{
+        model::{ActorKind, AuditAction, AuditFilters},
+        repository::AUDIT_LIST,
+    },
+    query::ListParams,
+    users::model::Permission,
+};
+
+use crate::mocker::{
+    app::create_mock_state,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn create_admin_new_role_successful() {
+    let database_path = "create_admin_new_role_successful.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+    let state = create_mock_state(db);
+
+    let admin = create_admin(&state, "admin", "correct horse").unwrap();
+    let full_admin = state.users.get_user(admin.id).unwrap();
+
+    {
+        assert_eq!(full_admin.username, "admin");
+        assert_eq!(full_admin.user_roles.len(), 1);
+        assert_eq!(full_admin.user_roles[0].user_role.name, ADMIN_ROLE_NAME);
+        assert_eq!(
+            full_admin.user_roles[0].user_role.permissions,
+            Permission::ALL
+        );
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(querier.contains_user(&admin));
+        assert!(querier.has_password(&admin.id));
+        assert!(querier.contains_num_user_roles(1));
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_admin_existing_role_successful() {
+    let database_path = "create_admin_existing_role_successful.sqlite";
+
+    let admin_role = MockUserBase::new_user_role()
+        .id(ADMIN_ROLE_ID)
+        .name(ADMIN_ROLE_NAME.to_string())
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&admin_role)
+        .build();
+    let state = create_mock_state(db);
+
+    let admin = create_admin(&state, "admin", "correct horse").unwrap();
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(querier.contains_num_user_roles(1));
+        assert!(querier.contains_user_user_role_mapping(&admin.id, &admin_role.id));
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_admin_username_taken_failure() {
+    let database_path = "create_admin_username_taken_failure.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .build();
+    let state = create_mock_state(db);
+
+    let result = create_admin(&state, &user.username, "correct horse");
+
+    {
+        assert!(matches!(result, Err(AdminError::Conflict(_))));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(querier.contains_num_users(1));
+        assert!(querier.contains_num_user_roles(1));
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn set_password_successful() {
+    let database_path = "set_password_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .build();
+    let state = create_mock_state(db);
+
+    set_password(&state, &user.username, "correct horse").unwrap();
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(querier.has_password(&user.id));
+    }
+
+    // The change is recorded without the password or its hash
+    let filters = AuditFilters {
+        action: Some(AuditAction::ChangePassword),
+        ..Default::default()
+    };
+    let params =
+        ListParams::parse(&"/audit".parse().unwrap(), Default::default(), &AUDIT_LIST).unwrap();
+    let page = state.audit.list_audit_entries(&filters, &params).unwrap();
+
+    assert_eq!(page.total, 1, "checking if the change was recorded");
+    assert_eq!(page.items[0].entity_id, user.id);
+    assert_eq!(page.items[0].actor_kind, ActorKind::System);
+    assert!(page.items[0].before.is_none() && page.items[0].after.is_none());
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
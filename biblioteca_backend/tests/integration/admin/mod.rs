@@ -0,0 +1,5 @@
+pub mod backup;
+pub mod create_admin;
+pub mod purge;
+pub mod seed;
+pub mod transfer;
@@ -0,0 +1,254 @@
+use biblioteca_backend::{
+    admin::{purge_deleted, PurgeSummary},
+    catalog::model::ItemStatus,
+};
+use chrono::{Duration, Utc};
+
+use crate::mocker::{
+    app::create_mock_state,
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    library::MockLibrary,
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn purge_deleted_past_retention_successful() {
+    let database_path = "purge_deleted_past_retention_successful.sqlite";
+
+    let long_ago = Utc::now() - Duration::days(40);
+    let recently = Utc::now() - Duration::days(1);
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user_a = MockUserBase::new_user().deleted_at(long_ago).build();
+    let user_b = MockUserBase::new_user().deleted_at(recently).build();
+    let author_a = MockCatalog::new_author().deleted_at(long_ago).build();
+    let author_b = MockCatalog::new_author().build();
+    let book_a = MockCatalog::new_book().deleted_at(long_ago).build();
+    let book_b = MockCatalog::new_book().deleted_at(recently).build();
+    let item = MockCatalog::new_item().book_id(book_a.id).build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user_a, &user_role)
+        .with_user(&user_b, &user_role)
+        .with_author(&author_a)
+        .with_author(&author_b)
+        .with_book(&book_a, &author_a.id)
+        .with_book(&book_b, &author_b.id)
+        .with_item(&item)
+        .build();
+    let state = create_mock_state(db);
+
+    let summary = purge_deleted(&state).unwrap();
+
+    {
+        assert_eq!(
+            summary,
+            PurgeSummary {
+                books: 1,
+                authors: 1,
+                users: 1,
+            }
+        );
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            !querier.contains_user(&user_a),
+            "checking if user was purged"
+        );
+        assert!(
+            !querier.contains_user_user_role_mapping(&user_a.id, &user_role.id),
+            "checking if roles of user were purged"
+        );
+        assert!(querier.is_user_deleted(&user_b.id));
+        assert!(
+            !querier.contains_author(&author_a),
+            "checking if author was purged"
+        );
+        assert!(
+            !querier.contains_book(&book_a),
+            "checking if book was purged"
+        );
+        assert!(querier.is_book_deleted(&book_b.id));
+        assert!(
+            !querier.contains_item(&item),
+            "checking if copies of book were purged"
+        );
+        assert!(
+            !querier.contains_book_author_mapping(&book_a.id, &author_a.id),
+            "checking if contributions of book were purged"
+        );
+        assert!(
+            querier.contains_num_audit_entries_of(&book_a.id, 1),
+            "checking if purge was recorded"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn purge_deleted_keeps_referenced_successful() {
+    let database_path = "purge_deleted_keeps_referenced_successful.sqlite";
+
+    let long_ago = Utc::now() - Duration::days(40);
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user_a = MockUserBase::new_user().deleted_at(long_ago).build();
+    let user_b = MockUserBase::new_user().deleted_at(long_ago).build();
+    let user_c = MockUserBase::new_user().build();
+    let author_a = MockCatalog::new_author().deleted_at(long_ago).build();
+    let author_b = MockCatalog::new_author().deleted_at(long_ago).build();
+    let book_a = MockCatalog::new_book().deleted_at(long_ago).build();
+    let book_b = MockCatalog::new_book().deleted_at(long_ago).build();
+    let book_c = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book_a.id).build();
+    let return_entry = MockLibrary::new_return_entry()
+        .user_id(user_a.id)
+        .book_id(book_a.id)
+        .item_id(item.id)
+        .build();
+    let hold = MockLibrary::new_hold()
+        .user_id(user_c.id)
+        .book_id(book_b.id)
+        .build();
+    let fine = MockLibrary::new_fine()
+        .user_id(user_b.id)
+        .amount_cents(150)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user_a, &user_role)
+        .with_user(&user_b, &user_role)
+        .with_user(&user_c, &user_role)
+        .with_author(&author_a)
+        .with_author(&author_b)
+        .with_book(&book_a, &author_a.id)
+        .with_book(&book_b, &author_a.id)
+        .with_book(&book_c, &author_b.id)
+        .with_item(&item)
+        .with_library_entry(&return_entry)
+        .with_hold(&hold)
+        .with_fine_entry(&fine)
+        .build();
+    let state = create_mock_state(db);
+
+    let summary = purge_deleted(&state).unwrap();
+
+    {
+        assert_eq!(summary, PurgeSummary::default());
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_book(&book_a),
+            "checking if book with loans was kept"
+        );
+        assert!(
+            querier.contains_book(&book_b),
+            "checking if book with holds was kept"
+        );
+        assert!(
+            querier.contains_user(&user_a),
+            "checking if user with loans was kept"
+        );
+        assert!(
+            querier.contains_user(&user_b),
+            "checking if user with fines was kept"
+        );
+        assert!(
+            querier.contains_author(&author_b),
+            "checking if author credited on a book was kept"
+        );
+        assert!(querier.contains_book_author_mapping(&book_c.id, &author_b.id));
+        assert!(querier.contains_num_loans(1));
+        assert!(querier.contains_num_holds(1));
+        assert!(querier.has_fine_balance(&user_b.id, 150));
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn purge_deleted_book_with_holds_successful() {
+    let database_path = "purge_deleted_book_with_holds_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::OnHold)
+        .build();
+    let hold = MockLibrary::new_hold()
+        .user_id(user.id)
+        .book_id(book.id)
+        .ready(item.id, Utc::now() + Duration::days(3))
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_hold(&hold)
+        .build();
+    let mut state = create_mock_state(db);
+    state.library_settings.deleted_retention_days = 0;
+
+    state.catalog.delete_book(book.id).unwrap();
+    let summary = purge_deleted(&state).unwrap();
+
+    {
+        assert_eq!(
+            summary,
+            PurgeSummary {
+                books: 1,
+                authors: 0,
+                users: 0,
+            }
+        );
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(!querier.contains_book(&book), "checking if book was purged");
+        assert!(
+            !querier.contains_item(&item),
+            "checking if copies of book were purged"
+        );
+        assert!(
+            querier.contains_num_holds(0),
+            "checking if no holds were left of the book"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn purge_deleted_nothing_deleted_successful() {
+    let database_path = "purge_deleted_nothing_deleted_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .build();
+    let state = create_mock_state(db);
+
+    let summary = purge_deleted(&state).unwrap();
+
+    {
+        assert_eq!(summary, PurgeSummary::default());
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(querier.contains_book(&book));
+        assert!(querier.contains_author(&author));
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,56 @@
+use biblioteca_backend::admin::{seed::seed_library, AdminError};
+
+use crate::mocker::{
+    app::create_mock_state,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn seed_empty_database_successful() {
+    let database_path = "seed_empty_database_successful.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+    let state = create_mock_state(db);
+
+    let summary = seed_library(&state).unwrap();
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(querier.contains_num_categories(summary.categories as i32));
+        assert!(querier.contains_num_authors(summary.authors as i32));
+        assert!(querier.contains_num_books(summary.books as i32));
+        assert!(querier.contains_num_items(summary.items as i32));
+        assert!(querier.contains_num_user_roles(summary.user_roles as i32));
+        assert!(querier.contains_num_users(summary.users as i32));
+        assert!(summary.books > 0 && summary.users > 0);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn seed_non_empty_database_failure() {
+    let database_path = "seed_non_empty_database_failure.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .build();
+    let state = create_mock_state(db);
+
+    let result = seed_library(&state);
+
+    {
+        assert!(matches!(result, Err(AdminError::NotEmpty)));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(querier.contains_num_users(1));
+        assert!(querier.contains_num_books(0));
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,128 @@
+use biblioteca_backend::{
+    admin::{
+        transfer::{export_library, import_library},
+        AdminError,
+    },
+    catalog::model::ItemStatus,
+};
+
+use crate::mocker::{
+    app::create_mock_state,
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn export_import_new_database_successful() {
+    let source_path = "export_import_new_database_successful_source.sqlite";
+    let target_path = "export_import_new_database_successful_target.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let category = MockCatalog::new_category().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::Borrowed)
+        .build();
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+
+    let source_db = MockDatabaseBuilder::create(source_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_category(&category)
+        .with_book_category(&book.id, &category.id)
+        .with_item(&item)
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .build();
+    let target_db = MockDatabaseBuilder::create(target_path.to_string()).build();
+
+    let export = export_library(&create_mock_state(source_db)).unwrap();
+    let summary = import_library(&create_mock_state(target_db), export).unwrap();
+
+    {
+        assert_eq!(summary.books, 1);
+        assert_eq!(summary.users, 1);
+
+        let querier = MockDatabaseQuerier::create(target_path.to_string());
+        assert!(querier.contains_author(&author));
+        assert!(querier.contains_book(&book));
+        assert!(querier.contains_book_author_mapping(&book.id, &author.id));
+        assert!(querier.contains_category(&category));
+        assert!(querier.contains_book_category_mapping(&book.id, &category.id));
+        assert!(querier.contains_user_role(&user_role));
+        assert!(querier.contains_user(&user));
+        assert!(querier.contains_user_user_role_mapping(&user.id, &user_role.id));
+        assert!(
+            querier.is_item_status(&item.id, ItemStatus::Available),
+            "checking if copies out on loan are imported as available"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(source_path.to_string());
+    MockDatabaseBuilder::teardown(target_path.to_string());
+}
+
+#[tokio::test]
+async fn import_existing_records_skipped_successful() {
+    let database_path = "import_existing_records_skipped_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .build();
+    let state = create_mock_state(db);
+
+    let export = export_library(&state).unwrap();
+    let summary = import_library(&state, export).unwrap();
+
+    {
+        assert_eq!(summary, Default::default());
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(querier.contains_num_authors(1));
+        assert!(querier.contains_num_books(1));
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn import_username_taken_failure() {
+    let source_path = "import_username_taken_failure_source.sqlite";
+    let target_path = "import_username_taken_failure_target.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+    let other_user = MockUserBase::new_user()
+        .username(user.username.clone())
+        .build();
+
+    let source_db = MockDatabaseBuilder::create(source_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .build();
+    let target_db = MockDatabaseBuilder::create(target_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&other_user, &user_role)
+        .build();
+
+    let export = export_library(&create_mock_state(source_db)).unwrap();
+    let result = import_library(&create_mock_state(target_db), export);
+
+    {
+        assert!(matches!(result, Err(AdminError::Conflict(_))));
+
+        let querier = MockDatabaseQuerier::create(target_path.to_string());
+        assert!(querier.contains_num_users(1));
+        assert!(querier.contains_user(&other_user));
+    }
+
+    MockDatabaseBuilder::teardown(source_path.to_string());
+    MockDatabaseBuilder::teardown(target_path.to_string());
+}
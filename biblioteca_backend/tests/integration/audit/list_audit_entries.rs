@@ -0,0 +1,687 @@
+use axum::Router;
+use biblioteca_backend::{
+    audit::model::{ActorKind, AuditAction, AuditEntry, EntityType},
+    auth::model::IssuedApiKey,
+    catalog::model::{Category, ItemStatus},
+    library::model::HoldStatus,
+    query::Page,
+    users::model::Permission,
+};
+use chrono::{Duration, Utc};
+use hyper::{header, Body, Method, Request, StatusCode};
+use serde_json::json;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::mocker::{
+    api::BibliotecaApiResponse,
+    app::{create_mock_app, create_mock_public_app},
+    auth::login,
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    library::MockLibrary,
+    users::MockUserBase,
+};
+
+async fn list_audit_entries(app: &Router, uri: &str) -> Page<AuditEntry> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn list_audit_entries_records_changes_successful() {
+    let database_path = "list_audit_entries_records_changes_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let requests = [
+        (
+            Method::PUT,
+            format!("/authors/{}", author.id),
+            json!({
+                "name": "New author name",
+                "description": author.description,
+                "country": author.country,
+            }),
+        ),
+        (Method::DELETE, format!("/authors/{}", author.id), json!({})),
+    ];
+
+    for (method, uri, body) in requests {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from(serde_json::to_string(&body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert!(
+            response.status().is_success(),
+            "checking if response is successful"
+        );
+    }
+
+    let page = list_audit_entries(&app, "/audit?entity_type=author").await;
+    let actions: Vec<AuditAction> = page.items.iter().map(|entry| entry.action).collect();
+
+    assert_eq!(
+        actions,
+        vec![AuditAction::Update, AuditAction::Delete],
+        "checking if every change was recorded in order"
+    );
+    assert!(
+        page.items.iter().all(|entry| entry.entity_id == author.id
+            && entry.actor_kind == ActorKind::User
+            && entry.actor_id.is_some()
+            && entry.request_id.is_some()),
+        "checking if entries record who made the change and in which request"
+    );
+
+    // Only the fields that changed are kept for updates
+    assert_eq!(
+        page.items[0].before,
+        Some(json!({ "name": author.name })),
+        "checking if update records the name before"
+    );
+    assert_eq!(
+        page.items[0].after,
+        Some(json!({ "name": "New author name" })),
+        "checking if update records the name after"
+    );
+    assert!(
+        page.items[1].before.is_some() && page.items[1].after.is_none(),
+        "checking if delete records the deleted author"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_audit_entries_of(&author.id, 2),
+            "checking if entries were stored"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_audit_entries_records_actor_and_request_id_successful() {
+    let database_path = "list_audit_entries_records_actor_and_request_id_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role()
+        .permissions(vec![Permission::CatalogWrite, Permission::AuditRead])
+        .build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .build();
+
+    let app = create_mock_public_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/categories")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .header("x-request-id", "request-of-category")
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": "Fantasy",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let category: Category = serde_json::from_slice(&body).unwrap();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/audit?request_id=request-of-category")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<AuditEntry> = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(page.total, 1, "checking if only the request is listed");
+    assert_eq!(page.items[0].action, AuditAction::Create);
+    assert_eq!(page.items[0].entity_type, EntityType::Category);
+    assert_eq!(
+        page.items[0].actor_id,
+        Some(user.id),
+        "checking if the user who made the change is recorded"
+    );
+    assert_eq!(
+        page.items[0].after,
+        Some(json!({ "id": category.id, "name": category.name })),
+        "checking if the created category is recorded"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_audit_entries_records_borrow_successful() {
+    let database_path = "list_audit_entries_records_borrow_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let author = MockCatalog::new_author().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is ACCEPTED"
+    );
+
+    let page = list_audit_entries(&app, "/audit?entity_type=loan&action=borrow").await;
+
+    assert_eq!(page.total, 1, "checking if the borrow was recorded");
+    assert_eq!(
+        page.items[0].after.as_ref().unwrap()["book_id"],
+        json!(book.id),
+        "checking if the loan is recorded"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_audit_entries_records_borrow_of_held_copy_successful() {
+    let database_path = "list_audit_entries_records_borrow_of_held_copy_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::OnHold)
+        .build();
+    let author = MockCatalog::new_author().build();
+    let hold = MockLibrary::new_hold()
+        .book_id(book.id)
+        .user_id(user.id)
+        .ready(item.id, Utc::now() + Duration::days(1))
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_hold(&hold)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is ACCEPTED"
+    );
+
+    let page = list_audit_entries(&app, &format!("/audit?entity_id={}", item.id)).await;
+
+    assert_eq!(page.total, 1, "checking if the lent copy was recorded");
+    assert_eq!(page.items[0].action, AuditAction::Update);
+    assert_eq!(
+        page.items[0].before.as_ref().unwrap()["status"],
+        json!(ItemStatus::OnHold),
+        "checking if the copy is recorded as set aside before"
+    );
+    assert_eq!(
+        page.items[0].after.as_ref().unwrap()["status"],
+        json!(ItemStatus::Borrowed),
+        "checking if the copy is recorded as lent after"
+    );
+
+    let page = list_audit_entries(&app, &format!("/audit?entity_id={}", hold.id)).await;
+
+    assert_eq!(page.total, 1, "checking if the fulfilled hold was recorded");
+    assert_eq!(page.items[0].action, AuditAction::Delete);
+    assert!(page.items[0].after.is_none());
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_audit_entries_records_hold_promotion_successful() {
+    let database_path = "list_audit_entries_records_hold_promotion_successful.sqlite";
+
+    let user_a = MockUserBase::new_user().build();
+    let user_b = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::Borrowed)
+        .build();
+    let author = MockCatalog::new_author().build();
+    let borrow_entry = MockLibrary::new_borrow_entry()
+        .user_id(user_a.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .build();
+    let hold = MockLibrary::new_hold()
+        .book_id(book.id)
+        .user_id(user_b.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user_a, &user_role)
+        .with_user(&user_b, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_library_entry(&borrow_entry)
+        .with_hold(&hold)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/books/{}/return", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user_a.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is ACCEPTED"
+    );
+
+    let page = list_audit_entries(&app, &format!("/audit?entity_id={}", hold.id)).await;
+
+    assert_eq!(page.total, 1, "checking if the promoted hold was recorded");
+    assert_eq!(page.items[0].action, AuditAction::Update);
+    assert_eq!(
+        page.items[0].before.as_ref().unwrap()["status"],
+        json!(HoldStatus::Waiting),
+        "checking if the hold is recorded as waiting before"
+    );
+    assert_eq!(
+        page.items[0].after.as_ref().unwrap()["status"],
+        json!(HoldStatus::Ready),
+        "checking if the hold is recorded as ready after"
+    );
+    assert_eq!(
+        page.items[0].after.as_ref().unwrap()["item_id"],
+        json!(item.id),
+        "checking if the copy set aside is recorded"
+    );
+
+    let page = list_audit_entries(&app, &format!("/audit?entity_id={}", item.id)).await;
+
+    assert_eq!(page.total, 1, "checking if the copy set aside was recorded");
+    assert_eq!(
+        page.items[0].after.as_ref().unwrap()["status"],
+        json!(ItemStatus::OnHold),
+        "checking if the copy is recorded as set aside"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_audit_entries_records_api_key_changes_successful() {
+    let database_path = "list_audit_entries_records_api_key_changes_successful.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/auth/keys")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({ "name": "kiosk-1" })).unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let issued: IssuedApiKey = serde_json::from_slice(&body).unwrap();
+    let key_id = issued.api_key.id;
+
+    for (method, uri) in [
+        (Method::POST, format!("/auth/keys/{}/rotate", key_id)),
+        (Method::DELETE, format!("/auth/keys/{}", key_id)),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert!(
+            response.status().is_success(),
+            "checking if response is successful"
+        );
+    }
+
+    let page = list_audit_entries(&app, "/audit?entity_type=api_key").await;
+    let actions: Vec<AuditAction> = page.items.iter().map(|entry| entry.action).collect();
+
+    assert_eq!(
+        actions,
+        vec![
+            AuditAction::Create,
+            AuditAction::Update,
+            AuditAction::Delete
+        ],
+        "checking if issuing, rotating and revoking were recorded in order"
+    );
+    assert!(
+        page.items.iter().all(|entry| entry.entity_id == key_id),
+        "checking if entries are of the API key"
+    );
+    assert!(
+        page.items[1]
+            .before
+            .as_ref()
+            .unwrap()
+            .get("prefix")
+            .is_some(),
+        "checking if rotation records the prefix replaced"
+    );
+    assert!(
+        page.items.iter().all(|entry| [&entry.before, &entry.after]
+            .into_iter()
+            .flatten()
+            .all(|fields| fields.get("key_hash").is_none())),
+        "checking if the hash of the key was left out"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_audit_entries_filter_by_entity_successful() {
+    let database_path = "list_audit_entries_filter_by_entity_successful.sqlite";
+
+    let author_a = MockCatalog::new_author().build();
+    let author_b = MockCatalog::new_author().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author_a)
+        .with_author(&author_b)
+        .build();
+
+    let app = create_mock_app(db);
+
+    for author in [&author_a, &author_b] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::DELETE)
+                    .uri(format!("/authors/{}", author.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert!(
+            response.status().is_success(),
+            "checking if response is successful"
+        );
+    }
+
+    let page = list_audit_entries(&app, &format!("/audit?entity_id={}", author_b.id)).await;
+
+    assert_eq!(page.total, 1, "checking if only the author is listed");
+    assert_eq!(page.items[0].entity_id, author_b.id);
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_audit_entries_invalid_filter_failure() {
+    let database_path = "list_audit_entries_invalid_filter_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/audit?action=borrowed")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        response.is_correct(
+            40001,
+            "invalid value of filter parameter: action".to_string()
+        ),
+        "checking if error message is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_audit_entries_without_permission_failure() {
+    let database_path = "list_audit_entries_without_permission_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role()
+        .permissions(vec![Permission::CatalogWrite])
+        .build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .build();
+
+    let app = create_mock_public_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/audit")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::FORBIDDEN,
+        "checking if response is FORBIDDEN"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn audit_log_is_append_only_successful() {
+    let database_path = "audit_log_is_append_only_successful.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    // Adding the mock librarian records the first entries
+    let _app = create_mock_app(db);
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.is_audit_log_append_only(),
+            "checking if entries cannot be altered"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_audit_entries_system_changes_successful() {
+    let database_path = "list_audit_entries_system_changes_successful.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let page = list_audit_entries(&app, "/audit?entity_type=user").await;
+
+    assert_eq!(page.total, 1, "checking if the mock librarian was recorded");
+    assert_eq!(page.items[0].actor_kind, ActorKind::System);
+    assert_eq!(page.items[0].actor_id, None::<Uuid>);
+    assert_eq!(page.items[0].request_id, None);
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1 @@
+pub mod list_audit_entries;
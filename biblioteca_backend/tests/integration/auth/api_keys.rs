@@ -0,0 +1,573 @@
+use biblioteca_backend::{
+    auth::{
+        model::{ApiKey, IssuedApiKey},
+        token::hash_api_key,
+    },
+    users::model::Permission,
+};
+use chrono::{Duration, Utc};
+use hyper::{header, Body, Method, Request, StatusCode};
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::mocker::{
+    api::BibliotecaApiResponse,
+    app::{create_mock_app, create_mock_public_app},
+    auth::{login, new_api_key},
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn create_api_key_successful() {
+    let database_path = "create_api_key_successful.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/auth/keys")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": "kiosk-1",
+                        "permissions": ["circulation:staff"],
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let issued: IssuedApiKey = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(issued.api_key.name, "kiosk-1", "checking name of API key");
+    assert_eq!(
+        issued.api_key.permissions,
+        vec![Permission::CirculationStaff],
+        "checking permissions of API key"
+    );
+    assert!(
+        issued.key.starts_with(&issued.api_key.prefix),
+        "checking if prefix is the start of the key"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_api_keys(1),
+            "checking if API key was added"
+        );
+        assert!(
+            querier.contains_api_key_hash(&hash_api_key(&issued.key)),
+            "checking if only the hash of the key was stored"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_api_key_duplicate_name_failure() {
+    let database_path = "create_api_key_duplicate_name_failure.sqlite";
+
+    let (api_key, _) = new_api_key("kiosk-1", Vec::new());
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_api_key(&api_key)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/auth/keys")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({ "name": "kiosk-1" })).unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        response.is_correct(40001, "already exists".to_string()),
+        "checking if error is correct"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_api_keys(1),
+            "checking if no API key was added"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_api_key_patron_failure() {
+    let database_path = "create_api_key_patron_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .build();
+
+    let app = create_mock_public_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/auth/keys")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({ "name": "kiosk-1" })).unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::FORBIDDEN,
+        "checking if response is FORBIDDEN"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_api_keys(0),
+            "checking if no API key was added"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_api_keys_successful() {
+    let database_path = "list_api_keys_successful.sqlite";
+
+    let (api_key, _) = new_api_key("reporting", Vec::new());
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_api_key(&api_key)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/auth/keys")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let listed: Vec<ApiKey> = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(listed.len(), 1, "checking number of API keys");
+    assert_eq!(listed[0].id, api_key.id, "checking id of API key");
+    assert!(
+        !String::from_utf8_lossy(&body).contains(&api_key.key_hash),
+        "checking if hash of the key is left out"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn api_key_borrow_book_successful() {
+    let database_path = "api_key_borrow_book_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let (api_key, key) = new_api_key("kiosk-1", vec![Permission::CirculationStaff]);
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_api_key(&api_key)
+        .build();
+
+    let app = create_mock_public_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::AUTHORIZATION, format!("Bearer {}", key))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({ "user_id": user.id })).unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is ACCEPTED"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.is_book_borrowed(&book.id),
+            "checking if book is borrowed"
+        );
+        assert!(
+            querier.is_api_key_used(&api_key.id),
+            "checking if use of the API key was recorded"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn api_key_borrow_book_without_user_failure() {
+    let database_path = "api_key_borrow_book_without_user_failure.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let (api_key, key) = new_api_key("kiosk-1", vec![Permission::CirculationStaff]);
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_api_key(&api_key)
+        .build();
+
+    let app = create_mock_public_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::AUTHORIZATION, format!("Bearer {}", key))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from("{}"))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        response.is_correct(40001, "must give the user_id".to_string()),
+        "checking if error is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn api_key_delete_book_without_permission_failure() {
+    let database_path = "api_key_delete_book_without_permission_failure.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let (api_key, key) = new_api_key("reporting", Vec::new());
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_api_key(&api_key)
+        .build();
+
+    let app = create_mock_public_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("/books/{}", book.id))
+                .header(header::AUTHORIZATION, format!("Bearer {}", key))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::FORBIDDEN,
+        "checking if response is FORBIDDEN"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(querier.contains_num_books(1), "checking if book was kept");
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn api_key_expired_failure() {
+    let database_path = "api_key_expired_failure.sqlite";
+
+    let (mut api_key, key) = new_api_key("reporting", vec![Permission::UsersAdmin]);
+    api_key.expires_at = Some(Utc::now() - Duration::hours(1));
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_api_key(&api_key)
+        .build();
+
+    let app = create_mock_public_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/users")
+                .header(header::AUTHORIZATION, format!("Bearer {}", key))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "checking if response is UNAUTHORIZED"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        response.is_correct(40101, "expired".to_string()),
+        "checking if error is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn api_key_unknown_failure() {
+    let database_path = "api_key_unknown_failure.sqlite";
+
+    let (api_key, _) = new_api_key("reporting", vec![Permission::UsersAdmin]);
+    let (_, unknown_key) = new_api_key("unknown", vec![Permission::UsersAdmin]);
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_api_key(&api_key)
+        .build();
+
+    let app = create_mock_public_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/users")
+                .header(header::AUTHORIZATION, format!("Bearer {}", unknown_key))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "checking if response is UNAUTHORIZED"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        response.is_correct(40101, "token is not valid".to_string()),
+        "checking if error is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn rotate_api_key_successful() {
+    let database_path = "rotate_api_key_successful.sqlite";
+
+    let (api_key, key) = new_api_key("reporting", vec![Permission::UsersAdmin]);
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_api_key(&api_key)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/auth/keys/{}/rotate", api_key.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let issued: IssuedApiKey = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(issued.api_key.id, api_key.id, "checking id of API key");
+    assert_ne!(issued.key, key, "checking if key was replaced");
+
+    // The previous key stops being accepted, while the new one is
+    for (key, status) in [
+        (key, StatusCode::UNAUTHORIZED),
+        (issued.key, StatusCode::OK),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri("/users")
+                    .header(header::AUTHORIZATION, format!("Bearer {}", key))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), status, "checking status of response");
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn revoke_api_key_successful() {
+    let database_path = "revoke_api_key_successful.sqlite";
+
+    let (api_key, key) = new_api_key("reporting", vec![Permission::UsersAdmin]);
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_api_key(&api_key)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("/auth/keys/{}", api_key.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is NO_CONTENT"
+    );
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/users")
+                .header(header::AUTHORIZATION, format!("Bearer {}", key))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "checking if response is UNAUTHORIZED"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_api_keys(0),
+            "checking if API key was deleted"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
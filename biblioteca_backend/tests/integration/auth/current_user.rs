@@ -0,0 +1,103 @@
+use biblioteca_backend::users::model::FullUser;
+use hyper::{header, Body, Method, Request, StatusCode};
+use tower::ServiceExt;
+
+use crate::mocker::{
+    api::BibliotecaApiResponse, app::create_mock_public_app, auth::login, db::MockDatabaseBuilder,
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn get_current_user_successful() {
+    let database_path = "get_current_user_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .build();
+
+    let app = create_mock_public_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/auth/me")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let current_user: FullUser = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(current_user.id, user.id);
+    assert_eq!(current_user.user_roles.len(), 1);
+    assert_eq!(current_user.user_roles[0].user_role.id, user_role.id);
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn get_current_user_forged_token_failure() {
+    let database_path = "get_current_user_forged_token_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .build();
+
+    let app = create_mock_public_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    // Keep the session id of the token but replace its signature
+    let (session_id, _) = token.split_once('.').unwrap();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/auth/me")
+                .header(
+                    header::AUTHORIZATION,
+                    format!(
+                        "Bearer {}.AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA",
+                        session_id
+                    ),
+                )
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "checking if response is UNAUTHORIZED"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(api_response.is_correct(40101, "token is not valid".to_string()));
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,249 @@
+use biblioteca_backend::auth::model::LoginResponse;
+use chrono::Utc;
+use hyper::{header, Body, Method, Request, StatusCode};
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::mocker::{
+    api::BibliotecaApiResponse,
+    app::create_mock_public_app,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn login_correct_password_successful() {
+    let database_path = "login_correct_password_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .build();
+
+    let app = create_mock_public_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/auth/login")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "username": user.username,
+                        "password": "correct horse",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let login: LoginResponse = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert_eq!(login.user_id, user.id);
+        assert!(!login.token.is_empty());
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_sessions(1),
+            "checking if session was added"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn login_wrong_password_failure() {
+    let database_path = "login_wrong_password_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .build();
+
+    let app = create_mock_public_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/auth/login")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "username": user.username,
+                        "password": "battery staple",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "checking if response is UNAUTHORIZED"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(api_response.is_correct(40101, "username or password is incorrect".to_string()));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_sessions(0),
+            "checking if no session was added"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn login_without_password_failure() {
+    let database_path = "login_without_password_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .build();
+
+    let app = create_mock_public_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/auth/login")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "username": user.username,
+                        "password": "",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "checking if response is UNAUTHORIZED"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn login_unknown_username_failure() {
+    let database_path = "login_unknown_username_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_public_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/auth/login")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "username": "nobody",
+                        "password": "correct horse",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "checking if response is UNAUTHORIZED"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn login_deleted_user_failure() {
+    let database_path = "login_deleted_user_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().deleted_at(Utc::now()).build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .build();
+
+    let app = create_mock_public_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/auth/login")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "username": user.username,
+                        "password": "correct horse",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "checking if response is UNAUTHORIZED"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_sessions(0),
+            "checking if no session was added"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
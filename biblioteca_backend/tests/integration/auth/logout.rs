@@ -0,0 +1,102 @@
+use hyper::{header, Body, Method, Request, StatusCode};
+use tower::ServiceExt;
+
+use crate::mocker::{
+    app::create_mock_public_app,
+    auth::login,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn logout_revokes_token_successful() {
+    let database_path = "logout_revokes_token_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .build();
+
+    let app = create_mock_public_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/auth/logout")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is NO_CONTENT"
+    );
+
+    // The token can no longer be used once its session has ended
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/auth/me")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "checking if response is UNAUTHORIZED"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_sessions(0),
+            "checking if session was deleted"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn logout_without_token_failure() {
+    let database_path = "logout_without_token_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_public_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/auth/logout")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "checking if response is UNAUTHORIZED"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,5 @@
+pub mod api_keys;
+pub mod current_user;
+pub mod login;
+pub mod logout;
+pub mod permissions;
@@ -0,0 +1,522 @@
+use biblioteca_backend::users::model::Permission;
+use chrono::{Duration, Utc};
+use hyper::{header, Body, Method, Request, StatusCode};
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::mocker::{
+    api::BibliotecaApiResponse,
+    app::{create_mock_app, create_mock_public_app},
+    auth::login,
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    library::MockLibrary,
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn patron_read_catalog_successful() {
+    let database_path = "patron_read_catalog_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .build();
+
+    let app = create_mock_public_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("/books/{}", book.id))
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn patron_delete_book_failure() {
+    let database_path = "patron_delete_book_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .build();
+
+    let app = create_mock_public_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("/books/{}", book.id))
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::FORBIDDEN,
+        "checking if response is FORBIDDEN"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(api_response.is_correct(40301, "catalog:write".to_string()));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_book(&book),
+            "checking if book was not deleted"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn patron_list_deleted_books_failure() {
+    let database_path = "patron_list_deleted_books_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .build();
+
+    let app = create_mock_public_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/books?include_deleted=true")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::FORBIDDEN,
+        "checking if response is FORBIDDEN"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(api_response.is_correct(40301, "catalog:write".to_string()));
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn catalog_writer_delete_book_successful() {
+    let database_path = "catalog_writer_delete_book_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role()
+        .permissions(vec![Permission::CatalogWrite])
+        .build();
+    let user = MockUserBase::new_user().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .build();
+
+    let app = create_mock_public_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("/books/{}", book.id))
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is NO_CONTENT"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.is_book_deleted(&book.id),
+            "checking if book was deleted"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn anonymous_create_author_failure() {
+    let database_path = "anonymous_create_author_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_public_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/authors")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": "Ursula K. Le Guin",
+                        "description": "Writer of speculative fiction",
+                        "country": "USA",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "checking if response is UNAUTHORIZED"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_authors(0),
+            "checking if author was not added"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn patron_list_users_failure() {
+    let database_path = "patron_list_users_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role()
+        .permissions(vec![Permission::CatalogWrite, Permission::CirculationStaff])
+        .build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .build();
+
+    let app = create_mock_public_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri("/users")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::FORBIDDEN,
+        "checking if response is FORBIDDEN"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(api_response.is_correct(40301, "users:admin".to_string()));
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn patron_list_own_loans_successful() {
+    let database_path = "patron_list_own_loans_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .build();
+
+    let app = create_mock_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("/users/{}/loans", user.id))
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn patron_list_other_user_loans_failure() {
+    let database_path = "patron_list_other_user_loans_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+    let other_user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user(&other_user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .build();
+
+    let app = create_mock_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("/users/{}/loans", other_user.id))
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::FORBIDDEN,
+        "checking if response is FORBIDDEN"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn patron_list_book_loans_failure() {
+    let database_path = "patron_list_book_loans_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .build();
+
+    let app = create_mock_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("/books/{}/loans", book.id))
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::FORBIDDEN,
+        "checking if response is FORBIDDEN"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn patron_cancel_other_user_hold_failure() {
+    let database_path = "patron_cancel_other_user_hold_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+    let other_user = MockUserBase::new_user().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let hold = MockLibrary::new_hold()
+        .user_id(other_user.id)
+        .book_id(book.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user(&other_user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_hold(&hold)
+        .build();
+
+    let app = create_mock_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("/holds/{}", hold.id))
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::FORBIDDEN,
+        "checking if response is FORBIDDEN"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_holds(1),
+            "checking if hold was not cancelled"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn expired_role_delete_book_failure() {
+    let database_path = "expired_role_delete_book_failure.sqlite";
+
+    let patron_role = MockUserBase::new_user_role().build();
+    let cataloguer_role = MockUserBase::new_user_role()
+        .permissions(vec![Permission::CatalogWrite])
+        .build();
+    let user = MockUserBase::new_user().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&patron_role)
+        .with_user_role(&cataloguer_role)
+        .with_user(&user, &patron_role)
+        .with_granted_user_role(
+            &user,
+            &cataloguer_role,
+            Some(Utc::now() - Duration::days(1)),
+        )
+        .with_user_password(&user, "correct horse")
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .build();
+
+    let app = create_mock_public_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("/books/{}", book.id))
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::FORBIDDEN,
+        "checking if response is FORBIDDEN"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_book(&book),
+            "checking if book was not deleted"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -51,6 +51,7 @@ async fn create_author_correct_parameters_successful() {
         name: original_author.name,
         description: original_author.description,
         country: original_author.country,
+        deleted_at: None,
     };
 
     {
@@ -182,6 +183,7 @@ async fn create_author_additional_parameters_successful() {
         name: original_author.name,
         description: original_author.description,
         country: original_author.country,
+        deleted_at: None,
     };
 
     {
@@ -44,16 +44,16 @@ async fn delete_author_existing_author_successful() {
     {
         let querier = MockDatabaseQuerier::create(database_path.to_string());
         assert!(
-            !querier.contains_author(&author),
-            "checking if author was removed properly"
+            querier.is_author_deleted(&author.id),
+            "checking if author was marked as deleted"
         );
         assert!(
-            !querier.contains_book_author_mapping(&book_a.id, &author.id),
-            "checking if book_a to author mapping doesn't exist"
+            querier.contains_book_author_mapping(&book_a.id, &author.id),
+            "checking if book_a to author mapping is kept to restore it"
         );
         assert!(
-            !querier.contains_book_author_mapping(&book_b.id, &author.id),
-            "checking if book_b to author mapping doesn't exist"
+            querier.contains_book_author_mapping(&book_b.id, &author.id),
+            "checking if book_b to author mapping is kept to restore it"
         );
     }
 
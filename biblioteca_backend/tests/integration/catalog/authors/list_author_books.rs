@@ -0,0 +1,163 @@
+use biblioteca_backend::{catalog::model::Book, query::Page};
+use hyper::{Body, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::mocker::{app::create_mock_app, catalog::MockCatalog, db::MockDatabaseBuilder};
+
+#[tokio::test]
+async fn list_author_books_successful() {
+    let database_path = "list_author_books_successful.sqlite";
+
+    let author_a = MockCatalog::new_author().build();
+    let author_b = MockCatalog::new_author().build();
+    let book_a = MockCatalog::new_book().build();
+    let book_b = MockCatalog::new_book().build();
+    let book_c = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author_a)
+        .with_author(&author_b)
+        .with_book(&book_a, &author_a.id)
+        .with_book(&book_b, &author_a.id)
+        .with_book(&book_c, &author_b.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/authors/{}/books", author_a.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<Book> = serde_json::from_slice(&body).unwrap();
+    let returned_books = page.items;
+
+    {
+        assert!(returned_books.len() == 2);
+        for book in returned_books.iter() {
+            assert!(book.id == book_a.id || book.id == book_b.id);
+        }
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_author_books_with_search_successful() {
+    let database_path = "list_author_books_with_search_successful.sqlite";
+
+    let author_a = MockCatalog::new_author().build();
+    let author_b = MockCatalog::new_author().build();
+
+    let book_a = MockCatalog::new_book()
+        .name("Alice in Wonderland".to_string())
+        .language("English".to_string())
+        .build();
+
+    let book_b = MockCatalog::new_book()
+        .name("Alice in Wonderland".to_string())
+        .language("Spanish".to_string())
+        .build();
+
+    let book_c = MockCatalog::new_book()
+        .name("Through the Looking-Glass".to_string())
+        .language("English".to_string())
+        .build();
+
+    let book_d = MockCatalog::new_book()
+        .name("Alice in Wonderland".to_string())
+        .language("English".to_string())
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author_a)
+        .with_author(&author_b)
+        .with_book(&book_a, &author_a.id)
+        .with_book(&book_b, &author_a.id)
+        .with_book(&book_c, &author_a.id)
+        .with_book(&book_d, &author_b.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!(
+                    "/authors/{}/books?name=Alice&language=English",
+                    author_a.id
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<Book> = serde_json::from_slice(&body).unwrap();
+    let returned_books = page.items;
+
+    {
+        assert!(returned_books.len() == 1);
+        assert!(returned_books[0].id == book_a.id);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_author_books_non_existent_author_failure() {
+    let database_path = "list_author_books_non_existent_author_failure.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+
+    let incorrect_id = Uuid::new_v4();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/authors/{}/books", incorrect_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NOT_FOUND,
+        "checking if response is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -1,8 +1,10 @@
-use biblioteca_backend::catalog::model::Author;
+use biblioteca_backend::{catalog::model::Author, query::Page};
+use chrono::Utc;
 use hyper::{Body, Request, StatusCode};
 use tower::ServiceExt;
 
 use crate::mocker::{
+    api::BibliotecaApiResponse,
     app::create_mock_app,
     catalog::MockCatalog,
     db::{MockDatabaseBuilder, MockDatabaseQuerier},
@@ -42,7 +44,8 @@ async fn list_authors_successful() {
     );
 
     let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-    let returned_authors: Vec<Author> = serde_json::from_slice(&body).unwrap();
+    let page: Page<Author> = serde_json::from_slice(&body).unwrap();
+    let returned_authors = page.items;
 
     {
         let querier = MockDatabaseQuerier::create(database_path.to_string());
@@ -95,7 +98,8 @@ async fn list_authors_with_name_search_successful() {
     );
 
     let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-    let returned_authors: Vec<Author> = serde_json::from_slice(&body).unwrap();
+    let page: Page<Author> = serde_json::from_slice(&body).unwrap();
+    let returned_authors = page.items;
 
     {
         assert!(returned_authors.len() == 2);
@@ -148,7 +152,8 @@ async fn list_authors_with_country_search_successful() {
     );
 
     let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-    let returned_authors: Vec<Author> = serde_json::from_slice(&body).unwrap();
+    let page: Page<Author> = serde_json::from_slice(&body).unwrap();
+    let returned_authors = page.items;
 
     {
         assert!(returned_authors.len() == 2);
@@ -162,8 +167,8 @@ async fn list_authors_with_country_search_successful() {
 }
 
 #[tokio::test]
-async fn list_authors_with_search_wrong_params_successful() {
-    let database_path = "list_authors_with_search_wrong_params_successful.sqlite";
+async fn list_authors_with_search_wrong_params_failure() {
+    let database_path = "list_authors_with_search_wrong_params_failure.sqlite";
 
     let author_a = MockCatalog::new_author().build();
     let author_b = MockCatalog::new_author().build();
@@ -188,6 +193,185 @@ async fn list_authors_with_search_wrong_params_successful() {
         .await
         .unwrap();
 
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "unknown filter parameter".to_string()),
+        "checking if API response message is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_authors_with_search_special_characters_successful() {
+    let database_path = "list_authors_with_search_special_characters_successful.sqlite";
+
+    let author_a = MockCatalog::new_author()
+        .name("Flann O'Brien".to_string())
+        .build();
+    let author_b = MockCatalog::new_author()
+        .name("O_Brien".to_string())
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author_a)
+        .with_author(&author_b)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/authors?name=O%27B")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<Author> = serde_json::from_slice(&body).unwrap();
+    let returned_authors = page.items;
+
+    {
+        assert!(returned_authors.len() == 1);
+        assert!(returned_authors[0].id == author_a.id);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_authors_with_sort_successful() {
+    let database_path = "list_authors_with_sort_successful.sqlite";
+
+    let author_a = MockCatalog::new_author()
+        .name("Jorge Luis Borges".to_string())
+        .country("Argentina".to_string())
+        .build();
+    let author_b = MockCatalog::new_author()
+        .name("Jorge Amado".to_string())
+        .country("Brazil".to_string())
+        .build();
+    let author_c = MockCatalog::new_author()
+        .name("Jorge Luis Borges".to_string())
+        .country("Spain".to_string())
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author_a)
+        .with_author(&author_b)
+        .with_author(&author_c)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/authors?sort=name,-country")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<Author> = serde_json::from_slice(&body).unwrap();
+
+    {
+        let returned_ids: Vec<_> = page.items.iter().map(|author| author.id).collect();
+        assert_eq!(returned_ids, vec![author_b.id, author_c.id, author_a.id]);
+        assert!(page.next.is_none() && page.prev.is_none());
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_authors_with_unknown_sort_failure() {
+    let database_path = "list_authors_with_unknown_sort_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/authors?sort=description")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "unknown sort field".to_string()),
+        "checking if API response message is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_authors_excludes_deleted_successful() {
+    let database_path = "list_authors_excludes_deleted_successful.sqlite";
+
+    let author_a = MockCatalog::new_author().build();
+    let author_b = MockCatalog::new_author().deleted_at(Utc::now()).build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author_a)
+        .with_author(&author_b)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/authors")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
     assert_eq!(
         response.status(),
         StatusCode::OK,
@@ -195,10 +379,11 @@ async fn list_authors_with_search_wrong_params_successful() {
     );
 
     let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-    let returned_authors: Vec<Author> = serde_json::from_slice(&body).unwrap();
+    let page: Page<Author> = serde_json::from_slice(&body).unwrap();
 
     {
-        assert!(returned_authors.len() == 3);
+        assert!(page.total == 1, "checking if deleted author is left out");
+        assert!(page.items[0].id == author_a.id);
     }
 
     MockDatabaseBuilder::teardown(database_path.to_string());
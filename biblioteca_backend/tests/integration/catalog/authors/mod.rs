@@ -1,5 +1,7 @@
 pub mod create_author;
 pub mod delete_author;
 pub mod get_author;
+pub mod list_author_books;
 pub mod list_authors;
+pub mod restore_author;
 pub mod update_author;
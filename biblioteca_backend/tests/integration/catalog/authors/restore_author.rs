@@ -0,0 +1,104 @@
+use biblioteca_backend::catalog::model::Author;
+use chrono::Utc;
+use hyper::{Body, Request, StatusCode};
+use tower::{Service, ServiceExt};
+use uuid::Uuid;
+
+use crate::mocker::{
+    app::create_mock_app,
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+};
+
+#[tokio::test]
+async fn restore_author_deleted_author_successful() {
+    let database_path = "restore_author_deleted_author_successful.sqlite";
+
+    let author = MockCatalog::new_author().deleted_at(Utc::now()).build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .build();
+
+    let mut app = create_mock_app(db);
+
+    let response = app
+        .call(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/authors/{}/restore", author.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let restored: Author = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert_eq!(restored.id, author.id);
+        assert!(restored.deleted_at.is_none());
+    }
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/authors/{}", author.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if restored author can be retrieved"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            !querier.is_author_deleted(&author.id),
+            "checking if author was restored"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn restore_author_non_existent_author_failure() {
+    let database_path = "restore_author_non_existent_author_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/authors/{}/restore", Uuid::new_v4()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NOT_FOUND,
+        "checking if response is NOT_FOUND"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
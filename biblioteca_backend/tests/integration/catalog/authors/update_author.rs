@@ -12,7 +12,7 @@ use crate::mocker::{
 
 #[tokio::test]
 async fn update_author_correct_parameters_successful() {
-    let database_path = "update_book_correct_parameters_successful.sqlite";
+    let database_path = "update_author_correct_parameters_successful.sqlite";
 
     let author = MockCatalog::new_author().build();
 
@@ -52,6 +52,7 @@ async fn update_author_correct_parameters_successful() {
         name: new_author.name,
         description: new_author.description,
         country: new_author.country,
+        deleted_at: None,
     };
 
     assert_eq!(
@@ -1,10 +1,11 @@
-use biblioteca_backend::catalog::model::Book;
+use biblioteca_backend::catalog::model::{Book, ContributorRole};
 use hyper::{header, Body, Method, Request, StatusCode};
 use serde_json::json;
 use tower::ServiceExt;
 use uuid::Uuid;
 
 use crate::mocker::{
+    api::BibliotecaApiResponse,
     app::create_mock_app,
     catalog::MockCatalog,
     db::{MockDatabaseBuilder, MockDatabaseQuerier},
@@ -34,7 +35,7 @@ async fn create_book_correct_parameters_successful() {
                         "name": original_book.name,
                         "description": original_book.description,
                         "language": original_book.language,
-                        "author_id": author.id,
+                        "contributors": [{ "author_id": author.id, "role": "author" }],
                     }))
                     .unwrap(),
                 ))
@@ -56,6 +57,11 @@ async fn create_book_correct_parameters_successful() {
         name: original_book.name,
         description: original_book.description,
         language: original_book.language,
+        contributors: Vec::new(),
+        categories: Vec::new(),
+        total_copies: 0,
+        available_copies: 0,
+        deleted_at: None,
     };
 
     {
@@ -97,7 +103,7 @@ async fn create_book_wrong_parameters_failure() {
                         "name_incorrect": original_book.name,
                         "description": original_book.description,
                         "language": original_book.language,
-                        "author_id": author.id,
+                        "contributors": [{ "author_id": author.id, "role": "author" }],
                     }))
                     .unwrap(),
                 ))
@@ -138,7 +144,7 @@ async fn create_book_missing_parameters_failure() {
                     serde_json::to_string(&json!({
                         "name": original_book.name,
                         "language": original_book.language,
-                        "author_id": author.id,
+                        "contributors": [{ "author_id": author.id, "role": "author" }],
                     }))
                     .unwrap(),
                 ))
@@ -180,7 +186,7 @@ async fn create_book_additional_parameters_successful() {
                         "name": original_book.name,
                         "description": original_book.description,
                         "language": original_book.language,
-                        "author_id": author.id,
+                        "contributors": [{ "author_id": author.id, "role": "author" }],
                         "additional_parameter": "hello world".to_string()
                     }))
                     .unwrap(),
@@ -203,6 +209,11 @@ async fn create_book_additional_parameters_successful() {
         name: original_book.name,
         description: original_book.description,
         language: original_book.language,
+        contributors: Vec::new(),
+        categories: Vec::new(),
+        total_copies: 0,
+        available_copies: 0,
+        deleted_at: None,
     };
 
     {
@@ -247,7 +258,7 @@ async fn create_book_invalid_author_failure() {
                         "name": original_book.name,
                         "description": original_book.description,
                         "language": original_book.language,
-                        "author_id": incorrect_uuid,
+                        "contributors": [{ "author_id": incorrect_uuid, "role": "author" }],
                     }))
                     .unwrap(),
                 ))
@@ -264,3 +275,305 @@ async fn create_book_invalid_author_failure() {
 
     MockDatabaseBuilder::teardown(database_path.to_string());
 }
+
+#[tokio::test]
+async fn create_book_with_categories_successful() {
+    let database_path = "create_book_with_categories_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let category_a = MockCatalog::new_category().build();
+    let category_b = MockCatalog::new_category().build();
+    let original_book = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_category(&category_a)
+        .with_category(&category_b)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/books")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": original_book.name,
+                        "description": original_book.description,
+                        "language": original_book.language,
+                        "contributors": [{ "author_id": author.id, "role": "author" }],
+                        "category_ids": [category_a.id, category_b.id],
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let created_book: Book = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        created_book.categories.len() == 2,
+        "checking if categories are returned with the book"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_book_category_mapping(&created_book.id, &category_a.id),
+            "checking if book to category_a mapping exists"
+        );
+        assert!(
+            querier.contains_book_category_mapping(&created_book.id, &category_b.id),
+            "checking if book to category_b mapping exists"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_book_invalid_category_failure() {
+    let database_path = "create_book_invalid_category_failure.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let original_book = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/books")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": original_book.name,
+                        "description": original_book.description,
+                        "language": original_book.language,
+                        "contributors": [{ "author_id": author.id, "role": "author" }],
+                        "category_ids": [Uuid::new_v4()],
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_books(0),
+            "checking if no book was added"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_book_with_multiple_contributors_successful() {
+    let database_path = "create_book_with_multiple_contributors_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let translator = MockCatalog::new_author().build();
+    let illustrator = MockCatalog::new_author().build();
+    let original_book = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_author(&translator)
+        .with_author(&illustrator)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/books")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": original_book.name,
+                        "description": original_book.description,
+                        "language": original_book.language,
+                        "contributors": [
+                            { "author_id": author.id, "role": "author" },
+                            { "author_id": translator.id, "role": "translator" },
+                            { "author_id": illustrator.id, "role": "illustrator" },
+                        ],
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let created_book: Book = serde_json::from_slice(&body).unwrap();
+
+    {
+        let contributors: Vec<(Uuid, ContributorRole)> = created_book
+            .contributors
+            .iter()
+            .map(|contributor| (contributor.author_id, contributor.role))
+            .collect();
+
+        assert_eq!(
+            contributors,
+            vec![
+                (author.id, ContributorRole::Author),
+                (translator.id, ContributorRole::Translator),
+                (illustrator.id, ContributorRole::Illustrator),
+            ],
+            "checking if contributors are returned in order"
+        );
+    }
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_book_contributor(
+                &created_book.id,
+                &translator.id,
+                ContributorRole::Translator,
+                1
+            ),
+            "checking if translator mapping exists"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_book_no_contributors_failure() {
+    let database_path = "create_book_no_contributors_failure.sqlite";
+
+    let original_book = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/books")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": original_book.name,
+                        "description": original_book.description,
+                        "language": original_book.language,
+                        "contributors": [],
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "at least one contributor".to_string()),
+        "checking if API response message is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_book_duplicate_contributor_failure() {
+    let database_path = "create_book_duplicate_contributor_failure.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let original_book = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/books")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": original_book.name,
+                        "description": original_book.description,
+                        "language": original_book.language,
+                        "contributors": [
+                            { "author_id": author.id, "role": "editor" },
+                            { "author_id": author.id, "role": "editor" },
+                        ],
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_books(0),
+            "checking if no book was added"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -1,11 +1,16 @@
+use biblioteca_backend::catalog::model::ItemStatus;
+use chrono::{Duration, Utc};
 use hyper::{Body, Request, StatusCode};
 use tower::ServiceExt;
 use uuid::Uuid;
 
 use crate::mocker::{
+    api::BibliotecaApiResponse,
     app::create_mock_app,
     catalog::MockCatalog,
     db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    library::MockLibrary,
+    users::MockUserBase,
 };
 
 #[tokio::test]
@@ -44,12 +49,93 @@ async fn delete_book_existing_book_successful() {
     {
         let querier = MockDatabaseQuerier::create(database_path.to_string());
         assert!(
-            !querier.contains_book(&book_a),
-            "checking if book was removed properly"
+            querier.is_book_deleted(&book_a.id),
+            "checking if book was marked as deleted"
         );
         assert!(
-            !querier.contains_book_author_mapping(&book_a.id, &author.id),
-            "checking if book to author mapping doesn't exist"
+            !querier.is_book_deleted(&book_b.id),
+            "checking if other book was not deleted"
+        );
+        assert!(
+            querier.contains_book_author_mapping(&book_a.id, &author.id),
+            "checking if book to author mapping is kept to restore it"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn delete_book_with_holds_successful() {
+    let database_path = "delete_book_with_holds_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user_a = MockUserBase::new_user().build();
+    let user_b = MockUserBase::new_user().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::OnHold)
+        .build();
+    let ready_hold = MockLibrary::new_hold()
+        .user_id(user_a.id)
+        .book_id(book.id)
+        .ready(item.id, Utc::now() + Duration::days(3))
+        .build();
+    let waiting_hold = MockLibrary::new_hold()
+        .user_id(user_b.id)
+        .book_id(book.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user_a, &user_role)
+        .with_user(&user_b, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_hold(&ready_hold)
+        .with_hold(&waiting_hold)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/books/{}", book.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(querier.is_book_deleted(&book.id));
+        assert!(
+            querier.contains_num_holds(0),
+            "checking if holds of book were cancelled"
+        );
+        assert!(
+            querier.is_item_status(&item.id, ItemStatus::Available),
+            "checking if copy on the hold shelf was put back",
+        );
+        assert!(
+            querier.contains_num_audit_entries_of(&ready_hold.id, 1),
+            "checking if cancelling the hold was recorded"
+        );
+        assert!(
+            querier.contains_num_audit_entries_of(&item.id, 1),
+            "checking if putting back the copy was recorded"
         );
     }
 
@@ -101,3 +187,62 @@ async fn delete_book_non_existent_book_successful() {
 
     MockDatabaseBuilder::teardown(database_path.to_string());
 }
+
+#[tokio::test]
+async fn delete_book_on_loan_failure() {
+    let database_path = "delete_book_on_loan_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let borrow_entry = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_library_entry(&borrow_entry)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/books/{}", book.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(response.is_correct(40001, "currently on loan".to_string()));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            !querier.is_book_deleted(&book.id),
+            "checking if book was kept"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
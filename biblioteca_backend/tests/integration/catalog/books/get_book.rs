@@ -1,4 +1,5 @@
-use biblioteca_backend::catalog::model::Book;
+use biblioteca_backend::catalog::model::{Book, ContributorRole, ItemStatus};
+use chrono::Utc;
 use hyper::{Body, Request, StatusCode};
 use tower::ServiceExt;
 use uuid::Uuid;
@@ -48,6 +49,105 @@ async fn get_book_book_exists_successful() {
     MockDatabaseBuilder::teardown(database_path.to_string());
 }
 
+#[tokio::test]
+async fn get_book_copy_counts_successful() {
+    let database_path = "get_book_copy_counts_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item_a = MockCatalog::new_item().book_id(book.id).build();
+    let item_b = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::Borrowed)
+        .build();
+    let item_c = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::Lost)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item_a)
+        .with_item(&item_b)
+        .with_item(&item_c)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/books/{}", book.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let returned_book: Book = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(returned_book.total_copies == 3);
+        assert!(returned_book.available_copies == 1);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn get_book_hides_deleted_contributors_successful() {
+    let database_path = "get_book_hides_deleted_contributors_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let translator = MockCatalog::new_author().deleted_at(Utc::now()).build();
+    let book = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_author(&translator)
+        .with_book(&book, &author.id)
+        .with_book_contributor(&book.id, &translator.id, ContributorRole::Translator, 1)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/books/{}", book.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let returned_book: Book = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert_eq!(returned_book.contributors.len(), 1);
+        assert_eq!(returned_book.contributors[0].author_id, author.id);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
 #[tokio::test]
 async fn get_book_non_existent_book_failure() {
     let database_path = "get_book_book_no_exists_failure.sqlite";
@@ -85,3 +185,37 @@ async fn get_book_non_existent_book_failure() {
 
     MockDatabaseBuilder::teardown(database_path.to_string());
 }
+
+#[tokio::test]
+async fn get_book_deleted_book_failure() {
+    let database_path = "get_book_deleted_book_failure.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().deleted_at(Utc::now()).build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/books/{}", book.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NOT_FOUND,
+        "checking if response is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
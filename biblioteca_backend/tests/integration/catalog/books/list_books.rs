@@ -1,9 +1,11 @@
-use biblioteca_backend::catalog::model::Book;
+use biblioteca_backend::{catalog::model::Book, query::Page};
+use chrono::Utc;
 use hyper::{Body, Request, StatusCode};
-use tower::ServiceExt;
+use tower::{Service, ServiceExt};
 
 use crate::mocker::{
-    app::create_mock_app,
+    api::BibliotecaApiResponse,
+    app::{create_mock_app, create_mock_public_app},
     catalog::MockCatalog,
     db::{MockDatabaseBuilder, MockDatabaseQuerier},
 };
@@ -44,7 +46,8 @@ async fn list_books_successful() {
     );
 
     let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-    let returned_books: Vec<Book> = serde_json::from_slice(&body).unwrap();
+    let page: Page<Book> = serde_json::from_slice(&body).unwrap();
+    let returned_books = page.items;
 
     {
         let querier = MockDatabaseQuerier::create(database_path.to_string());
@@ -102,7 +105,8 @@ async fn list_books_with_name_search_successful() {
     );
 
     let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-    let returned_books: Vec<Book> = serde_json::from_slice(&body).unwrap();
+    let page: Page<Book> = serde_json::from_slice(&body).unwrap();
+    let returned_books = page.items;
 
     {
         assert!(returned_books.len() == 2);
@@ -159,7 +163,8 @@ async fn list_books_with_language_search_successful() {
     );
 
     let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-    let returned_books: Vec<Book> = serde_json::from_slice(&body).unwrap();
+    let page: Page<Book> = serde_json::from_slice(&body).unwrap();
+    let returned_books = page.items;
 
     {
         assert!(returned_books.len() == 2);
@@ -172,8 +177,8 @@ async fn list_books_with_language_search_successful() {
 }
 
 #[tokio::test]
-async fn list_books_with_search_wrong_params_successful() {
-    let database_path = "list_books_with_search_wrong_params_successful.sqlite";
+async fn list_books_with_search_wrong_params_failure() {
+    let database_path = "list_books_with_search_wrong_params_failure.sqlite";
 
     let author = MockCatalog::new_author().build();
 
@@ -201,6 +206,275 @@ async fn list_books_with_search_wrong_params_successful() {
         .await
         .unwrap();
 
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "unknown filter parameter".to_string()),
+        "checking if API response message is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_books_with_category_search_successful() {
+    let database_path = "list_books_with_category_search_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let category_a = MockCatalog::new_category()
+        .name("Fantasy".to_string())
+        .build();
+    let category_b = MockCatalog::new_category()
+        .name("Biography".to_string())
+        .build();
+
+    let book_a = MockCatalog::new_book().build();
+    let book_b = MockCatalog::new_book().build();
+    let book_c = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_category(&category_a)
+        .with_category(&category_b)
+        .with_book(&book_a, &author.id)
+        .with_book(&book_b, &author.id)
+        .with_book(&book_c, &author.id)
+        .with_book_category(&book_a.id, &category_a.id)
+        .with_book_category(&book_b.id, &category_a.id)
+        .with_book_category(&book_b.id, &category_b.id)
+        .with_book_category(&book_c.id, &category_b.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/books?category=Fantasy")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<Book> = serde_json::from_slice(&body).unwrap();
+    let returned_books = page.items;
+
+    {
+        assert!(returned_books.len() == 2);
+        for book in returned_books.iter() {
+            assert!(book
+                .categories
+                .iter()
+                .any(|category| category.name == "Fantasy"));
+        }
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_books_with_search_special_characters_successful() {
+    let database_path = "list_books_with_search_special_characters_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+
+    let book_a = MockCatalog::new_book()
+        .name("Don't Panic: 100% Guide".to_string())
+        .build();
+    let book_b = MockCatalog::new_book()
+        .name("100 Guides".to_string())
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book_a, &author.id)
+        .with_book(&book_b, &author.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/books?name=n%27t%20Panic%3A%20100%25")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<Book> = serde_json::from_slice(&body).unwrap();
+    let returned_books = page.items;
+
+    {
+        assert!(returned_books.len() == 1);
+        assert!(returned_books[0].id == book_a.id);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_books_with_pagination_successful() {
+    let database_path = "list_books_with_pagination_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book_a = MockCatalog::new_book().name("Book A".to_string()).build();
+    let book_b = MockCatalog::new_book().name("Book B".to_string()).build();
+    let book_c = MockCatalog::new_book().name("Book C".to_string()).build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book_c, &author.id)
+        .with_book(&book_a, &author.id)
+        .with_book(&book_b, &author.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/books?sort=name&limit=1&offset=1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<Book> = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(page.total == 3);
+        assert!(page.items.len() == 1);
+        assert!(page.items[0].id == book_b.id);
+        assert_eq!(
+            page.next,
+            Some("/books?sort=name&limit=1&offset=2".to_string())
+        );
+        assert_eq!(
+            page.prev,
+            Some("/books?sort=name&limit=1&offset=0".to_string())
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_books_with_invalid_limit_failure() {
+    let database_path = "list_books_with_invalid_limit_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/books?limit=0")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "limit must be a number between".to_string()),
+        "checking if API response message is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_books_include_deleted_successful() {
+    let database_path = "list_books_include_deleted_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book_a = MockCatalog::new_book().build();
+    let book_b = MockCatalog::new_book().deleted_at(Utc::now()).build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book_a, &author.id)
+        .with_book(&book_b, &author.id)
+        .build();
+
+    let mut app = create_mock_app(db);
+
+    let response = app
+        .call(
+            Request::builder()
+                .method("GET")
+                .uri("/books")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<Book> = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(page.total == 1, "checking if deleted book is left out");
+        assert!(page.items[0].id == book_a.id);
+    }
+
+    let response = app
+        .call(
+            Request::builder()
+                .method("GET")
+                .uri("/books?include_deleted=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
     assert_eq!(
         response.status(),
         StatusCode::OK,
@@ -208,11 +482,79 @@ async fn list_books_with_search_wrong_params_successful() {
     );
 
     let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-    let returned_books: Vec<Book> = serde_json::from_slice(&body).unwrap();
+    let page: Page<Book> = serde_json::from_slice(&body).unwrap();
 
     {
-        assert!(returned_books.len() == 3);
+        assert!(page.total == 2, "checking if deleted book is included");
+        assert!(page
+            .items
+            .iter()
+            .any(|book| book.id == book_b.id && book.deleted_at.is_some()));
     }
 
     MockDatabaseBuilder::teardown(database_path.to_string());
 }
+
+#[tokio::test]
+async fn list_books_include_deleted_anonymous_failure() {
+    let database_path = "list_books_include_deleted_anonymous_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_public_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/books?include_deleted=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "checking if response is UNAUTHORIZED"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_books_invalid_include_deleted_failure() {
+    let database_path = "list_books_invalid_include_deleted_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/books?include_deleted=yes")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "include_deleted must be".to_string()),
+        "checking if API response message is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
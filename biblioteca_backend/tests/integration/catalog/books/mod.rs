@@ -2,4 +2,5 @@ pub mod create_book;
 pub mod delete_book;
 pub mod get_book;
 pub mod list_books;
+pub mod restore_book;
 pub mod update_book;
@@ -0,0 +1,101 @@
+use biblioteca_backend::catalog::model::Book;
+use chrono::Utc;
+use hyper::{Body, Request, StatusCode};
+use tower::ServiceExt;
+
+use crate::mocker::{
+    app::create_mock_app,
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+};
+
+#[tokio::test]
+async fn restore_book_deleted_book_successful() {
+    let database_path = "restore_book_deleted_book_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().deleted_at(Utc::now()).build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/restore", book.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let restored: Book = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert_eq!(restored.id, book.id);
+        assert!(restored.deleted_at.is_none());
+        assert_eq!(restored.contributors.len(), 1);
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            !querier.is_book_deleted(&book.id),
+            "checking if book was restored"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn restore_book_not_deleted_failure() {
+    let database_path = "restore_book_not_deleted_failure.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/restore", book.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NOT_FOUND,
+        "checking if response is NOT_FOUND"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_audit_entries_of(&book.id, 0),
+            "checking if nothing was recorded"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -1,4 +1,4 @@
-use biblioteca_backend::catalog::model::Book;
+use biblioteca_backend::catalog::model::{Book, ContributorRole};
 use hyper::{header, Body, Request, StatusCode};
 use serde_json::json;
 use tower::ServiceExt;
@@ -37,7 +37,7 @@ async fn update_book_correct_parameters_successful() {
                         "name": book_a.name,
                         "description": book_a.description,
                         "language": book_a.language,
-                        "author_id": author.id,
+                        "contributors": [{ "author_id": author.id, "role": "author" }],
                     }))
                     .unwrap(),
                 ))
@@ -51,6 +51,11 @@ async fn update_book_correct_parameters_successful() {
         name: book_a.name,
         description: book_a.description,
         language: book_a.language,
+        contributors: Vec::new(),
+        categories: Vec::new(),
+        total_copies: 0,
+        available_copies: 0,
+        deleted_at: None,
     };
 
     assert_eq!(
@@ -100,7 +105,7 @@ async fn update_book_non_existent_book_failure() {
                         "name": book_a.name,
                         "description": book_a.description,
                         "language": book_a.language,
-                        "author_id": author.id,
+                        "contributors": [{ "author_id": author.id, "role": "author" }],
                     }))
                     .unwrap(),
                 ))
@@ -154,7 +159,7 @@ async fn update_book_incorrect_parameters_failure() {
                         "name_incorrect": book_a.name,
                         "description": book_a.description,
                         "language": book_a.language,
-                        "author_id": author.id,
+                        "contributors": [{ "author_id": author.id, "role": "author" }],
                     }))
                     .unwrap(),
                 ))
@@ -198,7 +203,7 @@ async fn update_book_missing_parameters_failure() {
                     serde_json::to_string(&json!({
                         "description": book_a.description,
                         "language": book_a.language,
-                        "author_id": author.id,
+                        "contributors": [{ "author_id": author.id, "role": "author" }],
                     }))
                     .unwrap(),
                 ))
@@ -245,7 +250,7 @@ async fn update_book_existing_author_successful() {
                         "name": book_b.name,
                         "description": book_b.description,
                         "language": book_b.language,
-                        "author_id": author_a.id,
+                        "contributors": [{ "author_id": author_a.id, "role": "author" }],
                     }))
                     .unwrap(),
                 ))
@@ -306,7 +311,7 @@ async fn update_book_non_existent_author_failure() {
                         "name": book_b.name,
                         "description": book_b.description,
                         "language": book_b.language,
-                        "author_id": invalid_author_id,
+                        "contributors": [{ "author_id": invalid_author_id, "role": "author" }],
                     }))
                     .unwrap(),
                 ))
@@ -323,3 +328,124 @@ async fn update_book_non_existent_author_failure() {
 
     MockDatabaseBuilder::teardown(database_path.to_string());
 }
+
+#[tokio::test]
+async fn update_book_replace_categories_successful() {
+    let database_path = "update_book_replace_categories_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let category_a = MockCatalog::new_category().build();
+    let category_b = MockCatalog::new_category().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_category(&category_a)
+        .with_category(&category_b)
+        .with_book_category(&book.id, &category_a.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("PUT")
+                .uri(format!("/books/{}", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": book.name,
+                        "description": book.description,
+                        "language": book.language,
+                        "contributors": [{ "author_id": author.id, "role": "author" }],
+                        "category_ids": [category_b.id],
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            !querier.contains_book_category_mapping(&book.id, &category_a.id),
+            "checking if old book to category mapping was removed"
+        );
+        assert!(
+            querier.contains_book_category_mapping(&book.id, &category_b.id),
+            "checking if new book to category mapping exists"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn update_book_replace_contributors_successful() {
+    let database_path = "update_book_replace_contributors_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let editor = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_author(&editor)
+        .with_book(&book, &author.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("PUT")
+                .uri(format!("/books/{}", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": book.name,
+                        "description": book.description,
+                        "language": book.language,
+                        "contributors": [
+                            { "author_id": editor.id, "role": "editor" },
+                            { "author_id": author.id, "role": "author" },
+                        ],
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_book_contributor(&book.id, &editor.id, ContributorRole::Editor, 0),
+            "checking if editor is listed first"
+        );
+        assert!(
+            querier.contains_book_contributor(&book.id, &author.id, ContributorRole::Author, 1),
+            "checking if author is listed second"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,145 @@
+use biblioteca_backend::catalog::model::Category;
+use hyper::{header, Body, Method, Request, StatusCode};
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::mocker::{
+    api::BibliotecaApiResponse,
+    app::create_mock_app,
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+};
+
+#[tokio::test]
+async fn create_category_correct_parameters_successful() {
+    let database_path = "create_category_correct_parameters_successful.sqlite";
+
+    let original_category = MockCatalog::new_category().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/categories")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": original_category.name,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let created_category: Category = serde_json::from_slice(&body).unwrap();
+    let expected_category = Category {
+        id: created_category.id,
+        name: original_category.name,
+    };
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_category(&expected_category),
+            "checking if category was added properly"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_category_duplicate_name_failure() {
+    let database_path = "create_category_duplicate_name_failure.sqlite";
+
+    let category = MockCatalog::new_category().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_category(&category)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/categories")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": category.name,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "category with the same name".to_string()),
+        "checking if API response message is correct"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_categories(1),
+            "checking if no category was added"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_category_missing_parameters_failure() {
+    let database_path = "create_category_missing_parameters_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/categories")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_string(&json!({})).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "checking if response is correct (422)"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
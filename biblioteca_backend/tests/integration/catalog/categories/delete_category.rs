@@ -0,0 +1,61 @@
+use hyper::{Body, Request, StatusCode};
+use tower::ServiceExt;
+
+use crate::mocker::{
+    app::create_mock_app,
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+};
+
+#[tokio::test]
+async fn delete_category_existing_category_successful() {
+    let database_path = "delete_category_existing_category_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let category = MockCatalog::new_category().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_category(&category)
+        .with_book_category(&book.id, &category.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/categories/{}", category.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            !querier.contains_category(&category),
+            "checking if category was removed properly"
+        );
+        assert!(
+            !querier.contains_book_category_mapping(&book.id, &category.id),
+            "checking if book to category mapping doesn't exist"
+        );
+        assert!(
+            querier.contains_book(&book),
+            "checking if book was left untouched"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,80 @@
+use biblioteca_backend::catalog::model::Category;
+use hyper::{Body, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::mocker::{app::create_mock_app, catalog::MockCatalog, db::MockDatabaseBuilder};
+
+#[tokio::test]
+async fn get_category_category_exists_successful() {
+    let database_path = "get_category_category_exists_successful.sqlite";
+
+    let category = MockCatalog::new_category().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_category(&category)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/categories/{}", category.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let returned_category: Category = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(returned_category.id == category.id);
+        assert!(returned_category.name == category.name);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn get_category_non_existent_category_failure() {
+    let database_path = "get_category_non_existent_category_failure.sqlite";
+
+    let category = MockCatalog::new_category().build();
+
+    let incorrect_id = Uuid::new_v4();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_category(&category)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/categories/{}", incorrect_id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NOT_FOUND,
+        "checking if response is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,110 @@
+use biblioteca_backend::{catalog::model::Category, query::Page};
+use hyper::{Body, Request, StatusCode};
+use tower::ServiceExt;
+
+use crate::mocker::{
+    app::create_mock_app,
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+};
+
+#[tokio::test]
+async fn list_categories_successful() {
+    let database_path = "list_categories_successful.sqlite";
+
+    let category_a = MockCatalog::new_category().build();
+    let category_b = MockCatalog::new_category().build();
+    let category_c = MockCatalog::new_category().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_category(&category_a)
+        .with_category(&category_b)
+        .with_category(&category_c)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/categories")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<Category> = serde_json::from_slice(&body).unwrap();
+    let returned_categories = page.items;
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_categories(returned_categories.len() as i32),
+            "checking if category count is correct"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_categories_with_name_search_successful() {
+    let database_path = "list_categories_with_name_search_successful.sqlite";
+
+    let category_a = MockCatalog::new_category()
+        .name("Science Fiction".to_string())
+        .build();
+    let category_b = MockCatalog::new_category()
+        .name("Historical Fiction".to_string())
+        .build();
+    let category_c = MockCatalog::new_category()
+        .name("Biography".to_string())
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_category(&category_a)
+        .with_category(&category_b)
+        .with_category(&category_c)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/categories?name=Fiction")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<Category> = serde_json::from_slice(&body).unwrap();
+    let returned_categories = page.items;
+
+    {
+        assert!(returned_categories.len() == 2);
+        for category in returned_categories.iter() {
+            assert!(category.name.contains("Fiction"));
+        }
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
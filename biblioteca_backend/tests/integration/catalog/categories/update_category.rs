@@ -0,0 +1,118 @@
+use biblioteca_backend::catalog::model::Category;
+use hyper::{header, Body, Request, StatusCode};
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::mocker::{
+    api::BibliotecaApiResponse,
+    app::create_mock_app,
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+};
+
+#[tokio::test]
+async fn update_category_correct_parameters_successful() {
+    let database_path = "update_category_correct_parameters_successful.sqlite";
+
+    let category = MockCatalog::new_category().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_category(&category)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("PUT")
+                .uri(format!("/categories/{}", category.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": "New category name",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is OK"
+    );
+
+    let expected_category = Category {
+        id: category.id,
+        name: "New category name".to_string(),
+    };
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_category(&expected_category),
+            "checking if category was updated properly"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn update_category_duplicate_name_failure() {
+    let database_path = "update_category_duplicate_name_failure.sqlite";
+
+    let category_a = MockCatalog::new_category().build();
+    let category_b = MockCatalog::new_category().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_category(&category_a)
+        .with_category(&category_b)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("PUT")
+                .uri(format!("/categories/{}", category_a.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": category_b.name,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "category with the same name".to_string()),
+        "checking if API response message is correct"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_category(&category_a),
+            "checking if category was left unchanged"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,244 @@
+use biblioteca_backend::catalog::model::{Item, ItemCondition, ItemStatus};
+use hyper::{header, Body, Method, Request, StatusCode};
+use serde_json::json;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::mocker::{
+    api::BibliotecaApiResponse,
+    app::create_mock_app,
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    library::MockLibrary,
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn create_item_correct_parameters_successful() {
+    let database_path = "create_item_correct_parameters_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let original_item = MockCatalog::new_item()
+        .book_id(book.id)
+        .condition(ItemCondition::New)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/books/{}/items", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "barcode": original_item.barcode,
+                        "shelf_location": original_item.shelf_location,
+                        "condition": "new",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let created_item: Item = serde_json::from_slice(&body).unwrap();
+    let expected_item = Item {
+        id: created_item.id,
+        book_id: book.id,
+        barcode: original_item.barcode,
+        shelf_location: original_item.shelf_location,
+        condition: ItemCondition::New,
+        status: ItemStatus::Available,
+    };
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_item(&expected_item),
+            "checking if item was added properly"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_item_next_hold_ready_successful() {
+    let database_path = "create_item_next_hold_ready_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let original_item = MockCatalog::new_item().book_id(book.id).build();
+    let hold = MockLibrary::new_hold()
+        .user_id(user.id)
+        .book_id(book.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_hold(&hold)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/books/{}/items", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "barcode": original_item.barcode,
+                        "shelf_location": original_item.shelf_location,
+                        "condition": "new",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let created_item: Item = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert_eq!(created_item.status, ItemStatus::OnHold);
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.is_item_status(&created_item.id, ItemStatus::OnHold),
+            "checking if copy went to the hold shelf",
+        );
+        assert!(
+            querier.is_hold_ready(&hold.id, &created_item.id),
+            "checking if copy was set aside for the patron",
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_item_non_existent_book_failure() {
+    let database_path = "create_item_non_existent_book_failure.sqlite";
+
+    let item = MockCatalog::new_item().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/books/{}/items", Uuid::new_v4()))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "barcode": item.barcode,
+                        "shelf_location": item.shelf_location,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "book does not exist".to_string()),
+        "checking if API response message is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_item_duplicate_barcode_failure() {
+    let database_path = "create_item_duplicate_barcode_failure.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/books/{}/items", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "barcode": item.barcode,
+                        "shelf_location": "A1",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_items(1),
+            "checking if no item was added"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,187 @@
+use biblioteca_backend::{catalog::model::ItemStatus, library::model::LoanRecord, query::Page};
+use chrono::{Duration, Utc};
+use hyper::{Body, Request, StatusCode};
+use tower::{Service, ServiceExt};
+
+use crate::mocker::{
+    app::create_mock_app,
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    library::MockLibrary,
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn delete_item_existing_item_successful() {
+    let database_path = "delete_item_existing_item_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/items/{}", item.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.is_item_status(&item.id, ItemStatus::Withdrawn),
+            "checking if item was withdrawn properly"
+        );
+        assert!(
+            querier.contains_book(&book),
+            "checking if book was left untouched"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn delete_item_on_loan_failure() {
+    let database_path = "delete_item_on_loan_failure.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::Borrowed)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/items/{}", item.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_item(&item),
+            "checking if item was not removed"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn delete_item_keeps_loans_successful() {
+    let database_path = "delete_item_keeps_loans_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let borrow_entry = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .timestamp(Utc::now() - Duration::days(7))
+        .build();
+    let return_entry = MockLibrary::new_return_entry()
+        .id(borrow_entry.id)
+        .user_id(user.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_library_entry(&borrow_entry)
+        .with_library_entry(&return_entry)
+        .build();
+
+    let mut app = create_mock_app(db);
+
+    let response = app
+        .call(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/items/{}", item.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is OK"
+    );
+
+    let response = app
+        .call(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/users/{}/loans?status=returned", user.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<LoanRecord> = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(page.total == 1);
+        assert!(page.items[0].id == borrow_entry.id);
+        assert!(page.items[0].item_id == item.id);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,79 @@
+use biblioteca_backend::catalog::model::Item;
+use hyper::{Body, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::mocker::{app::create_mock_app, catalog::MockCatalog, db::MockDatabaseBuilder};
+
+#[tokio::test]
+async fn get_item_item_exists_successful() {
+    let database_path = "get_item_item_exists_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/items/{}", item.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let returned_item: Item = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(returned_item.id == item.id);
+        assert!(returned_item.book_id == book.id);
+        assert!(returned_item.barcode == item.barcode);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn get_item_non_existent_item_failure() {
+    let database_path = "get_item_non_existent_item_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/items/{}", Uuid::new_v4()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NOT_FOUND,
+        "checking if response is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
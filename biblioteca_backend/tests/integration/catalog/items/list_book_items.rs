@@ -0,0 +1,86 @@
+use biblioteca_backend::catalog::model::Item;
+use hyper::{Body, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::mocker::{app::create_mock_app, catalog::MockCatalog, db::MockDatabaseBuilder};
+
+#[tokio::test]
+async fn list_book_items_successful() {
+    let database_path = "list_book_items_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book_a = MockCatalog::new_book().build();
+    let book_b = MockCatalog::new_book().build();
+    let item_a = MockCatalog::new_item().book_id(book_a.id).build();
+    let item_b = MockCatalog::new_item().book_id(book_a.id).build();
+    let item_c = MockCatalog::new_item().book_id(book_b.id).build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book_a, &author.id)
+        .with_book(&book_b, &author.id)
+        .with_item(&item_a)
+        .with_item(&item_b)
+        .with_item(&item_c)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/books/{}/items", book_a.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let returned_items: Vec<Item> = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(returned_items.len() == 2);
+        for item in returned_items.iter() {
+            assert!(item.book_id == book_a.id);
+        }
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_book_items_non_existent_book_failure() {
+    let database_path = "list_book_items_non_existent_book_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/books/{}/items", Uuid::new_v4()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NOT_FOUND,
+        "checking if response is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,5 @@
+pub mod create_item;
+pub mod delete_item;
+pub mod get_item;
+pub mod list_book_items;
+pub mod update_item;
@@ -1,2 +1,5 @@
 pub mod authors;
 pub mod books;
+pub mod categories;
+pub mod items;
+pub mod search;
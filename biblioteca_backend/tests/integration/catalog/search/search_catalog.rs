@@ -0,0 +1,333 @@
+use axum::Router;
+use biblioteca_backend::{
+    catalog::model::{SearchResult, SearchResultKind},
+    query::Page,
+};
+use chrono::Utc;
+use hyper::{header, Body, Request, StatusCode};
+use serde_json::json;
+use tower::{Service, ServiceExt};
+
+use crate::mocker::{
+    api::BibliotecaApiResponse, app::create_mock_app, catalog::MockCatalog, db::MockDatabaseBuilder,
+};
+
+#[tokio::test]
+async fn search_catalog_ranked_successful() {
+    let database_path = "search_catalog_ranked_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book_a = MockCatalog::new_book()
+        .name("The Name of the Wind".to_string())
+        .description("A young wizard recounts his life at the University.".to_string())
+        .build();
+    let book_b = MockCatalog::new_book()
+        .name("A Wizard of Earthsea".to_string())
+        .description("A boy with a gift for magic learns its price.".to_string())
+        .build();
+    let book_c = MockCatalog::new_book()
+        .name("Dune".to_string())
+        .description("Politics and spice on a desert planet.".to_string())
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book_a, &author.id)
+        .with_book(&book_b, &author.id)
+        .with_book(&book_c, &author.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/search?q=wizard")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<SearchResult> = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(page.total == 2);
+        assert!(
+            page.items[0].id == book_b.id,
+            "checking if name matches rank first"
+        );
+        assert!(page.items[1].id == book_a.id);
+        assert!(page.items[1].kind == SearchResultKind::Book);
+        assert!(page.items[1].snippet.contains("<mark>wizard</mark>"));
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn search_catalog_escapes_snippet_successful() {
+    let database_path = "search_catalog_escapes_snippet_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book()
+        .name("<script>alert('wizard')</script> & friends".to_string())
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/search?q=wizard")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<SearchResult> = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(page.total == 1);
+        assert_eq!(
+            page.items[0].snippet,
+            "&lt;script&gt;alert(&#39;<mark>wizard</mark>&#39;)&lt;/script&gt; &amp; friends",
+            "checking if only the highlight is left as markup"
+        );
+        assert_eq!(
+            page.items[0].name, book.name,
+            "checking if the name is returned as is"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn search_catalog_multiple_words_successful() {
+    let database_path = "search_catalog_multiple_words_successful.sqlite";
+
+    let author_a = MockCatalog::new_author()
+        .name("Ursula K. Le Guin".to_string())
+        .description("Wrote of dragons and distant worlds.".to_string())
+        .build();
+    let author_b = MockCatalog::new_author()
+        .name("Anne McCaffrey".to_string())
+        .description("Wrote of the dragonriders of Pern.".to_string())
+        .build();
+    let category = MockCatalog::new_category()
+        .name("Dragons".to_string())
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author_a)
+        .with_author(&author_b)
+        .with_category(&category)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/search?q=dragon%20worlds&kind=author")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<SearchResult> = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(page.total == 1);
+        assert!(page.items[0].id == author_a.id);
+        assert!(page.items[0].kind == SearchResultKind::Author);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn search_catalog_after_update_and_delete_successful() {
+    let database_path = "search_catalog_after_update_and_delete_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book_a = MockCatalog::new_book()
+        .name("Foundation".to_string())
+        .build();
+    let book_b = MockCatalog::new_book()
+        .name("Foundation and Empire".to_string())
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book_a, &author.id)
+        .with_book(&book_b, &author.id)
+        .build();
+
+    let mut app = create_mock_app(db);
+
+    app.call(
+        Request::builder()
+            .method("PUT")
+            .uri(format!("/books/{}", book_a.id))
+            .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(
+                serde_json::to_string(&json!({
+                    "name": "I, Robot",
+                    "description": book_a.description,
+                    "language": book_a.language,
+                    "contributors": [{ "author_id": author.id, "role": "author" }],
+                }))
+                .unwrap(),
+            ))
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    app.call(
+        Request::builder()
+            .method("DELETE")
+            .uri(format!("/books/{}", book_b.id))
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    {
+        let page = search_with_api(&mut app, "foundation").await;
+        assert!(page.total == 0, "checking if old entries are removed");
+
+        let page = search_with_api(&mut app, "robot").await;
+        assert!(page.total == 1, "checking if updated entry is found");
+        assert!(page.items[0].id == book_a.id);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn search_catalog_after_restore_successful() {
+    let database_path = "search_catalog_after_restore_successful.sqlite";
+
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book()
+        .name("Foundation".to_string())
+        .deleted_at(Utc::now())
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .build();
+
+    let mut app = create_mock_app(db);
+
+    {
+        let page = search_with_api(&mut app, "foundation").await;
+        assert!(page.total == 0, "checking if deleted book is not found");
+    }
+
+    app.call(
+        Request::builder()
+            .method("POST")
+            .uri(format!("/books/{}/restore", book.id))
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    {
+        let page = search_with_api(&mut app, "foundation").await;
+        assert!(page.total == 1, "checking if restored book is found");
+        assert!(page.items[0].id == book.id);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn search_catalog_without_query_failure() {
+    let database_path = "search_catalog_without_query_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/search?q=%22%2A%20-")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "search must have a query".to_string()),
+        "checking if API response message is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+async fn search_with_api(app: &mut Router, q: &str) -> Page<SearchResult> {
+    let response = app
+        .call(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/search?q={}", q))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
@@ -0,0 +1,190 @@
+use std::{fs, net::SocketAddr, path::PathBuf};
+
+use biblioteca_backend::config::{Config, ConfigError, ConfigOverrides, LogFormat, LogLevel};
+
+// Writes a config file for a test, returning the overrides that point to it
+fn write_config(config_path: &str, contents: &str) -> ConfigOverrides {
+    fs::write(config_path, contents).unwrap();
+
+    ConfigOverrides {
+        config: Some(PathBuf::from(config_path)),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn load_config_from_file_successful() {
+    let config_path = "load_config_from_file_successful.toml";
+
+    let overrides = write_config(
+        config_path,
+        r#"
+        [server]
+        bind_address = "0.0.0.0:8080"
+
+        [database]
+        path = "production.sqlite"
+        pool_size = 4
+
+        [log]
+        level = "warn"
+        format = "json"
+
+        [library]
+        hold_pickup_days = 5
+        default_loan_period_days = 21
+        "#,
+    );
+
+    let config = Config::load(&overrides).unwrap();
+
+    {
+        assert_eq!(
+            config.server.bind_address,
+            "0.0.0.0:8080".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(config.database.path, "production.sqlite");
+        assert_eq!(config.database.pool_size, 4);
+        assert_eq!(config.log.level, LogLevel::Warn);
+        assert_eq!(config.log.format, LogFormat::Json);
+        assert_eq!(config.library.hold_pickup_days, 5);
+        assert_eq!(config.library.default_loan_period_days, 21);
+        assert_eq!(
+            config.library.max_outstanding_fines_cents,
+            Config::default().library.max_outstanding_fines_cents,
+            "checking if values left out are kept at their defaults"
+        );
+    }
+
+    fs::remove_file(config_path).unwrap();
+}
+
+#[test]
+fn load_config_overrides_file_successful() {
+    let config_path = "load_config_overrides_file_successful.toml";
+
+    let overrides = ConfigOverrides {
+        database_path: Some("override.sqlite".to_string()),
+        log_level: Some(LogLevel::Error),
+        default_max_renewals: Some(0),
+        ..write_config(
+            config_path,
+            r#"
+            [database]
+            path = "file.sqlite"
+            pool_size = 4
+
+            [log]
+            level = "info"
+            "#,
+        )
+    };
+
+    let config = Config::load(&overrides).unwrap();
+
+    {
+        assert_eq!(config.database.path, "override.sqlite");
+        assert_eq!(config.database.pool_size, 4);
+        assert_eq!(config.log.level, LogLevel::Error);
+        assert_eq!(config.library.default_max_renewals, 0);
+    }
+
+    fs::remove_file(config_path).unwrap();
+}
+
+#[test]
+fn load_config_unknown_field_failure() {
+    let config_path = "load_config_unknown_field_failure.toml";
+
+    let overrides = write_config(
+        config_path,
+        r#"
+        [server]
+        bind_adress = "0.0.0.0:8080"
+        "#,
+    );
+
+    let result = Config::load(&overrides);
+
+    {
+        assert!(matches!(result, Err(ConfigError::ParseError(_, _))));
+        assert!(result.unwrap_err().to_string().contains("bind_adress"));
+    }
+
+    fs::remove_file(config_path).unwrap();
+}
+
+#[test]
+fn load_config_invalid_value_failure() {
+    let config_path = "load_config_invalid_value_failure.toml";
+
+    let overrides = write_config(
+        config_path,
+        r#"
+        [database]
+        pool_size = 0
+        "#,
+    );
+
+    let result = Config::load(&overrides);
+
+    {
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidValue("database.pool_size", _))
+        ));
+    }
+
+    fs::remove_file(config_path).unwrap();
+}
+
+#[test]
+fn load_config_invalid_override_failure() {
+    let overrides = ConfigOverrides {
+        default_loan_period_days: Some(0),
+        ..Default::default()
+    };
+
+    let result = Config::load(&overrides);
+
+    {
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidValue(
+                "library.default_loan_period_days",
+                _
+            ))
+        ));
+    }
+}
+
+#[test]
+fn load_config_missing_file_failure() {
+    let overrides = ConfigOverrides {
+        config: Some(PathBuf::from("load_config_missing_file_failure.toml")),
+        ..Default::default()
+    };
+
+    let result = Config::load(&overrides);
+
+    {
+        assert!(matches!(result, Err(ConfigError::ReadError(_, _))));
+    }
+}
+
+#[test]
+fn load_config_short_token_secret_failure() {
+    let overrides = ConfigOverrides {
+        token_secret: Some("too short".to_string()),
+        ..Default::default()
+    };
+
+    let result = Config::load(&overrides);
+
+    {
+        assert!(matches!(
+            result,
+            Err(ConfigError::InvalidValue("auth.token_secret", _))
+        ));
+    }
+}
@@ -0,0 +1 @@
+pub mod load_config;
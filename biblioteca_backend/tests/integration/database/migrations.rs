@@ -0,0 +1,218 @@
+use biblioteca_backend::{
+    admin::ADMIN_ROLE_ID,
+    database::{
+        get_schema_version, latest_schema_version, migrate_db, setup_db, MigrationError,
+        DEFAULT_POOL_SIZE, MIGRATIONS,
+    },
+};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+use uuid::Uuid;
+
+use crate::mocker::db::MockDatabaseBuilder;
+
+// Creates a database as it was before migrations were tracked, with a book out on loan, the role
+// of the administrators and another role of the same name
+fn create_unversioned_db(database_path: &str, book_id: Uuid, borrowed_at: DateTime<Utc>) {
+    let conn = Connection::open(database_path).unwrap();
+    conn.execute_batch(MIGRATIONS[0].sql).unwrap();
+
+    let author_id = Uuid::new_v4();
+    let user_id = Uuid::new_v4();
+    let user_role_id = Uuid::new_v4();
+
+    conn.execute(
+        "INSERT INTO authors (id, name, description, country) VALUES (?1, 'Isaac Asimov', '', 'USA')",
+        [author_id],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO books (id, name, description, language) VALUES (?1, 'Foundation', '', 'English')",
+        [book_id],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO map_books_to_authors (book_id, author_id) VALUES (?1, ?2)",
+        (book_id, author_id),
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO user_roles (id, name, num_borrowable_books) VALUES (?1, 'Student', 2)",
+        [user_role_id],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO user_roles (id, name, num_borrowable_books) VALUES (?1, 'Administrator', 0)",
+        [ADMIN_ROLE_ID],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO user_roles (id, name, num_borrowable_books) VALUES (?1, 'Administrator', 2)",
+        [Uuid::new_v4()],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO users (id, username) VALUES (?1, 'student')",
+        [user_id],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO map_users_to_user_roles (user_id, user_role_id) VALUES (?1, ?2)",
+        (user_id, user_role_id),
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO map_users_to_borrowed_books (id, user_id, book_id, timestamp, action) VALUES (?1, ?2, ?3, ?4, 'Borrowed')",
+        (Uuid::new_v4(), user_id, book_id, borrowed_at),
+    )
+    .unwrap();
+}
+
+#[tokio::test]
+async fn migrate_new_database_successful() {
+    let database_path = "migrate_new_database_successful.sqlite";
+
+    let pool = setup_db(database_path.to_string(), DEFAULT_POOL_SIZE).unwrap();
+
+    {
+        let conn = pool.get().unwrap();
+        assert_eq!(get_schema_version(&conn).unwrap(), latest_schema_version());
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn migrate_unversioned_database_successful() {
+    let database_path = "migrate_unversioned_database_successful.sqlite";
+
+    let book_id = Uuid::new_v4();
+    let borrowed_at = Utc::now();
+    create_unversioned_db(database_path, book_id, borrowed_at);
+
+    let pool = setup_db(database_path.to_string(), DEFAULT_POOL_SIZE).unwrap();
+
+    {
+        let conn = pool.get().unwrap();
+        assert_eq!(get_schema_version(&conn).unwrap(), latest_schema_version());
+
+        let role: String = conn
+            .query_row(
+                "SELECT role FROM map_books_to_authors WHERE book_id = ?1",
+                [book_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(role, "author", "checking if author is kept as contributor");
+
+        let item_status: String = conn
+            .query_row(
+                "SELECT status FROM items WHERE book_id = ?1",
+                [book_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(
+            item_status, "borrowed",
+            "checking if loaned copy is created"
+        );
+
+        let due_at: DateTime<Utc> = conn
+            .query_row(
+                "SELECT due_at FROM map_users_to_borrowed_books WHERE book_id = ?1",
+                [book_id],
+                |row| row.get(0),
+            )
+            .unwrap();
+        let expected_due_at = borrowed_at + Duration::days(14);
+        assert!(
+            (due_at - expected_due_at).num_seconds().abs() < 1,
+            "checking if loan is due after the default loan period"
+        );
+
+        let num_search_entries: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM catalog_search WHERE catalog_search MATCH 'foundation'",
+                (),
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(num_search_entries, 1, "checking if book is indexed");
+
+        let admin_permissions: String = conn
+            .query_row(
+                "SELECT permissions FROM user_roles WHERE id = ?1",
+                [ADMIN_ROLE_ID],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(
+            admin_permissions, "catalog:write,users:admin,circulation:staff,audit:read",
+            "checking if administrators keep running the library and may read the audit log"
+        );
+
+        let num_other_roles_with_permissions: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM user_roles WHERE id != ?1 AND permissions != ''",
+                [ADMIN_ROLE_ID],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(
+            num_other_roles_with_permissions, 0,
+            "checking if roles named like the administrators' are given no permissions"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn migrate_database_dry_run_successful() {
+    let database_path = "migrate_database_dry_run_successful.sqlite";
+
+    create_unversioned_db(database_path, Uuid::new_v4(), Utc::now());
+
+    {
+        let mut conn = Connection::open(database_path).unwrap();
+        let migrations = migrate_db(&mut conn, true).unwrap();
+
+        assert_eq!(migrations.len(), MIGRATIONS.len());
+        assert_eq!(get_schema_version(&conn).unwrap(), 0);
+
+        let num_items_tables: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE name = 'items'",
+                (),
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(
+            num_items_tables, 0,
+            "checking if migrations are rolled back"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn migrate_newer_database_failure() {
+    let database_path = "migrate_newer_database_failure.sqlite";
+
+    {
+        let conn = Connection::open(database_path).unwrap();
+        conn.pragma_update(None, "user_version", latest_schema_version() + 1)
+            .unwrap();
+    }
+
+    {
+        let result = setup_db(database_path.to_string(), DEFAULT_POOL_SIZE);
+        assert!(
+            matches!(result, Err(MigrationError::DatabaseTooNew(..))),
+            "checking if newer database is refused"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,134 @@
+use biblioteca_backend::library::model::{FineEntry, FineEntryKind};
+use hyper::{header, Body, Request, StatusCode};
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::mocker::{
+    api::BibliotecaApiResponse,
+    app::create_mock_app,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    library::MockLibrary,
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn add_waiver_with_reason_successful() {
+    let database_path = "add_waiver_with_reason_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let fine = MockLibrary::new_fine()
+        .user_id(user.id)
+        .amount_cents(400)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_fine_entry(&fine)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/users/{}/waivers", user.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "amount_cents": 400,
+                        "reason": "book was returned in the drop box on time",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let waiver: FineEntry = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(waiver.kind == FineEntryKind::Waiver);
+        assert!(waiver.reason == Some("book was returned in the drop box on time".to_string()));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.has_fine_balance(&user.id, 0),
+            "checking if waiver cleared the balance"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn add_waiver_missing_reason_failure() {
+    let database_path = "add_waiver_missing_reason_failure.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let fine = MockLibrary::new_fine()
+        .user_id(user.id)
+        .amount_cents(400)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_fine_entry(&fine)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/users/{}/waivers", user.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "amount_cents": 400,
+                        "reason": " ",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "must have a reason".to_string()),
+        "checking if API response message is correct"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.has_fine_balance(&user.id, 400),
+            "checking if balance was left unchanged"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -1,3 +1,5 @@
+use biblioteca_backend::{catalog::model::ItemStatus, library::model::Loan};
+use chrono::{Duration, Utc};
 use hyper::{header, Body, Request, StatusCode};
 use serde_json::json;
 use tower::ServiceExt;
@@ -5,7 +7,8 @@ use uuid::Uuid;
 
 use crate::mocker::{
     api::BibliotecaApiResponse,
-    app::create_mock_app,
+    app::{create_mock_app, create_mock_public_app},
+    auth::login,
     catalog::MockCatalog,
     db::{MockDatabaseBuilder, MockDatabaseQuerier},
     library::MockLibrary,
@@ -19,6 +22,7 @@ async fn borrow_book_can_borrow_successful() {
     let user = MockUserBase::new_user().build();
     let user_role = MockUserBase::new_user_role().build();
     let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
     let author = MockCatalog::new_author().build();
 
     let db = MockDatabaseBuilder::create(database_path.to_string())
@@ -26,6 +30,7 @@ async fn borrow_book_can_borrow_successful() {
         .with_user(&user, &user_role)
         .with_author(&author)
         .with_book(&book, &author.id)
+        .with_item(&item)
         .build();
 
     let app = create_mock_app(db);
@@ -71,6 +76,7 @@ async fn borrow_book_book_non_existent_failure() {
     let user = MockUserBase::new_user().build();
     let user_role = MockUserBase::new_user_role().build();
     let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
     let author = MockCatalog::new_author().build();
 
     let incorrect_book_id = Uuid::new_v4();
@@ -80,6 +86,7 @@ async fn borrow_book_book_non_existent_failure() {
         .with_user(&user, &user_role)
         .with_author(&author)
         .with_book(&book, &author.id)
+        .with_item(&item)
         .build();
 
     let app = create_mock_app(db);
@@ -125,6 +132,7 @@ async fn borrow_book_user_non_existent_failure() {
     let user = MockUserBase::new_user().build();
     let user_role = MockUserBase::new_user_role().build();
     let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
     let author = MockCatalog::new_author().build();
 
     let incorrect_user_id = Uuid::new_v4();
@@ -134,6 +142,7 @@ async fn borrow_book_user_non_existent_failure() {
         .with_user(&user, &user_role)
         .with_author(&author)
         .with_book(&book, &author.id)
+        .with_item(&item)
         .build();
 
     let app = create_mock_app(db);
@@ -180,9 +189,14 @@ async fn borrow_book_book_already_borrowed_failure() {
     let user_b = MockUserBase::new_user().build();
     let user_role = MockUserBase::new_user_role().build();
     let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::Borrowed)
+        .build();
     let author = MockCatalog::new_author().build();
     let user_a_borrow_entry = MockLibrary::new_borrow_entry()
         .book_id(book.id)
+        .item_id(item.id)
         .user_id(user_a.id)
         .build();
 
@@ -192,6 +206,7 @@ async fn borrow_book_book_already_borrowed_failure() {
         .with_user(&user_b, &user_role)
         .with_author(&author)
         .with_book(&book, &author.id)
+        .with_item(&item)
         .with_library_entry(&user_a_borrow_entry)
         .build();
 
@@ -240,10 +255,16 @@ async fn borrow_book_user_borrowed_too_many_failure() {
         .num_borrowable_books(1)
         .build();
     let book_a = MockCatalog::new_book().build();
+    let item_a = MockCatalog::new_item()
+        .book_id(book_a.id)
+        .status(ItemStatus::Borrowed)
+        .build();
     let book_b = MockCatalog::new_book().build();
+    let item_b = MockCatalog::new_item().book_id(book_b.id).build();
     let author = MockCatalog::new_author().build();
     let user_borrow_entry = MockLibrary::new_borrow_entry()
         .book_id(book_a.id)
+        .item_id(item_a.id)
         .user_id(user.id)
         .build();
 
@@ -252,7 +273,9 @@ async fn borrow_book_user_borrowed_too_many_failure() {
         .with_user(&user, &user_role)
         .with_author(&author)
         .with_book(&book_a, &author.id)
+        .with_item(&item_a)
         .with_book(&book_b, &author.id)
+        .with_item(&item_b)
         .with_library_entry(&user_borrow_entry)
         .build();
 
@@ -294,3 +317,1106 @@ async fn borrow_book_user_borrowed_too_many_failure() {
 
     MockDatabaseBuilder::teardown(database_path.to_string());
 }
+
+#[tokio::test]
+async fn borrow_book_limit_of_other_role_successful() {
+    let database_path = "borrow_book_limit_of_other_role_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let student_role = MockUserBase::new_user_role()
+        .num_borrowable_books(1)
+        .loan_period_days(7)
+        .build();
+    let staff_role = MockUserBase::new_user_role()
+        .num_borrowable_books(3)
+        .loan_period_days(28)
+        .build();
+    let book_a = MockCatalog::new_book().build();
+    let item_a = MockCatalog::new_item()
+        .book_id(book_a.id)
+        .status(ItemStatus::Borrowed)
+        .build();
+    let book_b = MockCatalog::new_book().build();
+    let item_b = MockCatalog::new_item().book_id(book_b.id).build();
+    let author = MockCatalog::new_author().build();
+    let user_borrow_entry = MockLibrary::new_borrow_entry()
+        .book_id(book_a.id)
+        .item_id(item_a.id)
+        .user_id(user.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&student_role)
+        .with_user_role(&staff_role)
+        .with_user(&user, &student_role)
+        .with_granted_user_role(&user, &staff_role, Some(Utc::now() + Duration::days(30)))
+        .with_author(&author)
+        .with_book(&book_a, &author.id)
+        .with_item(&item_a)
+        .with_book(&book_b, &author.id)
+        .with_item(&item_b)
+        .with_library_entry(&user_borrow_entry)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book_b.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let loan: Loan = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        loan.due_at - loan.borrowed_at == Duration::days(28),
+        "checking if longest loan period of the roles is used"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_limit_of_expired_role_failure() {
+    let database_path = "borrow_book_limit_of_expired_role_failure.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let student_role = MockUserBase::new_user_role()
+        .num_borrowable_books(1)
+        .build();
+    let staff_role = MockUserBase::new_user_role()
+        .num_borrowable_books(3)
+        .build();
+    let book_a = MockCatalog::new_book().build();
+    let item_a = MockCatalog::new_item()
+        .book_id(book_a.id)
+        .status(ItemStatus::Borrowed)
+        .build();
+    let book_b = MockCatalog::new_book().build();
+    let item_b = MockCatalog::new_item().book_id(book_b.id).build();
+    let author = MockCatalog::new_author().build();
+    let user_borrow_entry = MockLibrary::new_borrow_entry()
+        .book_id(book_a.id)
+        .item_id(item_a.id)
+        .user_id(user.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&student_role)
+        .with_user_role(&staff_role)
+        .with_user(&user, &student_role)
+        .with_granted_user_role(&user, &staff_role, Some(Utc::now() - Duration::days(1)))
+        .with_author(&author)
+        .with_book(&book_a, &author.id)
+        .with_item(&item_a)
+        .with_book(&book_b, &author.id)
+        .with_item(&item_b)
+        .with_library_entry(&user_borrow_entry)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book_b.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (bad request)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(
+            40001,
+            "user has reached max num of borrowable books (max: 1)".to_string()
+        ),
+        "checking if limit of expired role is left out"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_other_copy_available_successful() {
+    let database_path = "borrow_book_other_copy_available_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let borrowed_item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::Borrowed)
+        .build();
+    let available_item = MockCatalog::new_item().book_id(book.id).build();
+    let author = MockCatalog::new_author().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&borrowed_item)
+        .with_item(&available_item)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.is_item_status(&available_item.id, ItemStatus::Borrowed),
+            "checking if available copy was lent",
+        )
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_specific_item_successful() {
+    let database_path = "borrow_book_specific_item_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item_a = MockCatalog::new_item().book_id(book.id).build();
+    let item_b = MockCatalog::new_item().book_id(book.id).build();
+    let author = MockCatalog::new_author().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item_a)
+        .with_item(&item_b)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user.id,
+                        "item_id": item_b.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.is_item_status(&item_b.id, ItemStatus::Borrowed),
+            "checking if requested copy was lent",
+        );
+        assert!(
+            querier.is_item_status(&item_a.id, ItemStatus::Available),
+            "checking if other copy is still available",
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_item_in_maintenance_failure() {
+    let database_path = "borrow_book_item_in_maintenance_failure.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::Maintenance)
+        .build();
+    let author = MockCatalog::new_author().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user.id,
+                        "item_id": item.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "not available for borrowing".to_string()),
+        "checking if API response message is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_no_copies_failure() {
+    let database_path = "borrow_book_no_copies_failure.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let author = MockCatalog::new_author().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "no copies".to_string()),
+        "checking if API response message is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_due_date_from_user_role_successful() {
+    let database_path = "borrow_book_due_date_from_user_role_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().loan_period_days(21).build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let author = MockCatalog::new_author().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let loan: Loan = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(loan.book_id == book.id);
+        assert!(loan.item_id == item.id);
+        assert!(loan.user_id == user.id);
+        assert!(loan.due_at - loan.borrowed_at == Duration::days(21));
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_on_hold_for_other_user_failure() {
+    let database_path = "borrow_book_on_hold_for_other_user_failure.sqlite";
+
+    let user_a = MockUserBase::new_user().build();
+    let user_b = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::OnHold)
+        .build();
+    let author = MockCatalog::new_author().build();
+    let hold = MockLibrary::new_hold()
+        .book_id(book.id)
+        .user_id(user_a.id)
+        .ready(item.id, Utc::now() + Duration::days(1))
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user_a, &user_role)
+        .with_user(&user_b, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_hold(&hold)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user_b.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "on hold for another user".to_string()),
+        "checking if API response message is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_on_hold_for_user_successful() {
+    let database_path = "borrow_book_on_hold_for_user_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::OnHold)
+        .build();
+    let author = MockCatalog::new_author().build();
+    let hold = MockLibrary::new_hold()
+        .book_id(book.id)
+        .user_id(user.id)
+        .ready(item.id, Utc::now() + Duration::days(1))
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_hold(&hold)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.is_item_status(&item.id, ItemStatus::Borrowed),
+            "checking if held copy was lent",
+        );
+        assert!(
+            querier.contains_num_holds(0),
+            "checking if hold was fulfilled"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_hold_pickup_expired_successful() {
+    let database_path = "borrow_book_hold_pickup_expired_successful.sqlite";
+
+    let user_a = MockUserBase::new_user().build();
+    let user_b = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::OnHold)
+        .build();
+    let author = MockCatalog::new_author().build();
+    let hold = MockLibrary::new_hold()
+        .book_id(book.id)
+        .user_id(user_a.id)
+        .ready(item.id, Utc::now() - Duration::hours(1))
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user_a, &user_role)
+        .with_user(&user_b, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_hold(&hold)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user_b.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.is_item_status(&item.id, ItemStatus::Borrowed),
+            "checking if copy was lent after pickup window passed",
+        );
+        assert!(querier.contains_num_holds(0), "checking if hold expired");
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_other_user_waiting_failure() {
+    let database_path = "borrow_book_other_user_waiting_failure.sqlite";
+
+    let user_a = MockUserBase::new_user().build();
+    let user_b = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let author = MockCatalog::new_author().build();
+    let hold = MockLibrary::new_hold()
+        .book_id(book.id)
+        .user_id(user_a.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user_a, &user_role)
+        .with_user(&user_b, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_hold(&hold)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user_b.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::CONFLICT,
+        "checking if response is correct (409)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40901, "active holds by other users".to_string()),
+        "checking if API response message is correct"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.is_item_status(&item.id, ItemStatus::Available),
+            "checking if copy was not lent past the queue",
+        );
+        assert!(querier.contains_num_holds(1), "checking if hold was kept");
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_outstanding_fines_failure() {
+    let database_path = "borrow_book_outstanding_fines_failure.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let author = MockCatalog::new_author().build();
+    let fine = MockLibrary::new_fine()
+        .user_id(user.id)
+        .amount_cents(501)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_fine_entry(&fine)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "outstanding fines".to_string()),
+        "checking if API response message is correct"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            !querier.is_book_borrowed(&book.id),
+            "checking if book was not borrowed"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_user_suspended_failure() {
+    let database_path = "borrow_book_user_suspended_failure.sqlite";
+
+    let user = MockUserBase::new_user()
+        .suspended("lost a borrowed book", None)
+        .build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let author = MockCatalog::new_author().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (bad request)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(
+            api_response.is_correct(40001, "user is suspended".to_string()),
+            "checking if suspended user is refused"
+        );
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            !querier.is_book_borrowed(&book.id),
+            "checking if book was not borrowed",
+        )
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_suspension_ended_successful() {
+    let database_path = "borrow_book_suspension_ended_successful.sqlite";
+
+    let user = MockUserBase::new_user()
+        .suspended("returned books late", Some(Utc::now() - Duration::days(1)))
+        .build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let author = MockCatalog::new_author().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.is_book_borrowed(&book.id),
+            "checking if book is borrowed",
+        )
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_membership_expired_failure() {
+    let database_path = "borrow_book_membership_expired_failure.sqlite";
+
+    let user = MockUserBase::new_user()
+        .membership_expires_at(Utc::now() - Duration::days(1))
+        .build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let author = MockCatalog::new_author().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (bad request)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40001, "membership of user has expired".to_string()),
+        "checking if user with expired membership is refused"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_authenticated_user_successful() {
+    let database_path = "borrow_book_authenticated_user_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let author = MockCatalog::new_author().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .build();
+
+    let app = create_mock_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::from(serde_json::to_string(&json!({})).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let loan: Loan = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(loan.user_id, user.id, "checking if loan is of the user");
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_other_user_than_authenticated_failure() {
+    let database_path = "borrow_book_other_user_than_authenticated_failure.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let other_user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let author = MockCatalog::new_author().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user(&other_user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .build();
+
+    let app = create_mock_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": other_user.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::FORBIDDEN,
+        "checking if response is correct (403)"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        api_response.is_correct(40301, "only be made for the authenticated user".to_string()),
+        "checking if API response message is correct"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            !querier.is_book_borrowed(&book.id),
+            "checking if book was not borrowed"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn borrow_book_anonymous_failure() {
+    let database_path = "borrow_book_anonymous_failure.sqlite";
+
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let author = MockCatalog::new_author().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .build();
+
+    let app = create_mock_public_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/borrow", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(serde_json::to_string(&json!({})).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "checking if response is correct (401)"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
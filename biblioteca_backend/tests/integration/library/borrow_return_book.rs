@@ -18,6 +18,7 @@ async fn borrow_book_return_book_successful() {
     let user = MockUserBase::new_user().build();
     let user_role = MockUserBase::new_user_role().build();
     let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
     let author = MockCatalog::new_author().build();
 
     let db = MockDatabaseBuilder::create(database_path.to_string())
@@ -25,6 +26,7 @@ async fn borrow_book_return_book_successful() {
         .with_user(&user, &user_role)
         .with_author(&author)
         .with_book(&book, &author.id)
+        .with_item(&item)
         .build();
 
     let mut app = create_mock_app(db);
@@ -53,9 +55,13 @@ async fn borrow_multiple_books_return_multiple_books_successful() {
         .num_borrowable_books(4)
         .build();
     let book_a = MockCatalog::new_book().build();
+    let item_a = MockCatalog::new_item().book_id(book_a.id).build();
     let book_b = MockCatalog::new_book().build();
+    let item_b = MockCatalog::new_item().book_id(book_b.id).build();
     let book_c = MockCatalog::new_book().build();
+    let item_c = MockCatalog::new_item().book_id(book_c.id).build();
     let book_d = MockCatalog::new_book().build();
+    let item_d = MockCatalog::new_item().book_id(book_d.id).build();
     let author = MockCatalog::new_author().build();
 
     let db = MockDatabaseBuilder::create(database_path.to_string())
@@ -63,9 +69,13 @@ async fn borrow_multiple_books_return_multiple_books_successful() {
         .with_user(&user, &user_role)
         .with_author(&author)
         .with_book(&book_a, &author.id)
+        .with_item(&item_a)
         .with_book(&book_b, &author.id)
+        .with_item(&item_b)
         .with_book(&book_c, &author.id)
+        .with_item(&item_c)
         .with_book(&book_d, &author.id)
+        .with_item(&item_d)
         .build();
 
     let mut app = create_mock_app(db);
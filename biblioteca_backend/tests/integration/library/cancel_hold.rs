@@ -0,0 +1,175 @@
+use biblioteca_backend::catalog::model::ItemStatus;
+use chrono::{Duration, Utc};
+use hyper::{Body, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::mocker::{
+    app::create_mock_app,
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    library::MockLibrary,
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn cancel_hold_ready_hold_passes_to_next_successful() {
+    let database_path = "cancel_hold_ready_hold_passes_to_next_successful.sqlite";
+
+    let user_a = MockUserBase::new_user().build();
+    let user_b = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::OnHold)
+        .build();
+    let author = MockCatalog::new_author().build();
+    let ready_hold = MockLibrary::new_hold()
+        .book_id(book.id)
+        .user_id(user_a.id)
+        .created_at(Utc::now() - Duration::days(2))
+        .ready(item.id, Utc::now() + Duration::days(1))
+        .build();
+    let waiting_hold = MockLibrary::new_hold()
+        .book_id(book.id)
+        .user_id(user_b.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user_a, &user_role)
+        .with_user(&user_b, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_hold(&ready_hold)
+        .with_hold(&waiting_hold)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/holds/{}", ready_hold.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_holds(1),
+            "checking if hold was removed"
+        );
+        assert!(
+            querier.is_hold_ready(&waiting_hold.id, &item.id),
+            "checking if copy was set aside for the next patron"
+        );
+        assert!(
+            querier.is_item_status(&item.id, ItemStatus::OnHold),
+            "checking if copy is still on the hold shelf"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn cancel_hold_last_in_queue_successful() {
+    let database_path = "cancel_hold_last_in_queue_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::OnHold)
+        .build();
+    let author = MockCatalog::new_author().build();
+    let hold = MockLibrary::new_hold()
+        .book_id(book.id)
+        .user_id(user.id)
+        .ready(item.id, Utc::now() + Duration::days(1))
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_hold(&hold)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/holds/{}", hold.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_holds(0),
+            "checking if hold was removed"
+        );
+        assert!(
+            querier.is_item_status(&item.id, ItemStatus::Available),
+            "checking if copy was put back on the shelf"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn cancel_hold_non_existent_hold_failure() {
+    let database_path = "cancel_hold_non_existent_hold_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/holds/{}", Uuid::new_v4()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NOT_FOUND,
+        "checking if response is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,103 @@
+use biblioteca_backend::{catalog::model::ItemStatus, library::model::Hold};
+use chrono::{Duration, Utc};
+use hyper::{Body, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::mocker::{
+    app::create_mock_app, catalog::MockCatalog, db::MockDatabaseBuilder, library::MockLibrary,
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn list_book_holds_queue_order_successful() {
+    let database_path = "list_book_holds_queue_order_successful.sqlite";
+
+    let user_a = MockUserBase::new_user().build();
+    let user_b = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::Borrowed)
+        .build();
+    let author = MockCatalog::new_author().build();
+    let later_hold = MockLibrary::new_hold()
+        .book_id(book.id)
+        .user_id(user_a.id)
+        .build();
+    let earlier_hold = MockLibrary::new_hold()
+        .book_id(book.id)
+        .user_id(user_b.id)
+        .created_at(Utc::now() - Duration::days(1))
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user_a, &user_role)
+        .with_user(&user_b, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_hold(&later_hold)
+        .with_hold(&earlier_hold)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/books/{}/holds", book.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let holds: Vec<Hold> = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(holds.len() == 2);
+        assert!(holds[0].id == earlier_hold.id);
+        assert!(holds[1].id == later_hold.id);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_book_holds_non_existent_book_failure() {
+    let database_path = "list_book_holds_non_existent_book_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/books/{}/holds", Uuid::new_v4()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NOT_FOUND,
+        "checking if response is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,134 @@
+use biblioteca_backend::{library::model::LoanRecord, query::Page};
+use chrono::{Duration, Utc};
+use hyper::{Body, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::mocker::{
+    app::create_mock_app, catalog::MockCatalog, db::MockDatabaseBuilder, library::MockLibrary,
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn list_book_loans_last_borrower_first_successful() {
+    let database_path = "list_book_loans_last_borrower_first_successful.sqlite";
+
+    let user_a = MockUserBase::new_user().build();
+    let user_b = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let other_book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let other_item = MockCatalog::new_item().book_id(other_book.id).build();
+
+    let now = Utc::now();
+
+    let earlier_borrow = MockLibrary::new_borrow_entry()
+        .user_id(user_a.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .timestamp(now - Duration::days(30))
+        .build();
+    let earlier_return = MockLibrary::new_return_entry()
+        .id(earlier_borrow.id)
+        .user_id(user_a.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .timestamp(now - Duration::days(20))
+        .build();
+    let later_borrow = MockLibrary::new_borrow_entry()
+        .user_id(user_b.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .timestamp(now - Duration::days(10))
+        .build();
+    let later_return = MockLibrary::new_return_entry()
+        .id(later_borrow.id)
+        .user_id(user_b.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .timestamp(now - Duration::days(3))
+        .build();
+    let other_loan = MockLibrary::new_borrow_entry()
+        .user_id(user_a.id)
+        .book_id(other_book.id)
+        .item_id(other_item.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user_a, &user_role)
+        .with_user(&user_b, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_book(&other_book, &author.id)
+        .with_item(&item)
+        .with_item(&other_item)
+        .with_library_entry(&earlier_borrow)
+        .with_library_entry(&earlier_return)
+        .with_library_entry(&later_borrow)
+        .with_library_entry(&later_return)
+        .with_library_entry(&other_loan)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/books/{}/loans?status=returned", book.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<LoanRecord> = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(page.total == 2);
+        assert!(page.items[0].user_id == user_b.id);
+        assert!(page.items[0].duration_days == 7);
+        assert!(page.items[1].user_id == user_a.id);
+        assert!(page.items[1].duration_days == 10);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_book_loans_book_non_existent_failure() {
+    let database_path = "list_book_loans_book_non_existent_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/books/{}/loans", Uuid::new_v4()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NOT_FOUND,
+        "checking if response is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,89 @@
+use biblioteca_backend::library::model::{FineEntryKind, FineSummary};
+use hyper::{Body, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::mocker::{
+    app::create_mock_app, db::MockDatabaseBuilder, library::MockLibrary, users::MockUserBase,
+};
+
+#[tokio::test]
+async fn list_user_fines_with_balance_successful() {
+    let database_path = "list_user_fines_with_balance_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let fine = MockLibrary::new_fine()
+        .user_id(user.id)
+        .amount_cents(300)
+        .build();
+    let payment = MockLibrary::new_fine()
+        .user_id(user.id)
+        .kind(FineEntryKind::Payment)
+        .amount_cents(120)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_fine_entry(&fine)
+        .with_fine_entry(&payment)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/users/{}/fines", user.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let summary: FineSummary = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(summary.balance_cents == 180);
+        assert!(summary.entries.len() == 2);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_user_fines_user_non_existent_failure() {
+    let database_path = "list_user_fines_user_non_existent_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/users/{}/fines", Uuid::new_v4()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NOT_FOUND,
+        "checking if response is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,345 @@
+use axum::Router;
+use biblioteca_backend::{catalog::model::ItemStatus, library::model::LoanRecord, query::Page};
+use chrono::{Duration, SecondsFormat, SubsecRound, Utc};
+use hyper::{Body, Request, StatusCode};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::mocker::{
+    api::BibliotecaApiResponse, app::create_mock_app, catalog::MockCatalog,
+    db::MockDatabaseBuilder, library::MockLibrary, users::MockUserBase,
+};
+
+#[tokio::test]
+async fn list_user_loans_current_loans_successful() {
+    let database_path = "list_user_loans_current_loans_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let author = MockCatalog::new_author().build();
+    let book_a = MockCatalog::new_book().build();
+    let book_b = MockCatalog::new_book().build();
+    let item_a = MockCatalog::new_item()
+        .book_id(book_a.id)
+        .status(ItemStatus::Borrowed)
+        .build();
+    let item_b = MockCatalog::new_item()
+        .book_id(book_b.id)
+        .status(ItemStatus::Borrowed)
+        .build();
+    let returned_item = MockCatalog::new_item().book_id(book_b.id).build();
+
+    // PostgreSQL keeps timestamps to the microsecond, so the due dates are kept to it as well
+    let due_soon = (Utc::now() + Duration::days(2)).trunc_subsecs(6);
+    let due_later = (Utc::now() + Duration::days(10)).trunc_subsecs(6);
+
+    let loan_a = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book_a.id)
+        .item_id(item_a.id)
+        .due_at(due_later)
+        .build();
+    let loan_b = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book_b.id)
+        .item_id(item_b.id)
+        .due_at(due_soon)
+        .build();
+    let returned_borrow = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book_b.id)
+        .item_id(returned_item.id)
+        .build();
+    let returned_return = MockLibrary::new_return_entry()
+        .id(returned_borrow.id)
+        .user_id(user.id)
+        .book_id(book_b.id)
+        .item_id(returned_item.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book_a, &author.id)
+        .with_book(&book_b, &author.id)
+        .with_item(&item_a)
+        .with_item(&item_b)
+        .with_item(&returned_item)
+        .with_library_entry(&loan_a)
+        .with_library_entry(&loan_b)
+        .with_library_entry(&returned_borrow)
+        .with_library_entry(&returned_return)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let page = list_user_loans(&app, &format!("/users/{}/loans?sort=due_at", user.id)).await;
+
+    {
+        assert!(page.total == 2);
+        assert!(page.items[0].id == loan_b.id);
+        assert!(page.items[0].due_at == due_soon);
+        assert!(page.items[0].returned_at.is_none());
+        assert!(page.items[1].id == loan_a.id);
+        assert!(page.items[1].due_at == due_later);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_user_loans_user_non_existent_failure() {
+    let database_path = "list_user_loans_user_non_existent_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/users/{}/loans", Uuid::new_v4()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NOT_FOUND,
+        "checking if response is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_user_loans_returned_successful() {
+    let database_path = "list_user_loans_returned_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+
+    let borrowed_at = (Utc::now() - Duration::days(12)).trunc_subsecs(6);
+    let returned_at = (Utc::now() - Duration::days(2)).trunc_subsecs(6);
+
+    let active_loan = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .build();
+    let returned_borrow = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .timestamp(borrowed_at)
+        .build();
+    let returned_return = MockLibrary::new_return_entry()
+        .id(returned_borrow.id)
+        .user_id(user.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .timestamp(returned_at)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_library_entry(&active_loan)
+        .with_library_entry(&returned_borrow)
+        .with_library_entry(&returned_return)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let page = list_user_loans(&app, &format!("/users/{}/loans?status=returned", user.id)).await;
+
+    {
+        assert!(page.total == 1);
+        assert!(page.items[0].id == returned_borrow.id);
+        assert!(page.items[0].borrowed_at == borrowed_at);
+        assert!(page.items[0].returned_at == Some(returned_at));
+        assert!(page.items[0].duration_days == 10);
+    }
+
+    let page = list_user_loans(&app, &format!("/users/{}/loans?status=all", user.id)).await;
+
+    {
+        assert!(page.total == 2);
+        assert!(page.items[0].id == active_loan.id);
+        assert!(page.items[1].id == returned_borrow.id);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_user_loans_date_range_successful() {
+    let database_path = "list_user_loans_date_range_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+
+    let now = Utc::now();
+
+    // Out well before the range, during it, and still out since before it ended
+    let early_borrow = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .timestamp(now - Duration::days(60))
+        .build();
+    let early_return = MockLibrary::new_return_entry()
+        .id(early_borrow.id)
+        .user_id(user.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .timestamp(now - Duration::days(50))
+        .build();
+    let during_borrow = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .timestamp(now - Duration::days(35))
+        .build();
+    let during_return = MockLibrary::new_return_entry()
+        .id(during_borrow.id)
+        .user_id(user.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .timestamp(now - Duration::days(25))
+        .build();
+    let active_loan = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .timestamp(now - Duration::days(21))
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_library_entry(&early_borrow)
+        .with_library_entry(&early_return)
+        .with_library_entry(&during_borrow)
+        .with_library_entry(&during_return)
+        .with_library_entry(&active_loan)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let from = (now - Duration::days(40)).to_rfc3339_opts(SecondsFormat::Secs, true);
+    let to = (now - Duration::days(20)).to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    let page = list_user_loans(
+        &app,
+        &format!(
+            "/users/{}/loans?status=all&from={}&to={}",
+            user.id, from, to
+        ),
+    )
+    .await;
+
+    {
+        assert!(page.total == 2);
+        assert!(page.items[0].id == active_loan.id);
+        assert!(page.items[1].id == during_borrow.id);
+    }
+
+    let page = list_user_loans(
+        &app,
+        &format!("/users/{}/loans?status=all&limit=1&offset=2", user.id),
+    )
+    .await;
+
+    {
+        assert!(page.total == 3);
+        assert!(page.items.len() == 1);
+        assert!(page.items[0].id == early_borrow.id);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_user_loans_invalid_status_failure() {
+    let database_path = "list_user_loans_invalid_status_failure.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/users/{}/loans?status=overdue", user.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        response.is_correct(
+            40001,
+            "invalid value of filter parameter: status".to_string()
+        ),
+        "checking if error message is correct"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+async fn list_user_loans(app: &Router, uri: &str) -> Page<LoanRecord> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
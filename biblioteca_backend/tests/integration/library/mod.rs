@@ -1,3 +1,12 @@
+pub mod add_payment;
+pub mod add_waiver;
 pub mod borrow_book;
 pub mod borrow_return_book;
+pub mod cancel_hold;
+pub mod list_book_holds;
+pub mod list_book_loans;
+pub mod list_user_fines;
+pub mod list_user_loans;
+pub mod place_hold;
+pub mod renew_book;
 pub mod return_book;
@@ -1,12 +1,14 @@
-use chrono::Duration;
+use biblioteca_backend::catalog::model::ItemStatus;
+use chrono::{Duration, Utc};
 use hyper::{header, Body, Request, StatusCode};
 use serde_json::json;
-use tower::ServiceExt;
+use tower::{Service, ServiceExt};
 use uuid::Uuid;
 
 use crate::mocker::{
     api::BibliotecaApiResponse,
     app::create_mock_app,
+    auth::login,
     catalog::MockCatalog,
     db::{MockDatabaseBuilder, MockDatabaseQuerier},
     library::MockLibrary,
@@ -20,10 +22,15 @@ async fn return_book_can_return_successful() {
     let user = MockUserBase::new_user().build();
     let user_role = MockUserBase::new_user_role().build();
     let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::Borrowed)
+        .build();
     let author = MockCatalog::new_author().build();
     let borrow_entry = MockLibrary::new_borrow_entry()
         .user_id(user.id)
         .book_id(book.id)
+        .item_id(item.id)
         .build();
 
     let db = MockDatabaseBuilder::create(database_path.to_string())
@@ -31,6 +38,7 @@ async fn return_book_can_return_successful() {
         .with_user(&user, &user_role)
         .with_author(&author)
         .with_book(&book, &author.id)
+        .with_item(&item)
         .with_library_entry(&borrow_entry)
         .build();
 
@@ -78,10 +86,15 @@ async fn return_book_user_not_same_failure() {
     let user_b = MockUserBase::new_user().build();
     let user_role = MockUserBase::new_user_role().build();
     let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::Borrowed)
+        .build();
     let author = MockCatalog::new_author().build();
     let borrow_entry = MockLibrary::new_borrow_entry()
         .user_id(user_a.id)
         .book_id(book.id)
+        .item_id(item.id)
         .build();
 
     let db = MockDatabaseBuilder::create(database_path.to_string())
@@ -90,6 +103,7 @@ async fn return_book_user_not_same_failure() {
         .with_user(&user_b, &user_role)
         .with_author(&author)
         .with_book(&book, &author.id)
+        .with_item(&item)
         .with_library_entry(&borrow_entry)
         .build();
 
@@ -188,10 +202,15 @@ async fn return_book_user_non_existent_failure() {
     let user = MockUserBase::new_user().build();
     let user_role = MockUserBase::new_user_role().build();
     let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::Borrowed)
+        .build();
     let author = MockCatalog::new_author().build();
     let borrow_entry = MockLibrary::new_borrow_entry()
         .user_id(user.id)
         .book_id(book.id)
+        .item_id(item.id)
         .build();
 
     let incorrect_user_id = Uuid::new_v4();
@@ -201,6 +220,7 @@ async fn return_book_user_non_existent_failure() {
         .with_user(&user, &user_role)
         .with_author(&author)
         .with_book(&book, &author.id)
+        .with_item(&item)
         .with_library_entry(&borrow_entry)
         .build();
 
@@ -247,14 +267,18 @@ async fn return_book_book_already_returned_failure() {
     let user = MockUserBase::new_user().build();
     let user_role = MockUserBase::new_user_role().build();
     let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
     let author = MockCatalog::new_author().build();
     let borrow_entry = MockLibrary::new_borrow_entry()
         .user_id(user.id)
         .book_id(book.id)
+        .item_id(item.id)
         .build();
     let return_entry = MockLibrary::new_return_entry()
+        .id(borrow_entry.id)
         .user_id(user.id)
         .book_id(book.id)
+        .item_id(item.id)
         .timestamp(borrow_entry.timestamp + Duration::days(1))
         .build();
 
@@ -263,6 +287,7 @@ async fn return_book_book_already_returned_failure() {
         .with_user(&user, &user_role)
         .with_author(&author)
         .with_book(&book, &author.id)
+        .with_item(&item)
         .with_library_entry(&borrow_entry)
         .with_library_entry(&return_entry)
         .build();
@@ -302,3 +327,302 @@ async fn return_book_book_already_returned_failure() {
 
     MockDatabaseBuilder::teardown(database_path.to_string());
 }
+
+#[tokio::test]
+async fn return_book_next_hold_ready_successful() {
+    let database_path = "return_book_next_hold_ready_successful.sqlite";
+
+    let user_a = MockUserBase::new_user().build();
+    let user_b = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::Borrowed)
+        .build();
+    let author = MockCatalog::new_author().build();
+    let borrow_entry = MockLibrary::new_borrow_entry()
+        .user_id(user_a.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .build();
+    let hold = MockLibrary::new_hold()
+        .book_id(book.id)
+        .user_id(user_b.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user_a, &user_role)
+        .with_user(&user_b, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_library_entry(&borrow_entry)
+        .with_hold(&hold)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/return", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user_a.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.is_item_status(&item.id, ItemStatus::OnHold),
+            "checking if copy went to the hold shelf",
+        );
+        assert!(
+            querier.is_hold_ready(&hold.id, &item.id),
+            "checking if copy was set aside for the patron",
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn return_book_overdue_fine_capped_successful() {
+    let database_path = "return_book_overdue_fine_capped_successful.sqlite";
+
+    let user_a = MockUserBase::new_user().build();
+    let user_b = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role()
+        .fine_per_day_cents(25)
+        .fine_cap_cents(100)
+        .build();
+    let author = MockCatalog::new_author().build();
+    let book_a = MockCatalog::new_book().build();
+    let book_b = MockCatalog::new_book().build();
+    let item_a = MockCatalog::new_item()
+        .book_id(book_a.id)
+        .status(ItemStatus::Borrowed)
+        .build();
+    let item_b = MockCatalog::new_item()
+        .book_id(book_b.id)
+        .status(ItemStatus::Borrowed)
+        .build();
+
+    // Three days late is under the cap, ten days late is over it
+    let slightly_late_entry = MockLibrary::new_borrow_entry()
+        .user_id(user_a.id)
+        .book_id(book_a.id)
+        .item_id(item_a.id)
+        .due_at(Utc::now() - Duration::days(3) + Duration::hours(1))
+        .build();
+    let very_late_entry = MockLibrary::new_borrow_entry()
+        .user_id(user_b.id)
+        .book_id(book_b.id)
+        .item_id(item_b.id)
+        .due_at(Utc::now() - Duration::days(10))
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user_a, &user_role)
+        .with_user(&user_b, &user_role)
+        .with_author(&author)
+        .with_book(&book_a, &author.id)
+        .with_book(&book_b, &author.id)
+        .with_item(&item_a)
+        .with_item(&item_b)
+        .with_library_entry(&slightly_late_entry)
+        .with_library_entry(&very_late_entry)
+        .build();
+
+    let mut app = create_mock_app(db);
+
+    for (user_id, book_id) in [(user_a.id, book_a.id), (user_b.id, book_b.id)] {
+        let response = app
+            .call(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/books/{}/return", book_id))
+                    .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                    .body(Body::from(
+                        serde_json::to_string(&json!({
+                            "user_id": user_id,
+                        }))
+                        .unwrap(),
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response.status(),
+            StatusCode::ACCEPTED,
+            "checking if response is OK"
+        );
+    }
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.has_fine_balance(&user_a.id, 75),
+            "checking if fine was charged per overdue day"
+        );
+        assert!(
+            querier.has_fine_balance(&user_b.id, 100),
+            "checking if fine was capped"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn return_book_fine_rates_of_one_role_successful() {
+    let database_path = "return_book_fine_rates_of_one_role_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role_a = MockUserBase::new_user_role()
+        .fine_per_day_cents(10)
+        .fine_cap_cents(1000)
+        .build();
+    let user_role_b = MockUserBase::new_user_role()
+        .fine_per_day_cents(50)
+        .fine_cap_cents(20)
+        .build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::Borrowed)
+        .build();
+    let borrow_entry = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .due_at(Utc::now() - Duration::days(10))
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role_a)
+        .with_user_role(&user_role_b)
+        .with_user(&user, &user_role_a)
+        .with_granted_user_role(&user, &user_role_b, None)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_library_entry(&borrow_entry)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/return", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_id": user.id,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.has_fine_balance(&user.id, 100),
+            "checking if rate and cap were both taken from the role with the lowest rate"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn return_book_authenticated_user_successful() {
+    let database_path = "return_book_authenticated_user_successful.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::Borrowed)
+        .build();
+    let author = MockCatalog::new_author().build();
+    let borrow_entry = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_library_entry(&borrow_entry)
+        .build();
+
+    let app = create_mock_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/books/{}/return", book.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::from(serde_json::to_string(&json!({})).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.is_book_returned(&book.id),
+            "checking if book is returned",
+        )
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -1,3 +1,9 @@
+pub mod admin;
+pub mod audit;
+pub mod auth;
 pub mod catalog;
+pub mod config;
+pub mod database;
 pub mod library;
+pub mod repository;
 pub mod users;
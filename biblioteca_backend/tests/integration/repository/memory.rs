@@ -0,0 +1,451 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{http::Uri, response::Response, Router};
+use biblioteca_backend::{
+    audit::{
+        model::{ActorKind, AuditAction, AuditFilters},
+        repository::{AuditRepository, AUDIT_LIST},
+    },
+    auth::repository::AuthRepository,
+    catalog::{
+        model::{Author, Book, BookContributorRequest, ContributorRole, ItemStatus, SearchResult},
+        repository::{CatalogRepository, AUTHOR_LIST, BOOK_LIST},
+    },
+    library::{
+        model::{Loan, LoanFilters, LoanRecord, LoanStatus},
+        repository::{LibraryRepository, LOAN_LIST},
+    },
+    query::{ListParams, ListSpec, Page},
+    repository::MemoryRepository,
+    users::repository::UserRepository,
+};
+use chrono::Utc;
+use hyper::{header, Body, Request, StatusCode};
+use serde_json::json;
+use tower::{Service, ServiceExt};
+use uuid::Uuid;
+
+use crate::mocker::{app::create_mock_memory_app, catalog::MockCatalog, users::MockUserBase};
+
+fn list_params(uri: &str, spec: &ListSpec) -> ListParams {
+    let uri: Uri = uri.parse().unwrap();
+    let params: HashMap<String, String> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+
+    ListParams::parse(&uri, params, spec).unwrap()
+}
+
+fn add_book(repository: &MemoryRepository, book: Book, author_id: Uuid, category_ids: &[Uuid]) {
+    let contributors = [BookContributorRequest {
+        author_id,
+        role: ContributorRole::Author,
+    }];
+
+    repository
+        .add_book(book, &contributors, category_ids)
+        .unwrap();
+}
+
+fn loans_of_user(
+    repository: &MemoryRepository,
+    user_id: Uuid,
+    filters: &LoanFilters,
+) -> Page<LoanRecord> {
+    repository
+        .list_loan_history_of_user(user_id, filters, &list_params("/loans", &LOAN_LIST))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn memory_repository_books_filter_by_category_successful() {
+    let repository = MemoryRepository::new();
+
+    let author = MockCatalog::new_author().build();
+    let category_a = MockCatalog::new_category()
+        .name("Fantasy".to_string())
+        .build();
+    let category_b = MockCatalog::new_category()
+        .name("Science".to_string())
+        .build();
+    let book_a = MockCatalog::new_book().build();
+    let book_b = MockCatalog::new_book().build();
+
+    repository.add_author(author.clone()).unwrap();
+    repository.add_category(category_a.clone()).unwrap();
+    repository.add_category(category_b.clone()).unwrap();
+    add_book(&repository, book_a.clone(), author.id, &[category_a.id]);
+    add_book(&repository, book_b.clone(), author.id, &[category_b.id]);
+
+    let page = repository
+        .list_books(&list_params("/books?category=fant", &BOOK_LIST))
+        .unwrap();
+
+    {
+        assert!(page.total == 1);
+        assert!(page.items[0].id == book_a.id);
+        assert!(page.items[0].contributors[0].name == author.name);
+        assert!(page.items[0].categories[0].id == category_a.id);
+    }
+
+    repository.delete_category(category_a.id).unwrap();
+
+    {
+        let book = repository.get_book(book_a.id).unwrap();
+        assert!(
+            book.categories.is_empty(),
+            "checking if category was removed from book"
+        );
+    }
+}
+
+#[tokio::test]
+async fn memory_repository_authors_sort_paginate_successful() {
+    let repository = MemoryRepository::new();
+
+    let author_a = MockCatalog::new_author()
+        .name("Asimov".to_string())
+        .country("USA".to_string())
+        .build();
+    let author_b = MockCatalog::new_author()
+        .name("Borges".to_string())
+        .country("Argentina".to_string())
+        .build();
+    let author_c = MockCatalog::new_author()
+        .name("Asimov".to_string())
+        .country("Russia".to_string())
+        .build();
+
+    repository.add_author(author_a.clone()).unwrap();
+    repository.add_author(author_b.clone()).unwrap();
+    repository.add_author(author_c.clone()).unwrap();
+
+    let page = repository
+        .list_authors(&list_params(
+            "/authors?sort=name,-country&limit=2",
+            &AUTHOR_LIST,
+        ))
+        .unwrap();
+
+    {
+        assert!(page.total == 3);
+        assert!(page.items.len() == 2);
+        assert!(page.items[0].id == author_a.id);
+        assert!(page.items[1].id == author_c.id);
+        assert!(page.next == Some("/authors?sort=name,-country&limit=2&offset=2".to_string()));
+    }
+}
+
+#[tokio::test]
+async fn memory_repository_delete_restore_book_successful() {
+    let repository = MemoryRepository::new();
+
+    let author = MockCatalog::new_author().build();
+    let book_a = MockCatalog::new_book().build();
+    let book_b = MockCatalog::new_book().build();
+
+    repository.add_author(author.clone()).unwrap();
+    add_book(&repository, book_a.clone(), author.id, &[]);
+    add_book(&repository, book_b.clone(), author.id, &[]);
+
+    repository.delete_book(book_a.id).unwrap();
+
+    {
+        assert!(repository.get_book(book_a.id).is_err());
+        assert!(!repository.is_book_exists(book_a.id).unwrap());
+
+        let page = repository
+            .list_books(&list_params("/books", &BOOK_LIST))
+            .unwrap();
+        assert!(page.total == 1);
+        assert!(page.items[0].id == book_b.id);
+
+        let page = repository
+            .list_books(&list_params("/books?include_deleted=true", &BOOK_LIST))
+            .unwrap();
+        assert!(page.total == 2);
+        assert!(page
+            .items
+            .iter()
+            .any(|book| book.id == book_a.id && book.deleted_at.is_some()));
+    }
+
+    let restored = repository.restore_book(book_a.id).unwrap();
+
+    {
+        assert!(restored.deleted_at.is_none());
+        assert!(repository.get_book(book_a.id).is_ok());
+        assert!(
+            repository.restore_book(book_a.id).is_err(),
+            "checking if book that is not deleted cannot be restored"
+        );
+    }
+
+    repository.delete_book(book_a.id).unwrap();
+
+    {
+        assert!(repository.purge_deleted_books(Utc::now()).unwrap() == 1);
+        assert!(repository.restore_book(book_a.id).is_err());
+        assert!(
+            repository
+                .list_books(&list_params("/books?include_deleted=true", &BOOK_LIST))
+                .unwrap()
+                .total
+                == 1
+        );
+    }
+}
+
+#[tokio::test]
+async fn memory_repository_add_user_with_password_successful() {
+    let repository = MemoryRepository::new();
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+
+    repository.add_user_role(user_role.clone()).unwrap();
+    repository
+        .add_user(user.clone(), user_role.id, Some("password hash"))
+        .unwrap();
+
+    {
+        let credentials = repository.get_credentials(&user.username).unwrap();
+        assert_eq!(credentials.password_hash.as_deref(), Some("password hash"));
+    }
+}
+
+#[tokio::test]
+async fn memory_repository_purge_user_keeps_loans_successful() {
+    let repository = MemoryRepository::new();
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+
+    repository.add_user_role(user_role.clone()).unwrap();
+    repository
+        .add_user(user.clone(), user_role.id, None)
+        .unwrap();
+    repository.add_author(author.clone()).unwrap();
+    add_book(&repository, book.clone(), author.id, &[]);
+    repository.add_item(item.clone()).unwrap();
+    repository
+        .add_borrow_entry(user.id, book.id, item.id, 14)
+        .unwrap();
+
+    {
+        assert!(repository.get_num_borrowed_of_book(book.id).unwrap() == 1);
+        assert!(repository.get_item(item.id).unwrap().status == ItemStatus::Borrowed);
+    }
+
+    repository.delete_user(user.id, 3).unwrap();
+
+    {
+        assert!(!repository.is_user_exists(user.id).unwrap());
+        assert!(
+            repository.get_num_borrowed_of_book(book.id).unwrap() == 1,
+            "checking if loans of deleted user are kept"
+        );
+    }
+
+    assert!(repository.purge_deleted_users(Utc::now()).unwrap() == 0);
+
+    {
+        assert!(
+            repository.restore_user(user.id).is_ok(),
+            "checking if user with loans was kept"
+        );
+        assert!(
+            repository.get_num_borrowed_of_book(book.id).unwrap() == 1,
+            "checking if loans of user were kept"
+        );
+    }
+}
+
+#[tokio::test]
+async fn memory_app_borrow_return_book_successful() {
+    let repository = Arc::new(MemoryRepository::new());
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+
+    repository.add_user_role(user_role.clone()).unwrap();
+    repository
+        .add_user(user.clone(), user_role.id, None)
+        .unwrap();
+    repository.add_author(author.clone()).unwrap();
+    add_book(&repository, book.clone(), author.id, &[]);
+    repository.add_item(item.clone()).unwrap();
+
+    let mut app = create_mock_memory_app(repository.clone());
+
+    let response = send_with_api(&mut app, "borrow", user.id, book.id).await;
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is ACCEPTED"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let loan: Loan = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(loan.item_id == item.id);
+        assert!(loans_of_user(&repository, user.id, &LoanFilters::default()).total == 1);
+        assert!(repository.get_item(item.id).unwrap().status == ItemStatus::Borrowed);
+    }
+
+    let response = send_with_api(&mut app, "return", user.id, book.id).await;
+    assert_eq!(
+        response.status(),
+        StatusCode::ACCEPTED,
+        "checking if response is ACCEPTED"
+    );
+
+    {
+        assert!(loans_of_user(&repository, user.id, &LoanFilters::default()).total == 0);
+        assert!(repository.get_item(item.id).unwrap().status == ItemStatus::Available);
+    }
+
+    let returned = LoanFilters {
+        status: LoanStatus::Returned,
+        ..Default::default()
+    };
+    let page = loans_of_user(&repository, user.id, &returned);
+
+    {
+        assert!(page.total == 1);
+        assert!(page.items[0].id == loan.id);
+        assert!(page.items[0].returned_at.is_some());
+    }
+}
+
+#[tokio::test]
+async fn memory_repository_records_audit_entries_successful() {
+    let repository = MemoryRepository::new();
+
+    let author = MockCatalog::new_author().build();
+
+    repository.add_author(author.clone()).unwrap();
+    repository
+        .update_author(Author {
+            name: "New author name".to_string(),
+            ..author.clone()
+        })
+        .unwrap();
+    repository.delete_author(author.id).unwrap();
+
+    let filters = AuditFilters {
+        entity_id: Some(author.id),
+        ..AuditFilters::default()
+    };
+    let page = repository
+        .list_audit_entries(&filters, &list_params("/audit", &AUDIT_LIST))
+        .unwrap();
+
+    {
+        let actions: Vec<AuditAction> = page.items.iter().map(|entry| entry.action).collect();
+        assert!(
+            actions
+                == [
+                    AuditAction::Create,
+                    AuditAction::Update,
+                    AuditAction::Delete
+                ]
+        );
+        assert!(
+            page.items[1].after == Some(json!({ "name": "New author name" })),
+            "checking if only the changed fields were recorded"
+        );
+        assert!(
+            page.items
+                .iter()
+                .all(|entry| entry.actor_kind == ActorKind::System),
+            "checking if changes outside of requests are made by the system"
+        );
+    }
+}
+
+#[tokio::test]
+async fn memory_app_search_catalog_ranked_successful() {
+    let repository = Arc::new(MemoryRepository::new());
+
+    let author = MockCatalog::new_author().build();
+    let book_a = MockCatalog::new_book()
+        .name("The Name of the Wind".to_string())
+        .description("A young wizard recounts his life at the University.".to_string())
+        .build();
+    let book_b = MockCatalog::new_book()
+        .name("A Wizard of Earthsea".to_string())
+        .description("A boy with a gift for magic learns its price.".to_string())
+        .build();
+    let book_c = MockCatalog::new_book()
+        .name("Dune".to_string())
+        .description("Politics and spice on a desert planet.".to_string())
+        .build();
+
+    repository.add_author(author.clone()).unwrap();
+    add_book(&repository, book_a.clone(), author.id, &[]);
+    add_book(&repository, book_b.clone(), author.id, &[]);
+    add_book(&repository, book_c.clone(), author.id, &[]);
+
+    let app = create_mock_memory_app(repository);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/search?q=wiz")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<SearchResult> = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(page.total == 2);
+        assert!(
+            page.items[0].id == book_b.id,
+            "checking if name matches rank first"
+        );
+        assert!(page.items[0].snippet.contains("<mark>Wizard</mark>"));
+        assert!(page.items[1].id == book_a.id);
+        assert!(page.items[1].snippet.contains("<mark>wizard</mark>"));
+    }
+}
+
+async fn send_with_api(app: &mut Router, action: &str, user_id: Uuid, book_id: Uuid) -> Response {
+    app.call(
+        Request::builder()
+            .method("POST")
+            .uri(format!("/books/{}/{}", book_id, action))
+            .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+            .body(Body::from(
+                serde_json::to_string(&json!({
+                    "user_id": user_id,
+                }))
+                .unwrap(),
+            ))
+            .unwrap(),
+    )
+    .await
+    .unwrap()
+}
@@ -1,9 +1,10 @@
-use biblioteca_backend::users::model::User;
+use biblioteca_backend::users::model::{Membership, User, UserStatus};
 use hyper::{header, Body, Method, Request, StatusCode};
 use serde_json::json;
 use tower::ServiceExt;
 
 use crate::mocker::{
+    api::BibliotecaApiResponse,
     app::create_mock_app,
     db::{MockDatabaseBuilder, MockDatabaseQuerier},
     users::MockUserBase,
@@ -51,6 +52,10 @@ async fn create_user_correct_parameters_successful() {
     let expected_user = User {
         id: created_user.id,
         username: user.username,
+        display_name: None,
+        email: None,
+        membership: Membership::default(),
+        deleted_at: None,
     };
 
     {
@@ -191,6 +196,10 @@ async fn create_user_additional_parameters_successful() {
     let expected_user = User {
         id: created_user.id,
         username: user.username,
+        display_name: None,
+        email: None,
+        membership: Membership::default(),
+        deleted_at: None,
     };
 
     {
@@ -249,3 +258,213 @@ async fn create_user_existing_username_failure() {
 
     MockDatabaseBuilder::teardown(database_path.to_string());
 }
+
+#[tokio::test]
+async fn create_user_with_password_successful() {
+    let database_path = "create_user_with_password_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/users")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "username": user.username,
+                        "user_role_id": user_role.id,
+                        "password": "correct horse",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let created_user: User = serde_json::from_slice(&body).unwrap();
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.has_password(&created_user.id),
+            "check if password was set"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_user_password_too_short_failure() {
+    let database_path = "create_user_password_too_short_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/users")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "username": user.username,
+                        "user_role_id": user_role.id,
+                        "password": "short",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(api_response.is_correct(40001, "at least 8 characters".to_string()));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        // Only the librarian the request was sent as
+        assert!(querier.contains_num_users(1), "check if user was not added");
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_user_pending_successful() {
+    let database_path = "create_user_pending_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/users")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "username": user.username,
+                        "user_role_id": user_role.id,
+                        "status": "pending",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let created_user: User = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(created_user.membership.status == UserStatus::Pending);
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.is_user_status(&created_user.id, UserStatus::Pending),
+            "check if user is pending"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_user_suspended_failure() {
+    let database_path = "create_user_suspended_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/users")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "username": user.username,
+                        "user_role_id": user_role.id,
+                        "status": "suspended",
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let api_response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(api_response.is_correct(40001, "active or pending".to_string()));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        // Only the librarian the request was sent as
+        assert!(querier.contains_num_users(1), "check if user was not added");
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
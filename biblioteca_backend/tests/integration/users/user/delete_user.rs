@@ -1,10 +1,16 @@
-use hyper::{Body, Request, StatusCode};
-use tower::ServiceExt;
+use biblioteca_backend::catalog::model::ItemStatus;
+use chrono::{Duration, Utc};
+use hyper::{header, Body, Request, StatusCode};
+use tower::{Service, ServiceExt};
 use uuid::Uuid;
 
 use crate::mocker::{
+    api::BibliotecaApiResponse,
     app::create_mock_app,
+    auth::login,
+    catalog::MockCatalog,
     db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    library::MockLibrary,
     users::MockUserBase,
 };
 
@@ -44,13 +50,13 @@ async fn delete_user_existing_user_successful() {
     {
         let querier = MockDatabaseQuerier::create(database_path.to_string());
         assert!(
-            !querier.contains_user(&user_a),
-            "checking if user was removed properly"
+            querier.is_user_deleted(&user_a.id),
+            "checking if user was marked as deleted"
         );
 
         assert!(
-            !querier.contains_user_user_role_mapping(&user_a.id, &user_role.id),
-            "checking if user_a to user_role mapping was removed properly"
+            querier.contains_user_user_role_mapping(&user_a.id, &user_role.id),
+            "checking if user_a to user_role mapping is kept to restore it"
         );
     }
 
@@ -100,3 +106,191 @@ async fn delete_user_non_existent_user_successful() {
 
     MockDatabaseBuilder::teardown(database_path.to_string());
 }
+
+#[tokio::test]
+async fn delete_user_ends_sessions_successful() {
+    let database_path = "delete_user_ends_sessions_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_user_password(&user, "correct horse")
+        .build();
+
+    let mut app = create_mock_app(db);
+    let token = login(&app, &user.username, "correct horse").await;
+
+    app.call(
+        Request::builder()
+            .method("DELETE")
+            .uri(format!("/users/{}", user.id))
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/auth/me")
+                .header(header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNAUTHORIZED,
+        "checking if token of deleted user is refused"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_sessions(1),
+            "checking if only the session of the librarian is left"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn delete_user_cancels_holds_successful() {
+    let database_path = "delete_user_cancels_holds_successful.sqlite";
+
+    let user_a = MockUserBase::new_user().build();
+    let user_b = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item()
+        .book_id(book.id)
+        .status(ItemStatus::OnHold)
+        .build();
+    let hold_a = MockLibrary::new_hold()
+        .book_id(book.id)
+        .user_id(user_a.id)
+        .created_at(Utc::now() - Duration::days(2))
+        .ready(item.id, Utc::now() + Duration::days(1))
+        .build();
+    let hold_b = MockLibrary::new_hold()
+        .book_id(book.id)
+        .user_id(user_b.id)
+        .created_at(Utc::now() - Duration::days(1))
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user_a, &user_role)
+        .with_user(&user_b, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_hold(&hold_a)
+        .with_hold(&hold_b)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/users/{}", user_a.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is OK"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_num_holds(1),
+            "checking if hold of deleted user was cancelled"
+        );
+        assert!(
+            querier.is_hold_ready(&hold_b.id, &item.id),
+            "checking if copy was passed to the next patron"
+        );
+        assert!(
+            querier.is_item_status(&item.id, ItemStatus::OnHold),
+            "checking if copy stayed on the hold shelf"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn delete_user_on_loan_failure() {
+    let database_path = "delete_user_on_loan_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+    let author = MockCatalog::new_author().build();
+    let book = MockCatalog::new_book().build();
+    let item = MockCatalog::new_item().book_id(book.id).build();
+    let borrow_entry = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book.id)
+        .item_id(item.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book, &author.id)
+        .with_item(&item)
+        .with_library_entry(&borrow_entry)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/users/{}", user.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(response.is_correct(40001, "currently on loan".to_string()));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            !querier.is_user_deleted(&user.id),
+            "checking if user was kept"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -0,0 +1,259 @@
+use biblioteca_backend::users::model::FullUser;
+use chrono::{Duration, Utc};
+use hyper::{header, Body, Method, Request, StatusCode};
+use serde_json::json;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::mocker::{
+    api::BibliotecaApiResponse,
+    app::create_mock_app,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn grant_user_role_correct_parameters_successful() {
+    let database_path = "grant_user_role_correct_parameters_successful.sqlite";
+
+    let student_role = MockUserBase::new_user_role()
+        .name("Student".to_string())
+        .build();
+    let staff_role = MockUserBase::new_user_role()
+        .name("Staff".to_string())
+        .build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&student_role)
+        .with_user_role(&staff_role)
+        .with_user(&user, &student_role)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let expires_at = Utc::now() + Duration::days(30);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/users/{}/roles", user.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_role_id": staff_role.id,
+                        "expires_at": expires_at,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is NO_CONTENT"
+    );
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("/users/{}", user.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let returned_user: FullUser = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert_eq!(
+            returned_user.user_roles.len(),
+            2,
+            "checking number of roles"
+        );
+        assert_eq!(returned_user.user_roles[0].user_role.id, staff_role.id);
+        assert!(returned_user.user_roles[0].expires_at.is_some());
+        assert_eq!(returned_user.user_roles[1].user_role.id, student_role.id);
+        assert!(returned_user.user_roles[1].expires_at.is_none());
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_user_user_role_mapping(&user.id, &staff_role.id),
+            "checking if role was granted"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn grant_user_role_already_held_successful() {
+    let database_path = "grant_user_role_already_held_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/users/{}/roles", user.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_role_id": user_role.id,
+                        "expires_at": Utc::now() + Duration::days(30),
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is NO_CONTENT"
+    );
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::GET)
+                .uri(format!("/users/{}", user.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let returned_user: FullUser = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert_eq!(
+            returned_user.user_roles.len(),
+            1,
+            "checking if role was not granted twice"
+        );
+        assert!(
+            returned_user.user_roles[0].expires_at.is_some(),
+            "checking if expiry was changed"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn grant_user_role_expiry_in_past_failure() {
+    let database_path = "grant_user_role_expiry_in_past_failure.sqlite";
+
+    let student_role = MockUserBase::new_user_role().build();
+    let staff_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&student_role)
+        .with_user_role(&staff_role)
+        .with_user(&user, &student_role)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/users/{}/roles", user.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_role_id": staff_role.id,
+                        "expires_at": Utc::now() - Duration::days(1),
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(response.is_correct(40001, "in the future".to_string()));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            !querier.contains_user_user_role_mapping(&user.id, &staff_role.id),
+            "checking if role was not granted"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn grant_user_role_non_existent_role_failure() {
+    let database_path = "grant_user_role_non_existent_role_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("/users/{}/roles", user.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "user_role_id": Uuid::new_v4(),
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
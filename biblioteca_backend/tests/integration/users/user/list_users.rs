@@ -1,6 +1,7 @@
-use biblioteca_backend::users::model::FullUser;
+use biblioteca_backend::{query::Page, users::model::FullUser};
+use chrono::Utc;
 use hyper::{Body, Request, StatusCode};
-use tower::ServiceExt;
+use tower::{Service, ServiceExt};
 
 use crate::mocker::{
     app::create_mock_app,
@@ -44,7 +45,8 @@ async fn list_users_successful() {
     );
 
     let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-    let returned_users: Vec<FullUser> = serde_json::from_slice(&body).unwrap();
+    let page: Page<FullUser> = serde_json::from_slice(&body).unwrap();
+    let returned_users = page.items;
 
     {
         let querier = MockDatabaseQuerier::create(database_path.to_string());
@@ -56,3 +58,153 @@ async fn list_users_successful() {
 
     MockDatabaseBuilder::teardown(database_path.to_string());
 }
+
+#[tokio::test]
+async fn list_users_with_role_filter_successful() {
+    let database_path = "list_users_with_role_filter_successful.sqlite";
+
+    let student_role = MockUserBase::new_user_role()
+        .name("Student".to_string())
+        .build();
+    let staff_role = MockUserBase::new_user_role()
+        .name("Staff".to_string())
+        .build();
+    let user_a = MockUserBase::new_user().build();
+    let user_b = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&student_role)
+        .with_user_role(&staff_role)
+        .with_user(&user_a, &student_role)
+        .with_user(&user_b, &staff_role)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/users?role=staff")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<FullUser> = serde_json::from_slice(&body).unwrap();
+    let returned_users = page.items;
+
+    {
+        assert!(returned_users.len() == 1);
+        assert!(returned_users[0].id == user_b.id);
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_users_with_unknown_filter_failure() {
+    let database_path = "list_users_with_unknown_filter_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("GET")
+                .uri("/users?email=test")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn list_users_include_deleted_successful() {
+    let database_path = "list_users_include_deleted_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role()
+        .name("Member".to_string())
+        .build();
+    let user_a = MockUserBase::new_user().build();
+    let user_b = MockUserBase::new_user().deleted_at(Utc::now()).build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user_a, &user_role)
+        .with_user(&user_b, &user_role)
+        .build();
+
+    let mut app = create_mock_app(db);
+
+    let response = app
+        .call(
+            Request::builder()
+                .method("GET")
+                .uri(format!("/users?role={}", user_role.name))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<FullUser> = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(page.total == 1, "checking if deleted user is left out");
+        assert!(page.items[0].id == user_a.id);
+    }
+
+    let response = app
+        .call(
+            Request::builder()
+                .method("GET")
+                .uri(format!(
+                    "/users?role={}&include_deleted=true",
+                    user_role.name
+                ))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let page: Page<FullUser> = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(page.total == 2, "checking if deleted user is included");
+        assert!(page
+            .items
+            .iter()
+            .any(|user| user.id == user_b.id && user.deleted_at.is_some()));
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -1,4 +1,10 @@
 mod create_user;
 mod delete_user;
 mod get_user;
+mod grant_user_role;
 mod list_users;
+mod reinstate_user;
+mod restore_user;
+mod revoke_user_role;
+mod suspend_user;
+mod update_user;
@@ -0,0 +1,274 @@
+use chrono::{Duration, Utc};
+use hyper::{Body, Method, Request, StatusCode};
+use tower::ServiceExt;
+
+use crate::mocker::{
+    api::BibliotecaApiResponse,
+    app::create_mock_app,
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    library::MockLibrary,
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn revoke_user_role_correct_parameters_successful() {
+    let database_path = "revoke_user_role_correct_parameters_successful.sqlite";
+
+    let student_role = MockUserBase::new_user_role().build();
+    let staff_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&student_role)
+        .with_user_role(&staff_role)
+        .with_user(&user, &student_role)
+        .with_granted_user_role(&user, &staff_role, None)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("/users/{}/roles/{}", user.id, staff_role.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is NO_CONTENT"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            !querier.contains_user_user_role_mapping(&user.id, &staff_role.id),
+            "checking if role was revoked"
+        );
+        assert!(
+            querier.contains_user_user_role_mapping(&user.id, &student_role.id),
+            "checking if other role was kept"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn revoke_user_role_last_role_failure() {
+    let database_path = "revoke_user_role_last_role_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("/users/{}/roles/{}", user.id, user_role.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(response.is_correct(40001, "at least one role".to_string()));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_user_user_role_mapping(&user.id, &user_role.id),
+            "checking if role was kept"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn revoke_user_role_last_active_role_failure() {
+    let database_path = "revoke_user_role_last_active_role_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+    let expired_user_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user_role(&expired_user_role)
+        .with_user(&user, &user_role)
+        .with_granted_user_role(
+            &user,
+            &expired_user_role,
+            Some(Utc::now() - Duration::days(1)),
+        )
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("/users/{}/roles/{}", user.id, user_role.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(response.is_correct(40001, "at least one role".to_string()));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_user_user_role_mapping(&user.id, &user_role.id),
+            "checking if active role was kept"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn revoke_user_role_below_loans_failure() {
+    let database_path = "revoke_user_role_below_loans_failure.sqlite";
+
+    let student_role = MockUserBase::new_user_role()
+        .num_borrowable_books(1)
+        .build();
+    let staff_role = MockUserBase::new_user_role()
+        .num_borrowable_books(5)
+        .build();
+    let user = MockUserBase::new_user().build();
+    let author = MockCatalog::new_author().build();
+    let book_a = MockCatalog::new_book().build();
+    let book_b = MockCatalog::new_book().build();
+    let item_a = MockCatalog::new_item().book_id(book_a.id).build();
+    let item_b = MockCatalog::new_item().book_id(book_b.id).build();
+    let borrow_entry_a = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book_a.id)
+        .item_id(item_a.id)
+        .build();
+    let borrow_entry_b = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book_b.id)
+        .item_id(item_b.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&student_role)
+        .with_user_role(&staff_role)
+        .with_user(&user, &student_role)
+        .with_granted_user_role(&user, &staff_role, None)
+        .with_author(&author)
+        .with_book(&book_a, &author.id)
+        .with_book(&book_b, &author.id)
+        .with_item(&item_a)
+        .with_item(&item_b)
+        .with_library_entry(&borrow_entry_a)
+        .with_library_entry(&borrow_entry_b)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("/users/{}/roles/{}", user.id, staff_role.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(response.is_correct(40001, "more books on loan".to_string()));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_user_user_role_mapping(&user.id, &staff_role.id),
+            "checking if role was kept"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn revoke_user_role_not_held_failure() {
+    let database_path = "revoke_user_role_not_held_failure.sqlite";
+
+    let student_role = MockUserBase::new_user_role().build();
+    let staff_role = MockUserBase::new_user_role().build();
+    let user = MockUserBase::new_user().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&student_role)
+        .with_user_role(&staff_role)
+        .with_user(&user, &student_role)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::DELETE)
+                .uri(format!("/users/{}/roles/{}", user.id, staff_role.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NOT_FOUND,
+        "checking if response is NOT_FOUND"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
@@ -1,4 +1,7 @@
-use biblioteca_backend::users::model::UserRole;
+use biblioteca_backend::{
+    library::model::LibrarySettings,
+    users::model::{Permission, UserRole},
+};
 use hyper::{header, Body, Method, Request, StatusCode};
 use serde_json::json;
 use tower::ServiceExt;
@@ -31,6 +34,10 @@ async fn create_user_role_correct_parameters_successful() {
                     serde_json::to_string(&json!({
                         "name": user_role.name,
                         "num_borrowable_books": user_role.num_borrowable_books,
+                        "loan_period_days": user_role.loan_period_days,
+                        "max_renewals": user_role.max_renewals,
+                        "fine_per_day_cents": user_role.fine_per_day_cents,
+                        "fine_cap_cents": user_role.fine_cap_cents,
                     }))
                     .unwrap(),
                 ))
@@ -51,6 +58,11 @@ async fn create_user_role_correct_parameters_successful() {
         id: created_user_role.id,
         name: user_role.name,
         num_borrowable_books: user_role.num_borrowable_books,
+        loan_period_days: user_role.loan_period_days,
+        max_renewals: user_role.max_renewals,
+        fine_per_day_cents: user_role.fine_per_day_cents,
+        fine_cap_cents: user_role.fine_cap_cents,
+        permissions: user_role.permissions,
     };
 
     {
@@ -86,6 +98,10 @@ async fn create_user_role_wrong_parameters_failure() {
                     serde_json::to_string(&json!({
                         "name_invalid": user_role.name,
                         "num_borrowable_books": user_role.num_borrowable_books,
+                        "loan_period_days": user_role.loan_period_days,
+                        "max_renewals": user_role.max_renewals,
+                        "fine_per_day_cents": user_role.fine_per_day_cents,
+                        "fine_cap_cents": user_role.fine_cap_cents,
                     }))
                     .unwrap(),
                 ))
@@ -124,6 +140,10 @@ async fn create_user_role_missing_parameters_failure() {
                 .body(Body::from(
                     serde_json::to_string(&json!({
                         "num_borrowable_books": user_role.num_borrowable_books,
+                        "loan_period_days": user_role.loan_period_days,
+                        "max_renewals": user_role.max_renewals,
+                        "fine_per_day_cents": user_role.fine_per_day_cents,
+                        "fine_cap_cents": user_role.fine_cap_cents,
                     }))
                     .unwrap(),
                 ))
@@ -163,6 +183,10 @@ async fn create_user_role_additional_parameters_successful() {
                     serde_json::to_string(&json!({
                         "name": user_role.name,
                         "num_borrowable_books": user_role.num_borrowable_books,
+                        "loan_period_days": user_role.loan_period_days,
+                        "max_renewals": user_role.max_renewals,
+                        "fine_per_day_cents": user_role.fine_per_day_cents,
+                        "fine_cap_cents": user_role.fine_cap_cents,
                         "additional_parameter": "hello_world".to_string(),
                     }))
                     .unwrap(),
@@ -184,6 +208,11 @@ async fn create_user_role_additional_parameters_successful() {
         id: created_user_role.id,
         name: user_role.name,
         num_borrowable_books: user_role.num_borrowable_books,
+        loan_period_days: user_role.loan_period_days,
+        max_renewals: user_role.max_renewals,
+        fine_per_day_cents: user_role.fine_per_day_cents,
+        fine_cap_cents: user_role.fine_cap_cents,
+        permissions: user_role.permissions,
     };
 
     {
@@ -221,6 +250,10 @@ async fn create_user_role_negative_borrowable_books_failure() {
                     serde_json::to_string(&json!({
                         "name": user_role.name,
                         "num_borrowable_books": user_role.num_borrowable_books,
+                        "loan_period_days": user_role.loan_period_days,
+                        "max_renewals": user_role.max_renewals,
+                        "fine_per_day_cents": user_role.fine_per_day_cents,
+                        "fine_cap_cents": user_role.fine_cap_cents,
                     }))
                     .unwrap(),
                 ))
@@ -237,3 +270,198 @@ async fn create_user_role_negative_borrowable_books_failure() {
 
     MockDatabaseBuilder::teardown(database_path.to_string());
 }
+
+#[tokio::test]
+async fn create_user_role_non_positive_loan_period_failure() {
+    let database_path = "create_user_role_non_positive_loan_period_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().loan_period_days(0).build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/users/roles")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": user_role.name,
+                        "num_borrowable_books": user_role.num_borrowable_books,
+                        "loan_period_days": user_role.loan_period_days,
+                        "max_renewals": user_role.max_renewals,
+                        "fine_per_day_cents": user_role.fine_per_day_cents,
+                        "fine_cap_cents": user_role.fine_cap_cents,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is correct (400)"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        // Only the role of the librarian the request was sent as
+        assert!(
+            querier.contains_num_user_roles(1),
+            "checking if no user role was added"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_user_role_default_loan_policies_successful() {
+    let database_path = "create_user_role_default_loan_policies_successful.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/users/roles")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": "Visitor",
+                        "num_borrowable_books": 1,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let created_user_role: UserRole = serde_json::from_slice(&body).unwrap();
+    let settings = LibrarySettings::default();
+    let expected_user_role = UserRole {
+        id: created_user_role.id,
+        name: "Visitor".to_string(),
+        num_borrowable_books: 1,
+        loan_period_days: settings.default_loan_period_days,
+        max_renewals: settings.default_max_renewals,
+        fine_per_day_cents: settings.default_fine_per_day_cents,
+        fine_cap_cents: settings.default_fine_cap_cents,
+        permissions: Vec::new(),
+    };
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_user_role(&expected_user_role),
+            "check if user role was given the default loan policies",
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_user_role_with_permissions_successful() {
+    let database_path = "create_user_role_with_permissions_successful.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/users/roles")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": "Cataloguer",
+                        "num_borrowable_books": 5,
+                        "permissions": ["catalog:write"],
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::OK,
+        "checking if response is OK"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let created_user_role: UserRole = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert_eq!(
+            created_user_role.permissions,
+            vec![Permission::CatalogWrite]
+        );
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_user_role(&created_user_role),
+            "check if user role was added with its permissions",
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn create_user_role_unknown_permission_failure() {
+    let database_path = "create_user_role_unknown_permission_failure.sqlite";
+
+    let db = MockDatabaseBuilder::create(database_path.to_string()).build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/users/roles")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": "Cataloguer",
+                        "num_borrowable_books": 5,
+                        "permissions": ["catalog:delete"],
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::UNPROCESSABLE_ENTITY,
+        "checking if response is UNPROCESSABLE_ENTITY"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
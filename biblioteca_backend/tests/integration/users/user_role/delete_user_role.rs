@@ -1,10 +1,14 @@
+use chrono::{Duration, Utc};
 use hyper::{Body, Request, StatusCode};
 use tower::ServiceExt;
 use uuid::Uuid;
 
 use crate::mocker::{
+    api::BibliotecaApiResponse,
     app::create_mock_app,
+    catalog::MockCatalog,
     db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    library::MockLibrary,
     users::MockUserBase,
 };
 
@@ -15,11 +19,15 @@ async fn delete_user_role_existing_role_successful() {
     let user_a = MockUserBase::new_user().build();
     let user_b = MockUserBase::new_user().build();
     let user_role = MockUserBase::new_user_role().build();
+    let other_user_role = MockUserBase::new_user_role().build();
 
     let db = MockDatabaseBuilder::create(database_path.to_string())
         .with_user_role(&user_role)
+        .with_user_role(&other_user_role)
         .with_user(&user_a, &user_role)
-        .with_user(&user_b, &user_role)
+        .with_user(&user_b, &other_user_role)
+        .with_granted_user_role(&user_a, &other_user_role, None)
+        .with_granted_user_role(&user_b, &user_role, None)
         .build();
 
     let app = create_mock_app(db);
@@ -62,6 +70,200 @@ async fn delete_user_role_existing_role_successful() {
     MockDatabaseBuilder::teardown(database_path.to_string());
 }
 
+#[tokio::test]
+async fn delete_user_role_only_role_of_user_failure() {
+    let database_path = "delete_user_role_only_role_of_user_failure.sqlite";
+
+    let user_a = MockUserBase::new_user().build();
+    let user_b = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let other_user_role = MockUserBase::new_user_role().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user_role(&other_user_role)
+        .with_user(&user_a, &user_role)
+        .with_user(&user_b, &user_role)
+        .with_granted_user_role(&user_a, &other_user_role, None)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/users/roles/{}", user_role.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(response.is_correct(40001, "only role".to_string()));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_user_role(&user_role),
+            "checking if user_role was kept"
+        );
+        assert!(
+            querier.contains_user_user_role_mapping(&user_b.id, &user_role.id),
+            "checking if user_b kept their only role"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn delete_user_role_only_unexpired_role_of_user_failure() {
+    let database_path = "delete_user_role_only_unexpired_role_of_user_failure.sqlite";
+
+    let user = MockUserBase::new_user().build();
+    let user_role = MockUserBase::new_user_role().build();
+    let expired_user_role = MockUserBase::new_user_role().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user_role(&expired_user_role)
+        .with_user(&user, &user_role)
+        .with_granted_user_role(
+            &user,
+            &expired_user_role,
+            Some(Utc::now() - Duration::days(1)),
+        )
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/users/roles/{}", user_role.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(response.is_correct(40001, "only role".to_string()));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_user_role(&user_role),
+            "checking if user_role was kept"
+        );
+        assert!(
+            querier.contains_user_user_role_mapping(&user.id, &user_role.id),
+            "checking if user kept their only unexpired role"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn delete_user_role_below_loans_failure() {
+    let database_path = "delete_user_role_below_loans_failure.sqlite";
+
+    let student_role = MockUserBase::new_user_role()
+        .num_borrowable_books(1)
+        .build();
+    let staff_role = MockUserBase::new_user_role()
+        .num_borrowable_books(5)
+        .build();
+    let user = MockUserBase::new_user().build();
+    let author = MockCatalog::new_author().build();
+    let book_a = MockCatalog::new_book().build();
+    let book_b = MockCatalog::new_book().build();
+    let item_a = MockCatalog::new_item().book_id(book_a.id).build();
+    let item_b = MockCatalog::new_item().book_id(book_b.id).build();
+    let borrow_entry_a = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book_a.id)
+        .item_id(item_a.id)
+        .build();
+    let borrow_entry_b = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book_b.id)
+        .item_id(item_b.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&student_role)
+        .with_user_role(&staff_role)
+        .with_user(&user, &student_role)
+        .with_granted_user_role(&user, &staff_role, None)
+        .with_author(&author)
+        .with_book(&book_a, &author.id)
+        .with_book(&book_b, &author.id)
+        .with_item(&item_a)
+        .with_item(&item_b)
+        .with_library_entry(&borrow_entry_a)
+        .with_library_entry(&borrow_entry_b)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/users/roles/{}", staff_role.id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    {
+        assert!(response.is_correct(40001, "more books on loan".to_string()));
+
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_user_role(&staff_role),
+            "checking if user_role was kept"
+        );
+        assert!(
+            querier.contains_user_user_role_mapping(&user.id, &staff_role.id),
+            "checking if user kept the role"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
 #[tokio::test]
 async fn delete_user_role_non_existent_role_successful() {
     let database_path = "delete_user_role_non_existent_role_successful.sqlite";
@@ -1,4 +1,4 @@
-use biblioteca_backend::users::model::UserRole;
+use biblioteca_backend::{query::Page, users::model::UserRole};
 use hyper::{Body, Request, StatusCode};
 use tower::ServiceExt;
 
@@ -42,7 +42,8 @@ async fn list_user_roles_successful() {
     );
 
     let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
-    let returned_user_roles: Vec<UserRole> = serde_json::from_slice(&body).unwrap();
+    let page: Page<UserRole> = serde_json::from_slice(&body).unwrap();
+    let returned_user_roles = page.items;
 
     {
         let querier = MockDatabaseQuerier::create(database_path.to_string());
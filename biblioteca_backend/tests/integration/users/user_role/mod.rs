@@ -2,3 +2,4 @@ mod create_user_role;
 mod delete_user_role;
 mod get_user_role;
 mod list_user_roles;
+mod update_user_role;
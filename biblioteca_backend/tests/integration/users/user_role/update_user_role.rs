@@ -0,0 +1,286 @@
+use biblioteca_backend::users::model::{Permission, UserRole};
+use hyper::{header, Body, Method, Request, StatusCode};
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::mocker::{
+    api::BibliotecaApiResponse,
+    app::create_mock_app,
+    catalog::MockCatalog,
+    db::{MockDatabaseBuilder, MockDatabaseQuerier},
+    library::MockLibrary,
+    users::MockUserBase,
+};
+
+#[tokio::test]
+async fn update_user_role_correct_parameters_successful() {
+    let database_path = "update_user_role_correct_parameters_successful.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::PUT)
+                .uri(format!("/users/roles/{}", user_role.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": "Staff",
+                        "num_borrowable_books": 10,
+                        "loan_period_days": 28,
+                        "max_renewals": 3,
+                        "fine_per_day_cents": 0,
+                        "fine_cap_cents": 0,
+                        "permissions": ["circulation:staff"],
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is NO_CONTENT"
+    );
+
+    let expected_user_role = UserRole {
+        id: user_role.id,
+        name: "Staff".to_string(),
+        num_borrowable_books: 10,
+        loan_period_days: 28,
+        max_renewals: 3,
+        fine_per_day_cents: 0,
+        fine_cap_cents: 0,
+        permissions: vec![Permission::CirculationStaff],
+    };
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_user_role(&expected_user_role),
+            "checking if user role was updated properly"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn update_user_role_below_loans_of_user_failure() {
+    let database_path = "update_user_role_below_loans_of_user_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role()
+        .num_borrowable_books(3)
+        .build();
+    let user = MockUserBase::new_user().build();
+    let author = MockCatalog::new_author().build();
+    let book_a = MockCatalog::new_book().build();
+    let book_b = MockCatalog::new_book().build();
+    let item_a = MockCatalog::new_item().book_id(book_a.id).build();
+    let item_b = MockCatalog::new_item().book_id(book_b.id).build();
+    let borrow_entry_a = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book_a.id)
+        .item_id(item_a.id)
+        .build();
+    let borrow_entry_b = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book_b.id)
+        .item_id(item_b.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .with_user(&user, &user_role)
+        .with_author(&author)
+        .with_book(&book_a, &author.id)
+        .with_book(&book_b, &author.id)
+        .with_item(&item_a)
+        .with_item(&item_b)
+        .with_library_entry(&borrow_entry_a)
+        .with_library_entry(&borrow_entry_b)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::PUT)
+                .uri(format!("/users/roles/{}", user_role.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": user_role.name,
+                        "num_borrowable_books": 1,
+                        "loan_period_days": user_role.loan_period_days,
+                        "max_renewals": user_role.max_renewals,
+                        "fine_per_day_cents": user_role.fine_per_day_cents,
+                        "fine_cap_cents": user_role.fine_cap_cents,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let response: BibliotecaApiResponse = serde_json::from_slice(&body).unwrap();
+
+    assert!(
+        response.is_correct(40001, "more books on loan".to_string()),
+        "checking if error is correct"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_user_role(&user_role),
+            "checking if user role was left unchanged"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn update_user_role_below_loans_allowed_by_other_role_successful() {
+    let database_path = "update_user_role_below_loans_allowed_by_other_role_successful.sqlite";
+
+    let student_role = MockUserBase::new_user_role()
+        .num_borrowable_books(3)
+        .build();
+    let staff_role = MockUserBase::new_user_role()
+        .num_borrowable_books(5)
+        .build();
+    let user = MockUserBase::new_user().build();
+    let author = MockCatalog::new_author().build();
+    let book_a = MockCatalog::new_book().build();
+    let book_b = MockCatalog::new_book().build();
+    let item_a = MockCatalog::new_item().book_id(book_a.id).build();
+    let item_b = MockCatalog::new_item().book_id(book_b.id).build();
+    let borrow_entry_a = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book_a.id)
+        .item_id(item_a.id)
+        .build();
+    let borrow_entry_b = MockLibrary::new_borrow_entry()
+        .user_id(user.id)
+        .book_id(book_b.id)
+        .item_id(item_b.id)
+        .build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&student_role)
+        .with_user_role(&staff_role)
+        .with_user(&user, &student_role)
+        .with_granted_user_role(&user, &staff_role, None)
+        .with_author(&author)
+        .with_book(&book_a, &author.id)
+        .with_book(&book_b, &author.id)
+        .with_item(&item_a)
+        .with_item(&item_b)
+        .with_library_entry(&borrow_entry_a)
+        .with_library_entry(&borrow_entry_b)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::PUT)
+                .uri(format!("/users/roles/{}", student_role.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": student_role.name,
+                        "num_borrowable_books": 1,
+                        "loan_period_days": student_role.loan_period_days,
+                        "max_renewals": student_role.max_renewals,
+                        "fine_per_day_cents": student_role.fine_per_day_cents,
+                        "fine_cap_cents": student_role.fine_cap_cents,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::NO_CONTENT,
+        "checking if response is NO_CONTENT"
+    );
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
+
+#[tokio::test]
+async fn update_user_role_invalid_loan_period_failure() {
+    let database_path = "update_user_role_invalid_loan_period_failure.sqlite";
+
+    let user_role = MockUserBase::new_user_role().build();
+
+    let db = MockDatabaseBuilder::create(database_path.to_string())
+        .with_user_role(&user_role)
+        .build();
+
+    let app = create_mock_app(db);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method(Method::PUT)
+                .uri(format!("/users/roles/{}", user_role.id))
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "name": user_role.name,
+                        "num_borrowable_books": user_role.num_borrowable_books,
+                        "loan_period_days": 0,
+                        "max_renewals": user_role.max_renewals,
+                        "fine_per_day_cents": user_role.fine_per_day_cents,
+                        "fine_cap_cents": user_role.fine_cap_cents,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        response.status(),
+        StatusCode::BAD_REQUEST,
+        "checking if response is BAD_REQUEST"
+    );
+
+    {
+        let querier = MockDatabaseQuerier::create(database_path.to_string());
+        assert!(
+            querier.contains_user_role(&user_role),
+            "checking if user role was left unchanged"
+        );
+    }
+
+    MockDatabaseBuilder::teardown(database_path.to_string());
+}
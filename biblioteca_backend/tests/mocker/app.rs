@@ -1,10 +1,109 @@
-use axum::{extract::State, Router};
-use biblioteca_backend::app::{create_app, AppState};
-use r2d2::Pool;
-use r2d2_sqlite::SqliteConnectionManager;
+use std::sync::Arc;
 
-pub fn create_mock_app(db_pool: Pool<SqliteConnectionManager>) -> Router {
-    let state = AppState { db_pool };
+use axum::{
+    extract::State,
+    http::{header, HeaderValue, Request},
+    middleware::map_request,
+    Router,
+};
+use biblioteca_backend::{
+    app::{create_app, create_new_state, create_state_with_repository, AppState},
+    auth::model::{AuthSettings, Session},
+    library::model::LibrarySettings,
+    repository::MemoryRepository,
+    users::model::{Membership, Permission, User},
+};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
 
-    create_app(State(state))
+use super::{db::MockDatabase, users::MockUserBase};
+
+// Username of the librarian that requests are sent as unless they carry a token of their own
+pub const MOCK_LIBRARIAN_USERNAME: &str = "mock-librarian";
+
+// Creates an app whose requests are sent as a librarian with every permission, unless they carry
+// a token of their own. The librarian and their role are added to the database along with it.
+pub fn create_mock_app(database: MockDatabase) -> Router {
+    with_librarian_token(create_mock_state(database))
+}
+
+// Creates an app whose requests are sent without a token unless they carry one, for tests of
+// what anonymous users may do
+pub fn create_mock_public_app(database: MockDatabase) -> Router {
+    create_app(State(create_mock_state(database)))
+}
+
+// Creates the state of an app backed by the database, for tests that use it without requests
+pub fn create_mock_state(database: MockDatabase) -> AppState {
+    match database {
+        MockDatabase::Sqlite(db_pool) => {
+            create_new_state(db_pool, LibrarySettings::default(), AuthSettings::default())
+        }
+        #[cfg(feature = "postgres")]
+        MockDatabase::Postgres(repository) => create_state_with_repository(
+            repository,
+            LibrarySettings::default(),
+            AuthSettings::default(),
+        ),
+    }
+}
+
+// Creates an app backed by the given in-memory repository, which tests can seed and inspect
+pub fn create_mock_memory_app(repository: Arc<MemoryRepository>) -> Router {
+    let state = create_state_with_repository(
+        repository,
+        LibrarySettings::default(),
+        AuthSettings::default(),
+    );
+
+    with_librarian_token(state)
+}
+
+// Adds a librarian and a session of theirs to the state, passing its token with every request
+// that does not carry one
+fn with_librarian_token(state: AppState) -> Router {
+    let librarian_role = MockUserBase::new_user_role()
+        .name("Mock Librarian".to_string())
+        .permissions(Permission::ALL.to_vec())
+        .build();
+    let librarian = User {
+        id: Uuid::new_v4(),
+        username: MOCK_LIBRARIAN_USERNAME.to_string(),
+        display_name: None,
+        email: None,
+        membership: Membership::default(),
+        deleted_at: None,
+    };
+
+    state.users.add_user_role(librarian_role.clone()).unwrap();
+    state
+        .users
+        .add_user(librarian.clone(), librarian_role.id, None)
+        .unwrap();
+
+    let session = state
+        .auth
+        .add_session(Session {
+            id: Uuid::new_v4(),
+            user_id: librarian.id,
+            created_at: Utc::now(),
+            expires_at: Utc::now() + Duration::hours(1),
+        })
+        .unwrap();
+    let authorization =
+        HeaderValue::from_str(&format!("Bearer {}", state.tokens.sign(session.id))).unwrap();
+
+    create_app(State(state)).layer(map_request(move |mut request: Request<_>| {
+        let authorization = authorization.clone();
+
+        async move {
+            if !request.headers().contains_key(header::AUTHORIZATION) {
+                request
+                    .headers_mut()
+                    .insert(header::AUTHORIZATION, authorization);
+            }
+
+            Ok::<_, std::convert::Infallible>(request)
+        }
+    }))
 }
@@ -0,0 +1,60 @@
+use axum::Router;
+use biblioteca_backend::{
+    auth::{
+        model::{ApiKey, LoginResponse},
+        token::{api_key_prefix, generate_api_key, hash_api_key},
+    },
+    users::model::Permission,
+};
+use chrono::Utc;
+use hyper::{header, Body, Method, Request, StatusCode};
+use serde_json::json;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+// Logs in to the app as the user, returning the bearer token the login was issued
+pub async fn login(app: &Router, username: &str, password: &str) -> String {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(Method::POST)
+                .uri("/auth/login")
+                .header(header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
+                .body(Body::from(
+                    serde_json::to_string(&json!({
+                        "username": username,
+                        "password": password,
+                    }))
+                    .unwrap(),
+                ))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK, "checking if login is OK");
+
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let login: LoginResponse = serde_json::from_slice(&body).unwrap();
+
+    login.token
+}
+
+// Creates an API key with the permissions, returning it along with the key that is accepted for it
+pub fn new_api_key(name: &str, permissions: Vec<Permission>) -> (ApiKey, String) {
+    let key = generate_api_key();
+
+    let api_key = ApiKey {
+        id: Uuid::new_v4(),
+        name: name.to_string(),
+        prefix: api_key_prefix(&key),
+        key_hash: hash_api_key(&key),
+        permissions,
+        created_at: Utc::now(),
+        expires_at: None,
+        last_used_at: None,
+    };
+
+    (api_key, key)
+}
@@ -1,4 +1,5 @@
-use biblioteca_backend::catalog::model::{Author, Book};
+use biblioteca_backend::catalog::model::{Author, Book, Category, Item, ItemCondition, ItemStatus};
+use chrono::{DateTime, Utc};
 use rand::Rng;
 use random_string::generate;
 use uuid::Uuid;
@@ -10,6 +11,7 @@ pub struct MockBookBuilder {
     name: String,
     description: String,
     language: String,
+    deleted_at: Option<DateTime<Utc>>,
 }
 
 impl MockBookBuilder {
@@ -33,12 +35,22 @@ impl MockBookBuilder {
         self
     }
 
+    pub fn deleted_at(mut self, deleted_at: DateTime<Utc>) -> MockBookBuilder {
+        self.deleted_at = Some(deleted_at);
+        self
+    }
+
     pub fn build(self) -> Book {
         Book {
             id: self.id,
             name: self.name,
             description: self.description,
             language: self.language,
+            contributors: Vec::new(),
+            categories: Vec::new(),
+            total_copies: 0,
+            available_copies: 0,
+            deleted_at: self.deleted_at,
         }
     }
 }
@@ -48,6 +60,7 @@ pub struct MockAuthorBuilder {
     name: String,
     description: String,
     country: String,
+    deleted_at: Option<DateTime<Utc>>,
 }
 
 impl MockAuthorBuilder {
@@ -71,12 +84,94 @@ impl MockAuthorBuilder {
         self
     }
 
+    pub fn deleted_at(mut self, deleted_at: DateTime<Utc>) -> MockAuthorBuilder {
+        self.deleted_at = Some(deleted_at);
+        self
+    }
+
     pub fn build(self) -> Author {
         Author {
             id: self.id,
             name: self.name,
             description: self.description,
             country: self.country,
+            deleted_at: self.deleted_at,
+        }
+    }
+}
+
+pub struct MockCategoryBuilder {
+    id: Uuid,
+    name: String,
+}
+
+impl MockCategoryBuilder {
+    pub fn id(mut self, id: Uuid) -> MockCategoryBuilder {
+        self.id = id;
+        self
+    }
+
+    pub fn name(mut self, name: String) -> MockCategoryBuilder {
+        self.name = name;
+        self
+    }
+
+    pub fn build(self) -> Category {
+        Category {
+            id: self.id,
+            name: self.name,
+        }
+    }
+}
+
+pub struct MockItemBuilder {
+    id: Uuid,
+    book_id: Uuid,
+    barcode: String,
+    shelf_location: String,
+    condition: ItemCondition,
+    status: ItemStatus,
+}
+
+impl MockItemBuilder {
+    pub fn id(mut self, id: Uuid) -> MockItemBuilder {
+        self.id = id;
+        self
+    }
+
+    pub fn book_id(mut self, book_id: Uuid) -> MockItemBuilder {
+        self.book_id = book_id;
+        self
+    }
+
+    pub fn barcode(mut self, barcode: String) -> MockItemBuilder {
+        self.barcode = barcode;
+        self
+    }
+
+    pub fn shelf_location(mut self, shelf_location: String) -> MockItemBuilder {
+        self.shelf_location = shelf_location;
+        self
+    }
+
+    pub fn condition(mut self, condition: ItemCondition) -> MockItemBuilder {
+        self.condition = condition;
+        self
+    }
+
+    pub fn status(mut self, status: ItemStatus) -> MockItemBuilder {
+        self.status = status;
+        self
+    }
+
+    pub fn build(self) -> Item {
+        Item {
+            id: self.id,
+            book_id: self.book_id,
+            barcode: self.barcode,
+            shelf_location: self.shelf_location,
+            condition: self.condition,
+            status: self.status,
         }
     }
 }
@@ -88,6 +183,7 @@ impl MockCatalog {
             name: Self::random_string(8, 24),
             description: Self::random_string(32, 64),
             language: Self::random_string(32, 64),
+            deleted_at: None,
         }
     }
 
@@ -97,6 +193,25 @@ impl MockCatalog {
             name: Self::random_string(16, 24),
             description: Self::random_string(32, 64),
             country: Self::random_string(8, 16),
+            deleted_at: None,
+        }
+    }
+
+    pub fn new_category() -> MockCategoryBuilder {
+        MockCategoryBuilder {
+            id: Uuid::new_v4(),
+            name: Self::random_string(8, 16),
+        }
+    }
+
+    pub fn new_item() -> MockItemBuilder {
+        MockItemBuilder {
+            id: Uuid::new_v4(),
+            book_id: Uuid::new_v4(),
+            barcode: Self::random_string(12, 16),
+            shelf_location: Self::random_string(4, 8),
+            condition: ItemCondition::Good,
+            status: ItemStatus::Available,
         }
     }
 
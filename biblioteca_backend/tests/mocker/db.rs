@@ -1,273 +1,731 @@
 use std::fs::remove_file;
 
 use biblioteca_backend::{
-    catalog::model::{Author, Book},
-    database::setup_db,
-    library::model::{BookBorrowEntry, BookBorrowState},
-    users::model::{User, UserRole},
+    auth::{model::ApiKey, password::hash_password},
+    catalog::model::{Author, Book, Category, ContributorRole, Item, ItemStatus},
+    database::{setup_db, DEFAULT_POOL_SIZE},
+    library::model::{BookBorrowEntry, FineEntry, Hold, HoldStatus},
+    users::model::{join_permissions, User, UserRole, UserStatus},
 };
 
+use chrono::{DateTime, Utc};
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
 use uuid::Uuid;
 
+#[cfg(feature = "postgres")]
+use std::sync::Arc;
+
+#[cfg(feature = "postgres")]
+use biblioteca_backend::{
+    database::setup_postgres_db,
+    repository::{postgres::run_blocking, PostgresRepository},
+};
+
+// PostgreSQL database that the tests run against instead of SQLite files, if it is set
+#[cfg(feature = "postgres")]
+const TEST_DATABASE_URL_VAR: &str = "BIBLIOTECA_TEST_DATABASE_URL";
+
+// Database of a test, which is a file of its own for SQLite and a schema of its own for PostgreSQL
+pub enum MockDatabase {
+    Sqlite(Pool<SqliteConnectionManager>),
+    #[cfg(feature = "postgres")]
+    Postgres(Arc<PostgresRepository>),
+}
+
+impl MockDatabase {
+    fn open(database_path: String) -> MockDatabase {
+        #[cfg(feature = "postgres")]
+        if let Some(database_url) = schema_url(&database_path) {
+            return MockDatabase::Postgres(Arc::new(PostgresRepository::new(
+                setup_postgres_db(database_url, DEFAULT_POOL_SIZE).unwrap(),
+            )));
+        }
+
+        MockDatabase::Sqlite(setup_db(database_path, DEFAULT_POOL_SIZE).unwrap())
+    }
+}
+
+// Runs a statement on the database of the test, binding the same parameters on either backend
+macro_rules! execute {
+    ($database:expr, $sql:expr, $($param:expr),* $(,)?) => {
+        match &$database {
+            MockDatabase::Sqlite(pool) => {
+                pool.get()
+                    .unwrap()
+                    .execute($sql, rusqlite::params![$($param),*])
+                    .unwrap();
+            }
+            #[cfg(feature = "postgres")]
+            MockDatabase::Postgres(repository) => {
+                repository
+                    .with_client(|client| Ok(client.execute($sql, &[$(&$param),*])?))
+                    .unwrap();
+            }
+        }
+    };
+}
+
+// Reads the number that a query of the database of the test results in, if it succeeds
+macro_rules! query_number {
+    ($database:expr, $sql:expr $(, $param:expr)* $(,)?) => {
+        match &$database {
+            MockDatabase::Sqlite(pool) => pool
+                .get()
+                .unwrap()
+                .query_row::<i64, _, _>($sql, rusqlite::params![$($param),*], |row| row.get(0))
+                .ok(),
+            #[cfg(feature = "postgres")]
+            MockDatabase::Postgres(repository) => repository
+                .with_client(|client| {
+                    Ok(client.query_one($sql, &[$(&$param),*])?.try_get::<_, i64>(0)?)
+                })
+                .ok(),
+        }
+    };
+}
+
 pub struct MockDatabaseBuilder {
-    pub connection: Pool<SqliteConnectionManager>,
+    pub connection: MockDatabase,
 }
 
 impl MockDatabaseBuilder {
     pub fn create(database_path: String) -> MockDatabaseBuilder {
+        #[cfg(feature = "postgres")]
+        reset_schema(&database_path, true);
+
         MockDatabaseBuilder {
-            connection: setup_db(database_path).unwrap(),
+            connection: MockDatabase::open(database_path),
         }
     }
 
     pub fn teardown(database_path: String) {
+        // Tests of SQLite itself still leave their files behind when run against PostgreSQL
+        #[cfg(feature = "postgres")]
+        if reset_schema(&database_path, false) && !std::path::Path::new(&database_path).exists() {
+            return;
+        }
+
         remove_file(database_path).unwrap();
     }
 
     pub fn with_author(self, author: &Author) -> MockDatabaseBuilder {
-        self.connection
-            .get()
-            .unwrap()
-            .execute(
-                "INSERT INTO authors (id, name, description, country) VALUES (?1, ?2, ?3, ?4)",
-                (
-                    &author.id,
-                    &author.name,
-                    &author.description,
-                    &author.country,
-                ),
-            )
-            .unwrap();
+        execute!(
+            self.connection,
+            "INSERT INTO authors (id, name, description, country) VALUES ($1, $2, $3, $4)",
+            &author.id,
+            &author.name,
+            &author.description,
+            &author.country,
+        );
+
+        // Deleted separately so that the author is taken out of the search index like when deleted
+        if author.deleted_at.is_some() {
+            execute!(
+                self.connection,
+                "UPDATE authors SET deleted_at = $1 WHERE id = $2",
+                &author.deleted_at,
+                &author.id,
+            );
+        }
 
         self
     }
 
     pub fn with_book(self, book: &Book, author_id: &Uuid) -> MockDatabaseBuilder {
-        self.connection
-            .get()
-            .unwrap()
-            .execute(
-                "INSERT INTO books (id, name, description, language) VALUES (?1, ?2, ?3, ?4)",
-                (&book.id, &book.name, &book.description, &book.language),
-            )
-            .unwrap();
+        execute!(
+            self.connection,
+            "INSERT INTO books (id, name, description, language) VALUES ($1, $2, $3, $4)",
+            &book.id,
+            &book.name,
+            &book.description,
+            &book.language,
+        );
+
+        execute!(
+            self.connection,
+            "INSERT INTO map_books_to_authors (book_id, author_id, role, position) VALUES ($1, $2, $3, $4)",
+            &book.id,
+            author_id,
+            ContributorRole::Author,
+            0,
+        );
+
+        if book.deleted_at.is_some() {
+            execute!(
+                self.connection,
+                "UPDATE books SET deleted_at = $1 WHERE id = $2",
+                &book.deleted_at,
+                &book.id,
+            );
+        }
 
-        self.connection
-            .get()
-            .unwrap()
-            .execute(
-                "INSERT INTO map_books_to_authors (book_id, author_id) VALUES (?1, ?2)",
-                (&book.id, author_id),
-            )
-            .unwrap();
+        self
+    }
+
+    pub fn with_book_contributor(
+        self,
+        book_id: &Uuid,
+        author_id: &Uuid,
+        role: ContributorRole,
+        position: i32,
+    ) -> MockDatabaseBuilder {
+        execute!(
+            self.connection,
+            "INSERT INTO map_books_to_authors (book_id, author_id, role, position) VALUES ($1, $2, $3, $4)",
+            book_id,
+            author_id,
+            role,
+            position,
+        );
+
+        self
+    }
+
+    pub fn with_item(self, item: &Item) -> MockDatabaseBuilder {
+        execute!(
+            self.connection,
+            "INSERT INTO items (id, book_id, barcode, shelf_location, condition, status) VALUES ($1, $2, $3, $4, $5, $6)",
+            &item.id,
+            &item.book_id,
+            &item.barcode,
+            &item.shelf_location,
+            &item.condition,
+            &item.status,
+        );
+
+        self
+    }
+
+    pub fn with_category(self, category: &Category) -> MockDatabaseBuilder {
+        execute!(
+            self.connection,
+            "INSERT INTO categories (id, name) VALUES ($1, $2)",
+            &category.id,
+            &category.name,
+        );
+
+        self
+    }
+
+    pub fn with_book_category(self, book_id: &Uuid, category_id: &Uuid) -> MockDatabaseBuilder {
+        execute!(
+            self.connection,
+            "INSERT INTO map_books_to_categories (book_id, category_id) VALUES ($1, $2)",
+            book_id,
+            category_id,
+        );
 
         self
     }
 
     pub fn with_user(self, user: &User, user_role: &UserRole) -> MockDatabaseBuilder {
-        self.connection
-            .get()
-            .unwrap()
-            .execute(
-                "INSERT INTO users (id, username) VALUES (?1, ?2)",
-                (&user.id, &user.username),
-            )
-            .unwrap();
+        execute!(
+            self.connection,
+            "INSERT INTO users (id, username, display_name, email, status, membership_expires_at, suspension_reason, suspended_until)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            &user.id,
+            &user.username,
+            &user.display_name,
+            &user.email,
+            &user.membership.status,
+            &user.membership.membership_expires_at,
+            &user.membership.suspension_reason,
+            &user.membership.suspended_until,
+        );
+
+        execute!(
+            self.connection,
+            "INSERT INTO map_users_to_user_roles (user_id, user_role_id) VALUES ($1, $2)",
+            &user.id,
+            &user_role.id,
+        );
+
+        if user.deleted_at.is_some() {
+            execute!(
+                self.connection,
+                "UPDATE users SET deleted_at = $1 WHERE id = $2",
+                &user.deleted_at,
+                &user.id,
+            );
+        }
 
-        self.connection
-            .get()
-            .unwrap()
-            .execute(
-                "INSERT INTO map_users_to_user_roles (user_id, user_role_id) VALUES (?1, ?2)",
-                (&user.id, &user_role.id),
-            )
-            .unwrap();
+        self
+    }
+
+    pub fn with_granted_user_role(
+        self,
+        user: &User,
+        user_role: &UserRole,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> MockDatabaseBuilder {
+        execute!(
+            self.connection,
+            "INSERT INTO map_users_to_user_roles (user_id, user_role_id, expires_at) VALUES ($1, $2, $3)",
+            &user.id,
+            &user_role.id,
+            &expires_at,
+        );
+
+        self
+    }
+
+    pub fn with_user_password(self, user: &User, password: &str) -> MockDatabaseBuilder {
+        execute!(
+            self.connection,
+            "UPDATE users SET password_hash = $1 WHERE id = $2",
+            &hash_password(password),
+            &user.id,
+        );
+
+        self
+    }
+
+    pub fn with_api_key(self, api_key: &ApiKey) -> MockDatabaseBuilder {
+        execute!(
+            self.connection,
+            "INSERT INTO api_keys (id, name, prefix, key_hash, permissions, created_at, expires_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            &api_key.id,
+            &api_key.name,
+            &api_key.prefix,
+            &api_key.key_hash,
+            &join_permissions(&api_key.permissions),
+            &api_key.created_at,
+            &api_key.expires_at,
+        );
 
         self
     }
 
     pub fn with_user_role(self, user_role: &UserRole) -> MockDatabaseBuilder {
-        self.connection
-            .get()
-            .unwrap()
-            .execute(
-                "INSERT INTO user_roles (id, name, num_borrowable_books) VALUES (?1, ?2, ?3)",
-                (
-                    &user_role.id,
-                    &user_role.name,
-                    &user_role.num_borrowable_books,
-                ),
-            )
-            .unwrap();
+        execute!(
+            self.connection,
+            "INSERT INTO user_roles (id, name, num_borrowable_books, loan_period_days, max_renewals, fine_per_day_cents, fine_cap_cents, permissions) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            &user_role.id,
+            &user_role.name,
+            &user_role.num_borrowable_books,
+            &user_role.loan_period_days,
+            &user_role.max_renewals,
+            &user_role.fine_per_day_cents,
+            &user_role.fine_cap_cents,
+            &join_permissions(&user_role.permissions),
+        );
 
         self
     }
 
     pub fn with_library_entry(self, book_borrow_entry: &BookBorrowEntry) -> MockDatabaseBuilder {
-        self.connection
-            .get()
-            .unwrap()
-            .execute(
-                "INSERT INTO map_users_to_borrowed_books (id, user_id, book_id, timestamp, action) VALUES (?1, ?2, ?3, ?4, ?5)",
-                (book_borrow_entry.id, &book_borrow_entry.user_id, &book_borrow_entry.book_id, book_borrow_entry.timestamp, book_borrow_entry.state),
-            )
-            .unwrap();
+        execute!(
+            self.connection,
+            "INSERT INTO map_users_to_borrowed_books (id, user_id, book_id, item_id, timestamp, due_at, renewals, action) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            &book_borrow_entry.id,
+            &book_borrow_entry.user_id,
+            &book_borrow_entry.book_id,
+            &book_borrow_entry.item_id,
+            &book_borrow_entry.timestamp,
+            &book_borrow_entry.due_at,
+            book_borrow_entry.renewals as i32,
+            &book_borrow_entry.state,
+        );
+
+        self
+    }
+
+    pub fn with_hold(self, hold: &Hold) -> MockDatabaseBuilder {
+        execute!(
+            self.connection,
+            "INSERT INTO holds (id, book_id, user_id, item_id, status, created_at, expires_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            &hold.id,
+            &hold.book_id,
+            &hold.user_id,
+            &hold.item_id,
+            &hold.status,
+            &hold.created_at,
+            &hold.expires_at,
+        );
+
+        self
+    }
+
+    pub fn with_fine_entry(self, entry: &FineEntry) -> MockDatabaseBuilder {
+        execute!(
+            self.connection,
+            "INSERT INTO fines (id, user_id, loan_id, kind, amount_cents, reason, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            &entry.id,
+            &entry.user_id,
+            &entry.loan_id,
+            &entry.kind,
+            &entry.amount_cents,
+            &entry.reason,
+            &entry.created_at,
+        );
 
         self
     }
 
-    pub fn build(self) -> Pool<SqliteConnectionManager> {
+    pub fn build(self) -> MockDatabase {
         self.connection
     }
 }
 
 pub struct MockDatabaseQuerier {
-    pool: Pool<SqliteConnectionManager>,
+    database: MockDatabase,
 }
 
 impl MockDatabaseQuerier {
     pub fn create(database_path: String) -> MockDatabaseQuerier {
+        #[cfg(feature = "postgres")]
+        if let Some(database_url) = schema_url(&database_path) {
+            let pool = run_blocking(move || {
+                let config = database_url.parse().unwrap();
+                let manager =
+                    r2d2_postgres::PostgresConnectionManager::new(config, postgres::NoTls);
+                r2d2::Pool::new(manager).unwrap()
+            });
+
+            return MockDatabaseQuerier {
+                database: MockDatabase::Postgres(Arc::new(PostgresRepository::new(pool))),
+            };
+        }
+
         let manager = SqliteConnectionManager::file(database_path);
         MockDatabaseQuerier {
-            pool: r2d2::Pool::new(manager).unwrap(),
+            database: MockDatabase::Sqlite(r2d2::Pool::new(manager).unwrap()),
         }
     }
 
     pub fn contains_num_books(&self, num: i32) -> bool {
-        match self.pool.get().unwrap().query_row::<i32, _, _>(
-            "SELECT COUNT(*) FROM books",
-            (),
-            |row| row.get(0),
-        ) {
-            Ok(count) => count == num,
-            Err(_) => false,
-        }
+        query_number!(self.database, "SELECT COUNT(*) FROM books") == Some(num.into())
     }
 
     pub fn contains_num_authors(&self, num: i32) -> bool {
-        match self.pool.get().unwrap().query_row::<i32, _, _>(
-            "SELECT COUNT(*) FROM authors",
-            (),
-            |row| row.get(0),
-        ) {
-            Ok(count) => count == num,
-            Err(_) => false,
-        }
+        query_number!(self.database, "SELECT COUNT(*) FROM authors") == Some(num.into())
+    }
+
+    pub fn contains_num_categories(&self, num: i32) -> bool {
+        query_number!(self.database, "SELECT COUNT(*) FROM categories") == Some(num.into())
     }
 
     pub fn contains_num_users(&self, num: i32) -> bool {
-        match self.pool.get().unwrap().query_row::<i32, _, _>(
-            "SELECT COUNT(*) FROM users",
-            (),
-            |row| row.get(0),
-        ) {
-            Ok(count) => count == num,
-            Err(_) => false,
-        }
+        query_number!(self.database, "SELECT COUNT(*) FROM users") == Some(num.into())
+    }
+
+    pub fn contains_num_sessions(&self, num: i32) -> bool {
+        query_number!(self.database, "SELECT COUNT(*) FROM sessions") == Some(num.into())
+    }
+
+    pub fn has_password(&self, user_id: &Uuid) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM users WHERE id = $1 AND password_hash IS NOT NULL",
+            user_id,
+        ) == Some(1)
+    }
+
+    pub fn contains_num_api_keys(&self, num: i32) -> bool {
+        query_number!(self.database, "SELECT COUNT(*) FROM api_keys") == Some(num.into())
+    }
+
+    pub fn contains_api_key_hash(&self, key_hash: &str) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM api_keys WHERE key_hash = $1",
+            key_hash,
+        ) == Some(1)
+    }
+
+    pub fn is_api_key_used(&self, api_key_id: &Uuid) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM api_keys WHERE id = $1 AND last_used_at IS NOT NULL",
+            api_key_id,
+        ) == Some(1)
     }
 
     pub fn contains_num_user_roles(&self, num: i32) -> bool {
-        match self.pool.get().unwrap().query_row::<i32, _, _>(
-            "SELECT COUNT(*) FROM user_roles",
-            (),
-            |row| row.get(0),
-        ) {
-            Ok(count) => count == num,
-            Err(_) => false,
-        }
+        query_number!(self.database, "SELECT COUNT(*) FROM user_roles") == Some(num.into())
     }
 
     pub fn contains_book(&self, book: &Book) -> bool {
-        match self.pool.get().unwrap().query_row::<i32,_,_>(
-            "SELECT COUNT(*) FROM books WHERE id = ?1 AND name = ?2 AND description = ?3 AND language = ?4", 
-            (&book.id, &book.name, &book.description, &book.language),
-            |row| row.get(0)
-        ) {
-            Ok(count) => count == 1,
-            Err(_) => false,
-        }
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM books WHERE id = $1 AND name = $2 AND description = $3 AND language = $4",
+            &book.id,
+            &book.name,
+            &book.description,
+            &book.language,
+        ) == Some(1)
     }
 
     pub fn contains_author(&self, author: &Author) -> bool {
-        match self.pool.get().unwrap().query_row::<i32,_,_>(
-            "SELECT COUNT(*) FROM authors WHERE id = ?1 AND name = ?2 AND description = ?3 AND country = ?4", 
-            (&author.id, &author.name, &author.description, &author.country),
-            |row| row.get(0)
-        ) {
-            Ok(count) => count == 1,
-            Err(_) => false,
-        }
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM authors WHERE id = $1 AND name = $2 AND description = $3 AND country = $4",
+            &author.id,
+            &author.name,
+            &author.description,
+            &author.country,
+        ) == Some(1)
+    }
+
+    pub fn contains_item(&self, item: &Item) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM items WHERE id = $1 AND book_id = $2 AND barcode = $3 AND shelf_location = $4 AND condition = $5 AND status = $6",
+            &item.id,
+            &item.book_id,
+            &item.barcode,
+            &item.shelf_location,
+            &item.condition,
+            &item.status,
+        ) == Some(1)
+    }
+
+    pub fn contains_num_items(&self, num: i32) -> bool {
+        query_number!(self.database, "SELECT COUNT(*) FROM items") == Some(num.into())
+    }
+
+    pub fn contains_num_loans(&self, num: i32) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM map_users_to_borrowed_books"
+        ) == Some(num.into())
+    }
+
+    pub fn contains_num_holds(&self, num: i32) -> bool {
+        query_number!(self.database, "SELECT COUNT(*) FROM holds") == Some(num.into())
+    }
+
+    pub fn is_hold_ready(&self, hold_id: &Uuid, item_id: &Uuid) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM holds WHERE id = $1 AND item_id = $2 AND status = $3 AND expires_at IS NOT NULL",
+            hold_id,
+            item_id,
+            HoldStatus::Ready,
+        ) == Some(1)
+    }
+
+    pub fn has_fine_balance(&self, user_id: &Uuid, balance_cents: i64) -> bool {
+        query_number!(
+            self.database,
+            "SELECT CAST(COALESCE(SUM(CASE kind WHEN 'fine' THEN amount_cents ELSE -amount_cents END), 0) AS BIGINT) FROM fines WHERE user_id = $1",
+            user_id,
+        ) == Some(balance_cents)
+    }
+
+    pub fn is_item_status(&self, item_id: &Uuid, status: ItemStatus) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM items WHERE id = $1 AND status = $2",
+            item_id,
+            status,
+        ) == Some(1)
     }
 
     pub fn contains_book_author_mapping(&self, book_id: &Uuid, author_id: &Uuid) -> bool {
-        match self.pool.get().unwrap().query_row::<i32, _, _>(
-            "SELECT COUNT(*) FROM map_books_to_authors WHERE book_id = ?1 AND author_id = ?2",
-            (book_id, author_id),
-            |row| row.get(0),
-        ) {
-            Ok(count) => count == 1,
-            Err(_) => false,
-        }
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM map_books_to_authors WHERE book_id = $1 AND author_id = $2",
+            book_id,
+            author_id,
+        ) == Some(1)
+    }
+
+    pub fn contains_category(&self, category: &Category) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM categories WHERE id = $1 AND name = $2",
+            &category.id,
+            &category.name,
+        ) == Some(1)
+    }
+
+    pub fn contains_book_category_mapping(&self, book_id: &Uuid, category_id: &Uuid) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM map_books_to_categories WHERE book_id = $1 AND category_id = $2",
+            book_id,
+            category_id,
+        ) == Some(1)
+    }
+
+    pub fn contains_book_contributor(
+        &self,
+        book_id: &Uuid,
+        author_id: &Uuid,
+        role: ContributorRole,
+        position: i32,
+    ) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM map_books_to_authors WHERE book_id = $1 AND author_id = $2 AND role = $3 AND position = $4",
+            book_id,
+            author_id,
+            role,
+            position,
+        ) == Some(1)
     }
 
     pub fn contains_user(&self, user: &User) -> bool {
-        match self.pool.get().unwrap().query_row::<i32, _, _>(
-            "SELECT COUNT(*) FROM users WHERE id = ?1 AND username = ?2",
-            (&user.id, &user.username),
-            |row| row.get(0),
-        ) {
-            Ok(count) => count == 1,
-            Err(_) => false,
-        }
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM users WHERE id = $1 AND username = $2",
+            &user.id,
+            &user.username,
+        ) == Some(1)
+    }
+
+    pub fn is_user_status(&self, user_id: &Uuid, status: UserStatus) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM users WHERE id = $1 AND status = $2",
+            user_id,
+            &status,
+        ) == Some(1)
+    }
+
+    pub fn is_book_deleted(&self, book_id: &Uuid) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM books WHERE id = $1 AND deleted_at IS NOT NULL",
+            book_id,
+        ) == Some(1)
+    }
+
+    pub fn is_author_deleted(&self, author_id: &Uuid) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM authors WHERE id = $1 AND deleted_at IS NOT NULL",
+            author_id,
+        ) == Some(1)
+    }
+
+    pub fn is_user_deleted(&self, user_id: &Uuid) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM users WHERE id = $1 AND deleted_at IS NOT NULL",
+            user_id,
+        ) == Some(1)
+    }
+
+    pub fn contains_num_audit_entries_of(&self, entity_id: &Uuid, num: i32) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM audit_log WHERE entity_id = $1",
+            entity_id,
+        ) == Some(num.into())
+    }
+
+    // Checks that an entry of the audit log can be neither changed nor removed
+    pub fn is_audit_log_append_only(&self) -> bool {
+        query_number!(self.database, "SELECT COUNT(*) FROM audit_log").unwrap_or(0) > 0
+            && query_number!(
+                self.database,
+                "UPDATE audit_log SET request_id = 'altered'
+                WHERE id = (SELECT id FROM audit_log LIMIT 1) RETURNING 1",
+            )
+            .is_none()
+            && query_number!(
+                self.database,
+                "DELETE FROM audit_log WHERE id = (SELECT id FROM audit_log LIMIT 1) RETURNING 1",
+            )
+            .is_none()
     }
 
     pub fn contains_user_role(&self, user_role: &UserRole) -> bool {
-        match self.pool.get().unwrap().query_row::<i32, _, _>(
-            "SELECT COUNT(*) FROM user_roles WHERE id = ?1 AND name = ?2 AND num_borrowable_books = ?3",
-            (&user_role.id, &user_role.name, &user_role.num_borrowable_books),
-            |row| row.get(0)
-        ) {
-            Ok(count) => count == 1,
-            Err(_) => false,
-        }
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM user_roles WHERE id = $1 AND name = $2 AND num_borrowable_books = $3 AND loan_period_days = $4 AND max_renewals = $5 AND fine_per_day_cents = $6 AND fine_cap_cents = $7 AND permissions = $8",
+            &user_role.id,
+            &user_role.name,
+            &user_role.num_borrowable_books,
+            &user_role.loan_period_days,
+            &user_role.max_renewals,
+            &user_role.fine_per_day_cents,
+            &user_role.fine_cap_cents,
+            &join_permissions(&user_role.permissions),
+        ) == Some(1)
     }
 
     pub fn contains_user_user_role_mapping(&self, user_id: &Uuid, user_role_id: &Uuid) -> bool {
-        match self.pool.get().unwrap().query_row::<i32, _, _>(
-            "SELECT COUNT(*) FROM map_users_to_user_roles WHERE user_id = ?1 AND user_role_id = ?2",
-            (&user_id, &user_role_id),
-            |row| row.get(0),
-        ) {
-            Ok(count) => count == 1,
-            Err(_) => false,
-        }
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM map_users_to_user_roles WHERE user_id = $1 AND user_role_id = $2",
+            user_id,
+            user_role_id,
+        ) == Some(1)
+    }
+
+    pub fn is_loan_renewed(&self, loan_id: &Uuid, renewals: u32) -> bool {
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM map_users_to_borrowed_books WHERE id = $1 AND action = 'Borrowed' AND renewals = $2",
+            loan_id,
+            renewals as i32,
+        ) == Some(1)
     }
 
+    // Checks whether the latest entry of the book in the ledger is a loan
     pub fn is_book_borrowed(&self, book_id: &Uuid) -> bool {
-        match self.pool.get().unwrap().query_row(
-            "SELECT * FROM map_users_to_borrowed_books WHERE book_id = ?1 ORDER BY timestamp DESC",
-            [book_id],
-            |row| {
-                Ok(BookBorrowEntry {
-                    id: row.get(0)?,
-                    user_id: row.get(1)?,
-                    book_id: row.get(2)?,
-                    timestamp: row.get(3)?,
-                    state: row.get(4)?,
-                })
-            },
-        ) {
-            Ok(entry) => match entry.state {
-                BookBorrowState::Borrowed => true,
-                BookBorrowState::Returned => false,
-            },
-            Err(_) => false,
-        }
+        query_number!(
+            self.database,
+            "SELECT COUNT(*) FROM (SELECT action FROM map_users_to_borrowed_books WHERE book_id = $1 ORDER BY timestamp DESC LIMIT 1) AS latest WHERE action = 'Borrowed'",
+            book_id,
+        ) == Some(1)
     }
 
     pub fn is_book_returned(&self, book_id: &Uuid) -> bool {
         !self.is_book_borrowed(book_id)
     }
 }
+
+// Connection string of the schema of a test in the test database, if there is one
+#[cfg(feature = "postgres")]
+fn schema_url(database_path: &str) -> Option<String> {
+    let database_url = std::env::var(TEST_DATABASE_URL_VAR).ok()?;
+    let separator = if database_url.contains('?') { '&' } else { '?' };
+
+    Some(format!(
+        "{}{}options=-c%20search_path%3D{}",
+        database_url,
+        separator,
+        schema_name(database_path)
+    ))
+}
+
+#[cfg(feature = "postgres")]
+fn schema_name(database_path: &str) -> String {
+    database_path
+        .trim_end_matches(".sqlite")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>()
+        .to_lowercase()
+}
+
+// Drops the schema of a test along with everything in it, creating it anew if asked to. Returns
+// whether the tests run against the test database at all.
+#[cfg(feature = "postgres")]
+fn reset_schema(database_path: &str, recreate: bool) -> bool {
+    let Ok(database_url) = std::env::var(TEST_DATABASE_URL_VAR) else {
+        return false;
+    };
+    let schema = schema_name(database_path);
+
+    run_blocking(move || {
+        let mut client = postgres::Client::connect(&database_url, postgres::NoTls).unwrap();
+
+        client
+            .batch_execute(&format!("DROP SCHEMA IF EXISTS {} CASCADE", schema))
+            .unwrap();
+
+        if recreate {
+            client
+                .batch_execute(&format!("CREATE SCHEMA {}", schema))
+                .unwrap();
+        }
+    });
+
+    true
+}
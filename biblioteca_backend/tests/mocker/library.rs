@@ -1,5 +1,7 @@
-use biblioteca_backend::library::model::{BookBorrowEntry, BookBorrowState};
-use chrono::{DateTime, Utc};
+use biblioteca_backend::library::model::{
+    BookBorrowEntry, BookBorrowState, FineEntry, FineEntryKind, Hold, HoldStatus,
+};
+use chrono::{DateTime, Duration, Utc};
 use uuid::Uuid;
 
 pub struct MockLibrary {}
@@ -7,8 +9,11 @@ pub struct MockLibrary {}
 pub struct MockBookBorrowEntryBuilder {
     id: Uuid,
     book_id: Uuid,
+    item_id: Uuid,
     user_id: Uuid,
     timestamp: DateTime<Utc>,
+    due_at: DateTime<Utc>,
+    renewals: u32,
     state: BookBorrowState,
 }
 
@@ -23,6 +28,11 @@ impl MockBookBorrowEntryBuilder {
         self
     }
 
+    pub fn item_id(mut self, item_id: Uuid) -> MockBookBorrowEntryBuilder {
+        self.item_id = item_id;
+        self
+    }
+
     pub fn user_id(mut self, user_id: Uuid) -> MockBookBorrowEntryBuilder {
         self.user_id = user_id;
         self
@@ -33,6 +43,16 @@ impl MockBookBorrowEntryBuilder {
         self
     }
 
+    pub fn due_at(mut self, due_at: DateTime<Utc>) -> MockBookBorrowEntryBuilder {
+        self.due_at = due_at;
+        self
+    }
+
+    pub fn renewals(mut self, renewals: u32) -> MockBookBorrowEntryBuilder {
+        self.renewals = renewals;
+        self
+    }
+
     pub fn state(mut self, state: BookBorrowState) -> MockBookBorrowEntryBuilder {
         self.state = state;
         self
@@ -42,20 +62,109 @@ impl MockBookBorrowEntryBuilder {
         BookBorrowEntry {
             id: self.id,
             book_id: self.book_id,
+            item_id: self.item_id,
             user_id: self.user_id,
             timestamp: self.timestamp,
+            due_at: self.due_at,
+            renewals: self.renewals,
             state: self.state,
         }
     }
 }
 
+pub struct MockHoldBuilder {
+    id: Uuid,
+    book_id: Uuid,
+    user_id: Uuid,
+    item_id: Option<Uuid>,
+    status: HoldStatus,
+    created_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl MockHoldBuilder {
+    pub fn book_id(mut self, book_id: Uuid) -> MockHoldBuilder {
+        self.book_id = book_id;
+        self
+    }
+
+    pub fn user_id(mut self, user_id: Uuid) -> MockHoldBuilder {
+        self.user_id = user_id;
+        self
+    }
+
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> MockHoldBuilder {
+        self.created_at = created_at;
+        self
+    }
+
+    // Marks the hold as ready for pickup of the given copy until the given time
+    pub fn ready(mut self, item_id: Uuid, expires_at: DateTime<Utc>) -> MockHoldBuilder {
+        self.item_id = Some(item_id);
+        self.status = HoldStatus::Ready;
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    pub fn build(self) -> Hold {
+        Hold {
+            id: self.id,
+            book_id: self.book_id,
+            user_id: self.user_id,
+            item_id: self.item_id,
+            status: self.status,
+            created_at: self.created_at,
+            expires_at: self.expires_at,
+        }
+    }
+}
+
+pub struct MockFineEntryBuilder {
+    id: Uuid,
+    user_id: Uuid,
+    kind: FineEntryKind,
+    amount_cents: i64,
+}
+
+impl MockFineEntryBuilder {
+    pub fn user_id(mut self, user_id: Uuid) -> MockFineEntryBuilder {
+        self.user_id = user_id;
+        self
+    }
+
+    pub fn kind(mut self, kind: FineEntryKind) -> MockFineEntryBuilder {
+        self.kind = kind;
+        self
+    }
+
+    pub fn amount_cents(mut self, amount_cents: i64) -> MockFineEntryBuilder {
+        self.amount_cents = amount_cents;
+        self
+    }
+
+    pub fn build(self) -> FineEntry {
+        FineEntry {
+            id: self.id,
+            user_id: self.user_id,
+            loan_id: None,
+            kind: self.kind,
+            amount_cents: self.amount_cents,
+            reason: None,
+            created_at: Utc::now(),
+        }
+    }
+}
+
 impl MockLibrary {
     pub fn new_borrow_entry() -> MockBookBorrowEntryBuilder {
         MockBookBorrowEntryBuilder {
             id: Uuid::new_v4(),
             book_id: Uuid::new_v4(),
+            item_id: Uuid::new_v4(),
             user_id: Uuid::new_v4(),
             timestamp: Utc::now(),
+            due_at: Utc::now() + Duration::days(14),
+            renewals: 0,
             state: BookBorrowState::Borrowed,
         }
     }
@@ -64,9 +173,33 @@ impl MockLibrary {
         MockBookBorrowEntryBuilder {
             id: Uuid::new_v4(),
             book_id: Uuid::new_v4(),
+            item_id: Uuid::new_v4(),
             user_id: Uuid::new_v4(),
             timestamp: Utc::now(),
+            due_at: Utc::now() + Duration::days(14),
+            renewals: 0,
             state: BookBorrowState::Returned,
         }
     }
+
+    pub fn new_hold() -> MockHoldBuilder {
+        MockHoldBuilder {
+            id: Uuid::new_v4(),
+            book_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            item_id: None,
+            status: HoldStatus::Waiting,
+            created_at: Utc::now(),
+            expires_at: None,
+        }
+    }
+
+    pub fn new_fine() -> MockFineEntryBuilder {
+        MockFineEntryBuilder {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            kind: FineEntryKind::Fine,
+            amount_cents: 100,
+        }
+    }
 }
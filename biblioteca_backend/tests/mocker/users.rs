@@ -1,4 +1,5 @@
-use biblioteca_backend::users::model::{User, UserRole};
+use biblioteca_backend::users::model::{Membership, Permission, User, UserRole, UserStatus};
+use chrono::{DateTime, Utc};
 use rand::Rng;
 use random_string::generate;
 use uuid::Uuid;
@@ -8,12 +9,19 @@ pub struct MockUserBase {}
 pub struct MockUserBuilder {
     id: Uuid,
     username: String,
+    membership: Membership,
+    deleted_at: Option<DateTime<Utc>>,
 }
 
 pub struct MockUserRoleBuilder {
     id: Uuid,
     name: String,
     num_borrowable_books: i32,
+    loan_period_days: i32,
+    max_renewals: i32,
+    fine_per_day_cents: i32,
+    fine_cap_cents: i32,
+    permissions: Vec<Permission>,
 }
 
 impl MockUserBase {
@@ -21,6 +29,8 @@ impl MockUserBase {
         MockUserBuilder {
             id: Uuid::new_v4(),
             username: Self::random_string(8, 16),
+            membership: Membership::default(),
+            deleted_at: None,
         }
     }
 
@@ -31,6 +41,11 @@ impl MockUserBase {
             id: Uuid::new_v4(),
             name: Self::random_string(8, 16),
             num_borrowable_books: rng.gen_range(2..4),
+            loan_period_days: rng.gen_range(7..28),
+            max_renewals: rng.gen_range(1..3),
+            fine_per_day_cents: rng.gen_range(10..50),
+            fine_cap_cents: rng.gen_range(500..1000),
+            permissions: Vec::new(),
         }
     }
 
@@ -52,10 +67,39 @@ impl MockUserBuilder {
         self
     }
 
+    pub fn status(mut self, status: UserStatus) -> MockUserBuilder {
+        self.membership.status = status;
+        self
+    }
+
+    pub fn membership_expires_at(
+        mut self,
+        membership_expires_at: DateTime<Utc>,
+    ) -> MockUserBuilder {
+        self.membership.membership_expires_at = Some(membership_expires_at);
+        self
+    }
+
+    pub fn suspended(mut self, reason: &str, until: Option<DateTime<Utc>>) -> MockUserBuilder {
+        self.membership.status = UserStatus::Suspended;
+        self.membership.suspension_reason = Some(reason.to_string());
+        self.membership.suspended_until = until;
+        self
+    }
+
+    pub fn deleted_at(mut self, deleted_at: DateTime<Utc>) -> MockUserBuilder {
+        self.deleted_at = Some(deleted_at);
+        self
+    }
+
     pub fn build(self) -> User {
         User {
             id: self.id,
             username: self.username,
+            display_name: None,
+            email: None,
+            membership: self.membership,
+            deleted_at: self.deleted_at,
         }
     }
 }
@@ -76,11 +120,41 @@ impl MockUserRoleBuilder {
         self
     }
 
+    pub fn loan_period_days(mut self, days: i32) -> MockUserRoleBuilder {
+        self.loan_period_days = days;
+        self
+    }
+
+    pub fn max_renewals(mut self, num: i32) -> MockUserRoleBuilder {
+        self.max_renewals = num;
+        self
+    }
+
+    pub fn fine_per_day_cents(mut self, cents: i32) -> MockUserRoleBuilder {
+        self.fine_per_day_cents = cents;
+        self
+    }
+
+    pub fn fine_cap_cents(mut self, cents: i32) -> MockUserRoleBuilder {
+        self.fine_cap_cents = cents;
+        self
+    }
+
+    pub fn permissions(mut self, permissions: Vec<Permission>) -> MockUserRoleBuilder {
+        self.permissions = permissions;
+        self
+    }
+
     pub fn build(self) -> UserRole {
         UserRole {
             id: self.id,
             name: self.name,
             num_borrowable_books: self.num_borrowable_books,
+            loan_period_days: self.loan_period_days,
+            max_renewals: self.max_renewals,
+            fine_per_day_cents: self.fine_per_day_cents,
+            fine_cap_cents: self.fine_cap_cents,
+            permissions: self.permissions,
         }
     }
 }